@@ -0,0 +1,29 @@
+use anyhow::Result;
+use ast::lang::graphs::architecture;
+use ast::repo::Repo;
+use ast::utils::logger;
+
+/*
+checks import cycles and the "layers" of .ast.json, exits 1 on any finding:
+REPO_PATH=ast/examples/senza-lnd cargo run --example arch
+*/
+
+#[tokio::main(flavor = "multi_thread")]
+async fn main() -> Result<()> {
+    logger();
+    let path = std::env::var("REPO_PATH").expect("REPO_PATH is not set");
+    let repos = Repo::new_multi_detect(&path, None, Vec::new(), Vec::new(), Some(false)).await?;
+    let layers = repos.0.first().map(|r| r.layers()).unwrap_or_default();
+    let graph = repos.build_graphs().await?;
+    let report = architecture::check(&graph, &layers);
+    report.print();
+    println!(
+        "{} cycles, {} layer violations",
+        report.cycles.len(),
+        report.violations.len()
+    );
+    if !report.is_clean() {
+        std::process::exit(1);
+    }
+    Ok(())
+}
@@ -6,7 +6,9 @@ use crate::lang::graphs::Neo4jGraph;
 use super::streaming::{StreamingUploadContext, drain_deltas};
 
 use crate::lang::{asg::{NodeData, TestRecord}, graphs::NodeType};
-use crate::lang::{ArrayGraph, BTreeMapGraph};
+use crate::lang::lockfile::{lock_covers, parse_lockfile, LockedPackage};
+use crate::lang::Edge;
+use crate::lang::{ArrayGraph, BTreeMapGraph, IndexedGraph};
 use crate::repo::Repo;
 use git_url_parse::GitUrl;
 use lsp::{git::get_commit_hash, strip_tmp, Cmd as LspCmd, DidOpen};
@@ -18,6 +20,9 @@ use tracing::{debug, info, trace};
 
 impl Repo {
     pub async fn build_graph(&self) -> Result<BTreeMapGraph> {
+        Ok(self.build_graph_indexed().await?.into_inner())
+    }
+    pub async fn build_graph_indexed(&self) -> Result<IndexedGraph> {
         self.build_graph_inner().await
     }
     pub async fn build_graph_array(&self) -> Result<ArrayGraph> {
@@ -61,6 +66,9 @@ impl Repo {
     self.process_libraries(&mut graph, &filez)?;
     #[cfg(feature = "neo4j")]
     if let Some(ctx) = &mut streaming_ctx { let (dn,de)=drain_deltas(); let _ = ctx.uploader.flush_stage(&ctx.neo, "libraries", &dn, &de).await; }
+        self.process_modules(&mut graph, &filez)?;
+    #[cfg(feature = "neo4j")]
+    if let Some(ctx) = &mut streaming_ctx { let (dn,de)=drain_deltas(); let _ = ctx.uploader.flush_stage(&ctx.neo, "modules", &dn, &de).await; }
         self.process_import_sections(&mut graph, &filez)?;
     #[cfg(feature = "neo4j")]
     if let Some(ctx) = &mut streaming_ctx { let (dn,de)=drain_deltas(); let _ = ctx.uploader.flush_stage(&ctx.neo, "imports", &dn, &de).await; }
@@ -76,6 +84,9 @@ impl Repo {
         self.process_data_models(&mut graph, &filez)?;
     #[cfg(feature = "neo4j")]
     if let Some(ctx) = &mut streaming_ctx { let (dn,de)=drain_deltas(); let _ = ctx.uploader.flush_stage(&ctx.neo, "data_models", &dn, &de).await; }
+        self.process_type_definitions(&mut graph, &filez)?;
+    #[cfg(feature = "neo4j")]
+    if let Some(ctx) = &mut streaming_ctx { let (dn,de)=drain_deltas(); let _ = ctx.uploader.flush_stage(&ctx.neo, "type_definitions", &dn, &de).await; }
         self.process_functions_and_tests(&mut graph, &filez).await?;
     #[cfg(feature = "neo4j")]
     if let Some(ctx) = &mut streaming_ctx { let (dn,de)=drain_deltas(); let _ = ctx.uploader.flush_stage(&ctx.neo, "functions_tests", &dn, &de).await; }
@@ -256,7 +267,14 @@ impl Repo {
             .filter(|(f, _)| self.lang.kind.is_package_file(f))
             .collect::<Vec<_>>();
 
+        let lock_files = filez
+            .iter()
+            .filter(|(f, _)| self.lang.kind.is_lock_file(f))
+            .map(|(f, code)| (f.clone(), parse_lockfile(f, code)))
+            .collect::<Vec<_>>();
+
         let total_pkg_files = pkg_files.len();
+        let mut declared = Vec::new();
 
         for (pkg_file, code) in pkg_files {
             i += 1;
@@ -276,17 +294,99 @@ impl Repo {
             let libs = self.lang.get_libs::<G>(&code, &pkg_file)?;
             lib_count += libs.len();
 
-            for lib in libs {
-                graph.add_node_with_parent(NodeType::Library, lib, NodeType::File, &pkg_file);
+            for mut lib in libs {
+                let locked = lock_files
+                    .iter()
+                    .filter(|(lock_file, _)| lock_covers(lock_file, pkg_file))
+                    .flat_map(|(_, pkgs)| pkgs.iter())
+                    .find(|p| p.name == lib.name);
+                if let Some(locked) = locked {
+                    lib.add_resolved_version(&locked.version);
+                }
+                lib.add_direct(true);
+                graph.add_node_with_parent(NodeType::Library, lib.clone(), NodeType::File, &pkg_file);
+                declared.push(lib);
+            }
+        }
+
+        // the full dependency tree can be large, DIRECT_LIBRARIES_ONLY leaves it out
+        let mut transitive_count = 0;
+        if !std::env::var("DIRECT_LIBRARIES_ONLY").is_ok_and(|v| v == "true" || v == "1") {
+            for (lock_file, pkgs) in &lock_files {
+                transitive_count += self.add_locked_libraries(graph, lock_file, pkgs, &declared);
             }
         }
+        lib_count += transitive_count;
 
         let mut stats = std::collections::HashMap::new();
         stats.insert("libraries".to_string(), lib_count);
         self.send_status_with_stats(stats);
 
         self.send_status_progress(100, 100, 3);
-        info!("=> got {} libs", lib_count);
+        info!("=> got {} libs ({} from lockfiles)", lib_count, transitive_count);
+        Ok(())
+    }
+    // transitive packages become Library nodes in the lockfile, and each
+    // package gets a Uses edge to the libraries it depends on
+    fn add_locked_libraries<G: Graph>(
+        &self,
+        graph: &mut G,
+        lock_file: &str,
+        pkgs: &[LockedPackage],
+        declared: &[NodeData],
+    ) -> usize {
+        let mut libs: std::collections::HashMap<String, NodeData> = declared
+            .iter()
+            .filter(|lib| lock_covers(lock_file, &lib.file))
+            .map(|lib| (lib.name.clone(), lib.clone()))
+            .collect();
+        let mut count = 0;
+        for pkg in pkgs {
+            if libs.contains_key(&pkg.name) {
+                continue;
+            }
+            let mut lib = NodeData::name_file(&pkg.name, lock_file);
+            lib.body = format!("{} {}", pkg.name, pkg.version);
+            lib.start = pkg.line;
+            lib.end = pkg.line;
+            lib.add_version(&pkg.version);
+            lib.add_resolved_version(&pkg.version);
+            lib.add_direct(false);
+            graph.add_node_with_parent(NodeType::Library, lib.clone(), NodeType::File, lock_file);
+            libs.insert(pkg.name.clone(), lib);
+            count += 1;
+        }
+        for pkg in pkgs {
+            let Some(lib) = libs.get(&pkg.name) else {
+                continue;
+            };
+            for dep in &pkg.dependencies {
+                if let Some(target) = libs.get(dep) {
+                    if target.name != lib.name {
+                        graph.add_edge(Edge::library_uses(lib, target));
+                    }
+                }
+            }
+        }
+        count
+    }
+    fn process_modules<G: Graph>(&self, graph: &mut G, filez: &[(String, String)]) -> Result<()> {
+        self.send_status_update("process_modules", 4);
+        info!("=> get_modules...");
+        let (modules, edges) = self.lang.collect_modules(filez)?;
+        let module_count = modules.len();
+        for module in modules {
+            graph.add_node(NodeType::Module, module);
+        }
+        for edge in edges {
+            graph.add_edge(edge);
+        }
+
+        let mut stats = std::collections::HashMap::new();
+        stats.insert("modules".to_string(), module_count);
+        self.send_status_with_stats(stats);
+        self.send_status_progress(100, 100, 4);
+        info!("=> got {} modules", module_count);
         Ok(())
     }
     fn process_import_sections<G: Graph>(
@@ -294,7 +394,7 @@ impl Repo {
         graph: &mut G,
         filez: &[(String, String)],
     ) -> Result<()> {
-        self.send_status_update("process_imports", 4);
+        self.send_status_update("process_imports", 5);
         let mut i = 0;
         let mut import_count = 0;
         let total = filez.len();
@@ -303,7 +403,7 @@ impl Repo {
         for (filename, code) in filez {
             i += 1;
             if i % 20 == 0 || i == total {
-                self.send_status_progress(i, total, 6);
+                self.send_status_progress(i, total, 7);
             }
 
             let imports = self.lang.get_imports::<G>(&code, &filename)?;
@@ -324,12 +424,12 @@ impl Repo {
         let mut stats = std::collections::HashMap::new();
         stats.insert("imports".to_string(), import_count);
         self.send_status_with_stats(stats);
-        self.send_status_progress(100, 100, 4);
+        self.send_status_progress(100, 100, 5);
         info!("=> got {} import sections", import_count);
         Ok(())
     }
     fn process_variables<G: Graph>(&self, graph: &mut G, filez: &[(String, String)]) -> Result<()> {
-        self.send_status_update("process_variables", 5);
+        self.send_status_update("process_variables", 6);
         let mut i = 0;
         let mut var_count = 0;
         let total = filez.len();
@@ -338,7 +438,7 @@ impl Repo {
         for (filename, code) in filez {
             i += 1;
             if i % 20 == 0 || i == total {
-                self.send_status_progress(i, total, 7);
+                self.send_status_progress(i, total, 8);
             }
 
             let variables = self.lang.get_vars::<G>(&code, &filename)?;
@@ -357,7 +457,7 @@ impl Repo {
         let mut stats = std::collections::HashMap::new();
         stats.insert("variables".to_string(), var_count);
         self.send_status_with_stats(stats);
-        self.send_status_progress(100, 100, 5);
+        self.send_status_progress(100, 100, 6);
 
         info!("=> got {} all vars", var_count);
         Ok(())
@@ -405,7 +505,7 @@ impl Repo {
         Ok(())
     }
     fn process_classes<G: Graph>(&self, graph: &mut G, filez: &[(String, String)]) -> Result<()> {
-        self.send_status_update("process_classes", 6);
+        self.send_status_update("process_classes", 7);
         let mut i = 0;
         let mut class_count = 0;
         let total = filez.len();
@@ -414,7 +514,7 @@ impl Repo {
         for (filename, code) in filez {
             i += 1;
             if i % 20 == 0 || i == total {
-                self.send_status_progress(i, total, 8);
+                self.send_status_progress(i, total, 9);
             }
 
             if !self.lang.kind.is_source_file(&filename) {
@@ -443,7 +543,7 @@ impl Repo {
         let mut stats = std::collections::HashMap::new();
         stats.insert("classes".to_string(), class_count);
         self.send_status_with_stats(stats);
-        self.send_status_progress(100, 100, 6);
+        self.send_status_progress(100, 100, 7);
 
         info!("=> got {} classes", class_count);
         graph.class_inherits();
@@ -455,7 +555,7 @@ impl Repo {
         graph: &mut G,
         filez: &[(String, String)],
     ) -> Result<()> {
-        self.send_status_update("process_instances_and_traits", 7);
+        self.send_status_update("process_instances_and_traits", 8);
         let mut cnt = 0;
         let mut instance_count = 0;
         let mut trait_count = 0;
@@ -465,7 +565,7 @@ impl Repo {
         for (filename, code) in filez {
             cnt += 1;
             if cnt % 20 == 0 || cnt == total {
-                self.send_status_progress(cnt, total, 9);
+                self.send_status_progress(cnt, total, 10);
             }
 
             if !self.lang.kind.is_source_file(&filename) {
@@ -506,7 +606,7 @@ impl Repo {
         stats.insert("instances".to_string(), instance_count);
         stats.insert("traits".to_string(), trait_count);
         self.send_status_with_stats(stats);
-        self.send_status_progress(100, 100, 7);
+        self.send_status_progress(100, 100, 8);
 
         info!("=> got {} traits", trait_count);
         Ok(())
@@ -516,7 +616,7 @@ impl Repo {
         graph: &mut G,
         filez: &[(String, String)],
     ) -> Result<()> {
-        self.send_status_update("process_data_models", 8);
+        self.send_status_update("process_data_models", 9);
         let mut i = 0;
         let mut datamodel_count = 0;
         let total = filez.len();
@@ -525,7 +625,7 @@ impl Repo {
         for (filename, code) in filez {
             i += 1;
             if i % 20 == 0 || i == total {
-                self.send_status_progress(i, total, 10);
+                self.send_status_progress(i, total, 11);
             }
 
             if !self.lang.kind.is_source_file(&filename) {
@@ -561,17 +661,70 @@ impl Repo {
         let mut stats = std::collections::HashMap::new();
         stats.insert("data_models".to_string(), datamodel_count);
         self.send_status_with_stats(stats);
-        self.send_status_progress(100, 100, 8);
+        self.send_status_progress(100, 100, 9);
 
         info!("=> got {} data models", datamodel_count);
         Ok(())
     }
+    fn process_type_definitions<G: Graph>(
+        &self,
+        graph: &mut G,
+        filez: &[(String, String)],
+    ) -> Result<()> {
+        self.send_status_update("process_type_definitions", 10);
+        let mut i = 0;
+        let mut type_count = 0;
+        let mut const_count = 0;
+        let total = filez.len();
+
+        info!("=> get_type_definitions...");
+        for (filename, code) in filez {
+            i += 1;
+            if i % 20 == 0 || i == total {
+                self.send_status_progress(i, total, 10);
+            }
+
+            if !self.lang.kind.is_source_file(filename) {
+                continue;
+            }
+            let stack = self.lang.lang();
+            let queries = [
+                (NodeType::Enum, stack.enum_query()),
+                (NodeType::Interface, stack.interface_query()),
+                (NodeType::TypeAlias, stack.type_alias_query()),
+                (NodeType::Const, stack.const_query()),
+            ];
+            for (node_type, q) in queries {
+                let nodes = self
+                    .lang
+                    .get_query_opt::<G>(q, code, filename, node_type.clone())?;
+                if node_type == NodeType::Const {
+                    const_count += nodes.len();
+                } else {
+                    type_count += nodes.len();
+                }
+                for nd in nodes {
+                    let file = nd.file.clone();
+                    graph.add_node_with_parent(node_type.clone(), nd, NodeType::File, &file);
+                }
+            }
+        }
+
+        let mut stats = std::collections::HashMap::new();
+        stats.insert("type_definitions".to_string(), type_count);
+        stats.insert("constants".to_string(), const_count);
+        self.send_status_with_stats(stats);
+        self.send_status_progress(100, 100, 10);
+
+        info!("=> got {} type definitions and {} constants", type_count, const_count);
+        Ok(())
+    }
     async fn process_functions_and_tests<G: Graph>(
         &self,
         graph: &mut G,
         filez: &[(String, String)],
     ) -> Result<()> {
-        self.send_status_update("process_functions_and_tests", 9);
+        self.send_status_update("process_functions_and_tests", 11);
         let mut i = 0;
         let mut function_count = 0;
         let mut test_count = 0;
@@ -581,7 +734,7 @@ impl Repo {
         for (filename, code) in filez {
             i += 1;
             if i % 10 == 0 || i == total {
-                self.send_status_progress(i, total, 11);
+                self.send_status_progress(i, total, 13);
             }
 
             if !self.lang.kind.is_source_file(&filename) {
@@ -600,7 +753,7 @@ impl Repo {
         stats.insert("functions".to_string(), function_count);
         stats.insert("tests".to_string(), test_count);
         self.send_status_with_stats(stats);
-        self.send_status_progress(100, 100, 9);
+        self.send_status_progress(100, 100, 11);
 
         info!("=> got {} functions and tests", function_count + test_count);
         Ok(())
@@ -610,7 +763,7 @@ impl Repo {
         graph: &mut G,
         filez: &[(String, String)],
     ) -> Result<()> {
-        self.send_status_update("process_pages_and_templates", 10);
+        self.send_status_update("process_pages_and_templates", 12);
         let mut i = 0;
         let mut page_count = 0;
         let mut template_count = 0;
@@ -620,7 +773,7 @@ impl Repo {
         for (filename, code) in filez {
             i += 1;
             if i % 10 == 0 || i == total {
-                self.send_status_progress(i, total, 12);
+                self.send_status_progress(i, total, 14);
             }
 
             if self.lang.lang().is_router_file(&filename, &code) {
@@ -696,7 +849,7 @@ impl Repo {
         stats.insert("pages".to_string(), page_count);
         stats.insert("templates".to_string(), template_count);
         self.send_status_with_stats(stats);
-        self.send_status_progress(100, 100, 10);
+        self.send_status_progress(100, 100, 12);
 
         info!("=> got {} component templates/styles", template_count);
 
@@ -727,7 +880,7 @@ impl Repo {
         Ok(())
     }
     fn process_endpoints<G: Graph>(&self, graph: &mut G, filez: &[(String, String)]) -> Result<()> {
-        self.send_status_update("process_endpoints", 11);
+        self.send_status_update("process_endpoints", 13);
         let mut _i = 0;
         let mut endpoint_count = 0;
         let total = filez.len();
@@ -736,14 +889,14 @@ impl Repo {
         for (filename, code) in filez {
             _i += 1;
             if _i % 10 == 0 || _i == total {
-                self.send_status_progress(_i, total, 11);
+                self.send_status_progress(_i, total, 13);
             }
 
             if !self.lang.kind.is_source_file(&filename) {
                 continue;
             }
             if let Some(epf) = self.lang.lang().endpoint_path_filter() {
-                if !filename.contains(&epf) {
+                if !filename.contains(&epf) && !self.lang.lang().is_channel_file(filename) {
                     continue;
                 }
             }
@@ -792,15 +945,31 @@ impl Repo {
         filez: &[(String, String)],
         stats: &mut std::collections::HashMap<String, usize>,
     ) -> Result<()> {
+        let mut modules = self.lang.module_index(graph);
+        if !modules.is_empty() {
+            info!("=> get_reexports...");
+            let reexports = self.lang.collect_reexports(filez, graph, &mut modules)?;
+            stats.insert("reexports".to_string(), reexports.len());
+            info!("=> got {} reexports", reexports.len());
+            for edge in reexports {
+                graph.add_edge(edge);
+            }
+        }
+
         let mut _i = 0;
         let mut import_edges_count = 0;
         info!("=> get_import_edges...");
         for (filename, code) in filez {
             if let Some(import_query) = self.lang.lang().imports_query() {
                 let q = self.lang.q(&import_query, &NodeType::Import);
-                let import_edges =
-                    self.lang
-                        .collect_import_edges(&q, &code, &filename, graph, &self.lsp_tx)?;
+                let import_edges = self.lang.collect_import_edges(
+                    &q,
+                    &code,
+                    &filename,
+                    graph,
+                    &self.lsp_tx,
+                    &modules,
+                )?;
                 for edge in import_edges {
                     graph.add_edge(edge);
                     import_edges_count += 1;
@@ -811,7 +980,33 @@ impl Repo {
         stats.insert("import_edges".to_string(), import_edges_count);
         info!("=> got {} import edges", import_edges_count);
 
-        self.send_status_update("process_integration_tests", 12);
+        let mut library_uses_count = 0;
+        let libs = graph.find_nodes_by_type(NodeType::Library);
+        if let Some(import_query) = self.lang.lang().imports_query() {
+            info!("=> get_library_uses...");
+            let q = self.lang.q(&import_query, &NodeType::Import);
+            for (filename, code) in filez {
+                if libs.is_empty() {
+                    break;
+                }
+                if !self.lang.kind.is_source_file(filename)
+                    || self.lang.kind.is_package_file(filename)
+                {
+                    continue;
+                }
+                let edges = self
+                    .lang
+                    .collect_library_uses(&q, code, filename, &libs, graph)?;
+                library_uses_count += edges.len();
+                for edge in edges {
+                    graph.add_edge(edge);
+                }
+            }
+        }
+        stats.insert("library_uses".to_string(), library_uses_count);
+        info!("=> got {} library uses edges", library_uses_count);
+
+        self.send_status_update("process_integration_tests", 14);
 
         let mut _i = 0;
         let mut cnt = 0;
@@ -823,7 +1018,7 @@ impl Repo {
             for (filename, code) in filez {
                 cnt += 1;
                 if cnt % 10 == 0 || cnt == total {
-                    self.send_status_progress(cnt, total, 12);
+                    self.send_status_progress(cnt, total, 14);
                 }
 
                 if !self.lang.lang().is_test_file(&filename) {
@@ -846,7 +1041,7 @@ impl Repo {
         if skip_calls {
             info!("=> Skipping function_calls...");
         } else {
-            self.send_status_update("process_function_calls", 13);
+            self.send_status_update("process_function_calls", 15);
             _i = 0;
             let mut cnt = 0;
             let mut function_call_count = 0;
@@ -856,7 +1051,7 @@ impl Repo {
             for (filename, code) in filez {
                 cnt += 1;
                 if cnt % 5 == 0 || cnt == total {
-                    self.send_status_progress(cnt, total, 13);
+                    self.send_status_progress(cnt, total, 15);
                 }
 
                 let all_calls = self
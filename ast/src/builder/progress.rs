@@ -32,11 +32,13 @@ impl Repo {
             "initialization" => "Initializing repository, directories, and files",
             "setup_lsp" => "Initializing language server",
             "process_libraries" => "Analyzing libraries",
+            "process_modules" => "Building module hierarchy",
             "process_imports" => "Processing imports",
             "process_variables" => "Analyzing variables",
             "process_classes" => "Detecting classes",
             "process_instances_and_traits" => "Processing traits",
             "process_data_models" => "Analyzing data models",
+            "process_type_definitions" => "Analyzing enums, interfaces and constants",
             "process_functions_and_tests" => "Processing functions and tests",
             "process_pages_and_templates" => "Analyzing pages and templates",
             "process_endpoints" => "Detecting endpoints",
@@ -52,7 +54,7 @@ impl Repo {
             status: "".to_string(),
             message: formatted_msg,
             step,
-            total_steps: 16,
+            total_steps: 18,
             progress: 0,
             stats: None,
             step_description: Some(step_description.to_string()),
@@ -96,7 +98,7 @@ impl Repo {
             *last_time_mutex.lock().unwrap() = now;
 
             let su = StatusUpdate {
-                total_steps: 16,
+                total_steps: 18,
                 progress: current_progress,
                 step,
                 ..Default::default()
@@ -112,7 +114,7 @@ impl Repo {
 
     pub fn send_status_with_stats(&self, stats: HashMap<String, usize>) {
         let su = StatusUpdate {
-            total_steps: 16,
+            total_steps: 18,
             stats: Some(stats),
             ..Default::default()
         };
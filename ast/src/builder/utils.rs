@@ -1,3 +1,4 @@
+use crate::lang::parse::docs::doc_tags;
 use crate::lang::{asg::NodeData, graphs::NodeType};
 use crate::lang::{Graph, Node};
 use crate::repo::{check_revs_files, Repo};
@@ -97,6 +98,12 @@ impl Repo {
             file_data.body = code.to_string();
         }
         file_data.hash = Some(sha256::digest(&file_data.body));
+        if self.lang.kind.is_source_file(path) {
+            if let Ok(Some(docs)) = self.lang.module_docs(code) {
+                file_data.meta.extend(doc_tags(&docs));
+                file_data.docs = Some(docs);
+            }
+        }
         file_data
     }
     pub fn get_parent_info(&self, path: &PathBuf) -> (NodeType, String) {
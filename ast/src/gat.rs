@@ -1,3 +1,4 @@
+use crate::lang::graphs::impact::DiffHunk;
 use git2::{DiffOptions, Repository};
 use shared::error::Result;
 
@@ -39,3 +40,35 @@ pub fn get_changed_files(repo_path: &str, old_rev: &str, new_rev: &str) -> Resul
 
     Ok(changed_files)
 }
+
+// line ranges changed between two revisions, on the new side
+pub fn get_changed_hunks(repo_path: &str, old_rev: &str, new_rev: &str) -> Result<Vec<DiffHunk>> {
+    let repo = Repository::open(repo_path)?;
+    let old_tree = repo.revparse_single(old_rev)?.peel_to_commit()?.tree()?;
+    let new_tree = repo.revparse_single(new_rev)?.peel_to_commit()?.tree()?;
+    let mut diff_opts = DiffOptions::new();
+    diff_opts.context_lines(0);
+    let diff = repo.diff_tree_to_tree(Some(&old_tree), Some(&new_tree), Some(&mut diff_opts))?;
+
+    let mut hunks = Vec::new();
+    diff.foreach(
+        &mut |_, _| true,
+        None,
+        Some(&mut |delta, hunk| {
+            // deleted files have nothing left in the new graph
+            if delta.status() != git2::Delta::Deleted {
+                if let Some(path) = delta.new_file().path().and_then(|p| p.to_str()) {
+                    hunks.push(DiffHunk::from_new_side(
+                        path,
+                        hunk.new_start() as usize,
+                        hunk.new_lines() as usize,
+                    ));
+                }
+            }
+            true
+        }),
+        None,
+    )?;
+
+    Ok(hunks)
+}
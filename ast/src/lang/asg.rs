@@ -1,11 +1,15 @@
 use crate::lang::NodeType;
 use serde::ser::{SerializeMap, Serializer};
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Deserializer, Serialize};
 use shared::error::{Error, Result};
 use std::collections::BTreeMap;
 use std::str::FromStr;
 use crate::lang::Edge;
 
+#[cfg(feature = "neo4j")]
+use crate::lang::graphs::importance::{IMPORTANCE_KEYS, PAGERANK};
+#[cfg(feature = "neo4j")]
+use crate::lang::parse::metrics::METRIC_KEYS;
 #[cfg(feature = "neo4j")]
 use crate::lang::graphs::neo4j_utils::{boltmap_insert_int, boltmap_insert_str};
 #[cfg(feature = "neo4j")]
@@ -63,21 +67,16 @@ impl From<NodeData> for NodeKeys {
     }
 }
 
-#[derive(Clone, Debug, Deserialize, Default, Eq, PartialEq, PartialOrd, Ord, Hash)]
+#[derive(Clone, Debug, Default, Eq, PartialEq, PartialOrd, Ord, Hash)]
 pub struct NodeData {
     pub name: String,
     pub file: String,
     pub body: String,
     pub start: usize,
     pub end: usize,
-    #[serde(skip_serializing_if = "Option::is_none")]
     pub docs: Option<String>,
-    #[serde(skip_serializing_if = "Option::is_none")]
     pub hash: Option<String>,
-    #[serde(skip_serializing_if = "Option::is_none")]
     pub data_type: Option<String>,
-    #[serde(default)]
-    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
     pub meta: BTreeMap<String, String>,
 }
 
@@ -119,6 +118,54 @@ impl Serialize for NodeData {
     }
 }
 
+// meta is serialized flat next to the named fields, so collect the leftover keys back into it
+impl<'de> Deserialize<'de> for NodeData {
+    fn deserialize<D>(deserializer: D) -> std::result::Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        #[derive(Deserialize)]
+        struct RawNodeData {
+            name: String,
+            file: String,
+            body: String,
+            start: usize,
+            end: usize,
+            docs: Option<String>,
+            hash: Option<String>,
+            data_type: Option<String>,
+            #[serde(default)]
+            meta: BTreeMap<String, String>,
+            #[serde(flatten)]
+            extra: BTreeMap<String, serde_json::Value>,
+        }
+        let raw = RawNodeData::deserialize(deserializer)?;
+        let mut meta = raw.meta;
+        for (k, v) in raw.extra {
+            match v {
+                serde_json::Value::Null => {}
+                serde_json::Value::String(s) => {
+                    meta.insert(k, s);
+                }
+                other => {
+                    meta.insert(k, other.to_string());
+                }
+            }
+        }
+        Ok(NodeData {
+            name: raw.name,
+            file: raw.file,
+            body: raw.body,
+            start: raw.start,
+            end: raw.end,
+            docs: raw.docs,
+            hash: raw.hash,
+            data_type: raw.data_type,
+            meta,
+        })
+    }
+}
+
 impl NodeData {
     pub fn name_file(name: &str, file: &str) -> Self {
         Self {
@@ -160,6 +207,13 @@ impl NodeData {
     pub fn add_version(&mut self, version: &str) {
         self.meta.insert("version".to_string(), version.to_string());
     }
+    pub fn add_resolved_version(&mut self, version: &str) {
+        self.meta
+            .insert("resolved_version".to_string(), version.to_string());
+    }
+    pub fn add_direct(&mut self, direct: bool) {
+        self.meta.insert("direct".to_string(), direct.to_string());
+    }
     pub fn add_action(&mut self, action: &str) {
         self.meta.insert("action".to_string(), action.to_string());
     }
@@ -191,6 +245,10 @@ impl NodeData {
         if interface.is_empty() { return; }
         self.meta.insert("interface".to_string(), interface.to_string());
     }
+    pub fn add_variants(&mut self, variants: &[String]) {
+        if variants.is_empty() { return; }
+        self.meta.insert("variants".to_string(), variants.join(","));
+    }
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -245,12 +303,21 @@ impl FromStr for NodeType {
             "E2etest" => Ok(NodeType::E2eTest),
             "File" => Ok(NodeType::File),
             "Repository" => Ok(NodeType::Repository),
+            "Language" => Ok(NodeType::Language),
+            "Directory" => Ok(NodeType::Directory),
+            "Import" => Ok(NodeType::Import),
+            "Library" => Ok(NodeType::Library),
             "Endpoint" => Ok(NodeType::Endpoint),
             "Request" => Ok(NodeType::Request),
             "Datamodel" => Ok(NodeType::DataModel),
             "Feature" => Ok(NodeType::Feature),
             "Page" => Ok(NodeType::Page),
             "Var" => Ok(NodeType::Var),
+            "Enum" => Ok(NodeType::Enum),
+            "Interface" => Ok(NodeType::Interface),
+            "TypeAlias" => Ok(NodeType::TypeAlias),
+            "Const" => Ok(NodeType::Const),
+            "Module" => Ok(NodeType::Module),
             _ => Err(Error::Custom(format!("Invalid NodeType string: {}", s))),
         }
     }
@@ -277,6 +344,11 @@ impl ToString for NodeType {
             NodeType::Feature => "Feature".to_string(),
             NodeType::Page => "Page".to_string(),
             NodeType::Var => "Var".to_string(),
+            NodeType::Enum => "Enum".to_string(),
+            NodeType::Interface => "Interface".to_string(),
+            NodeType::TypeAlias => "TypeAlias".to_string(),
+            NodeType::Const => "Const".to_string(),
+            NodeType::Module => "Module".to_string(),
         }
     }
 }
@@ -367,6 +439,14 @@ impl TryFrom<&BoltNode> for NodeData {
             if !known_fields.contains(&k) {
                 if let Ok(val) = node.get::<String>(k) {
                     meta.insert(k.to_string(), val);
+                } else if k == PAGERANK {
+                    if let Ok(val) = node.get::<f64>(k) {
+                        meta.insert(k.to_string(), format!("{:.6}", val));
+                    }
+                } else if IMPORTANCE_KEYS.contains(&k) || METRIC_KEYS.contains(&k) {
+                    if let Ok(val) = node.get::<i64>(k) {
+                        meta.insert(k.to_string(), val.to_string());
+                    }
                 }
             }
         }
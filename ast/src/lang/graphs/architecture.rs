@@ -0,0 +1,288 @@
+use super::{graph::Graph, EdgeType, Node};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+
+// a named group of paths and the other layers it may depend on, from .ast.json:
+// "layers": [{ "name": "handlers", "paths": ["src/handlers"], "may_import": ["services"] }]
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct Layer {
+    pub name: String,
+    pub paths: Vec<String>,
+    #[serde(default)]
+    pub may_import: Vec<String>,
+}
+
+impl Layer {
+    // paths are relative to the repo, graph files may carry the repo name in front
+    pub fn contains(&self, file: &str) -> bool {
+        self.paths.iter().any(|path| {
+            let path = path.trim_matches('/');
+            std::iter::once(0)
+                .chain(file.match_indices('/').map(|(i, _)| i + 1))
+                .any(|start| {
+                    file[start..]
+                        .strip_prefix(path)
+                        .is_some_and(|rest| rest.is_empty() || rest.starts_with('/'))
+                })
+        })
+    }
+}
+
+// one node-level edge that makes a file depend on another
+#[derive(Clone, Debug, Serialize)]
+pub struct Dependency {
+    pub edge_type: EdgeType,
+    pub source: Node,
+    pub target: Node,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct DependencyCycle {
+    pub files: Vec<String>,
+    // the edges between files of the cycle
+    pub edges: Vec<Dependency>,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct LayerViolation {
+    pub from_layer: String,
+    pub to_layer: String,
+    pub dependency: Dependency,
+}
+
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct ArchitectureReport {
+    pub cycles: Vec<DependencyCycle>,
+    pub violations: Vec<LayerViolation>,
+}
+
+impl ArchitectureReport {
+    pub fn is_clean(&self) -> bool {
+        self.cycles.is_empty() && self.violations.is_empty()
+    }
+
+    pub fn print(&self) {
+        for cycle in &self.cycles {
+            println!("Cycle: {}", cycle.files.join(" -> "));
+        }
+        for v in &self.violations {
+            println!(
+                "Layer violation: {} -> {} ({} {:?} {} in {})",
+                v.from_layer,
+                v.to_layer,
+                v.dependency.source.node_data.name,
+                v.dependency.edge_type,
+                v.dependency.target.node_data.name,
+                v.dependency.source.node_data.file,
+            );
+        }
+    }
+}
+
+// file -> file dependencies: a file importing something, or a function calling into
+// another file. edges within one file are dropped
+pub fn file_dependencies<G: Graph>(graph: &G) -> BTreeMap<(String, String), Vec<Dependency>> {
+    let mut deps: BTreeMap<(String, String), Vec<Dependency>> = BTreeMap::new();
+    for (edge_type, source, target) in graph.find_all_edges() {
+        if !matches!(edge_type, EdgeType::Imports | EdgeType::Calls) {
+            continue;
+        }
+        let (from, to) = (&source.node_data.file, &target.node_data.file);
+        if from.is_empty() || to.is_empty() || from == to {
+            continue;
+        }
+        deps.entry((from.clone(), to.clone()))
+            .or_default()
+            .push(Dependency {
+                edge_type,
+                source,
+                target,
+            });
+    }
+    deps
+}
+
+// strongly connected components of the file graph with more than one file (tarjan)
+fn components(deps: &BTreeMap<(String, String), Vec<Dependency>>) -> Vec<Vec<String>> {
+    let mut next: BTreeMap<&str, Vec<&str>> = BTreeMap::new();
+    for (from, to) in deps.keys() {
+        next.entry(from).or_default().push(to);
+        next.entry(to).or_default();
+    }
+
+    let mut index: BTreeMap<&str, usize> = BTreeMap::new();
+    let mut low: BTreeMap<&str, usize> = BTreeMap::new();
+    let mut on_stack = BTreeSet::new();
+    let mut stack = Vec::new();
+    let mut found = Vec::new();
+    for &root in next.keys() {
+        if index.contains_key(root) {
+            continue;
+        }
+        // (file, position in its successor list)
+        let mut work = vec![(root, 0)];
+        while let Some(&mut (file, ref mut pos)) = work.last_mut() {
+            if *pos == 0 {
+                let i = index.len();
+                index.insert(file, i);
+                low.insert(file, i);
+                stack.push(file);
+                on_stack.insert(file);
+            }
+            if let Some(&succ) = next[file].get(*pos) {
+                *pos += 1;
+                if !index.contains_key(succ) {
+                    work.push((succ, 0));
+                } else if on_stack.contains(succ) {
+                    let l = low[file].min(index[succ]);
+                    low.insert(file, l);
+                }
+                continue;
+            }
+            work.pop();
+            if let Some(&(parent, _)) = work.last() {
+                let l = low[parent].min(low[file]);
+                low.insert(parent, l);
+            }
+            if low[file] == index[file] {
+                let mut component = Vec::new();
+                while let Some(member) = stack.pop() {
+                    on_stack.remove(member);
+                    component.push(member.to_string());
+                    if member == file {
+                        break;
+                    }
+                }
+                if component.len() > 1 {
+                    component.sort();
+                    found.push(component);
+                }
+            }
+        }
+    }
+    found.sort();
+    found
+}
+
+pub fn find_cycles<G: Graph>(graph: &G) -> Vec<DependencyCycle> {
+    cycles_in(&file_dependencies(graph))
+}
+
+fn cycles_in(deps: &BTreeMap<(String, String), Vec<Dependency>>) -> Vec<DependencyCycle> {
+    components(deps)
+        .into_iter()
+        .map(|files| {
+            let edges = deps
+                .iter()
+                .filter(|((from, to), _)| files.contains(from) && files.contains(to))
+                .flat_map(|(_, d)| d.iter().cloned())
+                .collect();
+            DependencyCycle { files, edges }
+        })
+        .collect()
+}
+
+fn layer_of<'a>(layers: &'a [Layer], file: &str) -> Option<&'a Layer> {
+    layers.iter().find(|l| l.contains(file))
+}
+
+// dependencies from one layer into another it does not list in may_import.
+// files outside every layer are not checked
+pub fn find_layer_violations<G: Graph>(graph: &G, layers: &[Layer]) -> Vec<LayerViolation> {
+    violations_in(&file_dependencies(graph), layers)
+}
+
+fn violations_in(
+    deps: &BTreeMap<(String, String), Vec<Dependency>>,
+    layers: &[Layer],
+) -> Vec<LayerViolation> {
+    let mut violations = Vec::new();
+    for ((from, to), edges) in deps {
+        let (Some(from_layer), Some(to_layer)) = (layer_of(layers, from), layer_of(layers, to))
+        else {
+            continue;
+        };
+        if from_layer.name == to_layer.name || from_layer.may_import.contains(&to_layer.name) {
+            continue;
+        }
+        for dependency in edges {
+            violations.push(LayerViolation {
+                from_layer: from_layer.name.clone(),
+                to_layer: to_layer.name.clone(),
+                dependency: dependency.clone(),
+            });
+        }
+    }
+    violations
+}
+
+pub fn check<G: Graph>(graph: &G, layers: &[Layer]) -> ArchitectureReport {
+    let deps = file_dependencies(graph);
+    ArchitectureReport {
+        cycles: cycles_in(&deps),
+        violations: violations_in(&deps, layers),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lang::graphs::{BTreeMapGraph, Edge, NodeRef, NodeType};
+    use crate::lang::NodeData;
+
+    #[test]
+    fn test_cycles_and_layers() {
+        let mut graph = BTreeMapGraph::default();
+        let f = NodeType::Function;
+        let handler = NodeData::name_file("create_user", "repo/src/handlers/users.rs");
+        let service = NodeData::name_file("save_user", "repo/src/services/users.rs");
+        let audit = NodeData::name_file("audit", "repo/src/services/audit.rs");
+        let model = NodeData::name_file("User", "repo/src/models/user.rs");
+        let file = NodeData::name_file("audit.rs", "repo/src/services/audit.rs");
+        for nd in [&handler, &service, &audit] {
+            graph.add_node(f.clone(), nd.clone());
+        }
+        graph.add_node(NodeType::DataModel, model.clone());
+        graph.add_node(NodeType::File, file.clone());
+        graph.add_edge(Edge::calls(f.clone(), &handler, f.clone(), &service));
+        graph.add_edge(Edge::calls(f.clone(), &service, f.clone(), &audit));
+        // services reaching back into handlers closes a cycle
+        graph.add_edge(Edge::calls(f.clone(), &audit, f.clone(), &handler));
+        graph.add_edge(Edge::new(
+            EdgeType::Imports,
+            NodeRef::from((&file).into(), NodeType::File),
+            NodeRef::from((&model).into(), NodeType::DataModel),
+        ));
+
+        let cycles = find_cycles(&graph);
+        assert_eq!(cycles.len(), 1);
+        assert_eq!(
+            cycles[0].files,
+            [
+                "repo/src/handlers/users.rs",
+                "repo/src/services/audit.rs",
+                "repo/src/services/users.rs",
+            ]
+        );
+        assert_eq!(cycles[0].edges.len(), 3);
+
+        let layers: Vec<Layer> = serde_json::from_str(
+            r#"[
+                { "name": "handlers", "paths": ["src/handlers"], "may_import": ["services"] },
+                { "name": "services", "paths": ["src/services/"], "may_import": ["models"] },
+                { "name": "models", "paths": ["src/models"] }
+            ]"#,
+        )
+        .unwrap();
+        assert!(!layers[0].contains("repo/src/handlers_old/x.rs"));
+        let report = check(&graph, &layers);
+        assert_eq!(report.violations.len(), 1);
+        let v = &report.violations[0];
+        assert_eq!(
+            (v.from_layer.as_str(), v.to_layer.as_str()),
+            ("services", "handlers")
+        );
+        assert_eq!(v.dependency.source.node_data.name, "audit");
+        assert!(!report.is_clean());
+    }
+}
@@ -6,8 +6,8 @@ use crate::utils::{create_node_key, create_node_key_from_ref, sanitize_string};
 use lsp::Language;
 use serde::{Deserialize, Serialize};
 use shared::error::Result;
-use std::collections::{BTreeMap, HashSet};
-use tracing::debug;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use tracing::{debug, info};
 #[cfg(feature = "neo4j")]
 use crate::builder::streaming;
 
@@ -50,6 +50,7 @@ impl Graph for ArrayGraph {
             let target_key = create_node_key_from_ref(&edge.target);
             println!("Edge: {} - {:?} -> {}", source_key, edge.edge, target_key);
         }
+        info!("dead code:\n{}", dead_code::find_dead_code(self));
     }
     fn create_filtered_graph(self, final_filter: &[String], lang_kind: Language) -> Self {
         let mut new_graph = Self::new(String::new(), lang_kind);
@@ -744,6 +745,55 @@ impl Graph for ArrayGraph {
                 && edge.target.node_data.file.ends_with(&target.node_data.file)
         })
     }
+
+    fn extend_node_meta(&mut self, updates: Vec<(Node, BTreeMap<String, String>)>) {
+        let mut updates: HashMap<String, BTreeMap<String, String>> = updates
+            .into_iter()
+            .map(|(node, meta)| (create_node_key(&node), meta))
+            .collect();
+        for node in self.nodes.iter_mut() {
+            if let Some(meta) = updates.remove(&create_node_key(node)) {
+                node.node_data.meta.extend(meta);
+                #[cfg(feature = "neo4j")]
+                if std::env::var("STREAM_UPLOAD").is_ok() { streaming::record_node(&node.node_type, &node.node_data); }
+            }
+        }
+    }
+
+    fn find_all_nodes(&self) -> Vec<Node> {
+        self.nodes.clone()
+    }
+
+    fn call_graph(&self) -> CallGraph {
+        let nodes: HashMap<String, &Node> =
+            self.nodes.iter().map(|n| (create_node_key(n), n)).collect();
+        let mut calls = CallGraph::default();
+        for edge in &self.edges {
+            if !call_graph::is_call_hop(&edge.edge, &edge.target.node_type) {
+                continue;
+            }
+            if let (Some(source), Some(target)) = (
+                nodes.get(&create_node_key_from_ref(&edge.source)),
+                nodes.get(&create_node_key_from_ref(&edge.target)),
+            ) {
+                calls.add_hop((*source).clone(), (*target).clone());
+            }
+        }
+        calls
+    }
+
+    fn find_all_edges(&self) -> Vec<(EdgeType, Node, Node)> {
+        let nodes: HashMap<String, &Node> =
+            self.nodes.iter().map(|n| (create_node_key(n), n)).collect();
+        self.edges
+            .iter()
+            .filter_map(|edge| {
+                let source = nodes.get(&create_node_key_from_ref(&edge.source))?;
+                let target = nodes.get(&create_node_key_from_ref(&edge.target))?;
+                Some((edge.edge.clone(), (*source).clone(), (*target).clone()))
+            })
+            .collect()
+    }
 }
 
 impl ArrayGraph {
@@ -757,6 +807,19 @@ impl ArrayGraph {
         })
     }
 
+    // rebuilds the key sets for nodes and edges read back from a snapshot
+    pub(crate) fn from_parts(nodes: Vec<Node>, edges: Vec<Edge>, errors: Vec<String>) -> Self {
+        let mut graph = ArrayGraph {
+            node_keys: nodes.iter().map(create_node_key).collect(),
+            ..Default::default()
+        };
+        graph.edge_keys = edges.iter().map(|e| graph.create_edge_key(e)).collect();
+        graph.nodes = nodes;
+        graph.edges = edges;
+        graph.errors = errors;
+        graph
+    }
+
     pub fn find_index_by_name(&self, nt: NodeType, name: &str) -> Option<usize> {
         self.nodes
             .iter()
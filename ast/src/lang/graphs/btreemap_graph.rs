@@ -5,6 +5,7 @@ use lsp::Language;
 use serde::Serialize;
 use shared::error::Result;
 use std::collections::{BTreeMap, BTreeSet, HashSet};
+use tracing::info;
 #[cfg(feature = "neo4j")]
 use crate::builder::streaming;
 
@@ -39,6 +40,7 @@ impl Graph for BTreeMapGraph {
         for (src_key, dst_key, edge_type) in &self.edges {
             println!("Edge: {} - {:?} -> {}", src_key, edge_type, dst_key);
         }
+        info!("dead code:\n{}", dead_code::find_dead_code(self));
     }
 
     fn extend_graph(&mut self, other: Self) {
@@ -761,9 +763,53 @@ impl Graph for BTreeMapGraph {
             return false;
         }
     }
+
+    fn find_all_nodes(&self) -> Vec<Node> {
+        self.nodes.values().cloned().collect()
+    }
+
+    fn call_graph(&self) -> CallGraph {
+        let mut calls = CallGraph::default();
+        for (src_key, dst_key, edge_type) in &self.edges {
+            if let (Some(source), Some(target)) = (self.nodes.get(src_key), self.nodes.get(dst_key))
+            {
+                if call_graph::is_call_hop(edge_type, &target.node_type) {
+                    calls.add_hop(source.clone(), target.clone());
+                }
+            }
+        }
+        calls
+    }
+
+    fn find_all_edges(&self) -> Vec<(EdgeType, Node, Node)> {
+        self.edges
+            .iter()
+            .filter_map(|(src_key, dst_key, edge_type)| {
+                let source = self.nodes.get(src_key)?;
+                let target = self.nodes.get(dst_key)?;
+                Some((edge_type.clone(), source.clone(), target.clone()))
+            })
+            .collect()
+    }
 }
 
 impl BTreeMapGraph {
+    // rebuilds the edge keys for nodes and edges read back from a snapshot
+    pub(crate) fn from_parts(
+        nodes: BTreeMap<String, Node>,
+        edges: BTreeSet<(String, String, EdgeType)>,
+    ) -> Self {
+        let edge_keys = edges
+            .iter()
+            .map(|(src, dst, edge_type)| format!("{}-{}-{:?}", src, dst, edge_type))
+            .collect();
+        BTreeMapGraph {
+            nodes,
+            edges,
+            edge_keys,
+        }
+    }
+
     pub fn to_array_graph_edges(&self) -> Vec<Edge> {
         let mut formatted_edges = Vec::with_capacity(self.edges.len());
 
@@ -0,0 +1,208 @@
+use super::{graph::Graph, EdgeType, Node, NodeType};
+use crate::utils::create_node_key;
+use std::collections::{HashMap, VecDeque};
+
+// longest call path shortest_call_path looks for (the neo4j query needs a bound)
+pub const MAX_CALL_DEPTH: usize = 15;
+
+// an edge is a call hop if it is a call, an endpoint handing off to its handler, or a
+// function touching a data model, so "endpoint -> ... -> model" is a call path
+pub fn is_call_hop(edge_type: &EdgeType, target_type: &NodeType) -> bool {
+    match edge_type {
+        EdgeType::Calls | EdgeType::Handler => true,
+        EdgeType::Contains => *target_type == NodeType::DataModel,
+        _ => false,
+    }
+}
+
+// the node types found at either end of a call hop
+pub const CALLER_TYPES: [NodeType; 6] = [
+    NodeType::Function,
+    NodeType::UnitTest,
+    NodeType::IntegrationTest,
+    NodeType::E2eTest,
+    NodeType::Request,
+    NodeType::Endpoint,
+];
+pub const CALLEE_TYPES: [NodeType; 4] = [
+    NodeType::Function,
+    NodeType::Endpoint,
+    NodeType::Page,
+    NodeType::DataModel,
+];
+const CALL_HOP_EDGES: [EdgeType; 3] = [EdgeType::Calls, EdgeType::Handler, EdgeType::Contains];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CallDirection {
+    Callers,
+    Callees,
+}
+
+// adjacency over call hops only, keyed by node key
+#[derive(Debug, Default)]
+pub struct CallGraph {
+    nodes: HashMap<String, Node>,
+    callees: HashMap<String, Vec<String>>,
+    callers: HashMap<String, Vec<String>>,
+}
+
+impl CallGraph {
+    pub fn from_edges(edges: impl IntoIterator<Item = (EdgeType, Node, Node)>) -> Self {
+        let mut graph = CallGraph::default();
+        for (edge_type, source, target) in edges {
+            if is_call_hop(&edge_type, &target.node_type) {
+                graph.add_hop(source, target);
+            }
+        }
+        graph
+    }
+
+    // asks the graph for the call hop edges only, one (edge, source, target) type at a time
+    pub fn from_graph<G: Graph>(graph: &G) -> Self {
+        let mut calls = CallGraph::default();
+        for edge_type in CALL_HOP_EDGES {
+            for source_type in CALLER_TYPES {
+                for target_type in CALLEE_TYPES {
+                    if !is_call_hop(&edge_type, &target_type) {
+                        continue;
+                    }
+                    for (source, target) in graph.find_nodes_with_edge_type(
+                        source_type.clone(),
+                        target_type.clone(),
+                        edge_type.clone(),
+                    ) {
+                        calls.add_hop(
+                            Node::new(source_type.clone(), source),
+                            Node::new(target_type.clone(), target),
+                        );
+                    }
+                }
+            }
+        }
+        calls
+    }
+
+    pub fn add_hop(&mut self, source: Node, target: Node) {
+        let (source_key, target_key) = (create_node_key(&source), create_node_key(&target));
+        let callees = self.callees.entry(source_key.clone()).or_default();
+        if callees.contains(&target_key) {
+            return;
+        }
+        callees.push(target_key.clone());
+        self.callers
+            .entry(target_key.clone())
+            .or_default()
+            .push(source_key.clone());
+        self.nodes.entry(source_key).or_insert(source);
+        self.nodes.entry(target_key).or_insert(target);
+    }
+
+    fn next(&self, key: &str, direction: CallDirection) -> &[String] {
+        let map = match direction {
+            CallDirection::Callers => &self.callers,
+            CallDirection::Callees => &self.callees,
+        };
+        map.get(key).map(|v| v.as_slice()).unwrap_or_default()
+    }
+
+    pub fn neighbors(&self, node: &Node, direction: CallDirection) -> Vec<Node> {
+        self.next(&create_node_key(node), direction)
+            .iter()
+            .filter_map(|key| self.nodes.get(key).cloned())
+            .collect()
+    }
+
+    // every node within `depth` hops, with its distance, nearest first
+    pub fn walk(&self, start: &Node, direction: CallDirection, depth: usize) -> Vec<(Node, usize)> {
+        let start_key = create_node_key(start);
+        let mut seen = HashMap::from([(start_key.clone(), 0)]);
+        let mut queue = VecDeque::from([(start_key, 0)]);
+        let mut found = Vec::new();
+        while let Some((key, level)) = queue.pop_front() {
+            if level >= depth {
+                continue;
+            }
+            for next in self.next(&key, direction) {
+                if seen.contains_key(next) {
+                    continue;
+                }
+                seen.insert(next.clone(), level + 1);
+                if let Some(node) = self.nodes.get(next) {
+                    found.push((node.clone(), level + 1));
+                }
+                queue.push_back((next.clone(), level + 1));
+            }
+        }
+        found
+    }
+
+    // fewest hops from `from` to `to` following calls forward, both ends included
+    pub fn shortest_path(&self, from: &Node, to: &Node) -> Option<Vec<Node>> {
+        let (from_key, to_key) = (create_node_key(from), create_node_key(to));
+        if from_key == to_key {
+            return Some(vec![from.clone()]);
+        }
+        let mut parent: HashMap<String, String> = HashMap::new();
+        let mut queue = VecDeque::from([(from_key.clone(), 0)]);
+        while let Some((key, level)) = queue.pop_front() {
+            if level >= MAX_CALL_DEPTH {
+                continue;
+            }
+            for next in self.next(&key, CallDirection::Callees) {
+                if *next == from_key || parent.contains_key(next) {
+                    continue;
+                }
+                parent.insert(next.clone(), key.clone());
+                if *next == to_key {
+                    let mut path = vec![to.clone()];
+                    let mut cur = next;
+                    while let Some(prev) = parent.get(cur) {
+                        path.push(self.nodes.get(prev)?.clone());
+                        cur = prev;
+                    }
+                    path.reverse();
+                    return Some(path);
+                }
+                queue.push_back((next.clone(), level + 1));
+            }
+        }
+        None
+    }
+
+    // the fewest-hop path from `from` to every node of `node_type` it reaches within
+    // `depth` hops, both ends included, nearest first
+    pub fn paths_to_type(&self, from: &Node, node_type: &NodeType, depth: usize) -> Vec<Vec<Node>> {
+        let from_key = create_node_key(from);
+        let mut parent: HashMap<String, String> = HashMap::new();
+        let mut queue = VecDeque::from([(from_key.clone(), 0)]);
+        let mut found = Vec::new();
+        while let Some((key, level)) = queue.pop_front() {
+            if level >= depth {
+                continue;
+            }
+            for next in self.next(&key, CallDirection::Callees) {
+                if *next == from_key || parent.contains_key(next) {
+                    continue;
+                }
+                parent.insert(next.clone(), key.clone());
+                queue.push_back((next.clone(), level + 1));
+                if self.nodes.get(next).is_some_and(|n| n.node_type == *node_type) {
+                    found.push(next.clone());
+                }
+            }
+        }
+        found
+            .into_iter()
+            .filter_map(|key| {
+                let mut path = vec![self.nodes.get(&key)?.clone()];
+                let mut cur = &key;
+                while let Some(prev) = parent.get(cur) {
+                    path.push(self.nodes.get(prev)?.clone());
+                    cur = prev;
+                }
+                path.reverse();
+                Some(path)
+            })
+            .collect()
+    }
+}
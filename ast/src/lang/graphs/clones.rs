@@ -0,0 +1,277 @@
+use super::{graph::Graph, Edge, Node, NodeType};
+use crate::lang::{Lang, NodeData};
+use crate::utils::create_node_key;
+use lsp::language::PROGRAMMING_LANGUAGES;
+use serde::Serialize;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::hash::{Hash, Hasher};
+use tree_sitter::Node as TreeNode;
+
+pub const DEFAULT_THRESHOLD: f64 = 0.8;
+// bodies shorter than this once normalized are too small to call clones
+const MIN_TOKENS: usize = 30;
+const SHINGLE_SIZE: usize = 5;
+// shingles in more functions than this are boilerplate, and do not pair functions up
+const MAX_SHINGLE_FUNCTIONS: usize = 50;
+const IDENTIFIER: &str = "ID";
+
+#[derive(Clone, Debug, Serialize)]
+pub struct ClonePair {
+    pub source: Node,
+    pub target: Node,
+    // jaccard similarity of the normalized token shingles
+    pub similarity: f64,
+    // the same tokens once identifiers are anonymized
+    pub exact: bool,
+}
+
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct CloneReport {
+    // most similar first
+    pub pairs: Vec<ClonePair>,
+}
+
+impl CloneReport {
+    // keeps the pairs with either side under a directory or file
+    pub fn in_path(mut self, path: &str) -> Self {
+        self.pairs.retain(|p| {
+            p.source.node_data.file.starts_with(path) || p.target.node_data.file.starts_with(path)
+        });
+        self
+    }
+
+    pub fn print(&self) {
+        for p in &self.pairs {
+            println!(
+                "Clone {:.2}: {} ~ {}",
+                p.similarity,
+                create_node_key(&p.source),
+                create_node_key(&p.target)
+            );
+        }
+    }
+}
+
+// parsers of every language, built on first use
+struct Normalizer {
+    langs: Vec<Option<Lang>>,
+}
+
+impl Normalizer {
+    fn new() -> Self {
+        Normalizer {
+            langs: PROGRAMMING_LANGUAGES.iter().map(|_| None).collect(),
+        }
+    }
+
+    // the body's tokens without comments or whitespace, identifiers replaced by ID
+    fn tokens(&mut self, nd: &NodeData) -> Vec<String> {
+        let Some(i) = PROGRAMMING_LANGUAGES
+            .iter()
+            .position(|l| l.is_from_language(&nd.file))
+        else {
+            return plain_tokens(&nd.body);
+        };
+        let lang = self.langs[i]
+            .get_or_insert_with(|| Lang::from_language(PROGRAMMING_LANGUAGES[i].clone()));
+        match lang.lang().parse(&nd.body, &NodeType::Function) {
+            Ok(tree) => {
+                let mut tokens = Vec::new();
+                leaf_tokens(tree.root_node(), &nd.body, &mut tokens);
+                tokens
+            }
+            Err(_) => plain_tokens(&nd.body),
+        }
+    }
+}
+
+fn is_identifier(kind: &str) -> bool {
+    kind.ends_with("identifier") || kind == "constant" || kind == "name"
+}
+
+fn leaf_tokens(node: TreeNode, source: &str, tokens: &mut Vec<String>) {
+    let kind = node.kind();
+    if kind.contains("comment") {
+        return;
+    }
+    if is_identifier(kind) {
+        tokens.push(IDENTIFIER.to_string());
+        return;
+    }
+    if node.child_count() == 0 {
+        if let Ok(text) = node.utf8_text(source.as_bytes()) {
+            let text = text.trim();
+            if !text.is_empty() {
+                tokens.push(text.to_string());
+            }
+        }
+        return;
+    }
+    let mut cursor = node.walk();
+    for child in node.children(&mut cursor) {
+        leaf_tokens(child, source, tokens);
+    }
+}
+
+// for files no parser claims: words and single symbols, skipping line comments
+fn plain_tokens(body: &str) -> Vec<String> {
+    let mut tokens = Vec::new();
+    for line in body.lines() {
+        let line = line.trim();
+        if line.starts_with("//") || line.starts_with('#') {
+            continue;
+        }
+        let mut word = String::new();
+        for c in line.chars().chain(std::iter::once(' ')) {
+            if c.is_alphanumeric() || c == '_' {
+                word.push(c);
+                continue;
+            }
+            if !word.is_empty() {
+                let is_number = word.starts_with(|c: char| c.is_ascii_digit());
+                tokens.push(if is_number {
+                    std::mem::take(&mut word)
+                } else {
+                    word.clear();
+                    IDENTIFIER.to_string()
+                });
+            }
+            if !c.is_whitespace() {
+                tokens.push(c.to_string());
+            }
+        }
+    }
+    tokens
+}
+
+fn shingles(tokens: &[String]) -> HashSet<u64> {
+    tokens
+        .windows(SHINGLE_SIZE)
+        .map(|w| {
+            let mut hasher = DefaultHasher::new();
+            w.hash(&mut hasher);
+            hasher.finish()
+        })
+        .collect()
+}
+
+struct Candidate {
+    key: String,
+    node: Node,
+    digest: String,
+    shingles: HashSet<u64>,
+}
+
+fn overlaps(a: &NodeData, b: &NodeData) -> bool {
+    a.file == b.file && a.start <= b.end && b.start <= a.end
+}
+
+// pairs of functions whose normalized bodies are the same (exact) or share at least
+// `threshold` of their token shingles. each exact group links its members to the first
+pub fn find_clones<G: Graph>(graph: &G, threshold: f64) -> CloneReport {
+    let mut normalizer = Normalizer::new();
+    let mut candidates = Vec::new();
+    for nd in graph.find_nodes_by_type(NodeType::Function) {
+        let tokens = normalizer.tokens(&nd);
+        if tokens.len() < MIN_TOKENS {
+            continue;
+        }
+        let node = Node::new(NodeType::Function, nd);
+        candidates.push(Candidate {
+            key: create_node_key(&node),
+            node,
+            digest: sha256::digest(tokens.join(" ")),
+            shingles: shingles(&tokens),
+        });
+    }
+    candidates.sort_by(|a, b| a.key.cmp(&b.key));
+
+    let mut pairs = Vec::new();
+    let mut groups: BTreeMap<&str, Vec<usize>> = BTreeMap::new();
+    for (i, c) in candidates.iter().enumerate() {
+        groups.entry(c.digest.as_str()).or_default().push(i);
+    }
+    for members in groups.values() {
+        let first = &candidates[members[0]];
+        for &i in &members[1..] {
+            if overlaps(&first.node.node_data, &candidates[i].node.node_data) {
+                continue;
+            }
+            pairs.push(ClonePair {
+                source: first.node.clone(),
+                target: candidates[i].node.clone(),
+                similarity: 1.0,
+                exact: true,
+            });
+        }
+    }
+
+    let mut postings: HashMap<u64, Vec<usize>> = HashMap::new();
+    for (i, c) in candidates.iter().enumerate() {
+        for s in &c.shingles {
+            postings.entry(*s).or_default().push(i);
+        }
+    }
+    let mut shared: HashMap<(usize, usize), usize> = HashMap::new();
+    for ids in postings.values() {
+        if ids.len() > MAX_SHINGLE_FUNCTIONS {
+            continue;
+        }
+        for (x, &i) in ids.iter().enumerate() {
+            for &j in &ids[x + 1..] {
+                *shared.entry((i, j)).or_default() += 1;
+            }
+        }
+    }
+    for ((i, j), common) in shared {
+        let (a, b) = (&candidates[i], &candidates[j]);
+        if a.digest == b.digest || overlaps(&a.node.node_data, &b.node.node_data) {
+            continue;
+        }
+        let similarity = common as f64 / (a.shingles.len() + b.shingles.len() - common) as f64;
+        if similarity >= threshold {
+            pairs.push(ClonePair {
+                source: a.node.clone(),
+                target: b.node.clone(),
+                similarity,
+                exact: false,
+            });
+        }
+    }
+
+    pairs.sort_by(|a, b| {
+        b.similarity
+            .total_cmp(&a.similarity)
+            .then_with(|| create_node_key(&a.source).cmp(&create_node_key(&b.source)))
+            .then_with(|| create_node_key(&a.target).cmp(&create_node_key(&b.target)))
+    });
+    CloneReport { pairs }
+}
+
+fn scored_edges(report: &CloneReport) -> Vec<(Edge, f64)> {
+    report
+        .pairs
+        .iter()
+        .map(|p| {
+            let edge = Edge::similar_to(&p.source.node_data, &p.target.node_data);
+            (edge, p.similarity)
+        })
+        .collect()
+}
+
+// finds the clones and links each pair with a SIMILAR_TO edge carrying the similarity
+pub fn store_clones<G: Graph>(graph: &mut G, threshold: f64) -> CloneReport {
+    let report = find_clones(graph, threshold);
+    graph.add_scored_edges(scored_edges(&report));
+    report
+}
+
+// the SIMILAR_TO edges with their similarity, for uploads that stream plain edges
+#[cfg(feature = "neo4j")]
+pub fn scored_edge_queries(report: &CloneReport) -> Vec<(String, neo4rs::BoltMap)> {
+    scored_edges(report)
+        .iter()
+        .map(|(edge, score)| super::neo4j_utils::add_scored_edge_query(edge, *score))
+        .collect()
+}
@@ -0,0 +1,286 @@
+use super::impact::same_file;
+use super::importance::PAGERANK;
+use super::{graph::Graph, EdgeType, Node, NodeType};
+use crate::lang::NodeData;
+use crate::utils::{calculate_token_count, create_node_key};
+use serde::Serialize;
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap};
+
+// where a pack starts from
+#[derive(Clone, Debug)]
+pub enum Seed {
+    // every function, class, endpoint... with this name
+    Name(String),
+    // the innermost node around a 0-based line. the file may be relative to the repo
+    At { file: String, line: usize },
+    Node(Node),
+    // a vector search result and its similarity, which becomes its starting score
+    Hit(NodeData, f64),
+}
+
+const SEED_TYPES: [NodeType; 6] = [
+    NodeType::Function,
+    NodeType::Class,
+    NodeType::Endpoint,
+    NodeType::DataModel,
+    NodeType::Page,
+    NodeType::Trait,
+];
+
+// structure nodes are never packed, only code
+const SKIPPED_TYPES: [NodeType; 7] = [
+    NodeType::Repository,
+    NodeType::Language,
+    NodeType::Directory,
+    NodeType::File,
+    NodeType::Import,
+    NodeType::Library,
+    NodeType::Feature,
+];
+
+// how much of its score a node passes on per hop, following the edge or against it
+fn hop_weight(edge_type: &EdgeType, forward: bool) -> Option<f64> {
+    match (edge_type, forward) {
+        (EdgeType::Handler, _) => Some(0.8),
+        (EdgeType::Calls, true) => Some(0.7),
+        (EdgeType::Calls, false) => Some(0.5),
+        (EdgeType::Contains, true) => Some(0.6),
+        (EdgeType::Contains, false) => Some(0.4),
+        (EdgeType::ArgOf, _) => Some(0.5),
+        _ => None,
+    }
+}
+
+// nodes below this score are not worth expanding
+const MIN_SCORE: f64 = 0.01;
+// a top-ranked node counts up to this much more
+const IMPORTANCE_BONUS: f64 = 0.25;
+const SIGNATURE_LINES: usize = 5;
+
+#[derive(Clone, Debug, Serialize)]
+pub struct ContextItem {
+    pub node_type: NodeType,
+    pub name: String,
+    pub file: String,
+    pub start: usize,
+    pub end: usize,
+    pub score: f64,
+    // hops from the nearest seed
+    pub distance: usize,
+    pub tokens: usize,
+    // only the signature fit in the budget
+    pub signature_only: bool,
+    pub text: String,
+}
+
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct ContextPack {
+    pub budget: usize,
+    pub tokens: usize,
+    // highest score first
+    pub items: Vec<ContextItem>,
+    // candidates that did not fit
+    pub skipped: usize,
+}
+
+impl ContextPack {
+    pub fn render(&self) -> String {
+        self.items
+            .iter()
+            .map(|i| i.text.as_str())
+            .collect::<Vec<_>>()
+            .join("\n\n")
+    }
+}
+
+fn seed_nodes<G: Graph>(graph: &G, seed: &Seed) -> Vec<(Node, f64)> {
+    match seed {
+        Seed::Name(name) => SEED_TYPES
+            .iter()
+            .flat_map(|nt| {
+                graph
+                    .find_nodes_by_name(nt.clone(), name)
+                    .into_iter()
+                    .map(|nd| (Node::new(nt.clone(), nd), 1.0))
+            })
+            .collect(),
+        Seed::At { file, line } => SEED_TYPES
+            .iter()
+            .flat_map(|nt| {
+                graph
+                    .find_nodes_by_file_ends_with(nt.clone(), file)
+                    .into_iter()
+                    .filter(|nd| same_file(&nd.file, file) && nd.start <= *line && nd.end >= *line)
+                    .map(|nd| Node::new(nt.clone(), nd))
+            })
+            .min_by_key(|n| n.node_data.end - n.node_data.start)
+            .map(|n| vec![(n, 1.0)])
+            .unwrap_or_default(),
+        Seed::Node(node) => vec![(node.clone(), 1.0)],
+        Seed::Hit(hit, similarity) => SEED_TYPES
+            .iter()
+            .find_map(|nt| {
+                graph
+                    .find_node_by_name_in_file(nt.clone(), &hit.name, &hit.file)
+                    .map(|nd| (Node::new(nt.clone(), nd), *similarity))
+            })
+            .into_iter()
+            .collect(),
+    }
+}
+
+fn importance(node: &Node) -> f64 {
+    let rank = node
+        .node_data
+        .meta
+        .get(PAGERANK)
+        .and_then(|r| r.parse::<f64>().ok())
+        .unwrap_or(0.0);
+    1.0 + IMPORTANCE_BONUS * rank
+}
+
+// the declaration without its body: lines up to the one opening the body
+fn signature(body: &str) -> String {
+    let mut lines = Vec::new();
+    for line in body.lines().take(SIGNATURE_LINES) {
+        lines.push(line);
+        let trimmed = line.trim_end();
+        if trimmed.ends_with('{') || trimmed.ends_with(':') || trimmed.ends_with("do") {
+            break;
+        }
+    }
+    format!("{} ...", lines.join("\n"))
+}
+
+fn render_item(node: &Node, code: &str) -> String {
+    let nd = &node.node_data;
+    format!(
+        "// {}:{} {} {}\n{}",
+        nd.file,
+        nd.start + 1,
+        node.node_type.to_string(),
+        nd.name,
+        code
+    )
+}
+
+fn tokens(text: &str) -> usize {
+    calculate_token_count(text).unwrap_or(0).max(0) as usize
+}
+
+// best-first expansion from the seeds along Calls/Contains/Handler/ArgOf edges (each hop
+// scales the score down), then greedily packs bodies, or signatures when a body does not
+// fit, until `budget` tokens are used
+pub fn build_context_pack<G: Graph>(
+    graph: &G,
+    seeds: &[Seed],
+    budget: usize,
+    depth: usize,
+) -> ContextPack {
+    let mut neighbors: HashMap<String, Vec<(Node, f64)>> = HashMap::new();
+    for (edge_type, source, target) in graph.find_all_edges() {
+        if SKIPPED_TYPES.contains(&source.node_type) || SKIPPED_TYPES.contains(&target.node_type) {
+            continue;
+        }
+        if let Some(w) = hop_weight(&edge_type, true) {
+            neighbors
+                .entry(create_node_key(&source))
+                .or_default()
+                .push((target.clone(), w));
+        }
+        if let Some(w) = hop_weight(&edge_type, false) {
+            neighbors
+                .entry(create_node_key(&target))
+                .or_default()
+                .push((source, w));
+        }
+    }
+
+    // key -> (node, score, distance)
+    let mut best: HashMap<String, (Node, f64, usize)> = HashMap::new();
+    let mut frontier = BinaryHeap::new();
+    for seed in seeds {
+        for (node, score) in seed_nodes(graph, seed) {
+            let key = create_node_key(&node);
+            if best.get(&key).is_some_and(|(_, s, _)| *s >= score) {
+                continue;
+            }
+            frontier.push(((score * 1e9) as u64, Reverse(key.clone())));
+            best.insert(key, (node, score, 0));
+        }
+    }
+    while let Some((_, Reverse(key))) = frontier.pop() {
+        let (score, distance) = match best.get(&key) {
+            Some((_, score, distance)) => (*score, *distance),
+            None => continue,
+        };
+        if distance >= depth {
+            continue;
+        }
+        for (next, weight) in neighbors.get(&key).into_iter().flatten() {
+            let next_score = score * weight * importance(next);
+            if next_score < MIN_SCORE {
+                continue;
+            }
+            let next_key = create_node_key(next);
+            if best
+                .get(&next_key)
+                .is_some_and(|(_, s, _)| *s >= next_score)
+            {
+                continue;
+            }
+            best.insert(next_key.clone(), (next.clone(), next_score, distance + 1));
+            frontier.push(((next_score * 1e9) as u64, Reverse(next_key)));
+        }
+    }
+
+    let mut candidates: Vec<(String, Node, f64, usize)> = best
+        .into_iter()
+        .map(|(key, (node, score, distance))| (key, node, score, distance))
+        .collect();
+    candidates.sort_by(|a, b| b.2.total_cmp(&a.2).then_with(|| a.0.cmp(&b.0)));
+
+    let mut pack = ContextPack {
+        budget,
+        ..Default::default()
+    };
+    for (_, node, score, distance) in candidates {
+        let nd = &node.node_data;
+        // a method is already in the pack when its whole class is
+        let covered = pack.items.iter().any(|i| {
+            !i.signature_only && i.file == nd.file && i.start <= nd.start && nd.end <= i.end
+        });
+        if covered || nd.body.is_empty() {
+            continue;
+        }
+        let left = budget - pack.tokens;
+        let full = render_item(&node, &nd.body);
+        let full_tokens = tokens(&full);
+        let (text, item_tokens, signature_only) = if full_tokens <= left {
+            (full, full_tokens, false)
+        } else {
+            let short = render_item(&node, &signature(&nd.body));
+            let short_tokens = tokens(&short);
+            if short_tokens > left {
+                pack.skipped += 1;
+                continue;
+            }
+            (short, short_tokens, true)
+        };
+        pack.tokens += item_tokens;
+        pack.items.push(ContextItem {
+            node_type: node.node_type.clone(),
+            name: nd.name.clone(),
+            file: nd.file.clone(),
+            start: nd.start,
+            end: nd.end,
+            score,
+            distance,
+            tokens: item_tokens,
+            signature_only,
+            text,
+        });
+    }
+    pack
+}
@@ -0,0 +1,125 @@
+use super::graph::Graph;
+use super::utils::tests_sources;
+use super::{EdgeType, Node, NodeData, NodeType};
+use crate::utils::create_node_key;
+use std::collections::HashSet;
+
+#[derive(Debug, Clone)]
+pub struct CoverageStat {
+    pub total: usize,
+    pub total_tests: usize,
+    pub covered: usize,
+    pub percent: f64,
+}
+
+#[derive(Debug, Clone)]
+pub struct GraphCoverage {
+    pub unit_tests: Option<CoverageStat>,
+    pub integration_tests: Option<CoverageStat>,
+    pub e2e_tests: Option<CoverageStat>,
+}
+
+fn coverage_key(n: &NodeData) -> String {
+    format!("{}:{}:{}", n.name, n.file, n.start)
+}
+
+// unit tests cover functions, integration tests endpoints and e2e tests pages
+pub fn graph_coverage<G: Graph>(graph: &G, repo: Option<&str>) -> GraphCoverage {
+    let in_scope = |n: &NodeData| repo.is_none_or(|r| n.file.starts_with(r));
+    let scoped = |nt: NodeType| -> Vec<NodeData> {
+        graph
+            .find_nodes_by_type(nt)
+            .into_iter()
+            .filter(|n| in_scope(n))
+            .collect()
+    };
+    let targets = |src: NodeType, tgt: NodeType| -> HashSet<String> {
+        graph
+            .find_nodes_with_edge_type(src, tgt, EdgeType::Calls)
+            .iter()
+            .map(|(_, t)| coverage_key(t))
+            .collect()
+    };
+
+    let functions: Vec<NodeData> = scoped(NodeType::Function)
+        .into_iter()
+        .filter(|n| !n.body.trim().is_empty())
+        .filter(|n| n.meta.get("component").map(|v| v != "true").unwrap_or(true))
+        .collect();
+
+    let build_stat = |nodes: &[NodeData], tests: usize, covered: &HashSet<String>| {
+        if nodes.is_empty() {
+            return None;
+        }
+        let covered_count = nodes
+            .iter()
+            .filter(|n| covered.contains(&coverage_key(n)))
+            .count();
+        let percent = (covered_count as f64 / nodes.len() as f64) * 100.0;
+        Some(CoverageStat {
+            total: nodes.len(),
+            total_tests: tests,
+            covered: covered_count,
+            percent: (percent * 100.0).round() / 100.0,
+        })
+    };
+
+    GraphCoverage {
+        unit_tests: build_stat(
+            &functions,
+            scoped(NodeType::UnitTest).len(),
+            &targets(NodeType::UnitTest, NodeType::Function),
+        ),
+        integration_tests: build_stat(
+            &scoped(NodeType::Endpoint),
+            scoped(NodeType::IntegrationTest).len(),
+            &targets(NodeType::IntegrationTest, NodeType::Endpoint),
+        ),
+        e2e_tests: build_stat(
+            &scoped(NodeType::Page),
+            scoped(NodeType::E2eTest).len(),
+            &targets(NodeType::E2eTest, NodeType::Page),
+        ),
+    }
+}
+
+// whether a function (or the handler of an endpoint) is called by a test
+pub fn node_has_coverage<G: Graph>(
+    graph: &G,
+    node_type: NodeType,
+    name: &str,
+    file: &str,
+    start: Option<usize>,
+    root: Option<&str>,
+    tests_filter: Option<&str>,
+) -> bool {
+    let in_scope = |n: &NodeData| root.is_none_or(|r| n.file.starts_with(r));
+    let covered_funcs = || -> HashSet<String> {
+        tests_sources(tests_filter)
+            .into_iter()
+            .flat_map(|nt| graph.find_nodes_with_edge_type(nt, NodeType::Function, EdgeType::Calls))
+            .filter(|(_, f)| in_scope(f))
+            .map(|(_, f)| create_node_key(&Node::new(NodeType::Function, f)))
+            .collect()
+    };
+    let targets = match node_type {
+        NodeType::Function => graph
+            .find_node_by_name_in_file(NodeType::Function, name, file)
+            .filter(|n| start.is_none_or(|s| n.start == s))
+            .into_iter()
+            .collect::<Vec<_>>(),
+        NodeType::Endpoint => match graph.find_endpoint(name, file, "") {
+            Some(endpoint) if in_scope(&endpoint) => graph.find_handlers_for_endpoint(&endpoint),
+            _ => Vec::new(),
+        },
+        _ => Vec::new(),
+    };
+    let targets: Vec<NodeData> = targets.into_iter().filter(|n| in_scope(n)).collect();
+    if targets.is_empty() {
+        return false;
+    }
+    let covered = covered_funcs();
+    targets
+        .into_iter()
+        .any(|t| covered.contains(&create_node_key(&Node::new(NodeType::Function, t))))
+}
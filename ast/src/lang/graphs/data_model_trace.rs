@@ -0,0 +1,57 @@
+use super::call_graph::CallGraph;
+use super::{graph::Graph, Node, NodeType};
+use serde::Serialize;
+
+#[derive(Clone, Debug, Serialize)]
+pub struct DataModelTrace {
+    pub data_model: Node,
+    // the endpoint, its handler, the calls in between and the data model, in order
+    pub path: Vec<Node>,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct EndpointTrace {
+    pub endpoint: Node,
+    // nearest first
+    pub data_models: Vec<DataModelTrace>,
+}
+
+// data models the endpoint reaches through HANDLER -> CALLS* -> CONTAINS, each with the
+// fewest-hop path to it. the graph has no read/write distinction, so every use counts
+pub fn trace_endpoint(
+    call_graph: &CallGraph,
+    endpoint: &Node,
+    depth: usize,
+) -> Vec<DataModelTrace> {
+    call_graph
+        .paths_to_type(endpoint, &NodeType::DataModel, depth)
+        .into_iter()
+        .filter_map(|path| {
+            let data_model = path.last()?.clone();
+            Some(DataModelTrace { data_model, path })
+        })
+        .collect()
+}
+
+// every endpoint with the data models it touches, ordered by file and line
+pub fn trace_endpoints<G: Graph>(graph: &G, depth: usize) -> Vec<EndpointTrace> {
+    let call_graph = graph.call_graph();
+    let mut endpoints = graph.find_nodes_by_type(NodeType::Endpoint);
+    endpoints.sort_by(|a, b| {
+        a.file
+            .cmp(&b.file)
+            .then(a.start.cmp(&b.start))
+            .then_with(|| a.name.cmp(&b.name))
+    });
+    endpoints
+        .into_iter()
+        .map(|nd| {
+            let endpoint = Node::new(NodeType::Endpoint, nd);
+            let data_models = trace_endpoint(&call_graph, &endpoint, depth);
+            EndpointTrace {
+                endpoint,
+                data_models,
+            }
+        })
+        .collect()
+}
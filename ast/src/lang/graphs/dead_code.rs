@@ -0,0 +1,185 @@
+use super::{graph::Graph, EdgeType, Node, NodeType};
+use crate::lang::Lang;
+use crate::utils::create_node_key;
+use lsp::language::PROGRAMMING_LANGUAGES;
+use serde::Serialize;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::fmt;
+
+// an incoming edge of one of these means the target is used
+const USAGE_EDGES: [EdgeType; 3] = [EdgeType::Calls, EdgeType::Handler, EdgeType::Renders];
+
+const CANDIDATE_TYPES: [NodeType; 2] = [NodeType::Function, NodeType::Class];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DeadKind {
+    // nothing uses it
+    Unused,
+    // used, but only from other dead code
+    Unreachable,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct DeadCode {
+    pub node: Node,
+    pub kind: DeadKind,
+}
+
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct DeadCodeReport {
+    // ordered by file, then line
+    pub dead: Vec<DeadCode>,
+}
+
+impl DeadCodeReport {
+    pub fn of_kind(&self, kind: DeadKind) -> impl Iterator<Item = &DeadCode> {
+        self.dead.iter().filter(move |d| d.kind == kind)
+    }
+
+    // keeps the nodes under a directory or file
+    pub fn in_path(mut self, path: &str) -> Self {
+        self.dead
+            .retain(|d| d.node.node_data.file.starts_with(path));
+        self
+    }
+}
+
+impl fmt::Display for DeadCodeReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for d in &self.dead {
+            writeln!(f, "{:?}: {}", d.kind, create_node_key(&d.node))?;
+        }
+        Ok(())
+    }
+}
+
+// entrypoint rules of every language that claims the file, built on first use
+struct Entrypoints {
+    langs: Vec<Option<Lang>>,
+    sources: HashMap<String, String>,
+}
+
+impl Entrypoints {
+    fn new(sources: HashMap<String, String>) -> Self {
+        Entrypoints {
+            langs: PROGRAMMING_LANGUAGES.iter().map(|_| None).collect(),
+            sources,
+        }
+    }
+
+    fn contains(&mut self, node: &Node) -> bool {
+        let nd = &node.node_data;
+        if nd.name == "main" {
+            return true;
+        }
+        let source = self.sources.get(&nd.file).map(|s| s.as_str()).unwrap_or("");
+        for (i, language) in PROGRAMMING_LANGUAGES.iter().enumerate() {
+            if !language.is_from_language(&nd.file) {
+                continue;
+            }
+            let stack = self.langs[i]
+                .get_or_insert_with(|| Lang::from_language(language.clone()))
+                .lang();
+            if stack.is_lib_file(&nd.file)
+                || stack.is_test_file(&nd.file)
+                || stack.is_test(&nd.name, &nd.file)
+                || stack.lifecycle_hooks().contains(&nd.name.as_str())
+                || stack.is_exported(nd, source)
+            {
+                return true;
+            }
+        }
+        false
+    }
+}
+
+// functions and classes no entrypoint can reach. entrypoints are main, tests, exported or
+// re-exported api and lifecycle hooks, plus whatever tests, endpoints and pages use
+pub fn find_dead_code<G: Graph>(graph: &G) -> DeadCodeReport {
+    let mut candidates = Vec::new();
+    for node_type in CANDIDATE_TYPES {
+        for nd in graph.find_nodes_by_type(node_type.clone()) {
+            candidates.push(Node::new(node_type.clone(), nd));
+        }
+    }
+    let sources = graph
+        .find_nodes_by_type(NodeType::File)
+        .into_iter()
+        .map(|nd| (nd.file, nd.body))
+        .collect();
+    let mut entrypoints = Entrypoints::new(sources);
+
+    let mut uses: HashMap<String, Vec<String>> = HashMap::new();
+    let mut used = HashSet::new();
+    let mut owners: Vec<(String, String)> = Vec::new();
+    let mut queue = VecDeque::new();
+    for (edge_type, source, target) in graph.find_all_edges() {
+        if edge_type == EdgeType::Exports {
+            queue.push_back(create_node_key(&target));
+            continue;
+        }
+        let (source_key, target_key) = (create_node_key(&source), create_node_key(&target));
+        // membership only: a live class does not make its methods live
+        if edge_type == EdgeType::Operand {
+            owners.push((target_key, source_key));
+            continue;
+        }
+        if !USAGE_EDGES.contains(&edge_type) {
+            continue;
+        }
+        if !CANDIDATE_TYPES.contains(&source.node_type) {
+            queue.push_back(target_key.clone());
+        }
+        used.insert(target_key.clone());
+        uses.entry(source_key).or_default().push(target_key);
+    }
+    // a class is used, and reached, as soon as one of its methods is
+    for (method, class) in owners {
+        if used.contains(&method) {
+            used.insert(class.clone());
+        }
+        uses.entry(method).or_default().push(class);
+    }
+    for node in &candidates {
+        if entrypoints.contains(node) {
+            queue.push_back(create_node_key(node));
+        }
+    }
+
+    let mut reached = HashSet::new();
+    while let Some(key) = queue.pop_front() {
+        if !reached.insert(key.clone()) {
+            continue;
+        }
+        for next in uses.get(&key).into_iter().flatten() {
+            if !reached.contains(next) {
+                queue.push_back(next.clone());
+            }
+        }
+    }
+
+    let mut dead: Vec<DeadCode> = candidates
+        .into_iter()
+        .filter_map(|node| {
+            let key = create_node_key(&node);
+            if reached.contains(&key) {
+                return None;
+            }
+            let kind = if used.contains(&key) {
+                DeadKind::Unreachable
+            } else {
+                DeadKind::Unused
+            };
+            Some(DeadCode { node, kind })
+        })
+        .collect();
+    dead.sort_by(|a, b| {
+        let (a, b) = (&a.node.node_data, &b.node.node_data);
+        a.file
+            .cmp(&b.file)
+            .then(a.start.cmp(&b.start))
+            .then_with(|| a.name.cmp(&b.name))
+    });
+    DeadCodeReport { dead }
+}
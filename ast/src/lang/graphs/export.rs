@@ -0,0 +1,390 @@
+use super::{graph::Graph, EdgeType, Node, NodeType};
+use crate::utils::create_node_key;
+use shared::error::Result;
+use std::collections::{BTreeSet, HashSet};
+use std::fmt::Write;
+use std::str::FromStr;
+
+// which node and edge types end up in an export. `None` keeps everything
+#[derive(Clone, Debug, Default)]
+pub struct ExportFilter {
+    pub node_types: Option<Vec<NodeType>>,
+    pub edge_types: Option<Vec<EdgeType>>,
+}
+
+impl ExportFilter {
+    // EXPORT_NODE_TYPES=Function,Class and EXPORT_EDGE_TYPES=CALLS,CONTAINS
+    pub fn from_env() -> Result<Self> {
+        Ok(ExportFilter {
+            node_types: parse_list("EXPORT_NODE_TYPES")?,
+            edge_types: parse_list("EXPORT_EDGE_TYPES")?,
+        })
+    }
+
+    fn keeps_node(&self, node_type: &NodeType) -> bool {
+        self.node_types
+            .as_ref()
+            .is_none_or(|types| types.contains(node_type))
+    }
+
+    fn keeps_edge(&self, edge_type: &EdgeType) -> bool {
+        self.edge_types
+            .as_ref()
+            .is_none_or(|types| types.contains(edge_type))
+    }
+}
+
+fn parse_list<T: FromStr<Err = shared::Error>>(var: &str) -> Result<Option<Vec<T>>> {
+    let Ok(value) = std::env::var(var) else {
+        return Ok(None);
+    };
+    let items = value
+        .split(',')
+        .map(|s| s.trim())
+        .filter(|s| !s.is_empty())
+        .map(T::from_str)
+        .collect::<Result<Vec<_>>>()?;
+    Ok(Some(items))
+}
+
+struct ExportNode {
+    id: String,
+    node: Node,
+}
+
+// the filtered nodes (keyed by node key) and the edges whose both ends survived
+fn collect<G: Graph>(
+    graph: &G,
+    filter: &ExportFilter,
+) -> (Vec<ExportNode>, Vec<(EdgeType, String, String)>) {
+    let nodes: Vec<ExportNode> = graph
+        .find_all_nodes()
+        .into_iter()
+        .filter(|n| filter.keeps_node(&n.node_type))
+        .map(|node| ExportNode {
+            id: create_node_key(&node),
+            node,
+        })
+        .collect();
+    let ids: HashSet<&str> = nodes.iter().map(|n| n.id.as_str()).collect();
+    let edges = graph
+        .find_all_edges()
+        .into_iter()
+        .filter(|(edge_type, _, _)| filter.keeps_edge(edge_type))
+        .map(|(edge_type, source, target)| {
+            (
+                edge_type,
+                create_node_key(&source),
+                create_node_key(&target),
+            )
+        })
+        .filter(|(_, source, target)| {
+            ids.contains(source.as_str()) && ids.contains(target.as_str())
+        })
+        .collect();
+    (nodes, edges)
+}
+
+fn meta_keys(nodes: &[ExportNode]) -> BTreeSet<String> {
+    nodes
+        .iter()
+        .flat_map(|n| n.node.node_data.meta.keys().cloned())
+        .collect()
+}
+
+pub fn node_color(node_type: &NodeType) -> &'static str {
+    match node_type {
+        NodeType::Repository => "#1f2937",
+        NodeType::Language => "#6b7280",
+        NodeType::Directory => "#9ca3af",
+        NodeType::File => "#d1d5db",
+        NodeType::Import => "#a78bfa",
+        NodeType::Library => "#7c3aed",
+        NodeType::Class => "#2563eb",
+        NodeType::Trait | NodeType::Interface => "#0891b2",
+        NodeType::Instance => "#60a5fa",
+        NodeType::Function => "#16a34a",
+        NodeType::UnitTest | NodeType::IntegrationTest | NodeType::E2eTest => "#ca8a04",
+        NodeType::Endpoint => "#dc2626",
+        NodeType::Request => "#f97316",
+        NodeType::DataModel => "#db2777",
+        NodeType::Feature => "#4f46e5",
+        NodeType::Page => "#0d9488",
+        NodeType::Var | NodeType::Const => "#84cc16",
+        NodeType::Enum | NodeType::TypeAlias => "#0ea5e9",
+        NodeType::Module => "#92400e",
+    }
+}
+
+fn xml_escape(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&apos;"),
+            // control characters other than whitespace are not allowed in xml 1.0
+            c if c.is_control() && !matches!(c, '\n' | '\r' | '\t') => {}
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+fn dot_escape(s: &str) -> String {
+    s.replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+        .replace('\r', "")
+}
+
+// node bodies are left out of every format, they make the files unusable in viewers
+pub fn to_graphml<G: Graph>(graph: &G, filter: &ExportFilter) -> String {
+    let (nodes, edges) = collect(graph, filter);
+    let meta = meta_keys(&nodes);
+    let mut out = String::new();
+    out.push_str("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    out.push_str("<graphml xmlns=\"http://graphml.graphdrawing.org/xmlns\">\n");
+    for key in ["label", "name", "file", "start", "end", "color"] {
+        let _ = writeln!(
+            out,
+            "  <key id=\"{key}\" for=\"node\" attr.name=\"{key}\" attr.type=\"string\"/>"
+        );
+    }
+    for (i, key) in meta.iter().enumerate() {
+        let _ = writeln!(
+            out,
+            "  <key id=\"m{i}\" for=\"node\" attr.name=\"{}\" attr.type=\"string\"/>",
+            xml_escape(key)
+        );
+    }
+    out.push_str(
+        "  <key id=\"edge_label\" for=\"edge\" attr.name=\"label\" attr.type=\"string\"/>\n",
+    );
+    out.push_str("  <graph id=\"G\" edgedefault=\"directed\">\n");
+    for n in &nodes {
+        let nd = &n.node.node_data;
+        let _ = writeln!(out, "    <node id=\"{}\">", xml_escape(&n.id));
+        let mut data = vec![
+            ("label".to_string(), n.node.node_type.to_string()),
+            ("name".to_string(), nd.name.clone()),
+            ("file".to_string(), nd.file.clone()),
+            ("start".to_string(), nd.start.to_string()),
+            ("end".to_string(), nd.end.to_string()),
+            (
+                "color".to_string(),
+                node_color(&n.node.node_type).to_string(),
+            ),
+        ];
+        for (i, key) in meta.iter().enumerate() {
+            if let Some(value) = nd.meta.get(key) {
+                data.push((format!("m{i}"), value.clone()));
+            }
+        }
+        for (key, value) in data {
+            let _ = writeln!(
+                out,
+                "      <data key=\"{key}\">{}</data>",
+                xml_escape(&value)
+            );
+        }
+        out.push_str("    </node>\n");
+    }
+    for (i, (edge_type, source, target)) in edges.iter().enumerate() {
+        let _ = writeln!(
+            out,
+            "    <edge id=\"e{i}\" source=\"{}\" target=\"{}\"><data key=\"edge_label\">{}</data></edge>",
+            xml_escape(source),
+            xml_escape(target),
+            edge_type.to_string()
+        );
+    }
+    out.push_str("  </graph>\n</graphml>\n");
+    out
+}
+
+pub fn to_gexf<G: Graph>(graph: &G, filter: &ExportFilter) -> String {
+    let (nodes, edges) = collect(graph, filter);
+    let meta = meta_keys(&nodes);
+    let mut out = String::new();
+    out.push_str("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    out.push_str("<gexf xmlns=\"http://gexf.net/1.3\" xmlns:viz=\"http://gexf.net/1.3/viz\" version=\"1.3\">\n");
+    out.push_str("  <graph defaultedgetype=\"directed\">\n");
+    out.push_str("    <attributes class=\"node\">\n");
+    for (i, key) in ["node_type", "file", "start", "end"].iter().enumerate() {
+        let _ = writeln!(
+            out,
+            "      <attribute id=\"{i}\" title=\"{key}\" type=\"string\"/>"
+        );
+    }
+    for (i, key) in meta.iter().enumerate() {
+        let _ = writeln!(
+            out,
+            "      <attribute id=\"m{i}\" title=\"{}\" type=\"string\"/>",
+            xml_escape(key)
+        );
+    }
+    out.push_str("    </attributes>\n");
+    out.push_str("    <nodes>\n");
+    for n in &nodes {
+        let nd = &n.node.node_data;
+        let _ = writeln!(
+            out,
+            "      <node id=\"{}\" label=\"{}\">",
+            xml_escape(&n.id),
+            xml_escape(&nd.name)
+        );
+        out.push_str("        <attvalues>\n");
+        let mut values = vec![
+            ("0".to_string(), n.node.node_type.to_string()),
+            ("1".to_string(), nd.file.clone()),
+            ("2".to_string(), nd.start.to_string()),
+            ("3".to_string(), nd.end.to_string()),
+        ];
+        for (i, key) in meta.iter().enumerate() {
+            if let Some(value) = nd.meta.get(key) {
+                values.push((format!("m{i}"), value.clone()));
+            }
+        }
+        for (id, value) in values {
+            let _ = writeln!(
+                out,
+                "          <attvalue for=\"{id}\" value=\"{}\"/>",
+                xml_escape(&value)
+            );
+        }
+        out.push_str("        </attvalues>\n");
+        let (r, g, b) = hex_rgb(node_color(&n.node.node_type));
+        let _ = writeln!(out, "        <viz:color r=\"{r}\" g=\"{g}\" b=\"{b}\"/>");
+        out.push_str("      </node>\n");
+    }
+    out.push_str("    </nodes>\n    <edges>\n");
+    for (i, (edge_type, source, target)) in edges.iter().enumerate() {
+        let _ = writeln!(
+            out,
+            "      <edge id=\"{i}\" source=\"{}\" target=\"{}\" label=\"{}\"/>",
+            xml_escape(source),
+            xml_escape(target),
+            edge_type.to_string()
+        );
+    }
+    out.push_str("    </edges>\n  </graph>\n</gexf>\n");
+    out
+}
+
+fn hex_rgb(color: &str) -> (u8, u8, u8) {
+    let channel = |i: usize| u8::from_str_radix(&color[i..i + 2], 16).unwrap_or(0);
+    (channel(1), channel(3), channel(5))
+}
+
+pub fn to_dot<G: Graph>(graph: &G, filter: &ExportFilter) -> String {
+    let (nodes, edges) = collect(graph, filter);
+    let mut out = String::new();
+    out.push_str("digraph G {\n");
+    out.push_str("  node [shape=box, style=filled, fontname=\"Helvetica\"];\n");
+    for n in &nodes {
+        let nd = &n.node.node_data;
+        let mut attrs = vec![
+            format!(
+                "label=\"{}\\n{}\"",
+                n.node.node_type.to_string(),
+                dot_escape(&nd.name)
+            ),
+            format!("fillcolor=\"{}\"", node_color(&n.node.node_type)),
+            format!("file=\"{}\"", dot_escape(&nd.file)),
+            format!("start={}", nd.start),
+            format!("end={}", nd.end),
+        ];
+        for (key, value) in &nd.meta {
+            attrs.push(format!("\"{}\"=\"{}\"", dot_escape(key), dot_escape(value)));
+        }
+        let _ = writeln!(out, "  \"{}\" [{}];", dot_escape(&n.id), attrs.join(", "));
+    }
+    for (edge_type, source, target) in &edges {
+        let _ = writeln!(
+            out,
+            "  \"{}\" -> \"{}\" [label=\"{}\"];",
+            dot_escape(source),
+            dot_escape(target),
+            edge_type.to_string()
+        );
+    }
+    out.push_str("}\n");
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lang::graphs::{ArrayGraph, BTreeMapGraph, Edge};
+    use crate::lang::NodeData;
+
+    fn sample<G: Graph>() -> G {
+        let mut graph = G::default();
+        let file = NodeData::name_file("main.rs", "src/main.rs");
+        let mut func = NodeData::name_file("run<T>", "src/main.rs");
+        func.start = 3;
+        func.end = 9;
+        func.body = "fn run() { secret() }".to_string();
+        func.add_verb("\"GET\"");
+        let endpoint = NodeData::name_file("/items", "src/main.rs");
+        graph.add_node(NodeType::File, file.clone());
+        graph.add_node(NodeType::Function, func.clone());
+        graph.add_node(NodeType::Endpoint, endpoint.clone());
+        graph.add_edge(Edge::contains(
+            NodeType::File,
+            &file,
+            NodeType::Function,
+            &func,
+        ));
+        graph.add_edge(Edge::handler(&endpoint, &func));
+        graph
+    }
+
+    fn check_formats<G: Graph>() {
+        let graph = sample::<G>();
+        let all = ExportFilter::default();
+
+        let graphml = to_graphml(&graph, &all);
+        assert_eq!(graphml.matches("<node ").count(), 3);
+        assert_eq!(graphml.matches("<edge ").count(), 2);
+        assert!(graphml.contains("run&lt;T&gt;"));
+        assert!(graphml.contains("&quot;GET&quot;"));
+        assert!(!graphml.contains("secret"));
+
+        let gexf = to_gexf(&graph, &all);
+        assert_eq!(gexf.matches("<node ").count(), 3);
+        assert_eq!(gexf.matches("<edge ").count(), 2);
+        assert!(gexf.contains("<viz:color"));
+
+        let dot = to_dot(&graph, &all);
+        assert!(dot.contains("label=\"HANDLER\""));
+        assert!(dot.contains("\"verb\"=\"\\\"GET\\\"\""));
+
+        let functions_only = ExportFilter {
+            node_types: Some(vec![NodeType::Function, NodeType::Endpoint]),
+            edge_types: None,
+        };
+        let dot = to_dot(&graph, &functions_only);
+        assert_eq!(dot.matches(" -> ").count(), 1);
+        assert!(!dot.contains("CONTAINS"));
+
+        let no_handlers = ExportFilter {
+            node_types: None,
+            edge_types: Some(vec![EdgeType::Contains]),
+        };
+        let graphml = to_graphml(&graph, &no_handlers);
+        assert_eq!(graphml.matches("<edge ").count(), 1);
+        assert_eq!(graphml.matches("<node ").count(), 3);
+    }
+
+    #[test]
+    fn test_export_formats() {
+        check_formats::<ArrayGraph>();
+        check_formats::<BTreeMapGraph>();
+        #[cfg(feature = "sqlite")]
+        check_formats::<crate::lang::graphs::SqliteGraph>();
+    }
+}
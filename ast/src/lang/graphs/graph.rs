@@ -1,12 +1,17 @@
 use crate::lang::{Edge, Lang, Node, NodeType};
 use crate::lang::{Function, FunctionCall};
 use crate::lang::asg::TestRecord;
+use crate::utils::create_node_key;
 use lsp::Language;
 use shared::Result;
-use std::collections::HashSet;
+use std::collections::{BTreeMap, HashSet};
 use std::fmt::Debug;
 
-use super::{EdgeType, NodeData, NodeKeys};
+use super::call_graph::{CallDirection, CallGraph, CALLER_TYPES};
+use super::data_model_trace::{self, DataModelTrace, EndpointTrace};
+use super::mermaid::{self, MermaidKind};
+use super::subgraph;
+use super::{EdgeType, NodeData, NodeKeys, EDGE_TYPES, NODE_TYPES};
 
 pub trait Graph: Default + Debug {
     fn new(_root: String, _lang_kind: Language) -> Self
@@ -151,4 +156,137 @@ pub trait Graph: Default + Debug {
             .find(|node| node.file == file && node.start == line as usize)
     }
     fn has_edge(&self, source: &Node, target: &Node, edge_type: EdgeType) -> bool;
+
+    // merges meta into nodes already in the graph. the default re-adds them, which
+    // overwrites for backends keyed by node key
+    fn extend_node_meta(&mut self, updates: Vec<(Node, BTreeMap<String, String>)>) {
+        for (mut node, meta) in updates {
+            node.node_data.meta.extend(meta);
+            self.add_node(node.node_type, node.node_data);
+        }
+    }
+
+    // edges carrying a score, like the similarity on SIMILAR_TO. backends without edge
+    // properties keep just the edge
+    fn add_scored_edges(&mut self, edges: Vec<(Edge, f64)>) {
+        for (edge, _) in edges {
+            self.add_edge(edge);
+        }
+    }
+
+    fn find_all_nodes(&self) -> Vec<Node> {
+        NODE_TYPES
+            .iter()
+            .flat_map(|nt| {
+                self.find_nodes_by_type(nt.clone())
+                    .into_iter()
+                    .map(|nd| Node::new(nt.clone(), nd))
+            })
+            .collect()
+    }
+
+    // every edge as (edge type, source, target). the default asks for each type combination
+    fn find_all_edges(&self) -> Vec<(EdgeType, Node, Node)> {
+        let mut edges = Vec::new();
+        for edge_type in EDGE_TYPES.iter() {
+            for source_type in NODE_TYPES.iter() {
+                for target_type in NODE_TYPES.iter() {
+                    for (source, target) in self.find_nodes_with_edge_type(
+                        source_type.clone(),
+                        target_type.clone(),
+                        edge_type.clone(),
+                    ) {
+                        edges.push((
+                            edge_type.clone(),
+                            Node::new(source_type.clone(), source),
+                            Node::new(target_type.clone(), target),
+                        ));
+                    }
+                }
+            }
+        }
+        edges
+    }
+
+    // nodes within `depth` hops of the seed, following edges either way, and the edges
+    // between them. empty edge_types / node_types allow every type
+    fn neighborhood(
+        &self,
+        seed: &Node,
+        depth: usize,
+        edge_types: &[EdgeType],
+        node_types: &[NodeType],
+    ) -> (Vec<Node>, Vec<Edge>) {
+        subgraph::neighborhood(self.find_all_edges(), seed, depth, edge_types, node_types)
+    }
+
+    // the neighborhood of a node copied into a new graph, e.g. a BTreeMapGraph out of neo4j
+    fn subgraph<S: Graph>(
+        &self,
+        seed: &Node,
+        depth: usize,
+        edge_types: &[EdgeType],
+        node_types: &[NodeType],
+    ) -> S {
+        let (nodes, edges) = self.neighborhood(seed, depth, edge_types, node_types);
+        subgraph::graph_from(nodes, edges)
+    }
+
+    // the CALLS, HANDLER and CONTAINS-a-data-model edges, as an adjacency map. transitive
+    // walks build it once and answer every hop from it
+    fn call_graph(&self) -> CallGraph {
+        CallGraph::from_graph(self)
+    }
+
+    // the inverse of find_functions_called_by: functions, tests and endpoints calling it
+    fn find_callers(&self, function: &NodeData) -> Vec<NodeData> {
+        let key = create_node_key(&Node::new(NodeType::Function, function.clone()));
+        let is_function =
+            |nd: &NodeData| create_node_key(&Node::new(NodeType::Function, nd.clone())) == key;
+        let mut callers: Vec<NodeData> = CALLER_TYPES
+            .iter()
+            .flat_map(|source_type| {
+                self.find_nodes_with_edge_type(
+                    source_type.clone(),
+                    NodeType::Function,
+                    EdgeType::Calls,
+                )
+            })
+            .chain(self.find_nodes_with_edge_type(
+                NodeType::Endpoint,
+                NodeType::Function,
+                EdgeType::Handler,
+            ))
+            .filter(|(_, target)| is_function(target))
+            .map(|(source, _)| source)
+            .collect();
+        callers.dedup();
+        callers
+    }
+
+    fn transitive_callers(&self, node: &Node, depth: usize) -> Vec<(Node, usize)> {
+        self.call_graph().walk(node, CallDirection::Callers, depth)
+    }
+
+    fn transitive_callees(&self, node: &Node, depth: usize) -> Vec<(Node, usize)> {
+        self.call_graph().walk(node, CallDirection::Callees, depth)
+    }
+
+    fn shortest_call_path(&self, from: &Node, to: &Node) -> Option<Vec<Node>> {
+        self.call_graph().shortest_path(from, to)
+    }
+
+    // data models an endpoint touches through its handler and calls, with the call path
+    fn endpoint_data_models(&self, endpoint: &Node, depth: usize) -> Vec<DataModelTrace> {
+        data_model_trace::trace_endpoint(&self.call_graph(), endpoint, depth)
+    }
+
+    // endpoint_data_models of every endpoint
+    fn endpoint_data_model_report(&self, depth: usize) -> Vec<EndpointTrace> {
+        data_model_trace::trace_endpoints(self, depth)
+    }
+
+    fn to_mermaid(&self, root: &Node, kind: MermaidKind, depth: usize) -> String {
+        mermaid::to_mermaid(self, root, kind, depth)
+    }
 }
@@ -13,31 +13,18 @@ use crate::lang::linker::{
 use crate::lang::neo4j_utils::{add_edge_query, add_node_query, build_batch_edge_queries};
 use crate::lang::{Edge, EdgeType, Node, NodeData, NodeType};
 use crate::repo::{check_revs_files, Repo};
-use crate::utils::create_node_key;
+use crate::utils::{create_node_key, read_jsonl};
 use neo4rs::BoltMap;
 use shared::error::{Error, Result};
 use tracing::{debug, error, info};
 
+pub use super::coverage::{CoverageStat, GraphCoverage};
+
 #[derive(Debug, Clone)]
 pub struct GraphOps {
     pub graph: Neo4jGraph,
 }
 
-#[derive(Debug, Clone)]
-pub struct CoverageStat {
-    pub total: usize,
-    pub total_tests: usize,
-    pub covered: usize,
-    pub percent: f64,
-}
-
-#[derive(Debug, Clone)]
-pub struct GraphCoverage {
-    pub unit_tests: Option<CoverageStat>,
-    pub integration_tests: Option<CoverageStat>,
-    pub e2e_tests: Option<CoverageStat>,
-}
-
 impl GraphOps {
     pub fn new() -> Self {
         Self {
@@ -318,9 +305,9 @@ impl GraphOps {
         if let Some(tx) = &status_tx {
             let _ = tx.send(crate::repo::StatusUpdate {
                 status: "".to_string(),
-                message: "Step 15: Uploading nodes to Neo4j".to_string(),
-                step: 15,
-                total_steps: 16,
+                message: "Step 17: Uploading nodes to Neo4j".to_string(),
+                step: 17,
+                total_steps: 18,
                 progress: 0,
                 stats: None,
                 step_description: Some("Uploading nodes to Neo4j".to_string()),
@@ -341,9 +328,9 @@ impl GraphOps {
         if let Some(tx) = &status_tx {
             let _ = tx.send(crate::repo::StatusUpdate {
                 status: "".to_string(),
-                message: "Step 16: Uploading edges to Neo4j".to_string(),
-                step: 16,
-                total_steps: 16,
+                message: "Step 18: Uploading edges to Neo4j".to_string(),
+                step: 18,
+                total_steps: 18,
                 progress: 0,
                 stats: None,
                 step_description: Some("Uploading edges to Neo4j".to_string()),
@@ -362,6 +349,32 @@ impl GraphOps {
         Ok((nodes, edges))
     }
 
+    // ingest a print_json jsonl dump without the source repo, replacing each repository in it
+    pub async fn upload_jsonl_to_neo4j(
+        &mut self,
+        root: &str,
+        name: &str,
+        status_tx: Option<tokio::sync::broadcast::Sender<crate::repo::StatusUpdate>>,
+    ) -> Result<(u32, u32)> {
+        let btree_graph = read_jsonl::<BTreeMapGraph>(root, name, false)?;
+        info!(
+            "loaded {} nodes and {} edges from {}/{}",
+            btree_graph.nodes.len(),
+            btree_graph.edges.len(),
+            root,
+            name
+        );
+        for repo in btree_graph
+            .find_nodes_by_type(NodeType::Repository)
+            .into_iter()
+            .filter(|r| !r.file.is_empty())
+        {
+            info!("Clearing old data for {}...", repo.file);
+            self.graph.clear_existing_graph(&repo.file).await?;
+        }
+        self.upload_btreemap_to_neo4j(&btree_graph, status_tx).await
+    }
+
     pub async fn clear_existing_graph(&mut self, root: &str) -> Result<()> {
         self.graph.clear_existing_graph(root).await?;
         Ok(())
@@ -398,6 +411,7 @@ impl GraphOps {
         node_types: Vec<String>,
         similarity_threshold: f32,
         language: Option<&str>,
+        importance_weight: f32,
     ) -> Result<Vec<(NodeData, f64)>> {
         let embedding = vectorize_query(query).await?;
         let results = self
@@ -408,6 +422,7 @@ impl GraphOps {
                 node_types,
                 similarity_threshold,
                 language,
+                importance_weight,
             )
             .await?;
         Ok(results)
@@ -0,0 +1,274 @@
+use super::{graph::Graph, *};
+use crate::lang::asg::TestRecord;
+use crate::lang::{Function, FunctionCall, Lang};
+use lsp::Language;
+use serde_json::Value;
+use shared::error::{Context, Error, Result};
+use std::collections::HashSet;
+use std::time::Duration;
+use tracing::debug;
+
+#[derive(Clone, Debug)]
+pub struct HelixConfig {
+    pub url: String,
+    pub timeout: Duration,
+}
+
+impl Default for HelixConfig {
+    fn default() -> Self {
+        HelixConfig {
+            url: std::env::var("HELIX_URL").unwrap_or_else(|_| "http://localhost:6969".to_string()),
+            timeout: Duration::from_secs(30),
+        }
+    }
+}
+
+// calls the compiled queries in helixdb-cfg/queries.hx over http
+#[derive(Clone, Debug)]
+pub struct HelixClient {
+    config: HelixConfig,
+    http: reqwest::Client,
+}
+
+impl HelixClient {
+    pub fn new(config: HelixConfig) -> Result<Self> {
+        let http = reqwest::Client::builder()
+            .timeout(config.timeout)
+            .build()
+            .context("failed to build helix client")?;
+        Ok(HelixClient { config, http })
+    }
+
+    pub async fn query(&self, name: &str, params: Value) -> Result<Value> {
+        let url = format!("{}/{}", self.config.url.trim_end_matches('/'), name);
+        debug!("helix query {}", name);
+        let res = self
+            .http
+            .post(&url)
+            .json(&params)
+            .send()
+            .await
+            .context("helix request failed")?;
+        let status = res.status();
+        let body = res.text().await.context("helix response failed")?;
+        if !status.is_success() {
+            return Err(Error::Custom(format!(
+                "helix query {} failed ({}): {}",
+                name, status, body
+            )));
+        }
+        Ok(serde_json::from_str(&body).unwrap_or(Value::String(body)))
+    }
+
+    // the id of the node returned under `key`, if the query found one
+    pub async fn query_id(&self, name: &str, params: Value, key: &str) -> Result<Option<String>> {
+        let res = self.query(name, params).await?;
+        Ok(returned_id(&res, key))
+    }
+}
+
+pub(super) fn returned_id(res: &Value, key: &str) -> Option<String> {
+    let node = match res.get(key)? {
+        Value::Array(items) => items.first()?,
+        node => node,
+    };
+    node.get("id")?.as_str().map(|s| s.to_string())
+}
+
+// the helix schema stores one immutable snapshot per commit and has no node keys,
+// so the graph is built in memory and pushed as a VERSION with `publish`
+#[derive(Clone, Debug, Default)]
+pub struct HelixGraph {
+    graph: BTreeMapGraph,
+    pub config: HelixConfig,
+}
+
+impl HelixGraph {
+    pub fn with_config(config: HelixConfig) -> Self {
+        HelixGraph {
+            graph: BTreeMapGraph::default(),
+            config,
+        }
+    }
+
+    pub fn inner(&self) -> &BTreeMapGraph {
+        &self.graph
+    }
+
+    pub async fn publish(&self) -> Result<(u32, u32)> {
+        let mut ops = super::helix_ops::HelixGraphOps::with_config(self.config.clone())?;
+        ops.upload_btreemap_to_helix(&self.graph, None).await
+    }
+}
+
+impl Graph for HelixGraph {
+    fn new(root: String, lang_kind: Language) -> Self {
+        HelixGraph {
+            graph: BTreeMapGraph::new(root, lang_kind),
+            config: HelixConfig::default(),
+        }
+    }
+    fn with_capacity(nodes: usize, edges: usize, root: String, lang_kind: Language) -> Self {
+        HelixGraph {
+            graph: BTreeMapGraph::with_capacity(nodes, edges, root, lang_kind),
+            config: HelixConfig::default(),
+        }
+    }
+    fn analysis(&self) {
+        self.graph.analysis()
+    }
+    fn create_filtered_graph(self, final_filter: &[String], lang_kind: Language) -> Self {
+        HelixGraph {
+            graph: self.graph.create_filtered_graph(final_filter, lang_kind),
+            config: self.config,
+        }
+    }
+    fn extend_graph(&mut self, other: Self) {
+        self.graph.extend_graph(other.graph)
+    }
+    fn get_graph_size(&self) -> (u32, u32) {
+        self.graph.get_graph_size()
+    }
+    fn find_nodes_by_name(&self, node_type: NodeType, name: &str) -> Vec<NodeData> {
+        self.graph.find_nodes_by_name(node_type, name)
+    }
+    fn add_node_with_parent(
+        &mut self,
+        node_type: NodeType,
+        node_data: NodeData,
+        parent_type: NodeType,
+        parent_file: &str,
+    ) {
+        self.graph
+            .add_node_with_parent(node_type, node_data, parent_type, parent_file)
+    }
+    fn add_edge(&mut self, edge: Edge) {
+        self.graph.add_edge(edge)
+    }
+    fn add_node(&mut self, node_type: NodeType, node_data: NodeData) {
+        self.graph.add_node(node_type, node_data)
+    }
+    fn get_graph_keys(&self) -> (HashSet<String>, HashSet<String>) {
+        self.graph.get_graph_keys()
+    }
+    fn find_source_edge_by_name_and_file(
+        &self,
+        edge_type: EdgeType,
+        target_name: &str,
+        target_file: &str,
+    ) -> Option<NodeKeys> {
+        self.graph
+            .find_source_edge_by_name_and_file(edge_type, target_name, target_file)
+    }
+    fn process_endpoint_groups(&mut self, eg: Vec<NodeData>, lang: &Lang) -> Result<()> {
+        self.graph.process_endpoint_groups(eg, lang)
+    }
+    fn class_inherits(&mut self) {
+        self.graph.class_inherits()
+    }
+    fn class_includes(&mut self) {
+        self.graph.class_includes()
+    }
+    fn add_instances(&mut self, nodes: Vec<NodeData>) {
+        self.graph.add_instances(nodes)
+    }
+    fn add_functions(&mut self, functions: Vec<Function>) {
+        self.graph.add_functions(functions)
+    }
+    fn add_page(&mut self, page: (NodeData, Option<Edge>)) {
+        self.graph.add_page(page)
+    }
+    fn add_pages(&mut self, pages: Vec<(NodeData, Vec<Edge>)>) {
+        self.graph.add_pages(pages)
+    }
+    fn add_endpoints(&mut self, endpoints: Vec<(NodeData, Option<Edge>)>) {
+        self.graph.add_endpoints(endpoints)
+    }
+    fn add_tests(&mut self, tests: Vec<TestRecord>) {
+        self.graph.add_tests(tests)
+    }
+    fn add_calls(&mut self, calls: (Vec<FunctionCall>, Vec<FunctionCall>, Vec<Edge>, Vec<Edge>)) {
+        self.graph.add_calls(calls)
+    }
+    fn filter_out_nodes_without_children(
+        &mut self,
+        parent_type: NodeType,
+        child_type: NodeType,
+        child_meta_key: &str,
+    ) {
+        self.graph
+            .filter_out_nodes_without_children(parent_type, child_type, child_meta_key)
+    }
+    fn get_data_models_within(&mut self, lang: &Lang) {
+        self.graph.get_data_models_within(lang)
+    }
+    fn find_endpoint(&self, name: &str, file: &str, verb: &str) -> Option<NodeData> {
+        self.graph.find_endpoint(name, file, verb)
+    }
+    fn find_resource_nodes(&self, node_type: NodeType, verb: &str, path: &str) -> Vec<NodeData> {
+        self.graph.find_resource_nodes(node_type, verb, path)
+    }
+    fn find_handlers_for_endpoint(&self, endpoint: &NodeData) -> Vec<NodeData> {
+        self.graph.find_handlers_for_endpoint(endpoint)
+    }
+    fn check_direct_data_model_usage(&self, function_name: &str, data_model: &str) -> bool {
+        self.graph
+            .check_direct_data_model_usage(function_name, data_model)
+    }
+    fn find_functions_called_by(&self, function: &NodeData) -> Vec<NodeData> {
+        self.graph.find_functions_called_by(function)
+    }
+    fn find_nodes_by_type(&self, node_type: NodeType) -> Vec<NodeData> {
+        self.graph.find_nodes_by_type(node_type)
+    }
+    fn find_nodes_with_edge_type(
+        &self,
+        source_type: NodeType,
+        target_type: NodeType,
+        edge_type: EdgeType,
+    ) -> Vec<(NodeData, NodeData)> {
+        self.graph
+            .find_nodes_with_edge_type(source_type, target_type, edge_type)
+    }
+    fn count_edges_of_type(&self, edge_type: EdgeType) -> usize {
+        self.graph.count_edges_of_type(edge_type)
+    }
+    fn find_nodes_by_name_contains(&self, node_type: NodeType, name: &str) -> Vec<NodeData> {
+        self.graph.find_nodes_by_name_contains(node_type, name)
+    }
+    fn find_node_by_name_in_file(
+        &self,
+        node_type: NodeType,
+        name: &str,
+        file: &str,
+    ) -> Option<NodeData> {
+        self.graph.find_node_by_name_in_file(node_type, name, file)
+    }
+    fn find_nodes_by_file_ends_with(&self, node_type: NodeType, file: &str) -> Vec<NodeData> {
+        self.graph.find_nodes_by_file_ends_with(node_type, file)
+    }
+    fn find_node_by_name_and_file_end_with(
+        &self,
+        node_type: NodeType,
+        name: &str,
+        suffix: &str,
+    ) -> Option<NodeData> {
+        self.graph
+            .find_node_by_name_and_file_end_with(node_type, name, suffix)
+    }
+    fn find_node_in_range(&self, node_type: NodeType, row: u32, file: &str) -> Option<NodeData> {
+        self.graph.find_node_in_range(node_type, row, file)
+    }
+    fn find_node_at(&self, node_type: NodeType, file: &str, line: u32) -> Option<NodeData> {
+        self.graph.find_node_at(node_type, file, line)
+    }
+    fn has_edge(&self, source: &Node, target: &Node, edge_type: EdgeType) -> bool {
+        self.graph.has_edge(source, target, edge_type)
+    }
+    fn find_all_nodes(&self) -> Vec<Node> {
+        self.graph.find_all_nodes()
+    }
+    fn find_all_edges(&self) -> Vec<(EdgeType, Node, Node)> {
+        self.graph.find_all_edges()
+    }
+}
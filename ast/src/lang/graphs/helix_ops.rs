@@ -0,0 +1,548 @@
+use crate::lang::graphs::helix_graph::{returned_id, HelixClient, HelixConfig};
+use crate::lang::graphs::BTreeMapGraph;
+use crate::lang::{EdgeType, Node, NodeType};
+use futures::{StreamExt, TryStreamExt};
+use lsp::Language;
+use serde_json::{json, Map, Value};
+use shared::error::{Context, Result};
+use std::collections::{BTreeMap, HashMap};
+use std::str::FromStr;
+use tracing::{debug, info};
+
+const CONCURRENCY: usize = 16;
+const EDGE_BATCH: usize = 256;
+
+// a code node as one of the add_* queries in helixdb-cfg/queries.hx
+#[derive(Debug, Clone, PartialEq)]
+pub struct HelixNodeQuery {
+    pub query: &'static str,
+    pub returns: &'static str,
+    pub params: Value,
+}
+
+// a relationship as one of the batch create_*_edges queries
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct HelixEdgeQuery {
+    pub query: &'static str,
+    pub list: &'static str,
+    pub from: &'static str,
+    pub to: &'static str,
+}
+
+fn is_test(node_type: &NodeType) -> bool {
+    matches!(
+        node_type,
+        NodeType::UnitTest | NodeType::IntegrationTest | NodeType::E2eTest
+    )
+}
+
+// Repository and File are written with the snapshot; the rest has no schema node
+pub fn helix_node_query(node: &Node) -> Option<HelixNodeQuery> {
+    let data = &node.node_data;
+    let (start, end) = (data.start as i32, data.end as i32);
+    let (query, returns, params) = match node.node_type {
+        NodeType::Library => (
+            "add_library",
+            "lib_node",
+            json!({ "name": data.name, "version": data.meta.get("version").cloned().unwrap_or_default() }),
+        ),
+        NodeType::Class => (
+            "add_class",
+            "class_node",
+            json!({ "name": data.name, "start_line": start, "end_line": end }),
+        ),
+        NodeType::Function => {
+            let signature = data
+                .meta
+                .get("interface")
+                .cloned()
+                .unwrap_or_else(|| data.body.lines().next().unwrap_or_default().trim().to_string());
+            (
+                "add_function",
+                "func_node",
+                json!({
+                    "name": data.name,
+                    "signature": signature,
+                    "start_line": start,
+                    "end_line": end,
+                    "is_component": data.meta.get("component").is_some_and(|v| v == "true"),
+                }),
+            )
+        }
+        NodeType::DataModel
+        | NodeType::Trait
+        | NodeType::Interface
+        | NodeType::Enum
+        | NodeType::TypeAlias => {
+            let construct = match node.node_type {
+                NodeType::Trait => "trait",
+                NodeType::Interface => "interface",
+                NodeType::Enum => "enum",
+                NodeType::TypeAlias => "type",
+                _ => "struct",
+            };
+            (
+                "add_data_model",
+                "dm_node",
+                json!({ "name": data.name, "construct": construct, "start_line": start, "end_line": end }),
+            )
+        }
+        NodeType::Var | NodeType::Const => (
+            "add_variable",
+            "var_node",
+            json!({ "name": data.name, "data_type": data.data_type.clone().unwrap_or_default() }),
+        ),
+        NodeType::UnitTest | NodeType::IntegrationTest | NodeType::E2eTest => {
+            let kind = match node.node_type {
+                NodeType::UnitTest => "unit",
+                NodeType::IntegrationTest => "integration",
+                _ => "e2e",
+            };
+            (
+                "add_test",
+                "test_node",
+                json!({ "name": data.name, "test_kind": kind, "start_line": start, "end_line": end }),
+            )
+        }
+        NodeType::Endpoint => (
+            "add_endpoint",
+            "ep_node",
+            json!({ "path": data.name, "http_method": data.meta.get("verb").cloned().unwrap_or_default() }),
+        ),
+        _ => return None,
+    };
+    Some(HelixNodeQuery {
+        query,
+        returns,
+        params,
+    })
+}
+
+// Contains edges become DEFINES/CONTAINS_CODE when the nodes are created
+pub fn helix_edge_query(
+    edge_type: &EdgeType,
+    source: &NodeType,
+    target: &NodeType,
+) -> Option<HelixEdgeQuery> {
+    let q = |query, list, from, to| {
+        Some(HelixEdgeQuery {
+            query,
+            list,
+            from,
+            to,
+        })
+    };
+    let is_model = |nt: &NodeType| {
+        matches!(
+            nt,
+            NodeType::DataModel | NodeType::Trait | NodeType::Interface | NodeType::Enum | NodeType::TypeAlias
+        )
+    };
+    match (edge_type, source, target) {
+        (EdgeType::Calls, NodeType::Function, NodeType::Function) => {
+            q("create_calls_edges", "calls", "from_func_id", "to_func_id")
+        }
+        (EdgeType::Calls, s, NodeType::Function | NodeType::Class | NodeType::Endpoint)
+            if is_test(s) =>
+        {
+            q("create_tests_edges", "tests", "test_id", "target_id")
+        }
+        (EdgeType::Handler, NodeType::Endpoint, NodeType::Function) => {
+            q("create_handled_by_edges", "handlers", "endpoint_id", "function_id")
+        }
+        (EdgeType::Operand, NodeType::Class, NodeType::Function) => {
+            q("create_operand_edges", "operands", "class_id", "function_id")
+        }
+        (EdgeType::ParentOf, NodeType::Class, NodeType::Class) => {
+            q("create_parent_of_edges", "parents", "parent_id", "child_id")
+        }
+        (EdgeType::Implements, NodeType::Class, t) if is_model(t) => {
+            q("create_implements_edges", "impls", "class_id", "model_id")
+        }
+        (EdgeType::Imports, NodeType::File, NodeType::File) => {
+            q("create_imports_edges", "imports", "from_file_id", "to_file_id")
+        }
+        (EdgeType::Uses, NodeType::Function, NodeType::Library) => {
+            q("create_uses_edges", "uses", "function_id", "library_id")
+        }
+        _ => None,
+    }
+}
+
+// seconds since the epoch as the rfc3339 string helix expects for Date fields
+pub fn rfc3339(secs: i64) -> String {
+    let (days, rem) = (secs.div_euclid(86400), secs.rem_euclid(86400));
+    let z = days + 719468;
+    let era = z.div_euclid(146097);
+    let doe = z.rem_euclid(146097);
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+        year,
+        month,
+        day,
+        rem / 3600,
+        rem % 3600 / 60,
+        rem % 60
+    )
+}
+
+#[derive(Debug, Clone, Default)]
+struct CommitInfo {
+    message: String,
+    committed_at: String,
+    tag: String,
+}
+
+// message, date and tag of the commit, from the clone if it is still on disk
+fn commit_info(root: &str, sha: &str) -> CommitInfo {
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or_default();
+    let fallback = CommitInfo {
+        committed_at: rfc3339(now),
+        ..Default::default()
+    };
+    let repo = match git2::Repository::open(root)
+        .or_else(|_| git2::Repository::open(std::path::Path::new("/tmp").join(root)))
+    {
+        Ok(repo) => repo,
+        Err(_) => return fallback,
+    };
+    let Some(commit) = git2::Oid::from_str(sha)
+        .ok()
+        .and_then(|oid| repo.find_commit(oid).ok())
+    else {
+        return fallback;
+    };
+    let tag = repo
+        .tag_names(None)
+        .ok()
+        .and_then(|tags| {
+            tags.iter().flatten().find_map(|t| {
+                let target = repo.revparse_single(t).ok()?.peel_to_commit().ok()?;
+                (target.id() == commit.id()).then(|| t.to_string())
+            })
+        })
+        .unwrap_or_default();
+    CommitInfo {
+        message: commit.summary().unwrap_or_default().to_string(),
+        committed_at: rfc3339(commit.time().seconds()),
+        tag,
+    }
+}
+
+fn relative_path(root: &str, file: &str) -> String {
+    file.strip_prefix(root)
+        .map(|p| p.trim_start_matches('/'))
+        .unwrap_or(file)
+        .to_string()
+}
+
+fn file_language(path: &str, langs: &[Language]) -> String {
+    let ext = path.rsplit('.').next().unwrap_or_default();
+    langs
+        .iter()
+        .find(|l| l.exts().contains(&ext))
+        .or(langs.first())
+        .map(|l| l.to_string())
+        .unwrap_or_default()
+}
+
+#[derive(Debug, Clone)]
+pub struct HelixGraphOps {
+    pub client: HelixClient,
+}
+
+impl HelixGraphOps {
+    pub fn new() -> Result<Self> {
+        Self::with_config(HelixConfig::default())
+    }
+
+    pub fn with_config(config: HelixConfig) -> Result<Self> {
+        Ok(Self {
+            client: HelixClient::new(config)?,
+        })
+    }
+
+    pub async fn check_connection(&self) -> Result<()> {
+        self.client
+            .query("find_project_by_url", json!({ "url": "" }))
+            .await?;
+        Ok(())
+    }
+
+    // drops a version with every file and code node it contains
+    pub async fn delete_version(&self, sha: &str) -> Result<()> {
+        self.client
+            .query("delete_version", json!({ "version_sha": sha }))
+            .await?;
+        Ok(())
+    }
+
+    pub async fn upload_btreemap_to_helix(
+        &mut self,
+        btree_graph: &BTreeMapGraph,
+        status_tx: Option<tokio::sync::broadcast::Sender<crate::repo::StatusUpdate>>,
+    ) -> Result<(u32, u32)> {
+        let mut repos: Vec<&Node> = btree_graph
+            .nodes
+            .values()
+            .filter(|n| n.node_type == NodeType::Repository)
+            .collect();
+        if repos.is_empty() {
+            return Err(shared::Error::Custom(
+                "graph has no Repository node to snapshot".into(),
+            ));
+        }
+        // nested roots first, so each file lands in its closest repository
+        repos.sort_by_key(|r| std::cmp::Reverse(r.node_data.file.len()));
+
+        if let Some(tx) = &status_tx {
+            let _ = tx.send(crate::repo::StatusUpdate {
+                status: "".to_string(),
+                message: "Step 17: Uploading nodes to HelixDB".to_string(),
+                step: 17,
+                total_steps: 18,
+                progress: 0,
+                stats: None,
+                step_description: Some("Uploading nodes to HelixDB".to_string()),
+            });
+        }
+
+        let client = &self.client;
+        let mut ids: HashMap<String, String> = HashMap::new();
+        let mut edges = 0u32;
+        for repo in repos {
+            let root = repo.node_data.file.clone();
+            let langs: Vec<Language> = btree_graph
+                .nodes
+                .values()
+                .filter(|n| n.node_type == NodeType::Language && n.node_data.file == root)
+                .filter_map(|n| Language::from_str(&n.node_data.name).ok())
+                .collect();
+            let version_id = self.create_snapshot(repo, &langs).await?;
+            info!("uploading snapshot of {} to helix", repo.node_data.name);
+
+            let files: Vec<(String, String)> = btree_graph
+                .nodes
+                .iter()
+                .filter(|(key, n)| {
+                    n.node_type == NodeType::File
+                        && n.node_data.file.starts_with(&root)
+                        && !ids.contains_key(*key)
+                })
+                .map(|(key, n)| (key.clone(), n.node_data.file.clone()))
+                .collect();
+            let file_ids: Vec<(String, String, String)> = futures::stream::iter(files)
+                .map(|(key, file)| {
+                    let path = relative_path(&root, &file);
+                    let params = json!({
+                        "version_id": version_id,
+                        "path": path,
+                        "language": file_language(&path, &langs),
+                    });
+                    async move {
+                        let id = client
+                            .query_id("add_file", params, "file_node")
+                            .await?
+                            .context("add_file returned no id")?;
+                        Ok::<_, shared::Error>((key, file, id))
+                    }
+                })
+                .buffer_unordered(CONCURRENCY)
+                .try_collect()
+                .await?;
+            let mut by_path: HashMap<String, String> = HashMap::new();
+            for (key, file, id) in file_ids {
+                by_path.insert(file, id.clone());
+                ids.insert(key, id);
+            }
+            edges += by_path.len() as u32;
+
+            let code: Vec<(String, HelixNodeQuery, String)> = btree_graph
+                .nodes
+                .iter()
+                .filter(|(key, _)| !ids.contains_key(*key))
+                .filter_map(|(key, n)| {
+                    let file_id = by_path.get(&n.node_data.file)?;
+                    Some((key.clone(), helix_node_query(n)?, file_id.clone()))
+                })
+                .collect();
+            let code_ids: Vec<(String, String)> = futures::stream::iter(code)
+                .map(|(key, mut nq, file_id)| async move {
+                    nq.params["file_id"] = Value::String(file_id);
+                    let id = client
+                        .query_id(nq.query, nq.params, nq.returns)
+                        .await?
+                        .context("helix returned no node id")?;
+                    Ok::<_, shared::Error>((key, id))
+                })
+                .buffer_unordered(CONCURRENCY)
+                .try_collect()
+                .await?;
+            edges += code_ids.len() as u32;
+            ids.extend(code_ids);
+        }
+        let nodes = ids.len() as u32;
+        info!("node upload complete");
+
+        if let Some(tx) = &status_tx {
+            let _ = tx.send(crate::repo::StatusUpdate {
+                status: "".to_string(),
+                message: "Step 18: Uploading edges to HelixDB".to_string(),
+                step: 18,
+                total_steps: 18,
+                progress: 0,
+                stats: None,
+                step_description: Some("Uploading edges to HelixDB".to_string()),
+            });
+        }
+
+        let mut batches: BTreeMap<HelixEdgeQuery, Vec<Value>> = BTreeMap::new();
+        for (src_key, dst_key, edge_type) in &btree_graph.edges {
+            let (Some(src), Some(dst)) = (btree_graph.nodes.get(src_key), btree_graph.nodes.get(dst_key))
+            else {
+                continue;
+            };
+            let (Some(src_id), Some(dst_id)) = (ids.get(src_key), ids.get(dst_key)) else {
+                continue;
+            };
+            if let Some(eq) = helix_edge_query(edge_type, &src.node_type, &dst.node_type) {
+                let mut pair = Map::new();
+                pair.insert(eq.from.to_string(), Value::String(src_id.clone()));
+                pair.insert(eq.to.to_string(), Value::String(dst_id.clone()));
+                batches.entry(eq).or_default().push(Value::Object(pair));
+            }
+        }
+        for (eq, pairs) in batches {
+            debug!("uploading {} {} edges", pairs.len(), eq.list);
+            for chunk in pairs.chunks(EDGE_BATCH) {
+                client.query(eq.query, json!({ eq.list: chunk })).await?;
+                edges += chunk.len() as u32;
+            }
+        }
+        info!("edge upload complete! nodes: {}, edges: {}", nodes, edges);
+        Ok((nodes, edges))
+    }
+
+    // PROJECT -> VERSION -> COMMIT for the repository's hash, replacing an earlier upload of it
+    async fn create_snapshot(&self, repo: &Node, langs: &[Language]) -> Result<String> {
+        let data = &repo.node_data;
+        let sha = data.hash.clone().unwrap_or_default();
+        let url = data
+            .meta
+            .get("source_link")
+            .filter(|l| !l.is_empty())
+            .cloned()
+            .unwrap_or_else(|| data.name.clone());
+
+        let project_id = match self
+            .client
+            .query_id("find_project_by_url", json!({ "url": url }), "project")
+            .await?
+        {
+            Some(id) => id,
+            None => self
+                .client
+                .query_id(
+                    "create_project",
+                    json!({
+                        "url": url,
+                        "name": data.name,
+                        "description": "",
+                        "language": langs.first().map(|l| l.to_string()).unwrap_or_default(),
+                        "stars": 0,
+                        "forks": 0,
+                    }),
+                    "project",
+                )
+                .await?
+                .context("create_project returned no id")?,
+        };
+
+        if self
+            .client
+            .query_id("find_version", json!({ "sha": sha }), "version")
+            .await?
+            .is_some()
+        {
+            info!("replacing existing helix version {}", sha);
+            self.delete_version(&sha).await?;
+        }
+        self.client
+            .query("clear_head", json!({ "project_id": project_id }))
+            .await?;
+
+        let info = commit_info(&data.file, &sha);
+        let commit = self
+            .client
+            .query("find_commit", json!({ "sha": sha }))
+            .await?;
+        if returned_id(&commit, "commit").is_none() {
+            self.client
+                .query(
+                    "create_commit",
+                    json!({ "sha": sha, "message": info.message, "committed_at": info.committed_at }),
+                )
+                .await?;
+        }
+        self.client
+            .query_id(
+                "create_version",
+                json!({
+                    "project_id": project_id,
+                    "commit_sha": sha,
+                    "tag": info.tag,
+                    "is_head": true,
+                    "created_at": info.committed_at,
+                }),
+                "version",
+            )
+            .await?
+            .context("create_version returned no id")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lang::NodeData;
+
+    #[test]
+    fn test_helix_mapping() {
+        let mut func = NodeData::name_file("handler", "repo/src/api.rs");
+        func.body = "fn handler() -> String {\n    todo!()\n}".to_string();
+        func.start = 3;
+        func.end = 5;
+        let nq = helix_node_query(&Node::new(NodeType::Function, func)).unwrap();
+        assert_eq!(nq.query, "add_function");
+        assert_eq!(nq.params["signature"], "fn handler() -> String {");
+        assert_eq!(nq.params["is_component"], false);
+
+        let mut endpoint = NodeData::name_file("/users", "repo/src/api.rs");
+        endpoint.meta.insert("verb".to_string(), "GET".to_string());
+        let nq = helix_node_query(&Node::new(NodeType::Endpoint, endpoint)).unwrap();
+        assert_eq!(nq.params["http_method"], "GET");
+
+        let dir = NodeData::name_file("src", "repo/src");
+        assert!(helix_node_query(&Node::new(NodeType::Directory, dir)).is_none());
+
+        let eq = helix_edge_query(&EdgeType::Calls, &NodeType::UnitTest, &NodeType::Function);
+        assert_eq!(eq.unwrap().query, "create_tests_edges");
+        let eq = helix_edge_query(&EdgeType::Calls, &NodeType::Function, &NodeType::Function);
+        assert_eq!(eq.unwrap().query, "create_calls_edges");
+        assert!(helix_edge_query(&EdgeType::Contains, &NodeType::File, &NodeType::Function).is_none());
+
+        assert_eq!(relative_path("org/repo", "org/repo/src/main.rs"), "src/main.rs");
+        assert_eq!(file_language("src/main.rs", &[Language::Rust]), "rust");
+        assert_eq!(rfc3339(0), "1970-01-01T00:00:00Z");
+        assert_eq!(rfc3339(1700000000), "2023-11-14T22:13:20Z");
+    }
+}
@@ -0,0 +1,176 @@
+use super::{graph::Graph, EdgeType, Node, NodeType};
+use crate::utils::create_node_key;
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap, VecDeque};
+
+// changed lines of one file: 0-based rows of the new side, inclusive like NodeData start/end
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DiffHunk {
+    pub file: String,
+    pub start: usize,
+    pub end: usize,
+}
+
+impl DiffHunk {
+    // from a hunk header's new side: 1-based start and line count. a pure deletion
+    // (count 0) is pinned to the line it happened after
+    pub fn from_new_side(file: &str, start: usize, lines: usize) -> Self {
+        let first = start.saturating_sub(1);
+        DiffHunk {
+            file: file.to_string(),
+            start: first,
+            end: first + lines.saturating_sub(1),
+        }
+    }
+}
+
+// hunks of a unified diff (`git diff` output). deleted files are skipped
+pub fn parse_unified_diff(diff: &str) -> Vec<DiffHunk> {
+    let mut hunks = Vec::new();
+    let mut file: Option<String> = None;
+    for line in diff.lines() {
+        if let Some(path) = line.strip_prefix("+++ ") {
+            let path = path.split('\t').next().unwrap_or(path).trim();
+            file =
+                (path != "/dev/null").then(|| path.strip_prefix("b/").unwrap_or(path).to_string());
+        } else if line.starts_with("@@ ") {
+            let Some(file) = &file else {
+                continue;
+            };
+            let Some(new_side) = line.split_whitespace().find_map(|t| t.strip_prefix('+')) else {
+                continue;
+            };
+            let mut parts = new_side.splitn(2, ',');
+            let start = parts.next().and_then(|s| s.parse().ok());
+            let lines = parts.next().map_or(Some(1), |s| s.parse().ok());
+            if let (Some(start), Some(lines)) = (start, lines) {
+                hunks.push(DiffHunk::from_new_side(file, start, lines));
+            }
+        }
+    }
+    hunks
+}
+
+// node types a hunk can land in
+const CHANGED_TYPES: [NodeType; 3] = [NodeType::Function, NodeType::Class, NodeType::Endpoint];
+
+// who depends on a node: its callers, the endpoints it handles and the pages rendering it
+const IMPACT_EDGES: [EdgeType; 3] = [EdgeType::Calls, EdgeType::Handler, EdgeType::Renders];
+
+const AFFECTED_TYPES: [NodeType; 5] = [
+    NodeType::Endpoint,
+    NodeType::Page,
+    NodeType::UnitTest,
+    NodeType::IntegrationTest,
+    NodeType::E2eTest,
+];
+
+#[derive(Clone, Debug, Serialize)]
+pub struct Affected {
+    pub node: Node,
+    // fewest reverse hops from a changed node, 0 if it changed itself
+    pub distance: usize,
+    // how many changed nodes reach it
+    pub hits: usize,
+}
+
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct ImpactReport {
+    pub changed: Vec<Node>,
+    // nearest first, then the ones more changes reach
+    pub affected: Vec<Affected>,
+}
+
+impl ImpactReport {
+    pub fn tests(&self) -> impl Iterator<Item = &Affected> {
+        self.affected.iter().filter(|a| {
+            matches!(
+                a.node.node_type,
+                NodeType::UnitTest | NodeType::IntegrationTest | NodeType::E2eTest
+            )
+        })
+    }
+}
+
+// graph files carry the repo name in front, diff paths are relative to the repo
+pub(super) fn same_file(node_file: &str, diff_file: &str) -> bool {
+    node_file == diff_file
+        || node_file
+            .strip_suffix(diff_file)
+            .is_some_and(|prefix| prefix.ends_with('/'))
+}
+
+// the functions, classes and endpoints whose line ranges overlap a hunk
+pub fn changed_nodes<G: Graph>(graph: &G, hunks: &[DiffHunk]) -> Vec<Node> {
+    let mut changed = BTreeMap::new();
+    for hunk in hunks {
+        for node_type in CHANGED_TYPES {
+            for nd in graph.find_nodes_by_file_ends_with(node_type.clone(), &hunk.file) {
+                if same_file(&nd.file, &hunk.file) && nd.start <= hunk.end && nd.end >= hunk.start {
+                    let node = Node::new(node_type.clone(), nd);
+                    changed.insert(create_node_key(&node), node);
+                }
+            }
+        }
+    }
+    changed.into_values().collect()
+}
+
+// maps the hunks to changed nodes, then walks Calls/Handler/Renders edges backwards up to
+// `depth` hops to find the endpoints, pages and tests that can see the change
+pub fn analyze<G: Graph>(graph: &G, hunks: &[DiffHunk], depth: usize) -> ImpactReport {
+    let changed = changed_nodes(graph, hunks);
+
+    let mut dependents: HashMap<String, Vec<Node>> = HashMap::new();
+    for (edge_type, source, target) in graph.find_all_edges() {
+        if IMPACT_EDGES.contains(&edge_type) {
+            dependents
+                .entry(create_node_key(&target))
+                .or_default()
+                .push(source);
+        }
+    }
+
+    // key -> (node, distance, hits)
+    let mut reached: HashMap<String, (Node, usize, usize)> = HashMap::new();
+    for start in &changed {
+        let start_key = create_node_key(start);
+        let mut seen = HashMap::from([(start_key.clone(), 0)]);
+        let mut queue = VecDeque::from([(start.clone(), start_key, 0)]);
+        while let Some((node, key, level)) = queue.pop_front() {
+            if AFFECTED_TYPES.contains(&node.node_type) {
+                let entry = reached
+                    .entry(key.clone())
+                    .or_insert_with(|| (node.clone(), level, 0));
+                entry.1 = entry.1.min(level);
+                entry.2 += 1;
+            }
+            if level >= depth {
+                continue;
+            }
+            for source in dependents.get(&key).into_iter().flatten() {
+                let source_key = create_node_key(source);
+                if !seen.contains_key(&source_key) {
+                    seen.insert(source_key.clone(), level + 1);
+                    queue.push_back((source.clone(), source_key, level + 1));
+                }
+            }
+        }
+    }
+
+    let mut affected: Vec<Affected> = reached
+        .into_values()
+        .map(|(node, distance, hits)| Affected {
+            node,
+            distance,
+            hits,
+        })
+        .collect();
+    affected.sort_by(|a, b| {
+        a.distance
+            .cmp(&b.distance)
+            .then(b.hits.cmp(&a.hits))
+            .then_with(|| create_node_key(&a.node).cmp(&create_node_key(&b.node)))
+    });
+    ImpactReport { changed, affected }
+}
@@ -0,0 +1,108 @@
+use super::{graph::Graph, EdgeType, Node};
+use crate::utils::create_node_key;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+// NodeData.meta keys. pagerank is scaled so the top node has 1
+pub const PAGERANK: &str = "pagerank";
+pub const IN_DEGREE: &str = "in_degree";
+pub const OUT_DEGREE: &str = "out_degree";
+pub const IMPORTANCE_KEYS: [&str; 3] = [PAGERANK, IN_DEGREE, OUT_DEGREE];
+
+const RANK_EDGES: [EdgeType; 3] = [EdgeType::Calls, EdgeType::Imports, EdgeType::Handler];
+const DAMPING: f64 = 0.85;
+const MAX_ITERATIONS: usize = 100;
+const TOLERANCE: f64 = 1e-9;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Importance {
+    pub pagerank: f64,
+    pub in_degree: usize,
+    pub out_degree: usize,
+}
+
+// pagerank and degree centrality of every node with a Calls, Imports or Handler edge
+pub fn compute_importance<G: Graph>(graph: &G) -> Vec<(Node, Importance)> {
+    let mut nodes: BTreeMap<String, Node> = BTreeMap::new();
+    let mut links = BTreeSet::new();
+    for (edge_type, source, target) in graph.find_all_edges() {
+        if !RANK_EDGES.contains(&edge_type) {
+            continue;
+        }
+        let (source_key, target_key) = (create_node_key(&source), create_node_key(&target));
+        if source_key == target_key {
+            continue;
+        }
+        links.insert((source_key.clone(), target_key.clone()));
+        nodes.entry(source_key).or_insert(source);
+        nodes.entry(target_key).or_insert(target);
+    }
+    if nodes.is_empty() {
+        return Vec::new();
+    }
+
+    let position: HashMap<&str, usize> = nodes
+        .keys()
+        .enumerate()
+        .map(|(i, k)| (k.as_str(), i))
+        .collect();
+    let n = nodes.len();
+    let mut out_links = vec![Vec::new(); n];
+    let mut in_degree = vec![0; n];
+    for (source, target) in &links {
+        let (s, t) = (position[source.as_str()], position[target.as_str()]);
+        out_links[s].push(t);
+        in_degree[t] += 1;
+    }
+
+    let mut rank = vec![1.0 / n as f64; n];
+    for _ in 0..MAX_ITERATIONS {
+        // rank of nodes without outgoing links is spread over everyone
+        let dangling: f64 = (0..n)
+            .filter(|&i| out_links[i].is_empty())
+            .map(|i| rank[i])
+            .sum();
+        let base = (1.0 - DAMPING) / n as f64 + DAMPING * dangling / n as f64;
+        let mut next = vec![base; n];
+        for (i, targets) in out_links.iter().enumerate() {
+            let share = DAMPING * rank[i] / targets.len().max(1) as f64;
+            for &t in targets {
+                next[t] += share;
+            }
+        }
+        let delta: f64 = next.iter().zip(&rank).map(|(a, b)| (a - b).abs()).sum();
+        rank = next;
+        if delta < TOLERANCE {
+            break;
+        }
+    }
+
+    let top = rank.iter().cloned().fold(f64::MIN, f64::max);
+    nodes
+        .into_values()
+        .enumerate()
+        .map(|(i, node)| {
+            let importance = Importance {
+                pagerank: rank[i] / top,
+                in_degree: in_degree[i],
+                out_degree: out_links[i].len(),
+            };
+            (node, importance)
+        })
+        .collect()
+}
+
+// computes the scores and writes them into each node's meta
+pub fn store_importance<G: Graph>(graph: &mut G) {
+    let updates = compute_importance(graph)
+        .into_iter()
+        .map(|(node, importance)| {
+            let meta = BTreeMap::from([
+                (PAGERANK.to_string(), format!("{:.6}", importance.pagerank)),
+                (IN_DEGREE.to_string(), importance.in_degree.to_string()),
+                (OUT_DEGREE.to_string(), importance.out_degree.to_string()),
+            ]);
+            (node, meta)
+        })
+        .collect();
+    graph.extend_node_meta(updates);
+}
@@ -0,0 +1,415 @@
+use super::{graph::Graph, *};
+use crate::lang::{Function, FunctionCall, Lang};
+use crate::utils::create_node_key_from_ref;
+use lsp::Language;
+use serde::{Serialize, Serializer};
+use shared::error::Result;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+
+#[derive(Clone, Debug)]
+struct Interval {
+    start: usize,
+    end: usize,
+    key: String,
+}
+
+// the nodes of one type in one file, sorted by start. max_end[i] is the largest end in
+// intervals[..=i], so a row lookup walks left only while something there can still reach it
+#[derive(Clone, Debug, Default)]
+struct FileIntervals {
+    intervals: Vec<Interval>,
+    max_end: Vec<usize>,
+}
+
+impl FileIntervals {
+    fn insert(&mut self, start: usize, end: usize, key: String) {
+        let i = self.intervals.partition_point(|iv| iv.start <= start);
+        self.intervals.insert(i, Interval { start, end, key });
+        self.fix_max_end(i);
+    }
+
+    fn remove(&mut self, key: &str) {
+        if let Some(i) = self.intervals.iter().position(|iv| iv.key == key) {
+            self.intervals.remove(i);
+            self.fix_max_end(i);
+        }
+    }
+
+    fn fix_max_end(&mut self, from: usize) {
+        self.max_end.truncate(from);
+        for iv in &self.intervals[from..] {
+            let prev = self.max_end.last().copied().unwrap_or(0);
+            self.max_end.push(prev.max(iv.end));
+        }
+    }
+
+    fn containing(&self, row: usize) -> impl Iterator<Item = &str> {
+        let n = self.intervals.partition_point(|iv| iv.start <= row);
+        (0..n)
+            .rev()
+            .take_while(move |&i| self.max_end[i] >= row)
+            .filter(move |&i| self.intervals[i].end >= row)
+            .map(|i| self.intervals[i].key.as_str())
+    }
+
+    fn starting_at(&self, row: usize) -> impl Iterator<Item = &str> {
+        let from = self.intervals.partition_point(|iv| iv.start < row);
+        self.intervals[from..]
+            .iter()
+            .take_while(move |iv| iv.start == row)
+            .map(|iv| iv.key.as_str())
+    }
+
+    fn is_empty(&self) -> bool {
+        self.intervals.is_empty()
+    }
+}
+
+// secondary indexes over the node keys of a BTreeMapGraph. lookups by type are already
+// key prefix ranges there, these cover names, files and line ranges
+#[derive(Clone, Debug, Default)]
+struct NodeIndex {
+    by_name: BTreeMap<(NodeType, String), BTreeSet<String>>,
+    by_file: BTreeMap<(NodeType, String), FileIntervals>,
+    // key -> (type, name, file) it is indexed under, so a re-added key replaces its entries
+    entries: HashMap<String, (NodeType, String, String)>,
+}
+
+impl NodeIndex {
+    fn build(graph: &BTreeMapGraph) -> Self {
+        let mut index = NodeIndex::default();
+        for (key, node) in &graph.nodes {
+            index.insert(key, node);
+        }
+        index
+    }
+
+    fn insert(&mut self, key: &str, node: &Node) {
+        self.remove(key);
+        let nd = &node.node_data;
+        self.by_name
+            .entry((node.node_type.clone(), nd.name.clone()))
+            .or_default()
+            .insert(key.to_string());
+        self.by_file
+            .entry((node.node_type.clone(), nd.file.clone()))
+            .or_default()
+            .insert(nd.start, nd.end, key.to_string());
+        self.entries.insert(
+            key.to_string(),
+            (node.node_type.clone(), nd.name.clone(), nd.file.clone()),
+        );
+    }
+
+    fn remove(&mut self, key: &str) {
+        let Some((node_type, name, file)) = self.entries.remove(key) else {
+            return;
+        };
+        let name_key = (node_type.clone(), name);
+        if let Some(keys) = self.by_name.get_mut(&name_key) {
+            keys.remove(key);
+            if keys.is_empty() {
+                self.by_name.remove(&name_key);
+            }
+        }
+        let file_key = (node_type, file);
+        if let Some(intervals) = self.by_file.get_mut(&file_key) {
+            intervals.remove(key);
+            if intervals.is_empty() {
+                self.by_file.remove(&file_key);
+            }
+        }
+    }
+}
+
+fn key_of(node_type: NodeType, node_data: &NodeData) -> String {
+    create_node_key_from_ref(&NodeRef::from(node_data.into(), node_type))
+}
+
+// a BTreeMapGraph with name, file and line interval indexes, so the lookups call resolution
+// makes for every call site don't scan all nodes of a type. the default build backend
+#[derive(Clone, Debug, Default)]
+pub struct IndexedGraph {
+    graph: BTreeMapGraph,
+    index: NodeIndex,
+}
+
+impl IndexedGraph {
+    pub fn inner(&self) -> &BTreeMapGraph {
+        &self.graph
+    }
+
+    pub fn into_inner(self) -> BTreeMapGraph {
+        self.graph
+    }
+
+    // indexes whichever of `keys` a delegated call actually added
+    fn index_keys(&mut self, keys: impl IntoIterator<Item = String>) {
+        for key in keys {
+            if let Some(node) = self.graph.nodes.get(&key) {
+                self.index.insert(&key, node);
+            }
+        }
+    }
+
+    fn reindex(&mut self) {
+        self.index = NodeIndex::build(&self.graph);
+    }
+
+    fn nodes_for<'a>(&self, keys: impl IntoIterator<Item = &'a str>) -> Vec<NodeData> {
+        keys.into_iter()
+            .filter_map(|key| self.graph.nodes.get(key))
+            .map(|node| node.node_data.clone())
+            .collect()
+    }
+
+    // BTreeMapGraph returns the first match in key order, keep that when several nodes match
+    fn first_of<'a>(&self, keys: impl IntoIterator<Item = &'a str>) -> Option<NodeData> {
+        keys.into_iter()
+            .min()
+            .and_then(|key| self.graph.nodes.get(key))
+            .map(|node| node.node_data.clone())
+    }
+}
+
+impl From<BTreeMapGraph> for IndexedGraph {
+    fn from(graph: BTreeMapGraph) -> Self {
+        let index = NodeIndex::build(&graph);
+        IndexedGraph { graph, index }
+    }
+}
+
+impl Serialize for IndexedGraph {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        self.graph.serialize(serializer)
+    }
+}
+
+impl Graph for IndexedGraph {
+    fn new(root: String, lang_kind: Language) -> Self {
+        BTreeMapGraph::new(root, lang_kind).into()
+    }
+    fn with_capacity(nodes: usize, edges: usize, root: String, lang_kind: Language) -> Self {
+        BTreeMapGraph::with_capacity(nodes, edges, root, lang_kind).into()
+    }
+    fn analysis(&self) {
+        self.graph.analysis()
+    }
+    fn create_filtered_graph(self, final_filter: &[String], lang_kind: Language) -> Self {
+        self.graph
+            .create_filtered_graph(final_filter, lang_kind)
+            .into()
+    }
+    fn extend_graph(&mut self, other: Self) {
+        let keys: Vec<String> = other.graph.nodes.keys().cloned().collect();
+        self.graph.extend_graph(other.graph);
+        self.index_keys(keys);
+    }
+    fn get_graph_size(&self) -> (u32, u32) {
+        self.graph.get_graph_size()
+    }
+    fn find_nodes_by_name(&self, node_type: NodeType, name: &str) -> Vec<NodeData> {
+        match self.index.by_name.get(&(node_type, name.to_string())) {
+            Some(keys) => self.nodes_for(keys.iter().map(|k| k.as_str())),
+            None => Vec::new(),
+        }
+    }
+    fn add_node_with_parent(
+        &mut self,
+        node_type: NodeType,
+        node_data: NodeData,
+        parent_type: NodeType,
+        parent_file: &str,
+    ) {
+        let key = key_of(node_type.clone(), &node_data);
+        self.graph
+            .add_node_with_parent(node_type, node_data, parent_type, parent_file);
+        self.index_keys([key]);
+    }
+    fn add_edge(&mut self, edge: Edge) {
+        self.graph.add_edge(edge)
+    }
+    fn add_node(&mut self, node_type: NodeType, node_data: NodeData) {
+        let key = key_of(node_type.clone(), &node_data);
+        self.graph.add_node(node_type, node_data);
+        self.index_keys([key]);
+    }
+    fn get_graph_keys(&self) -> (HashSet<String>, HashSet<String>) {
+        self.graph.get_graph_keys()
+    }
+    fn find_source_edge_by_name_and_file(
+        &self,
+        edge_type: EdgeType,
+        target_name: &str,
+        target_file: &str,
+    ) -> Option<NodeKeys> {
+        self.graph
+            .find_source_edge_by_name_and_file(edge_type, target_name, target_file)
+    }
+    fn process_endpoint_groups(&mut self, eg: Vec<NodeData>, lang: &Lang) -> Result<()> {
+        // renames endpoints, which changes their keys
+        self.graph.process_endpoint_groups(eg, lang)?;
+        self.reindex();
+        Ok(())
+    }
+    fn class_inherits(&mut self) {
+        self.graph.class_inherits()
+    }
+    fn class_includes(&mut self) {
+        self.graph.class_includes()
+    }
+    fn add_instances(&mut self, nodes: Vec<NodeData>) {
+        let keys: Vec<String> = nodes
+            .iter()
+            .map(|n| key_of(NodeType::Instance, n))
+            .collect();
+        self.graph.add_instances(nodes);
+        self.index_keys(keys);
+    }
+    fn add_functions(&mut self, functions: Vec<Function>) {
+        let mut keys = Vec::new();
+        for (func, _, reqs, ..) in &functions {
+            keys.push(key_of(NodeType::Function, func));
+            keys.extend(reqs.iter().map(|r| key_of(NodeType::Request, r)));
+        }
+        self.graph.add_functions(functions);
+        self.index_keys(keys);
+    }
+    fn add_page(&mut self, page: (NodeData, Option<Edge>)) {
+        let key = key_of(NodeType::Page, &page.0);
+        self.graph.add_page(page);
+        self.index_keys([key]);
+    }
+    fn add_pages(&mut self, pages: Vec<(NodeData, Vec<Edge>)>) {
+        let keys: Vec<String> = pages
+            .iter()
+            .map(|(p, _)| key_of(NodeType::Page, p))
+            .collect();
+        self.graph.add_pages(pages);
+        self.index_keys(keys);
+    }
+    fn add_endpoints(&mut self, endpoints: Vec<(NodeData, Option<Edge>)>) {
+        let keys: Vec<String> = endpoints
+            .iter()
+            .map(|(e, _)| key_of(NodeType::Endpoint, e))
+            .collect();
+        self.graph.add_endpoints(endpoints);
+        self.index_keys(keys);
+    }
+    fn add_tests(&mut self, tests: Vec<TestRecord>) {
+        let keys: Vec<String> = tests
+            .iter()
+            .map(|t| key_of(t.kind.clone(), &t.node))
+            .collect();
+        self.graph.add_tests(tests);
+        self.index_keys(keys);
+    }
+    fn add_calls(&mut self, calls: (Vec<FunctionCall>, Vec<FunctionCall>, Vec<Edge>, Vec<Edge>)) {
+        // external functions that were called get added as nodes
+        let keys: Vec<String> = calls
+            .0
+            .iter()
+            .chain(calls.1.iter())
+            .filter_map(|(_, ext, _)| ext.as_ref())
+            .map(|ext| key_of(NodeType::Function, ext))
+            .collect();
+        self.graph.add_calls(calls);
+        self.index_keys(keys);
+    }
+    fn filter_out_nodes_without_children(
+        &mut self,
+        parent_type: NodeType,
+        child_type: NodeType,
+        child_meta_key: &str,
+    ) {
+        self.graph
+            .filter_out_nodes_without_children(parent_type, child_type, child_meta_key);
+        self.reindex();
+    }
+    fn get_data_models_within(&mut self, lang: &Lang) {
+        self.graph.get_data_models_within(lang)
+    }
+    fn find_endpoint(&self, name: &str, file: &str, verb: &str) -> Option<NodeData> {
+        self.graph.find_endpoint(name, file, verb)
+    }
+    fn find_resource_nodes(&self, node_type: NodeType, verb: &str, path: &str) -> Vec<NodeData> {
+        self.graph.find_resource_nodes(node_type, verb, path)
+    }
+    fn find_handlers_for_endpoint(&self, endpoint: &NodeData) -> Vec<NodeData> {
+        self.graph.find_handlers_for_endpoint(endpoint)
+    }
+    fn check_direct_data_model_usage(&self, function_name: &str, data_model: &str) -> bool {
+        self.graph
+            .check_direct_data_model_usage(function_name, data_model)
+    }
+    fn find_functions_called_by(&self, function: &NodeData) -> Vec<NodeData> {
+        self.graph.find_functions_called_by(function)
+    }
+    fn find_nodes_by_type(&self, node_type: NodeType) -> Vec<NodeData> {
+        self.graph.find_nodes_by_type(node_type)
+    }
+    fn find_nodes_with_edge_type(
+        &self,
+        source_type: NodeType,
+        target_type: NodeType,
+        edge_type: EdgeType,
+    ) -> Vec<(NodeData, NodeData)> {
+        self.graph
+            .find_nodes_with_edge_type(source_type, target_type, edge_type)
+    }
+    fn count_edges_of_type(&self, edge_type: EdgeType) -> usize {
+        self.graph.count_edges_of_type(edge_type)
+    }
+    fn find_nodes_by_name_contains(&self, node_type: NodeType, name: &str) -> Vec<NodeData> {
+        self.graph.find_nodes_by_name_contains(node_type, name)
+    }
+    fn find_node_by_name_in_file(
+        &self,
+        node_type: NodeType,
+        name: &str,
+        file: &str,
+    ) -> Option<NodeData> {
+        self.graph.find_node_by_name_in_file(node_type, name, file)
+    }
+    fn find_nodes_by_file_ends_with(&self, node_type: NodeType, file: &str) -> Vec<NodeData> {
+        // walk the files of this type instead of every node
+        let keys: BTreeSet<&str> = self
+            .index
+            .by_file
+            .range((node_type.clone(), String::new())..)
+            .take_while(|((nt, _), _)| *nt == node_type)
+            .filter(|((_, f), _)| f.ends_with(file))
+            .flat_map(|(_, intervals)| intervals.intervals.iter().map(|iv| iv.key.as_str()))
+            .collect();
+        self.nodes_for(keys)
+    }
+    fn find_node_by_name_and_file_end_with(
+        &self,
+        node_type: NodeType,
+        name: &str,
+        suffix: &str,
+    ) -> Option<NodeData> {
+        self.graph
+            .find_node_by_name_and_file_end_with(node_type, name, suffix)
+    }
+    fn find_node_in_range(&self, node_type: NodeType, row: u32, file: &str) -> Option<NodeData> {
+        let intervals = self.index.by_file.get(&(node_type, file.to_string()))?;
+        self.first_of(intervals.containing(row as usize))
+    }
+    fn find_node_at(&self, node_type: NodeType, file: &str, line: u32) -> Option<NodeData> {
+        let intervals = self.index.by_file.get(&(node_type, file.to_string()))?;
+        self.first_of(intervals.starting_at(line as usize))
+    }
+    fn has_edge(&self, source: &Node, target: &Node, edge_type: EdgeType) -> bool {
+        self.graph.has_edge(source, target, edge_type)
+    }
+    fn find_all_nodes(&self) -> Vec<Node> {
+        self.graph.find_all_nodes()
+    }
+    fn find_all_edges(&self) -> Vec<(EdgeType, Node, Node)> {
+        self.graph.find_all_edges()
+    }
+    fn call_graph(&self) -> CallGraph {
+        self.graph.call_graph()
+    }
+}
@@ -0,0 +1,335 @@
+use super::export::node_color;
+use super::{graph::Graph, EdgeType, Node, NodeType};
+use crate::utils::create_node_key;
+use shared::{Error, Result};
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
+use std::fmt::Write;
+use std::str::FromStr;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MermaidKind {
+    Sequence,
+    Flowchart,
+    Class,
+}
+
+impl MermaidKind {
+    // what a root of this type is usually drawn as
+    pub fn for_root(node_type: &NodeType) -> Self {
+        match node_type {
+            NodeType::Endpoint | NodeType::Request => MermaidKind::Sequence,
+            NodeType::Class | NodeType::Trait | NodeType::Interface => MermaidKind::Class,
+            _ => MermaidKind::Flowchart,
+        }
+    }
+}
+
+impl FromStr for MermaidKind {
+    type Err = Error;
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_lowercase().as_str() {
+            "sequence" | "sequencediagram" => Ok(MermaidKind::Sequence),
+            "flowchart" | "flow" => Ok(MermaidKind::Flowchart),
+            "class" | "classdiagram" => Ok(MermaidKind::Class),
+            _ => Err(Error::Custom(format!(
+                "Invalid mermaid diagram kind: {}",
+                s
+            ))),
+        }
+    }
+}
+
+const WALKED_EDGES: [EdgeType; 6] = [
+    EdgeType::Handler,
+    EdgeType::Calls,
+    EdgeType::Contains,
+    EdgeType::Operand,
+    EdgeType::ParentOf,
+    EdgeType::Implements,
+];
+
+// hierarchy edges are followed both ways so a class shows its parents and children
+fn walks_backwards(edge_type: &EdgeType) -> bool {
+    matches!(edge_type, EdgeType::ParentOf | EdgeType::Implements)
+}
+
+struct Walk {
+    nodes: Vec<Node>,
+    // (edge, source index, target index) in the order they were reached
+    edges: Vec<(EdgeType, usize, usize)>,
+}
+
+impl Walk {
+    fn index(&mut self, ids: &mut HashMap<String, usize>, node: &Node) -> usize {
+        *ids.entry(create_node_key(node)).or_insert_with(|| {
+            self.nodes.push(node.clone());
+            self.nodes.len() - 1
+        })
+    }
+}
+
+// depth first, so sequence diagrams list calls in the order they nest
+fn walk<G: Graph>(graph: &G, root: &Node, kind: MermaidKind, depth: usize) -> Walk {
+    let mut out: BTreeMap<String, Vec<(EdgeType, Node, bool)>> = BTreeMap::new();
+    for (edge_type, source, target) in graph.find_all_edges() {
+        if !WALKED_EDGES.contains(&edge_type) {
+            continue;
+        }
+        if walks_backwards(&edge_type) {
+            out.entry(create_node_key(&target)).or_default().push((
+                edge_type.clone(),
+                source.clone(),
+                false,
+            ));
+        }
+        out.entry(create_node_key(&source))
+            .or_default()
+            .push((edge_type, target, true));
+    }
+
+    let mut walk = Walk {
+        nodes: Vec::new(),
+        edges: Vec::new(),
+    };
+    let mut ids = HashMap::new();
+    let mut seen_edges = HashSet::new();
+    let mut pending = VecDeque::from([(root.clone(), 0)]);
+    let root_index = walk.index(&mut ids, root);
+    let mut expanded = HashSet::from([root_index]);
+    while let Some((node, level)) = pending.pop_front() {
+        if level >= depth {
+            continue;
+        }
+        let from = walk.index(&mut ids, &node);
+        let mut next = Vec::new();
+        for (edge_type, other, forward) in out.get(&create_node_key(&node)).into_iter().flatten() {
+            if !kind_follows(kind, edge_type) {
+                continue;
+            }
+            let to = walk.index(&mut ids, other);
+            let (source, target) = if *forward { (from, to) } else { (to, from) };
+            if seen_edges.insert((edge_type.clone(), source, target)) {
+                walk.edges.push((edge_type.clone(), source, target));
+            }
+            if expanded.insert(to) {
+                next.push((other.clone(), level + 1));
+            }
+        }
+        for item in next.into_iter().rev() {
+            pending.push_front(item);
+        }
+    }
+    walk
+}
+
+fn kind_follows(kind: MermaidKind, edge_type: &EdgeType) -> bool {
+    match kind {
+        MermaidKind::Sequence => matches!(edge_type, EdgeType::Handler | EdgeType::Calls),
+        MermaidKind::Flowchart => true,
+        MermaidKind::Class => matches!(
+            edge_type,
+            EdgeType::ParentOf | EdgeType::Implements | EdgeType::Operand | EdgeType::Contains
+        ),
+    }
+}
+
+// mermaid labels break on quotes and angle brackets, use the html entities it understands
+fn label(s: &str) -> String {
+    s.replace('"', "#quot;")
+        .replace('<', "#lt;")
+        .replace('>', "#gt;")
+        .replace('\n', " ")
+}
+
+fn type_class(node_type: &NodeType) -> String {
+    node_type.to_string().to_lowercase()
+}
+
+// walks Handler/Calls/Contains/ParentOf/Implements edges out of `root` up to `depth` hops
+// and renders what it reached as mermaid text
+pub fn to_mermaid<G: Graph>(
+    graph: &G,
+    root: &Node,
+    kind: MermaidKind,
+    depth: usize,
+) -> String {
+    let walk = walk(graph, root, kind, depth);
+    match kind {
+        MermaidKind::Sequence => sequence(&walk),
+        MermaidKind::Flowchart => flowchart(&walk),
+        MermaidKind::Class => class_diagram(&walk),
+    }
+}
+
+fn sequence(walk: &Walk) -> String {
+    let mut out = String::from("sequenceDiagram\n");
+    for (i, node) in walk.nodes.iter().enumerate() {
+        let kind = match node.node_type {
+            NodeType::Endpoint | NodeType::Request | NodeType::Page => "actor",
+            _ => "participant",
+        };
+        let _ = writeln!(out, "    {kind} n{i} as {}", label(&node.node_data.name));
+    }
+    for (edge_type, source, target) in &walk.edges {
+        let target_name = &walk.nodes[*target].node_data.name;
+        let message = match edge_type {
+            EdgeType::Handler => format!("handled by {}", target_name),
+            _ => format!("{}()", target_name),
+        };
+        let _ = writeln!(out, "    n{source}->>n{target}: {}", label(&message));
+    }
+    out
+}
+
+fn flowchart(walk: &Walk) -> String {
+    let mut out = String::from("flowchart TD\n");
+    let mut types = BTreeMap::new();
+    for (i, node) in walk.nodes.iter().enumerate() {
+        let _ = writeln!(
+            out,
+            "    n{i}[\"{}: {}\"]:::{}",
+            node.node_type.to_string(),
+            label(&node.node_data.name),
+            type_class(&node.node_type)
+        );
+        types.insert(type_class(&node.node_type), node_color(&node.node_type));
+    }
+    for (edge_type, source, target) in &walk.edges {
+        let _ = writeln!(
+            out,
+            "    n{source} -->|{}| n{target}",
+            edge_type.to_string()
+        );
+    }
+    for (class, color) in types {
+        let _ = writeln!(out, "    classDef {class} fill:{color},color:#fff");
+    }
+    out
+}
+
+fn class_diagram(walk: &Walk) -> String {
+    let mut out = String::from("classDiagram\n");
+    let is_type = |n: &Node| {
+        matches!(
+            n.node_type,
+            NodeType::Class | NodeType::Trait | NodeType::Interface | NodeType::DataModel
+        )
+    };
+    let mut methods: BTreeMap<usize, Vec<&str>> = BTreeMap::new();
+    for (edge_type, source, target) in &walk.edges {
+        let member = &walk.nodes[*target];
+        if matches!(edge_type, EdgeType::Operand | EdgeType::Contains)
+            && member.node_type == NodeType::Function
+        {
+            methods
+                .entry(*source)
+                .or_default()
+                .push(&member.node_data.name);
+        }
+    }
+    for (i, node) in walk.nodes.iter().enumerate() {
+        if !is_type(node) {
+            continue;
+        }
+        let _ = writeln!(
+            out,
+            "    class n{i}[\"{}\"] {{",
+            label(&node.node_data.name)
+        );
+        match node.node_type {
+            NodeType::Trait | NodeType::Interface => {
+                let _ = writeln!(out, "        <<interface>>");
+            }
+            NodeType::DataModel => {
+                let _ = writeln!(out, "        <<datamodel>>");
+            }
+            _ => {}
+        }
+        for method in methods.get(&i).into_iter().flatten() {
+            let _ = writeln!(out, "        +{}()", label(method));
+        }
+        out.push_str("    }\n");
+    }
+    for (edge_type, source, target) in &walk.edges {
+        if !is_type(&walk.nodes[*source]) || !is_type(&walk.nodes[*target]) {
+            continue;
+        }
+        match edge_type {
+            // parent -> child
+            EdgeType::ParentOf => {
+                let _ = writeln!(out, "    n{source} <|-- n{target}");
+            }
+            // class -> trait
+            EdgeType::Implements => {
+                let _ = writeln!(out, "    n{target} <|.. n{source}");
+            }
+            _ => {}
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lang::graphs::{BTreeMapGraph, Edge, NodeRef};
+    use crate::lang::NodeData;
+
+    fn sample() -> (BTreeMapGraph, Node, Node) {
+        let mut graph = BTreeMapGraph::default();
+        let mut endpoint = NodeData::name_file("/users", "src/routes.rs");
+        endpoint.add_verb("GET");
+        let handler = NodeData::name_file("list_users", "src/routes.rs");
+        let query = NodeData::name_file("find_all", "src/db.rs");
+        let base = NodeData::name_file("Repo", "src/db.rs");
+        let users = NodeData::name_file("UserRepo", "src/db.rs");
+        graph.add_node(NodeType::Endpoint, endpoint.clone());
+        graph.add_node(NodeType::Function, handler.clone());
+        graph.add_node(NodeType::Function, query.clone());
+        graph.add_node(NodeType::Class, base.clone());
+        graph.add_node(NodeType::Class, users.clone());
+        graph.add_edge(Edge::handler(&endpoint, &handler));
+        graph.add_edge(Edge::calls(
+            NodeType::Function,
+            &handler,
+            NodeType::Function,
+            &query,
+        ));
+        graph.add_edge(Edge::new(
+            EdgeType::Operand,
+            NodeRef::from((&users).into(), NodeType::Class),
+            NodeRef::from((&query).into(), NodeType::Function),
+        ));
+        graph.add_edge(Edge::parent_of(&base, &users));
+        (
+            graph,
+            Node::new(NodeType::Endpoint, endpoint),
+            Node::new(NodeType::Class, base),
+        )
+    }
+
+    #[test]
+    fn test_mermaid_diagrams() {
+        let (graph, endpoint, base) = sample();
+
+        let seq = to_mermaid(&graph, &endpoint, MermaidKind::Sequence, 3);
+        assert!(seq.starts_with("sequenceDiagram"));
+        assert!(seq.contains("n0->>n1: handled by list_users"));
+        assert!(seq.contains("n1->>n2: find_all()"));
+
+        let shallow = to_mermaid(&graph, &endpoint, MermaidKind::Sequence, 1);
+        assert!(!shallow.contains("find_all"));
+
+        let flow = to_mermaid(&graph, &endpoint, MermaidKind::Flowchart, 2);
+        assert!(flow.contains("n0 -->|HANDLER| n1"));
+        assert!(flow.contains("n1 -->|CALLS| n2"));
+        assert!(flow.contains("classDef endpoint"));
+
+        // walking down from the parent also reaches the child's methods
+        let class = graph.to_mermaid(&base, MermaidKind::Class, 2);
+        assert!(class.contains("class n0[\"Repo\"]"));
+        assert!(class.contains("class n1[\"UserRepo\"]"));
+        assert!(class.contains("+find_all()"));
+        assert!(class.contains("n0 <|-- n1"));
+    }
+}
@@ -1,6 +1,21 @@
+pub mod architecture;
 pub mod array_graph;
 pub mod btreemap_graph;
+pub mod call_graph;
+pub mod clones;
+pub mod context_pack;
+pub mod data_model_trace;
+pub mod coverage;
+pub mod dead_code;
+pub mod export;
 pub mod graph;
+pub mod impact;
+pub mod importance;
+pub mod indexed_graph;
+pub mod mermaid;
+pub mod scip_export;
+pub mod snapshot;
+pub mod subgraph;
 pub mod utils;
 
 #[cfg(feature = "neo4j")]
@@ -12,14 +27,37 @@ pub mod neo4j_utils;
 #[cfg(feature = "neo4j")]
 pub mod graph_ops;
 
+#[cfg(feature = "neo4j")]
+pub mod neo4j_csv;
+
+#[cfg(feature = "sqlite")]
+pub mod sqlite_graph;
+
+#[cfg(feature = "sqlite")]
+pub mod sqlite_ops;
+
+#[cfg(feature = "helix")]
+pub mod helix_graph;
+
+#[cfg(feature = "helix")]
+pub mod helix_ops;
+
 use std::str::FromStr;
 
 pub use array_graph::*;
 pub use btreemap_graph::*;
+pub use call_graph::{CallDirection, CallGraph};
 pub use graph::*;
+pub use indexed_graph::IndexedGraph;
+pub use mermaid::MermaidKind;
+pub use snapshot::GraphSnapshot;
 
 #[cfg(feature = "neo4j")]
 pub use neo4j_graph::*;
+#[cfg(feature = "sqlite")]
+pub use sqlite_graph::*;
+#[cfg(feature = "helix")]
+pub use helix_graph::*;
 use shared::Error;
 
 use crate::lang::asg::*;
@@ -47,8 +85,41 @@ pub enum NodeType {
     Feature,
     Page,
     Var,
+    Enum,
+    Interface,
+    TypeAlias,
+    Const,
+    Module,
 }
 
+// every variant in declaration order. snapshots store positions in these, so append new ones
+pub const NODE_TYPES: [NodeType; 24] = [
+    NodeType::Repository,
+    NodeType::Language,
+    NodeType::Directory,
+    NodeType::File,
+    NodeType::Import,
+    NodeType::Library,
+    NodeType::Class,
+    NodeType::Trait,
+    NodeType::Instance,
+    NodeType::Function,
+    NodeType::UnitTest,
+    NodeType::IntegrationTest,
+    NodeType::E2eTest,
+    NodeType::Endpoint,
+    NodeType::Request,
+    NodeType::DataModel,
+    NodeType::Feature,
+    NodeType::Page,
+    NodeType::Var,
+    NodeType::Enum,
+    NodeType::Interface,
+    NodeType::TypeAlias,
+    NodeType::Const,
+    NodeType::Module,
+];
+
 // pub enum TestType {
 //     Unit,
 //     Integration,
@@ -73,7 +144,7 @@ pub struct Edge {
 #[serde(rename_all = "UPPERCASE")]
 pub enum EdgeType {
     Calls,    // Function -> Function
-    Uses,     // like Calls but for libraries
+    Uses,     // like Calls but for libraries (also Library -> Library dependencies)
     Operand,  // Class -> Function
     ArgOf,    // Function -> Arg
     Contains, // Module -> Function/Class/Module OR File -> Function/Class/Module
@@ -85,8 +156,28 @@ pub enum EdgeType {
     #[serde(rename = "PARENT_OF")]
     ParentOf, // Class -> Class
     Implements, // Class -> Trait
+    Exports,    // Module -> re-exported Function/Class/Var/Module
+    #[serde(rename = "SIMILAR_TO")]
+    SimilarTo, // Function -> Function with a (near) duplicate body
 }
 
+pub const EDGE_TYPES: [EdgeType; 14] = [
+    EdgeType::Calls,
+    EdgeType::Uses,
+    EdgeType::Operand,
+    EdgeType::ArgOf,
+    EdgeType::Contains,
+    EdgeType::Imports,
+    EdgeType::Of,
+    EdgeType::Handler,
+    EdgeType::Includes,
+    EdgeType::Renders,
+    EdgeType::ParentOf,
+    EdgeType::Implements,
+    EdgeType::Exports,
+    EdgeType::SimilarTo,
+];
+
 #[derive(Clone, Debug, Serialize, Deserialize, Eq, PartialEq, PartialOrd, Ord)]
 pub struct NodeRef {
     pub node_type: NodeType,
@@ -222,6 +313,27 @@ impl Edge {
             NodeRef::from(target.into(), target_type),
         )
     }
+    pub fn function_uses(func: &NodeData, target_type: NodeType, target: &NodeData) -> Edge {
+        Edge::new(
+            EdgeType::Uses,
+            NodeRef::from(func.into(), NodeType::Function),
+            NodeRef::from(target.into(), target_type),
+        )
+    }
+    pub fn library_uses(lib: &NodeData, dep: &NodeData) -> Edge {
+        Edge::new(
+            EdgeType::Uses,
+            NodeRef::from(lib.into(), NodeType::Library),
+            NodeRef::from(dep.into(), NodeType::Library),
+        )
+    }
+    pub fn module_exports(module: &NodeData, target_type: NodeType, target: &NodeData) -> Edge {
+        Edge::new(
+            EdgeType::Exports,
+            NodeRef::from(module.into(), NodeType::Module),
+            NodeRef::from(target.into(), target_type),
+        )
+    }
     pub fn implements(class: &NodeData, tr: &NodeData) -> Edge {
         Edge::new(
             EdgeType::Implements,
@@ -229,6 +341,13 @@ impl Edge {
             NodeRef::from(tr.into(), NodeType::Trait),
         )
     }
+    pub fn similar_to(f: &NodeData, other: &NodeData) -> Edge {
+        Edge::new(
+            EdgeType::SimilarTo,
+            NodeRef::from(f.into(), NodeType::Function),
+            NodeRef::from(other.into(), NodeType::Function),
+        )
+    }
 }
 
 impl From<Operand> for Edge {
@@ -288,6 +407,8 @@ impl ToString for EdgeType {
             EdgeType::Includes => "INCLUDES".to_string(),
             EdgeType::Calls => "CALLS".to_string(),
             EdgeType::Implements => "IMPLEMENTS".to_string(),
+            EdgeType::Exports => "EXPORTS".to_string(),
+            EdgeType::SimilarTo => "SIMILAR_TO".to_string(),
         }
     }
 }
@@ -308,6 +429,8 @@ impl FromStr for EdgeType {
             "RENDERS" => Ok(EdgeType::Renders),
             "PARENT_OF" => Ok(EdgeType::ParentOf),
             "IMPLEMENTS" => Ok(EdgeType::Implements),
+            "EXPORTS" => Ok(EdgeType::Exports),
+            "SIMILAR_TO" => Ok(EdgeType::SimilarTo),
             _ => Err(Error::Custom(format!("Invalid EdgeType: {}", s))),
         }
     }
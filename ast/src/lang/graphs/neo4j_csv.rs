@@ -0,0 +1,228 @@
+use super::neo4j_utils::{add_node_query, DATA_BANK};
+use super::{graph::Graph, EdgeType, NodeType};
+use crate::utils::create_node_key;
+use neo4rs::BoltType;
+use shared::error::{Context, Result};
+use std::collections::{BTreeMap, HashSet};
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+use tracing::{info, warn};
+
+// files written by `write_neo4j_csv`, one per node type and per edge type
+#[derive(Debug, Default)]
+pub struct CsvExport {
+    pub nodes: Vec<PathBuf>,
+    pub edges: Vec<PathBuf>,
+    pub node_count: usize,
+    pub edge_count: usize,
+}
+
+impl CsvExport {
+    // arguments for `neo4j-admin database import full <database>`.
+    // bodies span lines, so the import has to allow multiline fields
+    pub fn import_args(&self) -> Vec<String> {
+        let mut args: Vec<String> = self
+            .nodes
+            .iter()
+            .map(|p| format!("--nodes={}", p.display()))
+            .collect();
+        args.extend(
+            self.edges
+                .iter()
+                .map(|p| format!("--relationships={}", p.display())),
+        );
+        args.push("--multiline-fields=true".to_string());
+        args
+    }
+}
+
+// neo4j-admin column types for the bolt values add_node_query produces
+fn column_type(value: &BoltType) -> &'static str {
+    match value {
+        BoltType::Integer(_) => ":long",
+        BoltType::Float(_) => ":double",
+        BoltType::Boolean(_) => ":boolean",
+        _ => "",
+    }
+}
+
+fn column_value(value: &BoltType) -> String {
+    match value {
+        BoltType::String(s) => s.value.clone(),
+        BoltType::Integer(i) => i.value.to_string(),
+        BoltType::Float(f) => f.value.to_string(),
+        BoltType::Boolean(b) => b.value.to_string(),
+        _ => String::new(),
+    }
+}
+
+fn csv_field(s: &str) -> String {
+    format!("\"{}\"", s.replace('"', "\"\""))
+}
+
+fn write_row(out: &mut impl Write, fields: &[String]) -> std::io::Result<()> {
+    writeln!(out, "{}", fields.join(","))
+}
+
+// writes neo4j-admin bulk import csvs for the whole graph into `dir`, nodes-<Type>.csv and
+// edges-<TYPE>.csv. nodes carry the same key and properties as add_node_query, so later
+// incremental syncs merge onto them
+pub fn write_neo4j_csv<G: Graph>(graph: &G, dir: &Path) -> Result<CsvExport> {
+    std::fs::create_dir_all(dir).context("failed to create csv export dir")?;
+    // the bolt upload stamps every node with the time of the query
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| format!("{:.7}", d.as_secs_f64()))
+        .unwrap_or_default();
+
+    let mut by_type: BTreeMap<NodeType, Vec<BTreeMap<String, BoltType>>> = BTreeMap::new();
+    let mut keys = HashSet::new();
+    for node in graph.find_all_nodes() {
+        let (_, properties) = add_node_query(&node.node_type, &node.node_data);
+        keys.insert(create_node_key(&node));
+        let row = properties
+            .value
+            .into_iter()
+            .map(|(k, v)| (k.value, v))
+            .collect();
+        by_type.entry(node.node_type).or_default().push(row);
+    }
+
+    let mut export = CsvExport::default();
+    for (node_type, rows) in &by_type {
+        // a property keeps its typed column only if every node agrees on the type
+        let mut columns: BTreeMap<&str, &'static str> = BTreeMap::new();
+        for row in rows {
+            for (k, v) in row {
+                if k == "node_key" {
+                    continue;
+                }
+                let ty = column_type(v);
+                columns
+                    .entry(k.as_str())
+                    .and_modify(|seen| {
+                        if *seen != ty {
+                            *seen = ""
+                        }
+                    })
+                    .or_insert(ty);
+            }
+        }
+        let path = dir.join(format!("nodes-{}.csv", node_type.to_string()));
+        let mut out = BufWriter::new(File::create(&path).context("failed to create node csv")?);
+        let mut header = vec!["node_key:ID".to_string(), ":LABEL".to_string()];
+        header.extend(columns.iter().map(|(k, ty)| format!("{k}{ty}")));
+        header.push("date_added_to_graph".to_string());
+        write_row(&mut out, &header)?;
+        let labels = csv_field(&format!("{};{DATA_BANK}", node_type.to_string()));
+        for row in rows {
+            let node_key = row.get("node_key").map(column_value).unwrap_or_default();
+            let mut fields = vec![csv_field(&node_key), labels.clone()];
+            for (k, ty) in &columns {
+                fields.push(match row.get(*k) {
+                    Some(v) if ty.is_empty() => csv_field(&column_value(v)),
+                    Some(v) => column_value(v),
+                    None => String::new(),
+                });
+            }
+            fields.push(csv_field(&now));
+            write_row(&mut out, &fields)?;
+        }
+        out.flush()?;
+        export.node_count += rows.len();
+        export.nodes.push(path);
+    }
+
+    let mut edges_by_type: BTreeMap<EdgeType, Vec<(String, String)>> = BTreeMap::new();
+    let mut dangling = 0;
+    for (edge_type, source, target) in graph.find_all_edges() {
+        let (source, target) = (create_node_key(&source), create_node_key(&target));
+        if !keys.contains(&source) || !keys.contains(&target) {
+            dangling += 1;
+            continue;
+        }
+        edges_by_type
+            .entry(edge_type)
+            .or_default()
+            .push((source, target));
+    }
+    if dangling > 0 {
+        warn!(
+            "skipped {} edges with an endpoint outside the graph",
+            dangling
+        );
+    }
+    for (edge_type, rows) in &edges_by_type {
+        let edge_type = edge_type.to_string();
+        let path = dir.join(format!("edges-{edge_type}.csv"));
+        let mut out = BufWriter::new(File::create(&path).context("failed to create edge csv")?);
+        write_row(
+            &mut out,
+            &[":START_ID".into(), ":END_ID".into(), ":TYPE".into()],
+        )?;
+        for (source, target) in rows {
+            write_row(
+                &mut out,
+                &[csv_field(source), csv_field(target), edge_type.clone()],
+            )?;
+        }
+        out.flush()?;
+        export.edge_count += rows.len();
+        export.edges.push(path);
+    }
+    info!(
+        "wrote {} nodes and {} edges for neo4j-admin import to {}",
+        export.node_count,
+        export.edge_count,
+        dir.display()
+    );
+    Ok(export)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lang::graphs::{BTreeMapGraph, Edge};
+    use crate::lang::NodeData;
+
+    #[test]
+    fn test_neo4j_csv() {
+        let mut graph = BTreeMapGraph::default();
+        let file = NodeData::name_file("main.rs", "src/main.rs");
+        let mut func = NodeData::name_file("run", "src/main.rs");
+        func.body = "fn run() {\n    println!(\"hi\");\n}".to_string();
+        func.end = 3;
+        graph.add_node(NodeType::File, file.clone());
+        graph.add_node(NodeType::Function, func.clone());
+        graph.add_edge(Edge::contains(
+            NodeType::File,
+            &file,
+            NodeType::Function,
+            &func,
+        ));
+
+        let dir = std::env::temp_dir().join(format!("stakgraph-csv-{}", std::process::id()));
+        let export = write_neo4j_csv(&graph, &dir).unwrap();
+        assert_eq!((export.node_count, export.edge_count), (2, 1));
+        assert_eq!(export.nodes.len(), 2);
+
+        let funcs = std::fs::read_to_string(dir.join("nodes-Function.csv")).unwrap();
+        let header = funcs.lines().next().unwrap();
+        assert!(header.starts_with("node_key:ID,:LABEL,"));
+        assert!(header.contains("start:long"));
+        assert!(header.contains("token_count:long"));
+        assert!(funcs.contains("\"Function;Data_Bank\""));
+        assert!(funcs.contains("println!(\"\"hi\"\");"));
+        assert!(header.contains(",ref_id,"));
+
+        let edges = std::fs::read_to_string(dir.join("edges-CONTAINS.csv")).unwrap();
+        let key = create_node_key(&crate::lang::Node::new(NodeType::Function, func));
+        assert!(edges.contains(&format!("\"{key}\",CONTAINS")));
+        assert!(export
+            .import_args()
+            .contains(&"--multiline-fields=true".to_string()));
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
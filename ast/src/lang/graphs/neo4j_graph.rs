@@ -1,12 +1,17 @@
+use super::call_graph::MAX_CALL_DEPTH;
 use super::{neo4j_utils::*, *};
-use crate::utils::sync_fn;
+use crate::utils::{create_node_key, sync_fn};
 use crate::{lang::Function, lang::Node, Lang};
 use crate::lang::asg::TestRecord;
 use lsp::Language;
 use neo4rs::{query, BoltMap, Graph as Neo4jConnection};
 use shared::{Context, Error, Result};
 use std::str::FromStr;
-use std::{collections::HashSet, sync::Arc, time::Duration};
+use std::{
+    collections::{BTreeMap, HashSet},
+    sync::Arc,
+    time::Duration,
+};
 use tokio::sync::Mutex;
 use tracing::{debug, info, warn};
 
@@ -286,6 +291,27 @@ impl Neo4jGraph {
         txn_manager.execute().await
     }
 
+    pub(super) async fn extend_node_meta_async(
+        &self,
+        updates: Vec<(Node, BTreeMap<String, String>)>,
+    ) -> Result<()> {
+        let connection = self.ensure_connected().await?;
+        let mut txn_manager = TransactionManager::new(&connection);
+        for (node, meta) in &updates {
+            txn_manager.add_query(set_node_meta_query(&create_node_key(node), meta));
+        }
+        txn_manager.execute().await
+    }
+
+    pub(super) async fn add_scored_edges_async(&self, edges: Vec<(Edge, f64)>) -> Result<()> {
+        let connection = self.ensure_connected().await?;
+        let mut txn_manager = TransactionManager::new(&connection);
+        for (edge, score) in &edges {
+            txn_manager.add_query(add_scored_edge_query(edge, *score));
+        }
+        txn_manager.execute().await
+    }
+
     pub(super) async fn add_edge_async(&self, edge: Edge) -> Result<()> {
         let connection = self.ensure_connected().await?;
         let mut txn_manager = TransactionManager::new(&connection);
@@ -995,6 +1021,153 @@ impl Neo4jGraph {
         txn_manager.execute().await
     }
 
+    pub(super) async fn find_all_edges_async(&self) -> Vec<(EdgeType, Node, Node)> {
+        let Ok(connection) = self.ensure_connected().await else {
+            warn!("Failed to connect to Neo4j in find_all_edges_async");
+            return vec![];
+        };
+        let (query_str, _) = find_all_edges_query();
+        let mut edges = Vec::new();
+        match connection.execute(query(&query_str)).await {
+            Ok(mut result) => {
+                while let Ok(Some(row)) = result.next().await {
+                    let edge_type: String = row.get("edge_type").unwrap_or_default();
+                    let (Ok(source), Ok(target)) = (
+                        row.get::<neo4rs::Node>("source"),
+                        row.get::<neo4rs::Node>("target"),
+                    ) else {
+                        continue;
+                    };
+                    if let (Ok(edge_type), Some(source), Some(target)) = (
+                        EdgeType::from_str(&edge_type),
+                        node_from_neo4j(&source),
+                        node_from_neo4j(&target),
+                    ) {
+                        edges.push((edge_type, source, target));
+                    }
+                }
+            }
+            Err(e) => {
+                debug!("Error executing find_all_edges query: {}", e);
+            }
+        }
+        edges
+    }
+
+    pub(super) async fn find_callers_async(&self, function: &NodeData) -> Vec<NodeData> {
+        let Ok(connection) = self.ensure_connected().await else {
+            warn!("Failed to connect to Neo4j in find_callers_async");
+            return vec![];
+        };
+        let (query, params) = find_callers_query(function);
+
+        execute_node_query(&connection, query, params).await
+    }
+
+    // rows of (n, depth) or, for paths, just n
+    async fn execute_call_query(&self, query_str: String, params: BoltMap) -> Vec<(Node, usize)> {
+        let Ok(connection) = self.ensure_connected().await else {
+            warn!("Failed to connect to Neo4j in execute_call_query");
+            return vec![];
+        };
+        let mut query_obj = query(&query_str);
+        for (key, value) in params.value.iter() {
+            query_obj = query_obj.param(key.value.as_str(), value.clone());
+        }
+        let mut nodes = Vec::new();
+        match connection.execute(query_obj).await {
+            Ok(mut result) => {
+                while let Ok(Some(row)) = result.next().await {
+                    let depth = row.get::<i64>("depth").unwrap_or_default() as usize;
+                    if let Some(node) = row
+                        .get::<neo4rs::Node>("n")
+                        .ok()
+                        .and_then(|n| node_from_neo4j(&n))
+                    {
+                        nodes.push((node, depth));
+                    }
+                }
+            }
+            Err(e) => {
+                debug!("Error executing call graph query: {}", e);
+            }
+        }
+        nodes
+    }
+
+    pub(super) async fn transitive_calls_async(
+        &self,
+        node: &Node,
+        depth: usize,
+        callers: bool,
+    ) -> Vec<(Node, usize)> {
+        if depth == 0 {
+            return vec![];
+        }
+        let (query_str, params) = transitive_calls_query(node, depth, callers);
+        self.execute_call_query(query_str, params).await
+    }
+
+    pub(super) async fn shortest_call_path_async(&self, from: &Node, to: &Node) -> Option<Vec<Node>> {
+        let (query_str, params) = shortest_call_path_query(from, to, MAX_CALL_DEPTH);
+        let path: Vec<Node> = self
+            .execute_call_query(query_str, params)
+            .await
+            .into_iter()
+            .map(|(node, _)| node)
+            .collect();
+        (!path.is_empty()).then_some(path)
+    }
+
+    pub(super) async fn neighborhood_async(
+        &self,
+        seed: &Node,
+        depth: usize,
+        edge_types: &[EdgeType],
+        node_types: &[NodeType],
+    ) -> (Vec<Node>, Vec<Edge>) {
+        let mut nodes = BTreeMap::from([(create_node_key(seed), seed.clone())]);
+        let mut edges = Vec::new();
+        if depth == 0 {
+            return (nodes.into_values().collect(), edges);
+        }
+        let Ok(connection) = self.ensure_connected().await else {
+            warn!("Failed to connect to Neo4j in neighborhood_async");
+            return (nodes.into_values().collect(), edges);
+        };
+        let (query_str, params) = subgraph_query(seed, depth, edge_types, node_types);
+        let mut query_obj = query(&query_str);
+        for (key, value) in params.value.iter() {
+            query_obj = query_obj.param(key.value.as_str(), value.clone());
+        }
+        match connection.execute(query_obj).await {
+            Ok(mut result) => {
+                while let Ok(Some(row)) = result.next().await {
+                    let edge_type: String = row.get("edge_type").unwrap_or_default();
+                    let (Ok(source), Ok(target)) = (
+                        row.get::<neo4rs::Node>("source"),
+                        row.get::<neo4rs::Node>("target"),
+                    ) else {
+                        continue;
+                    };
+                    if let (Ok(edge_type), Some(source), Some(target)) = (
+                        EdgeType::from_str(&edge_type),
+                        node_from_neo4j(&source),
+                        node_from_neo4j(&target),
+                    ) {
+                        edges.push(subgraph::edge_from(edge_type, &source, &target));
+                        nodes.insert(create_node_key(&source), source);
+                        nodes.insert(create_node_key(&target), target);
+                    }
+                }
+            }
+            Err(e) => {
+                debug!("Error executing subgraph query: {}", e);
+            }
+        }
+        (nodes.into_values().collect(), edges)
+    }
+
     pub(super) async fn has_edge_async(
         &self,
         source: &Node,
@@ -1061,15 +1234,28 @@ impl Neo4jGraph {
         node_types: Vec<String>,
         similarity_threshold: f32,
         language: Option<&str>,
+        importance_weight: f32,
     ) -> Result<Vec<(NodeData, f64)>> {
         let connection = self.ensure_connected().await?;
 
+        // without scores the blend would only scale every similarity down
+        if importance_weight > 0.0 {
+            let mut ranked = connection.execute(query(&ranked_node_query())).await?;
+            if ranked.next().await?.is_none() {
+                return Err(Error::Custom(
+                    "importance_weight needs pagerank scores, build the graph with RANK_NODES=1"
+                        .to_string(),
+                ));
+            }
+        }
+
         let (query_str, params) = vector_search_query(
             embedding,
             limit,
             node_types,
             similarity_threshold,
             language.map(|s| s.to_string()),
+            importance_weight,
         );
 
         let mut query_obj = query(&query_str);
@@ -1164,6 +1350,7 @@ impl Graph for Neo4jGraph {
     }
     fn analysis(&self) {
         let _ = sync_fn(|| async { self.analysis_async().await });
+        info!("dead code:\n{}", dead_code::find_dead_code(self));
     }
     fn create_filtered_graph(self, _final_filter: &[String], _lang_kind: Language) -> Self
     where
@@ -1386,4 +1573,46 @@ impl Graph for Neo4jGraph {
     fn has_edge(&self, source: &Node, target: &Node, edge_type: EdgeType) -> bool {
         sync_fn(|| async { self.has_edge_async(source, target, edge_type).await })
     }
+    fn find_all_edges(&self) -> Vec<(EdgeType, Node, Node)> {
+        sync_fn(|| async { self.find_all_edges_async().await })
+    }
+    fn neighborhood(
+        &self,
+        seed: &Node,
+        depth: usize,
+        edge_types: &[EdgeType],
+        node_types: &[NodeType],
+    ) -> (Vec<Node>, Vec<Edge>) {
+        sync_fn(|| async {
+            self.neighborhood_async(seed, depth, edge_types, node_types)
+                .await
+        })
+    }
+
+    fn add_scored_edges(&mut self, edges: Vec<(Edge, f64)>) {
+        sync_fn(|| async {
+            self.add_scored_edges_async(edges)
+                .await
+                .unwrap_or_default()
+        })
+    }
+    fn extend_node_meta(&mut self, updates: Vec<(Node, BTreeMap<String, String>)>) {
+        sync_fn(|| async {
+            self.extend_node_meta_async(updates)
+                .await
+                .unwrap_or_default()
+        })
+    }
+    fn find_callers(&self, function: &NodeData) -> Vec<NodeData> {
+        sync_fn(|| async { self.find_callers_async(function).await })
+    }
+    fn transitive_callers(&self, node: &Node, depth: usize) -> Vec<(Node, usize)> {
+        sync_fn(|| async { self.transitive_calls_async(node, depth, true).await })
+    }
+    fn transitive_callees(&self, node: &Node, depth: usize) -> Vec<(Node, usize)> {
+        sync_fn(|| async { self.transitive_calls_async(node, depth, false).await })
+    }
+    fn shortest_call_path(&self, from: &Node, to: &Node) -> Option<Vec<Node>> {
+        sync_fn(|| async { self.shortest_call_path_async(from, to).await })
+    }
 }
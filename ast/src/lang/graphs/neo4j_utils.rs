@@ -1,20 +1,19 @@
 use crate::lang::Node;
 use crate::utils::create_node_key;
 use crate::utils::create_node_key_from_ref;
-use lazy_static::lazy_static;
 use neo4rs::{query, BoltMap, BoltType, ConfigBuilder, Graph as Neo4jConnection};
 use shared::{Error, Result};
-use tiktoken_rs::{get_bpe_from_model, CoreBPE};
 use tracing::{debug, error, info};
 use lsp::language::Language;
 use std::str::FromStr;
+use super::importance::{IN_DEGREE, OUT_DEGREE, PAGERANK};
+use crate::lang::parse::metrics::METRIC_KEYS;
 use super::*;
+use std::collections::BTreeMap;
 
-lazy_static! {
-    static ref TOKENIZER: CoreBPE = get_bpe_from_model("gpt-4").unwrap();
-}
+pub use crate::utils::calculate_token_count;
 
-const DATA_BANK: &str = "Data_Bank";
+pub(super) const DATA_BANK: &str = "Data_Bank";
 const BATCH_SIZE: usize = 4096;
 
 pub struct Neo4jConnectionManager;
@@ -73,6 +72,7 @@ impl NodeQueryBuilder {
 
         let token_count = calculate_token_count(&self.node_data.body).unwrap_or(0);
         boltmap_insert_int(&mut properties, "token_count", token_count);
+        insert_numeric_meta(&mut properties, &self.node_data.meta);
 
         // println!("[NodeQueryBuilder] node_key: {}", node_key);
 
@@ -322,10 +322,58 @@ pub fn add_node_query(node_type: &NodeType, node_data: &NodeData) -> (String, Bo
     NodeQueryBuilder::new(node_type, node_data).build()
 }
 
+// importance scores and function metrics are stored as numbers so queries can order,
+// filter and blend by them
+fn insert_numeric_meta(properties: &mut BoltMap, meta: &BTreeMap<String, String>) {
+    if let Some(rank) = meta.get(PAGERANK).and_then(|v| v.parse::<f64>().ok()) {
+        boltmap_insert_float(properties, PAGERANK, rank);
+    }
+    for key in [IN_DEGREE, OUT_DEGREE].into_iter().chain(METRIC_KEYS) {
+        if let Some(value) = meta.get(key).and_then(|v| v.parse::<i64>().ok()) {
+            boltmap_insert_int(properties, key, value);
+        }
+    }
+}
+
+// sets meta on an existing node without touching its ref_id
+pub fn set_node_meta_query(node_key: &str, meta: &BTreeMap<String, String>) -> (String, BoltMap) {
+    let mut properties = BoltMap::new();
+    for (k, v) in meta {
+        boltmap_insert_str(&mut properties, k, v);
+    }
+    insert_numeric_meta(&mut properties, meta);
+    let mut params = BoltMap::new();
+    boltmap_insert_str(&mut params, "node_key", node_key);
+    boltmap_insert_map(&mut params, "properties", properties);
+    let query = format!(
+        "MATCH (n:{} {{node_key: $node_key}})
+         SET n += $properties",
+        DATA_BANK
+    );
+    (query, params)
+}
+
 pub fn add_edge_query(edge: &Edge) -> (String, BoltMap) {
     EdgeQueryBuilder::new(edge).build()
 }
 
+pub fn add_scored_edge_query(edge: &Edge, score: f64) -> (String, BoltMap) {
+    let mut params = BoltMap::new();
+    boltmap_insert_str(&mut params, "source_key", &create_node_key_from_ref(&edge.source));
+    boltmap_insert_str(&mut params, "target_key", &create_node_key_from_ref(&edge.target));
+    boltmap_insert_float(&mut params, "score", score);
+    let query = format!(
+        "MATCH (source:{} {{node_key: $source_key}}),
+             (target:{} {{node_key: $target_key}})
+         MERGE (source)-[r:{}]->(target)
+         SET r.score = $score",
+        edge.source.node_type.to_string(),
+        edge.target.node_type.to_string(),
+        edge.edge.to_string()
+    );
+    (query, params)
+}
+
 pub async fn execute_node_query(
     conn: &Neo4jConnection,
     query_str: String,
@@ -552,6 +600,13 @@ pub fn find_nodes_with_edge_type_query(
     (query, params)
 }
 
+pub fn find_all_edges_query() -> (String, BoltMap) {
+    let query = "MATCH (source)-[r]->(target)
+         RETURN source, type(r) as edge_type, target"
+        .to_string();
+    (query, BoltMap::new())
+}
+
 pub fn find_resource_nodes_query(
     node_type: &NodeType,
     verb: &str,
@@ -617,6 +672,106 @@ pub fn find_functions_called_by_query(function: &NodeData) -> (String, BoltMap)
     (query, params)
 }
 
+// CONTAINS only counts as a call hop into a data model, like call_graph::is_call_hop
+const CALL_HOPS: &str = "CALLS|HANDLER|CONTAINS";
+const CALL_HOP_FILTER: &str =
+    "all(r IN relationships(p) WHERE type(r) <> 'CONTAINS' OR endNode(r):Datamodel)";
+
+pub fn find_callers_query(function: &NodeData) -> (String, BoltMap) {
+    let mut params = BoltMap::new();
+    boltmap_insert_str(&mut params, "function_name", &function.name);
+    boltmap_insert_str(&mut params, "function_file", &function.file);
+    boltmap_insert_int(&mut params, "function_start", function.start as i64);
+
+    let query = "MATCH (n)-[:CALLS|HANDLER]->
+        (target:Function {name: $function_name, file: $function_file, start: $function_start})
+        RETURN DISTINCT n"
+        .to_string();
+
+    (query, params)
+}
+
+// nodes up to `depth` call hops away from the node, with the length of the shortest way there
+pub fn transitive_calls_query(node: &Node, depth: usize, callers: bool) -> (String, BoltMap) {
+    let mut params = BoltMap::new();
+    boltmap_insert_str(&mut params, "node_key", &create_node_key(node));
+    let pattern = if callers {
+        format!("(n)-[:{CALL_HOPS}*1..{depth}]->(start)")
+    } else {
+        format!("(start)-[:{CALL_HOPS}*1..{depth}]->(n)")
+    };
+    let query = format!(
+        "MATCH (start:{DATA_BANK} {{node_key: $node_key}})
+         MATCH p = {pattern}
+         WHERE n <> start AND {CALL_HOP_FILTER}
+         RETURN n, min(length(p)) AS depth
+         ORDER BY depth"
+    );
+    (query, params)
+}
+
+// one row per node on the path, in order
+pub fn shortest_call_path_query(from: &Node, to: &Node, max_depth: usize) -> (String, BoltMap) {
+    let mut params = BoltMap::new();
+    boltmap_insert_str(&mut params, "from_key", &create_node_key(from));
+    boltmap_insert_str(&mut params, "to_key", &create_node_key(to));
+    let query = format!(
+        "MATCH (a:{DATA_BANK} {{node_key: $from_key}}), (b:{DATA_BANK} {{node_key: $to_key}})
+         MATCH p = shortestPath((a)-[:{CALL_HOPS}*..{max_depth}]->(b))
+         WHERE {CALL_HOP_FILTER}
+         UNWIND range(0, length(p)) AS i
+         RETURN nodes(p)[i] AS n
+         ORDER BY i"
+    );
+    (query, params)
+}
+
+// every relationship on a path of at most `depth` hops from the seed, either direction.
+// types come from the enums so they are inlined as relationship types and labels
+pub fn subgraph_query(
+    seed: &Node,
+    depth: usize,
+    edge_types: &[EdgeType],
+    node_types: &[NodeType],
+) -> (String, BoltMap) {
+    let mut params = BoltMap::new();
+    boltmap_insert_str(&mut params, "seed_key", &create_node_key(seed));
+    let rel_types = if edge_types.is_empty() {
+        String::new()
+    } else {
+        let types: Vec<String> = edge_types.iter().map(|t| t.to_string()).collect();
+        format!(":{}", types.join("|"))
+    };
+    let node_filter = if node_types.is_empty() {
+        "true".to_string()
+    } else {
+        let labels: Vec<String> = node_types
+            .iter()
+            .map(|t| format!("x:{}", t.to_string()))
+            .collect();
+        format!("all(x IN nodes(p)[1..] WHERE {})", labels.join(" OR "))
+    };
+    let query = format!(
+        "MATCH (seed:{DATA_BANK} {{node_key: $seed_key}})
+         MATCH p = (seed)-[{rel_types}*1..{depth}]-(n)
+         WHERE {node_filter}
+         UNWIND relationships(p) AS r
+         WITH DISTINCT r
+         RETURN startNode(r) AS source, type(r) AS edge_type, endNode(r) AS target"
+    );
+    (query, params)
+}
+
+// a graph node with its type taken from the labels
+pub fn node_from_neo4j(node: &neo4rs::Node) -> Option<Node> {
+    let node_type = node
+        .labels()
+        .iter()
+        .find_map(|label| NodeType::from_str(label).ok())?;
+    let node_data = NodeData::try_from(node).ok()?;
+    Some(Node::new(node_type, node_data))
+}
+
 pub fn find_node_at_query(node_type: &NodeType, file: &str, line: u32) -> (String, BoltMap) {
     let mut params = BoltMap::new();
     boltmap_insert_str(&mut params, "node_type", &node_type.to_string());
@@ -646,6 +801,11 @@ pub fn all_node_keys_query() -> String {
     "MATCH (n) WHERE n.node_key IS NOT NULL RETURN n.node_key as node_key".to_string()
 }
 
+// any ranked node, so an importance blend has scores to use
+pub fn ranked_node_query() -> String {
+    format!("MATCH (n) WHERE n.{PAGERANK} IS NOT NULL RETURN n.node_key as node_key LIMIT 1")
+}
+
 pub fn all_edge_triples_query() -> String {
     "MATCH (s)-[e]->(t) RETURN s.node_key as s_key, type(e) as edge_type, t.node_key as t_key".to_string()
 }
@@ -962,11 +1122,6 @@ pub fn boltmap_insert_int(map: &mut BoltMap, key: &str, value: i64) {
 fn boltmap_to_bolttype_map(bolt_map: BoltMap) -> BoltType {
     BoltType::Map(bolt_map)
 }
-pub fn calculate_token_count(body: &str) -> Result<i64> {
-    let bpe = &TOKENIZER;
-    let token_count = bpe.encode_with_special_tokens(body).len() as i64;
-    Ok(token_count)
-}
 // Add these functions to neo4j_utils.rs
 
 pub fn find_group_function_query(group_function_name: &str) -> (String, BoltMap) {
@@ -1140,6 +1295,7 @@ pub fn vector_search_query(
     node_types: Vec<String>,
     similarity_threshold: f32,
     language: Option<String>,
+    importance_weight: f32,
 ) -> (String, BoltMap) {
     let mut params = BoltMap::new();
 
@@ -1154,6 +1310,12 @@ pub fn vector_search_query(
     boltmap_insert_int(&mut params, "limit", limit as i64);
 
     boltmap_insert_float(&mut params, "similarityThreshold", similarity_threshold as f64);
+    // 0 ranks by similarity only, 1 by pagerank only
+    boltmap_insert_float(
+        &mut params,
+        "importanceWeight",
+        importance_weight.clamp(0.0, 1.0) as f64,
+    );
 
     let node_types_list = node_types
         .into_iter()
@@ -1187,8 +1349,10 @@ pub fn vector_search_query(
             WHEN $extensions IS NULL OR size($extensions) = 0 THEN true
             ELSE node.file IS NOT NULL AND ANY(ext IN $extensions WHERE node.file ENDS WITH ext)
           END
-        WITH node, gds.similarity.cosine(node.embeddings, $embeddings) AS score
-        WHERE score >= $similarityThreshold
+        WITH node, gds.similarity.cosine(node.embeddings, $embeddings) AS similarity
+        WHERE similarity >= $similarityThreshold
+        WITH node, (1 - $importanceWeight) * similarity
+          + $importanceWeight * coalesce(toFloat(node.pagerank), 0.0) AS score
         RETURN node, score
         ORDER BY score DESC
         LIMIT toInteger($limit)
@@ -0,0 +1,418 @@
+use super::{graph::Graph, EdgeType, Node, NodeType};
+use crate::lang::scip::{self, escape_name, kind, role};
+use crate::utils::create_node_key;
+use shared::error::{Context, Result};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::Path;
+
+const SCHEME: &str = "stakgraph";
+
+fn symbol_kind(node: &Node) -> Option<i32> {
+    Some(match node.node_type {
+        NodeType::Function | NodeType::UnitTest | NodeType::IntegrationTest | NodeType::E2eTest => {
+            if node.node_data.meta.contains_key("operand") {
+                kind::METHOD
+            } else {
+                kind::FUNCTION
+            }
+        }
+        NodeType::Class => kind::CLASS,
+        NodeType::DataModel => kind::STRUCT,
+        NodeType::Trait => kind::TRAIT,
+        NodeType::Interface => kind::INTERFACE,
+        NodeType::Enum => kind::ENUM,
+        NodeType::TypeAlias => kind::TYPE_ALIAS,
+        NodeType::Var => kind::VARIABLE,
+        NodeType::Const => kind::CONSTANT,
+        _ => return None,
+    })
+}
+
+fn is_test(node_type: &NodeType) -> bool {
+    matches!(
+        node_type,
+        NodeType::UnitTest | NodeType::IntegrationTest | NodeType::E2eTest
+    )
+}
+
+// scip document languages are lowercase names
+fn language(file: &str) -> &'static str {
+    match file.rsplit('.').next().unwrap_or_default() {
+        "rs" => "rust",
+        "go" => "go",
+        "py" => "python",
+        "rb" => "ruby",
+        "ts" => "typescript",
+        "tsx" => "typescriptreact",
+        "js" => "javascript",
+        "jsx" => "javascriptreact",
+        "java" => "java",
+        "kt" | "kts" => "kotlin",
+        "swift" => "swift",
+        "svelte" => "svelte",
+        "cpp" | "h" => "cpp",
+        _ => "",
+    }
+}
+
+// `stakgraph . . . src/`main.rs`/Class#method().` - the file path as namespaces, then the
+// owning class for methods
+fn symbol_for(node: &Node, relative_path: &str) -> String {
+    let mut symbol = format!("{SCHEME} . . . ");
+    for part in relative_path.split('/').filter(|p| !p.is_empty()) {
+        symbol.push_str(&escape_name(part));
+        symbol.push('/');
+    }
+    let nd = &node.node_data;
+    match node.node_type {
+        NodeType::Class
+        | NodeType::DataModel
+        | NodeType::Trait
+        | NodeType::Interface
+        | NodeType::Enum
+        | NodeType::TypeAlias => format!("{symbol}{}#", escape_name(&nd.name)),
+        NodeType::Var | NodeType::Const => format!("{symbol}{}.", escape_name(&nd.name)),
+        _ => match nd.meta.get("operand") {
+            Some(owner) => format!(
+                "{symbol}{}#{}().",
+                escape_name(owner),
+                escape_name(&nd.name)
+            ),
+            None => format!("{symbol}{}().", escape_name(&nd.name)),
+        },
+    }
+}
+
+fn is_ident(c: char) -> bool {
+    c.is_alphanumeric() || c == '_' || c == '$'
+}
+
+// byte offsets of `name` in `line` that are not part of a longer identifier
+fn word_offsets(line: &str, name: &str) -> Vec<usize> {
+    if name.is_empty() {
+        return Vec::new();
+    }
+    line.match_indices(name)
+        .filter(|(i, _)| {
+            let before = line[..*i].chars().next_back();
+            let after = line[i + name.len()..].chars().next();
+            !before.is_some_and(is_ident) && !after.is_some_and(is_ident)
+        })
+        .map(|(i, _)| i)
+        .collect()
+}
+
+// source lines by file. File nodes carry the whole source, other nodes fill in gaps
+// with their own bodies (their first line may start mid-line, so columns are best effort)
+struct Sources {
+    lines: HashMap<String, BTreeMap<usize, String>>,
+}
+
+impl Sources {
+    fn new(nodes: &[Node]) -> Self {
+        let mut lines: HashMap<String, BTreeMap<usize, String>> = HashMap::new();
+        let mut ordered: Vec<&Node> = nodes.iter().collect();
+        // whole files first so node bodies never overwrite them
+        ordered.sort_by_key(|n| n.node_type != NodeType::File);
+        for node in ordered {
+            let nd = &node.node_data;
+            let file = lines.entry(nd.file.clone()).or_default();
+            for (i, line) in nd.body.lines().enumerate() {
+                file.entry(nd.start + i).or_insert_with(|| line.to_string());
+            }
+        }
+        Sources { lines }
+    }
+
+    fn line(&self, file: &str, line: usize) -> Option<&str> {
+        self.lines.get(file)?.get(&line).map(|s| s.as_str())
+    }
+
+    fn last_col(&self, file: &str, line: usize) -> usize {
+        self.line(file, line).map(|l| l.len()).unwrap_or(0)
+    }
+
+    // every occurrence of `name` in lines start..=end of `file`
+    fn find(&self, file: &str, start: usize, end: usize, name: &str) -> Vec<(usize, usize)> {
+        let Some(lines) = self.lines.get(file) else {
+            return Vec::new();
+        };
+        lines
+            .range(start..=end)
+            .flat_map(|(n, line)| word_offsets(line, name).into_iter().map(|col| (*n, col)))
+            .collect()
+    }
+}
+
+fn range(line: usize, col: usize, len: usize) -> Vec<i32> {
+    vec![line as i32, col as i32, (col + len) as i32]
+}
+
+// strips the project root from a graph file path
+fn relative<'a>(file: &'a str, project_root: &str) -> &'a str {
+    let root = project_root.trim_end_matches('/');
+    if root.is_empty() {
+        return file;
+    }
+    file.strip_prefix(root)
+        .map(|f| f.trim_start_matches('/'))
+        .unwrap_or(file)
+}
+
+// every Function/Class/Var-like node becomes a definition, Calls edges references inside
+// the caller and Imports edges import references. `project_root` is stripped from node
+// files to get document paths
+pub fn to_scip<G: Graph>(graph: &G, project_root: &str) -> scip::Index {
+    let nodes = graph.find_all_nodes();
+    let sources = Sources::new(&nodes);
+
+    let mut symbols: HashMap<String, String> = HashMap::new();
+    let mut taken = HashSet::new();
+    let mut documents: BTreeMap<String, scip::Document> = BTreeMap::new();
+    for node in &nodes {
+        let Some(kind) = symbol_kind(node) else {
+            continue;
+        };
+        let nd = &node.node_data;
+        let path = relative(&nd.file, project_root);
+        let base = symbol_for(node, path);
+        let mut symbol = base.clone();
+        // overloads in one file get a method disambiguator, `name(+1).`
+        if let Some(head) = base.strip_suffix("().") {
+            let mut n = 1;
+            while !taken.insert(symbol.clone()) {
+                symbol = format!("{head}(+{n}).");
+                n += 1;
+            }
+        }
+        symbols.insert(create_node_key(node), symbol.clone());
+
+        let doc = documents
+            .entry(path.to_string())
+            .or_insert_with(|| scip::Document {
+                relative_path: path.to_string(),
+                language: language(path).to_string(),
+                position_encoding: scip::POSITION_ENCODING_UTF8,
+                ..Default::default()
+            });
+        let col = sources
+            .find(&nd.file, nd.start, nd.start, &nd.name)
+            .first()
+            .map(|(_, col)| *col)
+            .unwrap_or(0);
+        let mut roles = role::DEFINITION;
+        if is_test(&node.node_type) {
+            roles |= role::TEST;
+        }
+        doc.occurrences.push(scip::Occurrence {
+            range: range(nd.start, col, nd.name.len()),
+            symbol: symbol.clone(),
+            symbol_roles: roles,
+            enclosing_range: vec![
+                nd.start as i32,
+                0,
+                nd.end as i32,
+                sources.last_col(&nd.file, nd.end) as i32,
+            ],
+        });
+        doc.symbols.push(scip::SymbolInformation {
+            symbol,
+            documentation: nd.docs.iter().cloned().collect(),
+            kind,
+            display_name: nd.name.clone(),
+            ..Default::default()
+        });
+    }
+
+    // import statements of each file, to place import references
+    let mut imports: HashMap<&str, Vec<(usize, usize)>> = HashMap::new();
+    for node in nodes.iter().filter(|n| n.node_type == NodeType::Import) {
+        let nd = &node.node_data;
+        imports
+            .entry(nd.file.as_str())
+            .or_default()
+            .push((nd.start, nd.end));
+    }
+
+    let mut seen = HashSet::new();
+    for (edge_type, source, target) in graph.find_all_edges() {
+        let Some(target_symbol) = symbols.get(&create_node_key(&target)) else {
+            continue;
+        };
+        let sd = &source.node_data;
+        let name = &target.node_data.name;
+        let (sites, roles) = match edge_type {
+            EdgeType::Calls => (sources.find(&sd.file, sd.start, sd.end, name), 0),
+            EdgeType::Imports => {
+                let mut sites: Vec<(usize, usize)> = imports
+                    .get(sd.file.as_str())
+                    .into_iter()
+                    .flatten()
+                    .flat_map(|(start, end)| sources.find(&sd.file, *start, *end, name))
+                    .collect();
+                if sites.is_empty() {
+                    sites = sources
+                        .find(&sd.file, 0, usize::MAX, name)
+                        .into_iter()
+                        .take(1)
+                        .collect();
+                }
+                (sites, role::IMPORT)
+            }
+            EdgeType::Implements | EdgeType::ParentOf => {
+                // ParentOf points parent -> child, Implements class -> trait
+                let (child, parent) = match edge_type {
+                    EdgeType::ParentOf => (&target, &source),
+                    _ => (&source, &target),
+                };
+                if let (Some(child_symbol), Some(parent_symbol)) = (
+                    symbols.get(&create_node_key(child)),
+                    symbols.get(&create_node_key(parent)),
+                ) {
+                    let path = relative(&child.node_data.file, project_root);
+                    if let Some(info) = documents
+                        .get_mut(path)
+                        .and_then(|d| d.symbols.iter_mut().find(|s| &s.symbol == child_symbol))
+                    {
+                        info.relationships.push(scip::Relationship {
+                            symbol: parent_symbol.clone(),
+                            is_implementation: true,
+                            ..Default::default()
+                        });
+                    }
+                }
+                continue;
+            }
+            _ => continue,
+        };
+        let path = relative(&sd.file, project_root);
+        for (line, col) in sites {
+            // the definition line of the callee itself is not a reference
+            if target.node_data.file == sd.file && line == target.node_data.start {
+                continue;
+            }
+            if !seen.insert((path.to_string(), line, col, target_symbol.clone())) {
+                continue;
+            }
+            let doc = documents
+                .entry(path.to_string())
+                .or_insert_with(|| scip::Document {
+                    relative_path: path.to_string(),
+                    language: language(path).to_string(),
+                    position_encoding: scip::POSITION_ENCODING_UTF8,
+                    ..Default::default()
+                });
+            doc.occurrences.push(scip::Occurrence {
+                range: range(line, col, name.len()),
+                symbol: target_symbol.clone(),
+                symbol_roles: roles,
+                ..Default::default()
+            });
+        }
+    }
+
+    let mut documents: Vec<scip::Document> = documents.into_values().collect();
+    for doc in documents.iter_mut() {
+        doc.occurrences.sort_by(|a, b| a.range.cmp(&b.range));
+    }
+    scip::Index {
+        metadata: scip::Metadata {
+            version: 0,
+            tool_info: scip::ToolInfo {
+                name: SCHEME.to_string(),
+                version: env!("CARGO_PKG_VERSION").to_string(),
+                arguments: Vec::new(),
+            },
+            project_root: format!("file://{}", project_root.trim_end_matches('/')),
+            text_document_encoding: scip::TEXT_ENCODING_UTF8,
+        },
+        documents,
+        external_symbols: Vec::new(),
+    }
+}
+
+pub fn write_scip<G: Graph>(graph: &G, project_root: &str, path: &Path) -> Result<()> {
+    let index = to_scip(graph, project_root);
+    std::fs::write(path, index.encode()).context("failed to write scip index")?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lang::graphs::{BTreeMapGraph, Edge, NodeRef};
+    use crate::lang::NodeData;
+
+    #[test]
+    fn test_scip_export() {
+        let source = "use crate::db::query;\n\nfn run() {\n    let x = query();\n    helper(x);\n}\n\nfn helper(x: u32) {}\n";
+        let mut graph = BTreeMapGraph::default();
+        let mut file = NodeData::name_file("main.rs", "repo/src/main.rs");
+        file.body = source.to_string();
+        let mut import = NodeData::name_file("imports", "repo/src/main.rs");
+        import.body = "use crate::db::query;".to_string();
+        let mut run = NodeData::name_file("run", "repo/src/main.rs");
+        run.start = 2;
+        run.end = 5;
+        let mut helper = NodeData::name_file("helper", "repo/src/main.rs");
+        helper.start = 7;
+        helper.end = 7;
+        helper.docs = Some("helps".to_string());
+        let query = NodeData::name_file("query", "repo/src/db.rs");
+        for (nt, nd) in [
+            (NodeType::File, &file),
+            (NodeType::Import, &import),
+            (NodeType::Function, &run),
+            (NodeType::Function, &helper),
+            (NodeType::Function, &query),
+        ] {
+            graph.add_node(nt, nd.clone());
+        }
+        graph.add_edge(Edge::calls(
+            NodeType::Function,
+            &run,
+            NodeType::Function,
+            &helper,
+        ));
+        graph.add_edge(Edge::calls(
+            NodeType::Function,
+            &run,
+            NodeType::Function,
+            &query,
+        ));
+        graph.add_edge(Edge::new(
+            EdgeType::Imports,
+            NodeRef::from((&file).into(), NodeType::File),
+            NodeRef::from((&query).into(), NodeType::Function),
+        ));
+
+        let index = scip::Index::decode(&to_scip(&graph, "repo").encode()).unwrap();
+        let main = index
+            .documents
+            .iter()
+            .find(|d| d.relative_path == "src/main.rs")
+            .unwrap();
+        assert_eq!(main.language, "rust");
+        let helper_symbol = "stakgraph . . . src/`main.rs`/helper().";
+        let query_symbol = "stakgraph . . . src/`db.rs`/query().";
+        let spans = |symbol: &str| -> Vec<(i32, i32, i32)> {
+            main.occurrences
+                .iter()
+                .filter(|o| o.symbol == symbol)
+                .map(|o| (o.range[0], o.range[1], o.symbol_roles))
+                .collect()
+        };
+        assert_eq!(
+            spans(helper_symbol),
+            vec![(4, 4, 0), (7, 3, role::DEFINITION)]
+        );
+        assert_eq!(spans(query_symbol), vec![(0, 15, role::IMPORT), (3, 12, 0)]);
+        let info = main
+            .symbols
+            .iter()
+            .find(|s| s.symbol == helper_symbol)
+            .unwrap();
+        assert_eq!(info.documentation, vec!["helps".to_string()]);
+        assert_eq!(info.kind, kind::FUNCTION);
+    }
+}
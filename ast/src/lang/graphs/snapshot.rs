@@ -0,0 +1,486 @@
+use super::{
+    ArrayGraph, BTreeMapGraph, Edge, EdgeType, Node, NodeData, NodeKeys, NodeRef, NodeType,
+    EDGE_TYPES, NODE_TYPES,
+};
+use shared::error::{Error, Result};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+// header: magic, format version, flags, graph kind. the rest is the payload,
+// zstd compressed when FLAG_ZSTD is set:
+//   string table (every name, file, body, key and meta value once)
+//   nodes, edges (and errors for ArrayGraph) referring to strings by index
+const MAGIC: &[u8; 4] = b"SGRF";
+const VERSION: u8 = 1;
+const FLAG_ZSTD: u8 = 1;
+const KIND_ARRAY: u8 = 0;
+const KIND_BTREEMAP: u8 = 1;
+#[cfg(feature = "zstd")]
+const ZSTD_LEVEL: i32 = 3;
+
+// tags are positions in NODE_TYPES / EDGE_TYPES, checked by test_snapshot_tags
+fn node_type_tag(node_type: &NodeType) -> u8 {
+    match node_type {
+        NodeType::Repository => 0,
+        NodeType::Language => 1,
+        NodeType::Directory => 2,
+        NodeType::File => 3,
+        NodeType::Import => 4,
+        NodeType::Library => 5,
+        NodeType::Class => 6,
+        NodeType::Trait => 7,
+        NodeType::Instance => 8,
+        NodeType::Function => 9,
+        NodeType::UnitTest => 10,
+        NodeType::IntegrationTest => 11,
+        NodeType::E2eTest => 12,
+        NodeType::Endpoint => 13,
+        NodeType::Request => 14,
+        NodeType::DataModel => 15,
+        NodeType::Feature => 16,
+        NodeType::Page => 17,
+        NodeType::Var => 18,
+        NodeType::Enum => 19,
+        NodeType::Interface => 20,
+        NodeType::TypeAlias => 21,
+        NodeType::Const => 22,
+        NodeType::Module => 23,
+    }
+}
+
+fn edge_type_tag(edge_type: &EdgeType) -> u8 {
+    match edge_type {
+        EdgeType::Calls => 0,
+        EdgeType::Uses => 1,
+        EdgeType::Operand => 2,
+        EdgeType::ArgOf => 3,
+        EdgeType::Contains => 4,
+        EdgeType::Imports => 5,
+        EdgeType::Of => 6,
+        EdgeType::Handler => 7,
+        EdgeType::Includes => 8,
+        EdgeType::Renders => 9,
+        EdgeType::ParentOf => 10,
+        EdgeType::Implements => 11,
+        EdgeType::Exports => 12,
+        EdgeType::SimilarTo => 13,
+    }
+}
+
+// a prebuilt graph that loads without reparsing the repo
+pub trait GraphSnapshot: Sized {
+    fn to_snapshot(&self, compress: bool) -> Result<Vec<u8>>;
+    fn from_snapshot(bytes: &[u8]) -> Result<Self>;
+
+    fn save_snapshot(&self, path: &str, compress: bool) -> Result<()> {
+        std::fs::write(path, self.to_snapshot(compress)?)?;
+        Ok(())
+    }
+    fn load_snapshot(path: &str) -> Result<Self> {
+        Self::from_snapshot(&std::fs::read(path)?)
+    }
+}
+
+impl GraphSnapshot for ArrayGraph {
+    fn to_snapshot(&self, compress: bool) -> Result<Vec<u8>> {
+        let mut w = Writer::default();
+        w.varint(self.nodes.len() as u64);
+        for node in &self.nodes {
+            w.node(node);
+        }
+        w.varint(self.edges.len() as u64);
+        for edge in &self.edges {
+            w.u8(edge_type_tag(&edge.edge));
+            w.node_ref(&edge.source);
+            w.node_ref(&edge.target);
+        }
+        w.varint(self.errors.len() as u64);
+        for error in &self.errors {
+            w.string(error);
+        }
+        w.finish(KIND_ARRAY, compress)
+    }
+
+    fn from_snapshot(bytes: &[u8]) -> Result<Self> {
+        let payload = read_header(bytes, KIND_ARRAY)?;
+        let mut r = Reader::new(&payload)?;
+        let nodes = (0..r.len()?).map(|_| r.node()).collect::<Result<Vec<_>>>()?;
+        let edges = (0..r.len()?)
+            .map(|_| {
+                let edge = r.edge_type()?;
+                let source = r.node_ref()?;
+                let target = r.node_ref()?;
+                Ok(Edge::new(edge, source, target))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let errors = (0..r.len()?)
+            .map(|_| r.string())
+            .collect::<Result<Vec<_>>>()?;
+        r.done()?;
+        Ok(ArrayGraph::from_parts(nodes, edges, errors))
+    }
+}
+
+impl GraphSnapshot for BTreeMapGraph {
+    fn to_snapshot(&self, compress: bool) -> Result<Vec<u8>> {
+        let mut w = Writer::default();
+        w.varint(self.nodes.len() as u64);
+        for (key, node) in &self.nodes {
+            w.string(key);
+            w.node(node);
+        }
+        w.varint(self.edges.len() as u64);
+        for (src, dst, edge_type) in &self.edges {
+            w.string(src);
+            w.string(dst);
+            w.u8(edge_type_tag(edge_type));
+        }
+        w.finish(KIND_BTREEMAP, compress)
+    }
+
+    fn from_snapshot(bytes: &[u8]) -> Result<Self> {
+        let payload = read_header(bytes, KIND_BTREEMAP)?;
+        let mut r = Reader::new(&payload)?;
+        let mut nodes = BTreeMap::new();
+        for _ in 0..r.len()? {
+            let key = r.string()?;
+            nodes.insert(key, r.node()?);
+        }
+        let mut edges = BTreeSet::new();
+        for _ in 0..r.len()? {
+            let src = r.string()?;
+            let dst = r.string()?;
+            edges.insert((src, dst, r.edge_type()?));
+        }
+        r.done()?;
+        Ok(BTreeMapGraph::from_parts(nodes, edges))
+    }
+}
+
+fn invalid(msg: &str) -> Error {
+    Error::Custom(format!("invalid graph snapshot: {}", msg))
+}
+
+fn read_header(bytes: &[u8], kind: u8) -> Result<Vec<u8>> {
+    if bytes.len() < 7 || &bytes[..4] != MAGIC {
+        return Err(invalid("missing header"));
+    }
+    if bytes[4] != VERSION {
+        return Err(invalid(&format!("unsupported version {}", bytes[4])));
+    }
+    if bytes[6] != kind {
+        return Err(invalid("snapshot was saved from a different graph type"));
+    }
+    let payload = &bytes[7..];
+    if bytes[5] & FLAG_ZSTD == 0 {
+        return Ok(payload.to_vec());
+    }
+    #[cfg(feature = "zstd")]
+    {
+        zstd::stream::decode_all(payload).map_err(|e| invalid(&e.to_string()))
+    }
+    #[cfg(not(feature = "zstd"))]
+    {
+        Err(Error::Custom(
+            "graph snapshot is zstd compressed, build with the zstd feature".into(),
+        ))
+    }
+}
+
+#[derive(Default)]
+struct Writer<'a> {
+    ids: HashMap<&'a str, u64>,
+    strings: Vec<&'a str>,
+    body: Vec<u8>,
+}
+
+impl<'a> Writer<'a> {
+    fn u8(&mut self, v: u8) {
+        self.body.push(v);
+    }
+    fn varint(&mut self, v: u64) {
+        write_varint(&mut self.body, v);
+    }
+    fn intern(&mut self, s: &'a str) -> u64 {
+        let next = self.strings.len() as u64;
+        *self.ids.entry(s).or_insert_with(|| {
+            self.strings.push(s);
+            next
+        })
+    }
+    fn string(&mut self, s: &'a str) {
+        let id = self.intern(s);
+        self.varint(id);
+    }
+    // 0 for None, the string id + 1 otherwise
+    fn opt_string(&mut self, s: &'a Option<String>) {
+        let id = s.as_deref().map_or(0, |s| self.intern(s) + 1);
+        self.varint(id);
+    }
+    fn node(&mut self, node: &'a Node) {
+        let data = &node.node_data;
+        self.u8(node_type_tag(&node.node_type));
+        self.string(&data.name);
+        self.string(&data.file);
+        self.string(&data.body);
+        self.varint(data.start as u64);
+        self.varint(data.end as u64);
+        self.opt_string(&data.docs);
+        self.opt_string(&data.hash);
+        self.opt_string(&data.data_type);
+        self.varint(data.meta.len() as u64);
+        for (k, v) in &data.meta {
+            self.string(k);
+            self.string(v);
+        }
+    }
+    fn node_ref(&mut self, node_ref: &'a NodeRef) {
+        let keys = &node_ref.node_data;
+        self.u8(node_type_tag(&node_ref.node_type));
+        self.string(&keys.name);
+        self.string(&keys.file);
+        self.varint(keys.start as u64);
+        self.opt_string(&keys.verb);
+    }
+    fn finish(self, kind: u8, compress: bool) -> Result<Vec<u8>> {
+        let mut payload = Vec::with_capacity(self.body.len());
+        write_varint(&mut payload, self.strings.len() as u64);
+        for s in &self.strings {
+            write_varint(&mut payload, s.len() as u64);
+            payload.extend_from_slice(s.as_bytes());
+        }
+        payload.extend_from_slice(&self.body);
+
+        let mut out = MAGIC.to_vec();
+        out.push(VERSION);
+        if compress {
+            #[cfg(feature = "zstd")]
+            {
+                out.push(FLAG_ZSTD);
+                out.push(kind);
+                out.extend(zstd::stream::encode_all(payload.as_slice(), ZSTD_LEVEL)?);
+                return Ok(out);
+            }
+            #[cfg(not(feature = "zstd"))]
+            return Err(Error::Custom(
+                "snapshot compression needs the zstd feature".into(),
+            ));
+        }
+        out.push(0);
+        out.push(kind);
+        out.extend(payload);
+        Ok(out)
+    }
+}
+
+fn write_varint(buf: &mut Vec<u8>, mut v: u64) {
+    while v >= 0x80 {
+        buf.push((v as u8) | 0x80);
+        v >>= 7;
+    }
+    buf.push(v as u8);
+}
+
+struct Reader<'a> {
+    buf: &'a [u8],
+    pos: usize,
+    strings: Vec<String>,
+}
+
+impl<'a> Reader<'a> {
+    fn new(buf: &'a [u8]) -> Result<Self> {
+        let mut r = Reader {
+            buf,
+            pos: 0,
+            strings: Vec::new(),
+        };
+        let count = r.len()?;
+        let mut strings = Vec::with_capacity(count.min(buf.len()));
+        for _ in 0..count {
+            let len = r.len()?;
+            let end = r
+                .pos
+                .checked_add(len)
+                .ok_or_else(|| invalid("string length out of range"))?;
+            let bytes = r
+                .buf
+                .get(r.pos..end)
+                .ok_or_else(|| invalid("truncated string table"))?;
+            strings.push(String::from_utf8(bytes.to_vec()).map_err(|e| invalid(&e.to_string()))?);
+            r.pos = end;
+        }
+        r.strings = strings;
+        Ok(r)
+    }
+    fn u8(&mut self) -> Result<u8> {
+        let v = *self.buf.get(self.pos).ok_or_else(|| invalid("truncated"))?;
+        self.pos += 1;
+        Ok(v)
+    }
+    fn varint(&mut self) -> Result<u64> {
+        let mut v = 0u64;
+        for shift in (0..64).step_by(7) {
+            let byte = self.u8()?;
+            v |= ((byte & 0x7f) as u64) << shift;
+            if byte & 0x80 == 0 {
+                return Ok(v);
+            }
+        }
+        Err(invalid("varint overflow"))
+    }
+    fn len(&mut self) -> Result<usize> {
+        Ok(self.varint()? as usize)
+    }
+    fn string(&mut self) -> Result<String> {
+        let id = self.len()?;
+        self.strings
+            .get(id)
+            .cloned()
+            .ok_or_else(|| invalid("string index out of range"))
+    }
+    fn opt_string(&mut self) -> Result<Option<String>> {
+        match self.len()? {
+            0 => Ok(None),
+            id => self
+                .strings
+                .get(id - 1)
+                .cloned()
+                .map(Some)
+                .ok_or_else(|| invalid("string index out of range")),
+        }
+    }
+    fn node_type(&mut self) -> Result<NodeType> {
+        NODE_TYPES
+            .get(self.u8()? as usize)
+            .cloned()
+            .ok_or_else(|| invalid("unknown node type"))
+    }
+    fn edge_type(&mut self) -> Result<EdgeType> {
+        EDGE_TYPES
+            .get(self.u8()? as usize)
+            .cloned()
+            .ok_or_else(|| invalid("unknown edge type"))
+    }
+    fn node(&mut self) -> Result<Node> {
+        let node_type = self.node_type()?;
+        let name = self.string()?;
+        let file = self.string()?;
+        let body = self.string()?;
+        let start = self.len()?;
+        let end = self.len()?;
+        let docs = self.opt_string()?;
+        let hash = self.opt_string()?;
+        let data_type = self.opt_string()?;
+        let mut meta = BTreeMap::new();
+        for _ in 0..self.len()? {
+            let k = self.string()?;
+            meta.insert(k, self.string()?);
+        }
+        Ok(Node::new(
+            node_type,
+            NodeData {
+                name,
+                file,
+                body,
+                start,
+                end,
+                docs,
+                hash,
+                data_type,
+                meta,
+            },
+        ))
+    }
+    fn node_ref(&mut self) -> Result<NodeRef> {
+        let node_type = self.node_type()?;
+        let name = self.string()?;
+        let file = self.string()?;
+        let start = self.len()?;
+        let verb = self.opt_string()?;
+        Ok(NodeRef::from(
+            NodeKeys {
+                name,
+                file,
+                start,
+                verb,
+            },
+            node_type,
+        ))
+    }
+    fn done(&self) -> Result<()> {
+        if self.pos != self.buf.len() {
+            return Err(invalid("trailing bytes"));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lang::{Graph, Lang};
+    use crate::repo::Repo;
+    use std::str::FromStr;
+
+    async fn build<G: Graph>() -> G {
+        let repo = Repo::new(
+            "src/testing/rust",
+            Lang::from_str("rust").unwrap(),
+            false,
+            Vec::new(),
+            Vec::new(),
+        )
+        .unwrap();
+        repo.build_graph_inner::<G>().await.unwrap()
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_snapshot_roundtrip() {
+        let graph = build::<BTreeMapGraph>().await;
+        let bytes = graph.to_snapshot(false).unwrap();
+        assert_eq!(BTreeMapGraph::from_snapshot(&bytes).unwrap(), graph);
+        assert!(ArrayGraph::from_snapshot(&bytes).is_err());
+
+        let graph = build::<ArrayGraph>().await;
+        let path = std::env::temp_dir().join(format!("{}.snap", uuid::Uuid::new_v4()));
+        let path = path.to_str().unwrap();
+        graph.save_snapshot(path, false).unwrap();
+        let loaded = ArrayGraph::load_snapshot(path).unwrap();
+        std::fs::remove_file(path).unwrap();
+        assert_eq!(loaded.nodes, graph.nodes);
+        assert_eq!(loaded.edges, graph.edges);
+        assert_eq!(loaded.get_graph_keys(), graph.get_graph_keys());
+
+        let json = serde_json::to_vec(&graph).unwrap();
+        let bytes = graph.to_snapshot(false).unwrap();
+        assert!(bytes.len() < json.len(), "snapshot should be smaller than json");
+        assert!(ArrayGraph::from_snapshot(&bytes[..bytes.len() - 1]).is_err());
+
+        #[cfg(feature = "zstd")]
+        {
+            let compressed = graph.to_snapshot(true).unwrap();
+            assert!(compressed.len() < bytes.len());
+            assert_eq!(ArrayGraph::from_snapshot(&compressed).unwrap().nodes, graph.nodes);
+        }
+    }
+
+    #[test]
+    fn test_snapshot_corrupt_length() {
+        // one string claiming usize::MAX bytes after a byte of padding
+        let mut buf = Vec::new();
+        write_varint(&mut buf, 2);
+        write_varint(&mut buf, 1);
+        buf.push(b'a');
+        write_varint(&mut buf, u64::MAX);
+        assert!(Reader::new(&buf).is_err());
+    }
+
+    #[test]
+    fn test_snapshot_tags() {
+        for (i, nt) in NODE_TYPES.iter().enumerate() {
+            assert_eq!(node_type_tag(nt) as usize, i);
+        }
+        for (i, et) in EDGE_TYPES.iter().enumerate() {
+            assert_eq!(edge_type_tag(et) as usize, i);
+        }
+    }
+}
@@ -0,0 +1,1072 @@
+use super::{graph::Graph, utils::tests_sources, *};
+use crate::lang::{Function, FunctionCall, Lang};
+use crate::utils::{create_node_key, create_node_key_from_ref, sanitize_string};
+use lsp::Language;
+use rusqlite::types::Type;
+use rusqlite::{params, params_from_iter, Connection, OptionalExtension, Row, ToSql};
+use shared::error::{Context, Error, Result};
+use std::collections::{BTreeMap, HashSet};
+use std::str::FromStr;
+use std::sync::{Arc, Mutex, MutexGuard};
+use tracing::{info, warn};
+
+const SCHEMA: &str = "
+    CREATE TABLE IF NOT EXISTS nodes (
+        node_key TEXT PRIMARY KEY,
+        node_type TEXT NOT NULL,
+        name TEXT NOT NULL,
+        file TEXT NOT NULL,
+        body TEXT NOT NULL,
+        start INTEGER NOT NULL,
+        end INTEGER NOT NULL,
+        docs TEXT,
+        hash TEXT,
+        data_type TEXT,
+        meta TEXT NOT NULL
+    );
+    CREATE INDEX IF NOT EXISTS nodes_type_name ON nodes (node_type, name);
+    CREATE INDEX IF NOT EXISTS nodes_file ON nodes (file);
+    CREATE TABLE IF NOT EXISTS edges (
+        source TEXT NOT NULL,
+        target TEXT NOT NULL,
+        edge_type TEXT NOT NULL,
+        PRIMARY KEY (source, target, edge_type)
+    ) WITHOUT ROWID;
+    CREATE INDEX IF NOT EXISTS edges_target ON edges (target, edge_type);
+    CREATE INDEX IF NOT EXISTS edges_type ON edges (edge_type);
+";
+
+const NODE_COLUMNS: &str = "n.name, n.file, n.body, n.start, n.end, n.docs, n.hash, n.data_type, n.meta";
+
+// nodes and edges in a sqlite database, keyed exactly like BTreeMapGraph.
+// `Default` is an in-memory database, `open` a file shared between processes
+#[derive(Clone, Debug)]
+pub struct SqliteGraph {
+    conn: Arc<Mutex<Connection>>,
+}
+
+impl SqliteGraph {
+    pub fn open(path: &str) -> Result<Self> {
+        let conn = Connection::open(path).context("failed to open sqlite graph")?;
+        conn.pragma_update(None, "journal_mode", "WAL")
+            .context("failed to enable WAL")?;
+        conn.busy_timeout(std::time::Duration::from_secs(30))
+            .context("failed to set busy timeout")?;
+        Self::from_connection(conn)
+    }
+    pub fn in_memory() -> Result<Self> {
+        let conn = Connection::open_in_memory().context("failed to open sqlite graph")?;
+        Self::from_connection(conn)
+    }
+    fn from_connection(conn: Connection) -> Result<Self> {
+        conn.execute_batch(SCHEMA)
+            .context("failed to create sqlite schema")?;
+        Ok(Self {
+            conn: Arc::new(Mutex::new(conn)),
+        })
+    }
+    fn conn(&self) -> MutexGuard<'_, Connection> {
+        self.conn.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    pub fn clear(&self) -> Result<()> {
+        self.conn()
+            .execute_batch("DELETE FROM edges; DELETE FROM nodes;")
+            .context("failed to clear sqlite graph")
+    }
+
+    // copy a built graph in, keeping its node keys
+    pub fn upload_btreemap(&self, btree_graph: &BTreeMapGraph) -> Result<(u32, u32)> {
+        let mut conn = self.conn();
+        let tx = conn.transaction().context("failed to start transaction")?;
+        for (key, node) in &btree_graph.nodes {
+            insert_node(&tx, key, &node.node_type, &node.node_data)
+                .context("failed to upload node")?;
+        }
+        for (src, dst, edge) in &btree_graph.edges {
+            insert_edge(&tx, src, dst, edge).context("failed to upload edge")?;
+        }
+        tx.commit().context("failed to commit upload")?;
+        drop(conn);
+        Ok(self.get_graph_size())
+    }
+
+    pub fn remove_nodes_by_file(&self, file_path: &str) -> Result<u32> {
+        let conn = self.conn();
+        let matches = "(file = ?1 OR substr(file, -length(?1)) = ?1)";
+        conn.execute(
+            &format!(
+                "DELETE FROM edges WHERE source IN (SELECT node_key FROM nodes WHERE {matches})
+                 OR target IN (SELECT node_key FROM nodes WHERE {matches})"
+            ),
+            params![file_path],
+        )
+        .context("failed to remove edges by file")?;
+        let deleted = conn
+            .execute(&format!("DELETE FROM nodes WHERE {matches}"), params![file_path])
+            .context("failed to remove nodes by file")?;
+        Ok(deleted as u32)
+    }
+
+    pub fn clear_existing_graph(&self, root: &str) -> Result<()> {
+        info!("Clearing existing graph for root: {}", root);
+        let conn = self.conn();
+        let under_root = "substr(file, 1, length(?1)) = ?1";
+        conn.execute(
+            &format!(
+                "DELETE FROM edges WHERE source IN (SELECT node_key FROM nodes WHERE {under_root})
+                 OR target IN (SELECT node_key FROM nodes WHERE {under_root})"
+            ),
+            params![root],
+        )
+        .context("failed to clear edges")?;
+        conn.execute(&format!("DELETE FROM nodes WHERE {under_root}"), params![root])
+            .context("failed to clear nodes")?;
+        Ok(())
+    }
+
+    pub fn get_repository_hash(&self, repo_url: &str) -> Result<String> {
+        let repo_name = repo_name_from_url(repo_url);
+        let hash: Option<Option<String>> = self
+            .conn()
+            .query_row(
+                "SELECT hash FROM nodes WHERE node_type = ?1 AND instr(name, ?2) > 0 LIMIT 1",
+                params![NodeType::Repository.to_string(), repo_name],
+                |row| row.get(0),
+            )
+            .optional()
+            .context("failed to get repository hash")?;
+        match hash {
+            Some(hash) => Ok(hash.unwrap_or_default()),
+            None => Err(Error::Custom(format!("No hash found for REPO {}", repo_url))),
+        }
+    }
+
+    pub fn update_repository_hash(&self, repo_name: &str, new_hash: &str) -> Result<()> {
+        self.conn()
+            .execute(
+                "UPDATE nodes SET hash = ?3 WHERE node_type = ?1 AND instr(name, ?2) > 0",
+                params![NodeType::Repository.to_string(), repo_name, new_hash],
+            )
+            .context("failed to update repository hash")?;
+        Ok(())
+    }
+
+    // functions or endpoints that no test (of the filtered kinds) reaches
+    pub fn find_uncovered_nodes_paginated(
+        &self,
+        node_type: NodeType,
+        with_usage: bool,
+        offset: usize,
+        limit: usize,
+        root: Option<&str>,
+        tests_filter: Option<&str>,
+    ) -> Result<Vec<(NodeData, usize)>> {
+        let test_types = tests_sources(tests_filter)
+            .iter()
+            .map(|t| format!("'{}'", t.to_string()))
+            .collect::<Vec<_>>()
+            .join(", ");
+        let calls = EdgeType::Calls.to_string();
+        let (coverage_check, caller_type) = match node_type {
+            NodeType::Function => (
+                format!(
+                    "EXISTS (SELECT 1 FROM edges e JOIN nodes t ON t.node_key = e.source
+                     WHERE e.target = n.node_key AND e.edge_type = '{calls}'
+                     AND t.node_type IN ({test_types}))"
+                ),
+                NodeType::Function,
+            ),
+            NodeType::Endpoint => (
+                format!(
+                    "EXISTS (SELECT 1 FROM edges h JOIN edges e ON e.target = h.target
+                     JOIN nodes t ON t.node_key = e.source
+                     WHERE h.source = n.node_key AND h.edge_type = '{}'
+                     AND e.edge_type = '{calls}' AND t.node_type IN ({test_types}))",
+                    EdgeType::Handler.to_string()
+                ),
+                NodeType::Request,
+            ),
+            _ => return Ok(Vec::new()),
+        };
+        let usage = if with_usage {
+            format!(
+                "(SELECT count(*) FROM edges e JOIN nodes c ON c.node_key = e.source
+                 WHERE e.target = n.node_key AND e.edge_type = '{calls}' AND c.node_type = '{}')",
+                caller_type.to_string()
+            )
+        } else {
+            "0".to_string()
+        };
+        let order = if with_usage {
+            "usage_count DESC, n.name ASC"
+        } else {
+            "n.name ASC"
+        };
+        let root_filter = if root.is_some() {
+            "AND substr(n.file, 1, length(?2)) = ?2"
+        } else {
+            "AND ?2 IS NULL"
+        };
+        let query = format!(
+            "SELECT {NODE_COLUMNS}, {usage} AS usage_count FROM nodes n
+             WHERE n.node_type = ?1 {root_filter} AND NOT {coverage_check}
+             ORDER BY {order} LIMIT ?3 OFFSET ?4"
+        );
+        let conn = self.conn();
+        let mut stmt = conn.prepare(&query).context("failed to prepare uncovered query")?;
+        let rows = stmt
+            .query_map(
+                params![node_type.to_string(), root, limit as i64, offset as i64],
+                |row| Ok((node_data_from_row(row)?, row.get::<_, i64>(9)? as usize)),
+            )
+            .context("failed to query uncovered nodes")?;
+        rows.collect::<rusqlite::Result<Vec<_>>>()
+            .context("failed to read uncovered nodes")
+    }
+
+    fn query_nodes(&self, clause: &str, args: &[&dyn ToSql]) -> Vec<NodeData> {
+        let query = format!("SELECT {NODE_COLUMNS} FROM nodes n WHERE {clause} ORDER BY n.node_key");
+        let conn = self.conn();
+        let res = conn.prepare(&query).and_then(|mut stmt| {
+            stmt.query_map(params_from_iter(args.iter()), node_data_from_row)?
+                .collect::<rusqlite::Result<Vec<_>>>()
+        });
+        res.unwrap_or_else(|e| {
+            warn!("sqlite node query failed: {}", e);
+            Vec::new()
+        })
+    }
+
+    fn query_node_pairs(&self, clause: &str, args: &[&dyn ToSql]) -> Vec<(NodeData, NodeData)> {
+        let cols = |alias: &str| NODE_COLUMNS.replace("n.", &format!("{alias}."));
+        let query = format!(
+            "SELECT {}, {} FROM edges e
+             JOIN nodes s ON s.node_key = e.source
+             JOIN nodes t ON t.node_key = e.target
+             WHERE {clause} ORDER BY e.source, e.target",
+            cols("s"),
+            cols("t")
+        );
+        let conn = self.conn();
+        let res = conn.prepare(&query).and_then(|mut stmt| {
+            stmt.query_map(params_from_iter(args.iter()), |row| {
+                Ok((node_data_from_row(row)?, node_data_at(row, 9)?))
+            })?
+            .collect::<rusqlite::Result<Vec<_>>>()
+        });
+        res.unwrap_or_else(|e| {
+            warn!("sqlite edge query failed: {}", e);
+            Vec::new()
+        })
+    }
+
+    // (key, node) for keys starting with prefix, in key order like a BTreeMap range
+    fn nodes_with_prefix(&self, prefix: &str) -> Vec<(String, Node)> {
+        let upper = format!("{prefix}{}", char::MAX);
+        let query = format!(
+            "SELECT n.node_key, n.node_type, {NODE_COLUMNS} FROM nodes n
+             WHERE n.node_key >= ?1 AND n.node_key < ?2 ORDER BY n.node_key"
+        );
+        let conn = self.conn();
+        let res = conn.prepare(&query).and_then(|mut stmt| {
+            stmt.query_map(params![prefix, upper], |row| {
+                let node_type = node_type_from_str(&row.get::<_, String>(1)?)?;
+                Ok((row.get(0)?, Node::new(node_type, node_data_at(row, 2)?)))
+            })?
+            .collect::<rusqlite::Result<Vec<_>>>()
+        });
+        res.unwrap_or_else(|e| {
+            warn!("sqlite prefix query failed: {}", e);
+            Vec::new()
+        })
+    }
+
+    fn all_edges(&self) -> Vec<(String, String, EdgeType)> {
+        let conn = self.conn();
+        let res = conn
+            .prepare("SELECT source, target, edge_type FROM edges ORDER BY source, target")
+            .and_then(|mut stmt| {
+                stmt.query_map([], |row| {
+                    Ok((row.get(0)?, row.get(1)?, edge_type_from_str(&row.get::<_, String>(2)?)?))
+                })?
+                .collect::<rusqlite::Result<Vec<_>>>()
+            });
+        res.unwrap_or_else(|e| {
+            warn!("sqlite edge query failed: {}", e);
+            Vec::new()
+        })
+    }
+
+    fn contains_node(&self, key: &str) -> bool {
+        self.conn()
+            .query_row("SELECT 1 FROM nodes WHERE node_key = ?1", params![key], |_| Ok(()))
+            .optional()
+            .map(|r| r.is_some())
+            .unwrap_or(false)
+    }
+
+    fn insert_node_key(&self, key: &str, node_type: &NodeType, node_data: &NodeData) {
+        if let Err(e) = insert_node(&self.conn(), key, node_type, node_data) {
+            warn!("failed to add sqlite node {}: {}", key, e);
+        }
+    }
+
+    fn remove_node_key(&self, key: &str) {
+        let conn = self.conn();
+        let res = conn
+            .execute("DELETE FROM nodes WHERE node_key = ?1", params![key])
+            .and_then(|_| {
+                conn.execute(
+                    "DELETE FROM edges WHERE source = ?1 OR target = ?1",
+                    params![key],
+                )
+            });
+        if let Err(e) = res {
+            warn!("failed to remove sqlite node {}: {}", key, e);
+        }
+    }
+}
+
+impl Default for SqliteGraph {
+    fn default() -> Self {
+        Self::in_memory().expect("failed to open in-memory sqlite graph")
+    }
+}
+
+impl Graph for SqliteGraph {
+    fn analysis(&self) {
+        for (key, _) in self.nodes_with_prefix("") {
+            println!("Node: {}", key);
+        }
+        for (src_key, dst_key, edge_type) in self.all_edges() {
+            println!("Edge: {} - {:?} -> {}", src_key, edge_type, dst_key);
+        }
+        info!("dead code:\n{}", dead_code::find_dead_code(self));
+    }
+
+    fn create_filtered_graph(self, final_filter: &[String], _lang_kind: Language) -> Self {
+        let res = (|| -> rusqlite::Result<()> {
+            let mut conn = self.conn();
+            let tx = conn.transaction()?;
+            tx.execute_batch("CREATE TEMP TABLE IF NOT EXISTS keep_files (file TEXT PRIMARY KEY)")?;
+            tx.execute("DELETE FROM keep_files", [])?;
+            for file in final_filter {
+                tx.execute("INSERT OR IGNORE INTO keep_files (file) VALUES (?1)", params![file])?;
+            }
+            // an edge stays if both ends exist and either end is in a kept file
+            tx.execute(
+                "DELETE FROM edges WHERE NOT EXISTS (
+                    SELECT 1 FROM nodes s, nodes t
+                    WHERE s.node_key = edges.source AND t.node_key = edges.target
+                    AND (s.file IN keep_files OR t.file IN keep_files))",
+                [],
+            )?;
+            tx.execute(
+                "DELETE FROM nodes WHERE node_type != ?1 AND file NOT IN keep_files",
+                params![NodeType::Repository.to_string()],
+            )?;
+            tx.execute_batch("DROP TABLE keep_files")?;
+            tx.commit()
+        })();
+        if let Err(e) = res {
+            warn!("failed to filter sqlite graph: {}", e);
+        }
+        self
+    }
+
+    fn extend_graph(&mut self, other: Self) {
+        if Arc::ptr_eq(&self.conn, &other.conn) {
+            return;
+        }
+        let nodes = other.nodes_with_prefix("");
+        let edges = other.all_edges();
+        let res = (|| -> rusqlite::Result<()> {
+            let mut conn = self.conn();
+            let tx = conn.transaction()?;
+            for (key, node) in &nodes {
+                insert_node(&tx, key, &node.node_type, &node.node_data)?;
+            }
+            for (src, dst, edge) in &edges {
+                insert_edge(&tx, src, dst, edge)?;
+            }
+            tx.commit()
+        })();
+        if let Err(e) = res {
+            warn!("failed to extend sqlite graph: {}", e);
+        }
+    }
+
+    fn get_graph_size(&self) -> (u32, u32) {
+        let conn = self.conn();
+        let count = |table: &str| -> u32 {
+            conn.query_row(&format!("SELECT count(*) FROM {table}"), [], |row| row.get(0))
+                .unwrap_or(0)
+        };
+        (count("nodes"), count("edges"))
+    }
+
+    fn find_nodes_by_name(&self, node_type: NodeType, name: &str) -> Vec<NodeData> {
+        self.query_nodes(
+            "n.node_type = ?1 AND n.name = ?2",
+            &[&node_type.to_string(), &name],
+        )
+    }
+
+    fn find_node_by_name_in_file(
+        &self,
+        node_type: NodeType,
+        name: &str,
+        file: &str,
+    ) -> Option<NodeData> {
+        self.query_nodes(
+            "n.node_type = ?1 AND n.name = ?2 AND n.file = ?3",
+            &[&node_type.to_string(), &name, &file],
+        )
+        .into_iter()
+        .next()
+    }
+
+    fn add_node_with_parent(
+        &mut self,
+        node_type: NodeType,
+        node_data: NodeData,
+        parent_type: NodeType,
+        parent_file: &str,
+    ) {
+        self.add_node(node_type.clone(), node_data.clone());
+        if let Some(parent) = self
+            .query_nodes(
+                "n.node_type = ?1 AND n.file = ?2",
+                &[&parent_type.to_string(), &parent_file],
+            )
+            .first()
+        {
+            let edge = Edge::contains(parent_type, parent, node_type, &node_data);
+            self.add_edge(edge);
+        }
+    }
+
+    fn add_edge(&mut self, edge: Edge) {
+        let source_key = create_node_key_from_ref(&edge.source);
+        let target_key = create_node_key_from_ref(&edge.target);
+        if let Err(e) = insert_edge(&self.conn(), &source_key, &target_key, &edge.edge) {
+            warn!("failed to add sqlite edge: {}", e);
+        }
+    }
+
+    fn add_node(&mut self, node_type: NodeType, node_data: NodeData) {
+        let key = create_node_key(&Node::new(node_type.clone(), node_data.clone()));
+        self.insert_node_key(&key, &node_type, &node_data);
+    }
+
+    fn get_graph_keys(&self) -> (HashSet<String>, HashSet<String>) {
+        let node_keys = self
+            .nodes_with_prefix("")
+            .into_iter()
+            .map(|(k, _)| k.to_lowercase())
+            .collect();
+        let edge_keys = self
+            .all_edges()
+            .into_iter()
+            .map(|(src, dst, edge)| format!("{}-{}-{:?}", src, dst, edge).to_lowercase())
+            .collect();
+        (node_keys, edge_keys)
+    }
+
+    fn find_source_edge_by_name_and_file(
+        &self,
+        edge_type: EdgeType,
+        target_name: &str,
+        target_file: &str,
+    ) -> Option<NodeKeys> {
+        self.query_node_pairs(
+            "e.edge_type = ?1 AND t.name = ?2 AND t.file = ?3",
+            &[&edge_type.to_string(), &target_name, &target_file],
+        )
+        .first()
+        .map(|(src, _)| NodeKeys::from(src))
+    }
+
+    fn process_endpoint_groups(&mut self, eg: Vec<NodeData>, lang: &Lang) -> Result<()> {
+        let mut updates = Vec::new();
+        for group in eg {
+            let Some(g) = group.meta.get("group") else {
+                continue;
+            };
+            let Some(gf) = self.find_nodes_by_name(NodeType::Function, g).into_iter().next() else {
+                continue;
+            };
+            for q in lang.lang().endpoint_finders() {
+                let endpoints_in_group = lang.get_query_opt::<Self>(
+                    Some(q),
+                    &gf.body,
+                    &gf.file,
+                    NodeType::Endpoint,
+                )?;
+                for end in endpoints_in_group {
+                    let prefix = format!("{:?}-{}", NodeType::Endpoint, sanitize_string(&end.name))
+                        .to_lowercase();
+                    if let Some((key, mut node)) = self.nodes_with_prefix(&prefix).into_iter().next() {
+                        node.node_data.name = format!("{}{}", group.name, &node.node_data.name);
+                        updates.push((key, node));
+                    }
+                }
+            }
+        }
+        let conn = self.conn();
+        for (old_key, node) in updates {
+            let new_key = create_node_key(&node);
+            conn.execute("DELETE FROM nodes WHERE node_key = ?1", params![old_key])
+                .context("failed to rename endpoint")?;
+            insert_node(&conn, &new_key, &node.node_type, &node.node_data)
+                .context("failed to rename endpoint")?;
+            conn.execute(
+                "UPDATE OR REPLACE edges SET source = ?2 WHERE source = ?1",
+                params![old_key, new_key],
+            )
+            .context("failed to rename endpoint edges")?;
+        }
+        Ok(())
+    }
+
+    fn class_inherits(&mut self) {
+        for node in self.find_nodes_by_type(NodeType::Class) {
+            if let Some(parent) = node.meta.get("parent") {
+                if let Some(parent_node) = self.find_nodes_by_name(NodeType::Class, parent).first() {
+                    let edge = Edge::parent_of(parent_node, &node);
+                    self.add_edge(edge);
+                }
+            }
+        }
+    }
+
+    fn class_includes(&mut self) {
+        for node in self.find_nodes_by_type(NodeType::Class) {
+            if let Some(includes) = node.meta.get("includes") {
+                for module in includes.split(',').map(|m| m.trim()) {
+                    if let Some(module_node) = self.find_nodes_by_name(NodeType::Class, module).first()
+                    {
+                        let edge = Edge::class_imports(&node, module_node);
+                        self.add_edge(edge);
+                    }
+                }
+            }
+        }
+    }
+
+    fn add_instances(&mut self, instances: Vec<NodeData>) {
+        for inst in instances {
+            if let Some(of) = &inst.data_type {
+                if let Some(class_node_data) = self.find_nodes_by_name(NodeType::Class, of).first() {
+                    self.add_node_with_parent(
+                        NodeType::Instance,
+                        inst.clone(),
+                        NodeType::File,
+                        &inst.file,
+                    );
+                    let edge = Edge::of(&inst, class_node_data);
+                    self.add_edge(edge);
+                }
+            }
+        }
+    }
+
+    fn add_functions(&mut self, functions: Vec<Function>) {
+        for (func_node_data, method_of, reqs, dms, trait_operand, return_types) in functions {
+            let func_clone = func_node_data.clone();
+            self.add_node(NodeType::Function, func_node_data);
+
+            if let Some(file_node_data) = self
+                .query_nodes(
+                    "n.node_type = ?1 AND n.file = ?2",
+                    &[&NodeType::File.to_string(), &func_clone.file],
+                )
+                .first()
+            {
+                let contains_edge = Edge::contains(
+                    NodeType::File,
+                    file_node_data,
+                    NodeType::Function,
+                    &func_clone,
+                );
+                self.add_edge(contains_edge);
+            }
+
+            if let Some(p) = method_of {
+                self.add_edge(p.into());
+            }
+            if let Some(to) = trait_operand {
+                self.add_edge(to);
+            }
+            for rt in return_types {
+                self.add_edge(rt);
+            }
+            for req in reqs {
+                let req_clone = req.clone();
+                self.add_node(NodeType::Request, req);
+                let calls_edge =
+                    Edge::calls(NodeType::Function, &func_clone, NodeType::Request, &req_clone);
+                self.add_edge(calls_edge);
+            }
+            for dm_edge in dms {
+                self.add_edge(dm_edge);
+            }
+        }
+    }
+
+    fn add_page(&mut self, page: (NodeData, Option<Edge>)) {
+        let (page_data, edge_opt) = page;
+        self.add_node(NodeType::Page, page_data);
+        if let Some(edge) = edge_opt {
+            self.add_edge(edge);
+        }
+    }
+
+    fn add_pages(&mut self, pages: Vec<(NodeData, Vec<Edge>)>) {
+        for (page_data, edges) in pages {
+            self.add_node(NodeType::Page, page_data);
+            for edge in edges {
+                self.add_edge(edge);
+            }
+        }
+    }
+
+    fn add_endpoints(&mut self, endpoints: Vec<(NodeData, Option<Edge>)>) {
+        for (endpoint_data, handler_edge) in endpoints {
+            if !endpoint_data.meta.contains_key("handler") {
+                continue;
+            }
+            let verb = endpoint_data.meta.get("verb").cloned().unwrap_or_default();
+            if self
+                .find_endpoint(&endpoint_data.name, &endpoint_data.file, &verb)
+                .is_some()
+            {
+                continue;
+            }
+            self.add_node(NodeType::Endpoint, endpoint_data);
+            if let Some(edge) = handler_edge {
+                self.add_edge(edge);
+            }
+        }
+    }
+
+    // Add calls only between function definitions not between function calls
+    fn add_calls(&mut self, calls: (Vec<FunctionCall>, Vec<FunctionCall>, Vec<Edge>, Vec<Edge>)) {
+        let (funcs, tests, int_tests, extras) = calls;
+        let mut unique_edges: HashSet<(String, String, String, String)> = HashSet::new();
+
+        for (fc, ext_func, class_call) in funcs {
+            if let Some(class_call) = &class_call {
+                self.add_edge(Edge::new(
+                    EdgeType::Calls,
+                    NodeRef::from(fc.source.clone(), NodeType::Function),
+                    NodeRef::from(class_call.into(), NodeType::Class),
+                ));
+            }
+            if fc.target.is_empty() {
+                continue;
+            }
+            if let Some(ext_nd) = ext_func {
+                let edge_key = (
+                    fc.source.name.clone(),
+                    fc.source.file.clone(),
+                    ext_nd.name.clone(),
+                    ext_nd.file.clone(),
+                );
+                if unique_edges.insert(edge_key) {
+                    self.add_external_function(&ext_nd);
+                    self.add_edge(Edge::uses(fc.source, &ext_nd));
+                }
+            } else {
+                let edge_key = (
+                    fc.source.name.clone(),
+                    fc.source.file.clone(),
+                    fc.target.name.clone(),
+                    fc.target.file.clone(),
+                );
+                if unique_edges.insert(edge_key) {
+                    self.add_edge(fc.into());
+                }
+            }
+        }
+
+        for (tc, ext_func, _) in tests {
+            if let Some(ext_nd) = ext_func {
+                let edge_key = (
+                    tc.source.name.clone(),
+                    tc.source.file.clone(),
+                    ext_nd.name.clone(),
+                    ext_nd.file.clone(),
+                );
+                if unique_edges.insert(edge_key) {
+                    self.add_edge(Edge::uses(tc.source, &ext_nd));
+                    self.add_external_function(&ext_nd);
+                }
+            } else {
+                let edge_key = (
+                    tc.source.name.clone(),
+                    tc.source.file.clone(),
+                    tc.target.name.clone(),
+                    tc.source.file.clone(),
+                );
+                if unique_edges.insert(edge_key) {
+                    self.add_edge(Edge::from_test_call(&tc));
+                }
+            }
+        }
+
+        for edge in int_tests {
+            self.add_edge(edge);
+        }
+        for extra in extras {
+            self.add_edge(extra);
+        }
+    }
+
+    fn filter_out_nodes_without_children(
+        &mut self,
+        parent_type: NodeType,
+        child_type: NodeType,
+        child_meta_key: &str,
+    ) {
+        let parent_prefix = format!("{:?}-", parent_type).to_lowercase();
+        let parents = self.nodes_with_prefix(&parent_prefix);
+        let mut has_children: BTreeMap<String, bool> = parents
+            .iter()
+            .map(|(_, node)| (node.node_data.name.clone(), false))
+            .collect();
+        for child in self.find_nodes_by_type(child_type) {
+            if let Some(parent_name) = child.meta.get(child_meta_key) {
+                if let Some(entry) = has_children.get_mut(parent_name) {
+                    *entry = true;
+                }
+            }
+        }
+        for (key, node) in parents {
+            if !has_children.get(&node.node_data.name).unwrap_or(&true) {
+                self.remove_node_key(&key);
+            }
+        }
+    }
+
+    fn get_data_models_within(&mut self, lang: &Lang) {
+        for data_model in self.find_nodes_by_type(NodeType::DataModel) {
+            let edges = lang.lang().data_model_within_finder(&data_model, &|file| {
+                self.find_nodes_by_file_ends_with(NodeType::Function, file)
+            });
+            for edge in edges {
+                self.add_edge(edge);
+            }
+        }
+    }
+
+    fn find_endpoint(&self, name: &str, file: &str, verb: &str) -> Option<NodeData> {
+        self.query_nodes(
+            "n.node_type = ?1 AND n.name = ?2 AND n.file = ?3",
+            &[&NodeType::Endpoint.to_string(), &name, &file],
+        )
+        .into_iter()
+        .find(|node| node.meta.get("verb").map(|v| v.as_str()) == Some(verb))
+    }
+
+    fn find_resource_nodes(&self, node_type: NodeType, verb: &str, path: &str) -> Vec<NodeData> {
+        self.query_nodes(
+            "n.node_type = ?1 AND instr(n.name, ?2) > 0",
+            &[&node_type.to_string(), &path],
+        )
+        .into_iter()
+        .filter(|node| match node.meta.get("verb") {
+            Some(node_verb) => node_verb.to_uppercase() == verb.to_uppercase(),
+            None => true,
+        })
+        .collect()
+    }
+
+    fn find_handlers_for_endpoint(&self, endpoint: &NodeData) -> Vec<NodeData> {
+        let endpoint_key = create_node_key(&Node::new(NodeType::Endpoint, endpoint.clone()));
+        self.query_node_pairs(
+            "e.edge_type = ?1 AND e.source = ?2",
+            &[&EdgeType::Handler.to_string(), &endpoint_key],
+        )
+        .into_iter()
+        .map(|(_, handler)| handler)
+        .collect()
+    }
+
+    fn check_direct_data_model_usage(&self, function_name: &str, data_model: &str) -> bool {
+        !self
+            .query_node_pairs(
+                "e.edge_type = ?1 AND s.name = ?2 AND instr(t.name, ?3) > 0",
+                &[&EdgeType::Contains.to_string(), &function_name, &data_model],
+            )
+            .is_empty()
+    }
+
+    fn find_functions_called_by(&self, function: &NodeData) -> Vec<NodeData> {
+        let prefix = format!(
+            "{:?}-{}-{}",
+            NodeType::Function,
+            sanitize_string(&function.name),
+            sanitize_string(&function.file)
+        )
+        .to_lowercase();
+        let upper = format!("{prefix}{}", char::MAX);
+        self.query_node_pairs(
+            "e.edge_type = ?1 AND e.source >= ?2 AND e.source < ?3",
+            &[&EdgeType::Calls.to_string(), &prefix, &upper],
+        )
+        .into_iter()
+        .map(|(_, called)| called)
+        .collect()
+    }
+
+    fn find_nodes_by_type(&self, node_type: NodeType) -> Vec<NodeData> {
+        self.query_nodes("n.node_type = ?1", &[&node_type.to_string()])
+    }
+
+    fn find_nodes_with_edge_type(
+        &self,
+        source_type: NodeType,
+        target_type: NodeType,
+        edge_type: EdgeType,
+    ) -> Vec<(NodeData, NodeData)> {
+        self.query_node_pairs(
+            "e.edge_type = ?1 AND s.node_type = ?2 AND t.node_type = ?3",
+            &[
+                &edge_type.to_string(),
+                &source_type.to_string(),
+                &target_type.to_string(),
+            ],
+        )
+    }
+
+    fn count_edges_of_type(&self, edge_type: EdgeType) -> usize {
+        self.conn()
+            .query_row(
+                "SELECT count(*) FROM edges WHERE edge_type = ?1",
+                params![edge_type.to_string()],
+                |row| row.get::<_, i64>(0),
+            )
+            .unwrap_or(0) as usize
+    }
+
+    fn find_nodes_by_name_contains(&self, node_type: NodeType, name: &str) -> Vec<NodeData> {
+        self.query_nodes(
+            "n.node_type = ?1 AND instr(n.name, ?2) > 0",
+            &[&node_type.to_string(), &name],
+        )
+    }
+
+    fn find_nodes_by_file_ends_with(&self, node_type: NodeType, file: &str) -> Vec<NodeData> {
+        self.query_nodes(
+            "n.node_type = ?1 AND substr(n.file, length(n.file) - length(?2) + 1) = ?2",
+            &[&node_type.to_string(), &file],
+        )
+    }
+
+    fn find_node_by_name_and_file_end_with(
+        &self,
+        node_type: NodeType,
+        name: &str,
+        suffix: &str,
+    ) -> Option<NodeData> {
+        self.find_nodes_by_name(node_type, name)
+            .into_iter()
+            .find(|node| node.file.ends_with(suffix))
+    }
+
+    fn find_node_in_range(&self, node_type: NodeType, row: u32, file: &str) -> Option<NodeData> {
+        self.query_nodes(
+            "n.node_type = ?1 AND n.file = ?2 AND n.start <= ?3 AND n.end >= ?3",
+            &[&node_type.to_string(), &file, &row],
+        )
+        .into_iter()
+        .next()
+    }
+
+    fn find_node_at(&self, node_type: NodeType, file: &str, line: u32) -> Option<NodeData> {
+        self.query_nodes(
+            "n.node_type = ?1 AND n.file = ?2 AND n.start = ?3",
+            &[&node_type.to_string(), &file, &line],
+        )
+        .into_iter()
+        .next()
+    }
+
+    fn has_edge(&self, source: &Node, target: &Node, edge_type: EdgeType) -> bool {
+        let source_key = create_node_key(source);
+        let target_key = create_node_key(target);
+        !self
+            .query_node_pairs(
+                "e.source = ?1 AND e.target = ?2 AND e.edge_type = ?3",
+                &[&source_key, &target_key, &edge_type.to_string()],
+            )
+            .is_empty()
+    }
+
+    fn find_all_nodes(&self) -> Vec<Node> {
+        self.nodes_with_prefix("").into_iter().map(|(_, n)| n).collect()
+    }
+
+    fn find_all_edges(&self) -> Vec<(EdgeType, Node, Node)> {
+        let nodes: BTreeMap<String, Node> = self.nodes_with_prefix("").into_iter().collect();
+        self.all_edges()
+            .into_iter()
+            .filter_map(|(src_key, dst_key, edge_type)| {
+                let source = nodes.get(&src_key)?;
+                let target = nodes.get(&dst_key)?;
+                Some((edge_type, source.clone(), target.clone()))
+            })
+            .collect()
+    }
+}
+
+impl SqliteGraph {
+    fn add_external_function(&self, ext_nd: &NodeData) {
+        let ext_node = Node::new(NodeType::Function, ext_nd.clone());
+        let ext_key = create_node_key(&ext_node);
+        if !self.contains_node(&ext_key) {
+            self.insert_node_key(&ext_key, &NodeType::Function, ext_nd);
+        }
+    }
+}
+
+fn insert_node(
+    conn: &Connection,
+    key: &str,
+    node_type: &NodeType,
+    nd: &NodeData,
+) -> rusqlite::Result<usize> {
+    let meta = serde_json::to_string(&nd.meta).unwrap_or_else(|_| "{}".to_string());
+    conn.execute(
+        "INSERT OR REPLACE INTO nodes
+         (node_key, node_type, name, file, body, start, end, docs, hash, data_type, meta)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)",
+        params![
+            key,
+            node_type.to_string(),
+            nd.name,
+            nd.file,
+            nd.body,
+            nd.start as i64,
+            nd.end as i64,
+            nd.docs,
+            nd.hash,
+            nd.data_type,
+            meta
+        ],
+    )
+}
+
+fn insert_edge(conn: &Connection, src: &str, dst: &str, edge: &EdgeType) -> rusqlite::Result<usize> {
+    conn.execute(
+        "INSERT OR IGNORE INTO edges (source, target, edge_type) VALUES (?1, ?2, ?3)",
+        params![src, dst, edge.to_string()],
+    )
+}
+
+fn node_data_from_row(row: &Row) -> rusqlite::Result<NodeData> {
+    node_data_at(row, 0)
+}
+
+// the NODE_COLUMNS of one node, starting at column `i`
+fn node_data_at(row: &Row, i: usize) -> rusqlite::Result<NodeData> {
+    let meta: String = row.get(i + 8)?;
+    Ok(NodeData {
+        name: row.get(i)?,
+        file: row.get(i + 1)?,
+        body: row.get(i + 2)?,
+        start: row.get::<_, i64>(i + 3)? as usize,
+        end: row.get::<_, i64>(i + 4)? as usize,
+        docs: row.get(i + 5)?,
+        hash: row.get(i + 6)?,
+        data_type: row.get(i + 7)?,
+        meta: serde_json::from_str(&meta).unwrap_or_default(),
+    })
+}
+
+fn node_type_from_str(s: &str) -> rusqlite::Result<NodeType> {
+    NodeType::from_str(s)
+        .map_err(|e| rusqlite::Error::FromSqlConversionFailure(1, Type::Text, e.to_string().into()))
+}
+
+fn edge_type_from_str(s: &str) -> rusqlite::Result<EdgeType> {
+    EdgeType::from_str(s)
+        .map_err(|e| rusqlite::Error::FromSqlConversionFailure(2, Type::Text, e.to_string().into()))
+}
+
+fn repo_name_from_url(repo_url: &str) -> &str {
+    match repo_url.rsplit('/').next() {
+        Some(name) if repo_url.contains('/') => name.trim_end_matches(".git"),
+        _ => repo_url,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn function(name: &str, file: &str, start: usize) -> NodeData {
+        let mut nd = NodeData::name_file(name, file);
+        nd.start = start;
+        nd.body = format!("fn {name}() {{}}");
+        nd
+    }
+
+    #[test]
+    fn test_uncovered_and_remove_by_file() {
+        let mut graph = SqliteGraph::default();
+        let covered = function("covered", "src/a.rs", 1);
+        let called = function("called", "src/a.rs", 5);
+        let lonely = function("lonely", "src/b.rs", 1);
+        let test = function("test_covered", "tests/a.rs", 1);
+        for f in [&covered, &called, &lonely] {
+            graph.add_node(NodeType::Function, f.clone());
+        }
+        graph.add_node(NodeType::UnitTest, test.clone());
+        graph.add_edge(Edge::test_calls(NodeType::UnitTest, &test, NodeType::Function, &covered));
+        graph.add_edge(Edge::calls(NodeType::Function, &covered, NodeType::Function, &called));
+
+        let uncovered = graph
+            .find_uncovered_nodes_paginated(NodeType::Function, true, 0, 10, None, None)
+            .unwrap();
+        let names: Vec<_> = uncovered.iter().map(|(n, u)| (n.name.as_str(), *u)).collect();
+        assert_eq!(names, vec![("called", 1), ("lonely", 0)]);
+
+        let in_a = graph
+            .find_uncovered_nodes_paginated(NodeType::Function, false, 0, 10, Some("src/a"), None)
+            .unwrap();
+        assert_eq!(in_a.len(), 1);
+        let integration_only = graph
+            .find_uncovered_nodes_paginated(NodeType::Function, false, 0, 10, None, Some("integration"))
+            .unwrap();
+        assert_eq!(integration_only.len(), 3);
+
+        assert_eq!(graph.remove_nodes_by_file("a.rs").unwrap(), 3);
+        assert_eq!(graph.get_graph_size(), (1, 0));
+    }
+
+    #[test]
+    fn test_repository_hash_persists() {
+        let path = std::env::temp_dir().join(format!("stakgraph-{}.db", uuid::Uuid::new_v4()));
+        let path = path.to_string_lossy().to_string();
+        {
+            let mut graph = SqliteGraph::open(&path).unwrap();
+            graph.add_node(NodeType::Repository, NodeData::name_file("org/repo", "/tmp/org/repo"));
+            graph.update_repository_hash("repo", "abc123").unwrap();
+        }
+        let graph = SqliteGraph::open(&path).unwrap();
+        assert_eq!(
+            graph.get_repository_hash("https://github.com/org/repo.git").unwrap(),
+            "abc123"
+        );
+        assert!(graph.get_repository_hash("other").is_err());
+        graph.clear_existing_graph("/tmp/org").unwrap();
+        assert_eq!(graph.get_graph_size(), (0, 0));
+        drop(graph);
+        for suffix in ["", "-wal", "-shm"] {
+            let _ = std::fs::remove_file(format!("{path}{suffix}"));
+        }
+    }
+}
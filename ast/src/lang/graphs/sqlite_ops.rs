@@ -0,0 +1,212 @@
+use crate::lang::graphs::coverage::{graph_coverage, node_has_coverage, GraphCoverage};
+use crate::lang::graphs::graph::Graph;
+use crate::lang::graphs::sqlite_graph::SqliteGraph;
+use crate::lang::graphs::BTreeMapGraph;
+use crate::lang::linker;
+use crate::lang::{EdgeType, NodeData, NodeType};
+use crate::repo::{check_revs_files, Repo};
+use shared::error::{Error, Result};
+use tracing::info;
+
+// the GraphOps operations on a single sqlite file (SQLITE_PATH, default stakgraph.db)
+#[derive(Debug, Clone, Default)]
+pub struct SqliteGraphOps {
+    pub graph: SqliteGraph,
+}
+
+pub fn sqlite_path() -> String {
+    std::env::var("SQLITE_PATH")
+        .ok()
+        .filter(|p| !p.is_empty())
+        .unwrap_or_else(|| "stakgraph.db".to_string())
+}
+
+impl SqliteGraphOps {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub async fn connect(&mut self) -> Result<()> {
+        self.graph = SqliteGraph::open(&sqlite_path())?;
+        Ok(())
+    }
+
+    pub async fn get_graph_size(&self) -> Result<(u32, u32)> {
+        Ok(self.graph.get_graph_size())
+    }
+
+    pub async fn clear(&mut self) -> Result<(u32, u32)> {
+        self.graph.clear()?;
+        let (nodes, edges) = self.graph.get_graph_size();
+        info!("Graph cleared - Nodes: {}, Edges: {}", nodes, edges);
+        Ok((nodes, edges))
+    }
+
+    pub async fn fetch_repo(&mut self, repo_name: &str) -> Result<NodeData> {
+        self.graph
+            .find_nodes_by_name(NodeType::Repository, repo_name)
+            .into_iter()
+            .next()
+            .ok_or_else(|| Error::Custom("Repo not found".into()))
+    }
+
+    pub async fn fetch_repos(&mut self) -> Vec<NodeData> {
+        self.graph.find_nodes_by_type(NodeType::Repository)
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub async fn update_incremental(
+        &mut self,
+        repo_url: &str,
+        username: Option<String>,
+        pat: Option<String>,
+        current_hash: &str,
+        stored_hash: &str,
+        commit: Option<&str>,
+        use_lsp: Option<bool>,
+    ) -> Result<(u32, u32)> {
+        let revs = vec![stored_hash.to_string(), current_hash.to_string()];
+        let repo_path = Repo::get_path_from_url(repo_url)?;
+        if let Some(modified_files) = check_revs_files(&repo_path, revs.clone()) {
+            info!(
+                "Processing {} changed files between commits",
+                modified_files.len()
+            );
+            if !modified_files.is_empty() {
+                for file in &modified_files {
+                    self.graph.remove_nodes_by_file(file)?;
+                }
+                let subgraph_repos = Repo::new_multi_detect(
+                    &repo_path,
+                    Some(repo_url.to_string()),
+                    modified_files,
+                    revs,
+                    use_lsp,
+                )
+                .await?;
+                let subgraph = subgraph_repos.build_graphs_inner::<BTreeMapGraph>().await?;
+                self.graph.upload_btreemap(&subgraph)?;
+
+                let (api_links, e2e_links) = self.link_cross_repo_relations().await?;
+                info!(
+                    "Linked cross-repo relations: api_links={}, e2e_links={}",
+                    api_links, e2e_links
+                );
+            }
+            self.graph.update_repository_hash(repo_url, current_hash)?;
+        } else if stored_hash.is_empty() && !current_hash.is_empty() {
+            info!("Processing new repository with hash: {}", current_hash);
+            let repos = Repo::new_clone_multi_detect(
+                repo_url,
+                username,
+                pat,
+                Vec::new(),
+                Vec::new(),
+                commit,
+                use_lsp,
+            )
+            .await?;
+            let graph = repos.build_graphs_inner::<BTreeMapGraph>().await?;
+            self.graph.upload_btreemap(&graph)?;
+            let (api_links, e2e_links) = self.link_cross_repo_relations().await?;
+            info!(
+                "Linked cross-repo relations: api_links={}, e2e_links={}",
+                api_links, e2e_links
+            );
+        }
+        Ok(self.graph.get_graph_size())
+    }
+
+    pub async fn update_full(
+        &mut self,
+        repo_url: &str,
+        username: Option<String>,
+        pat: Option<String>,
+        current_hash: &str,
+        commit: Option<&str>,
+        use_lsp: Option<bool>,
+    ) -> Result<(u32, u32)> {
+        let repos = Repo::new_clone_multi_detect(
+            repo_url,
+            username,
+            pat,
+            Vec::new(),
+            Vec::new(),
+            commit,
+            use_lsp,
+        )
+        .await?;
+        let temp_graph = repos.build_graphs_inner::<BTreeMapGraph>().await?;
+
+        self.graph.clear()?;
+        self.graph.upload_btreemap(&temp_graph)?;
+        self.graph.update_repository_hash(repo_url, current_hash)?;
+        Ok(self.graph.get_graph_size())
+    }
+
+    pub async fn upload_btreemap(&mut self, btree_graph: &BTreeMapGraph) -> Result<(u32, u32)> {
+        self.graph.upload_btreemap(btree_graph)
+    }
+
+    pub async fn clear_existing_graph(&mut self, root: &str) -> Result<()> {
+        self.graph.clear_existing_graph(root)
+    }
+
+    // link requests to endpoints and e2e tests to functions across every stored repo
+    pub async fn link_cross_repo_relations(&mut self) -> Result<(usize, usize)> {
+        let before = self.graph.count_edges_of_type(EdgeType::Calls);
+        linker::link_api_nodes(&mut self.graph)?;
+        let after_api = self.graph.count_edges_of_type(EdgeType::Calls);
+        linker::link_e2e_tests(&mut self.graph)?;
+        let after_e2e = self.graph.count_edges_of_type(EdgeType::Calls);
+        Ok((after_api - before, after_e2e - after_api))
+    }
+
+    pub async fn get_coverage(&mut self, repo: Option<&str>) -> Result<GraphCoverage> {
+        Ok(graph_coverage(&self.graph, repo))
+    }
+
+    pub async fn list_uncovered(
+        &mut self,
+        node_type: NodeType,
+        with_usage: bool,
+        offset: usize,
+        limit: usize,
+        root: Option<&str>,
+        tests_filter: Option<&str>,
+    ) -> Result<(Vec<(NodeData, usize)>, Vec<(NodeData, usize)>)> {
+        let results = self.graph.find_uncovered_nodes_paginated(
+            node_type.clone(),
+            with_usage,
+            offset,
+            limit,
+            root,
+            tests_filter,
+        )?;
+        match node_type {
+            NodeType::Function => Ok((results, vec![])),
+            NodeType::Endpoint => Ok((vec![], results)),
+            _ => Ok((vec![], vec![])),
+        }
+    }
+
+    pub async fn has_coverage(
+        &mut self,
+        node_type: NodeType,
+        name: &str,
+        file: &str,
+        start: Option<usize>,
+        root: Option<&str>,
+        tests_filter: Option<&str>,
+    ) -> Result<bool> {
+        Ok(node_has_coverage(
+            &self.graph,
+            node_type,
+            name,
+            file,
+            start,
+            root,
+            tests_filter,
+        ))
+    }
+}
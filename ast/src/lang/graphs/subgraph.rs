@@ -0,0 +1,102 @@
+use super::{graph::Graph, Edge, EdgeType, Node, NodeRef, NodeType};
+use crate::utils::create_node_key;
+use std::collections::{BTreeMap, HashMap, VecDeque};
+
+pub fn edge_from(edge_type: EdgeType, source: &Node, target: &Node) -> Edge {
+    Edge::new(
+        edge_type,
+        NodeRef::from((&source.node_data).into(), source.node_type.clone()),
+        NodeRef::from((&target.node_data).into(), target.node_type.clone()),
+    )
+}
+
+// breadth-first over the edges in both directions, keeping the nodes within `depth` hops
+// and every edge on a path of at most `depth` hops from the seed. empty filters allow
+// every type, and the seed is kept whatever its type
+pub fn neighborhood(
+    all_edges: Vec<(EdgeType, Node, Node)>,
+    seed: &Node,
+    depth: usize,
+    edge_types: &[EdgeType],
+    node_types: &[NodeType],
+) -> (Vec<Node>, Vec<Edge>) {
+    let seed_key = create_node_key(seed);
+    let allowed = |node: &Node| node_types.is_empty() || node_types.contains(&node.node_type);
+
+    let mut nodes: HashMap<String, Node> = HashMap::new();
+    let mut adjacent: HashMap<String, Vec<String>> = HashMap::new();
+    let mut edges = Vec::new();
+    for (edge_type, source, target) in all_edges {
+        if !edge_types.is_empty() && !edge_types.contains(&edge_type) {
+            continue;
+        }
+        let (source_key, target_key) = (create_node_key(&source), create_node_key(&target));
+        if (source_key != seed_key && !allowed(&source))
+            || (target_key != seed_key && !allowed(&target))
+        {
+            continue;
+        }
+        adjacent
+            .entry(source_key.clone())
+            .or_default()
+            .push(target_key.clone());
+        adjacent
+            .entry(target_key.clone())
+            .or_default()
+            .push(source_key.clone());
+        edges.push((edge_type, source_key.clone(), target_key.clone()));
+        nodes.entry(source_key).or_insert(source);
+        nodes.entry(target_key).or_insert(target);
+    }
+
+    let mut distance: BTreeMap<String, usize> = BTreeMap::from([(seed_key.clone(), 0)]);
+    let mut queue = VecDeque::from([seed_key.clone()]);
+    while let Some(key) = queue.pop_front() {
+        let d = distance[&key];
+        if d >= depth {
+            continue;
+        }
+        for next in adjacent.get(&key).into_iter().flatten() {
+            if !distance.contains_key(next) {
+                distance.insert(next.clone(), d + 1);
+                queue.push_back(next.clone());
+            }
+        }
+    }
+
+    let mut sub_edges = Vec::new();
+    for (edge_type, source_key, target_key) in edges {
+        if let (Some(s), Some(t)) = (distance.get(&source_key), distance.get(&target_key)) {
+            if (*s).min(*t) < depth {
+                sub_edges.push(edge_from(
+                    edge_type,
+                    &nodes[&source_key],
+                    &nodes[&target_key],
+                ));
+            }
+        }
+    }
+    let sub_nodes = distance
+        .into_keys()
+        .map(|key| {
+            if key == seed_key {
+                seed.clone()
+            } else {
+                nodes[&key].clone()
+            }
+        })
+        .collect();
+    (sub_nodes, sub_edges)
+}
+
+// a new graph holding just these nodes and edges
+pub fn graph_from<G: Graph>(nodes: Vec<Node>, edges: Vec<Edge>) -> G {
+    let mut graph = G::default();
+    for node in nodes {
+        graph.add_node(node.node_type, node.node_data);
+    }
+    for edge in edges {
+        graph.add_edge(edge);
+    }
+    graph
+}
@@ -135,7 +135,10 @@ pub fn link_api_nodes<G: Graph>(graph: &mut G) -> Result<()> {
     let endpoint_nodes = graph.find_nodes_by_type(NodeType::Endpoint);
 
     for node_data in request_nodes {
-        if let Some(normalized_path) = normalize_frontend_path(&node_data.name) {
+        if is_ws(&node_data) {
+            let event = normalize_ws_name(&node_data.name);
+            frontend_requests.push((node_data, event));
+        } else if let Some(normalized_path) = normalize_frontend_path(&node_data.name) {
             frontend_requests.push((node_data, normalized_path));
         }
     }
@@ -150,7 +153,12 @@ pub fn link_api_nodes<G: Graph>(graph: &mut G) -> Result<()> {
     let mut i = 0;
     for (req, req_path) in frontend_requests {
         for (endpoint, _) in &backend_endpoints {
-            if paths_match(&req_path, &endpoint.name) && verbs_match(&req, endpoint) {
+            let names_match = if is_ws(&req) {
+                ws_names_match(&req_path, &endpoint.name)
+            } else {
+                paths_match(&req_path, &endpoint.name)
+            };
+            if names_match && verbs_match(&req, endpoint) {
                 let edge = Edge::calls(NodeType::Request, &req, NodeType::Endpoint, endpoint);
                 graph.add_edge(edge);
                 i += 1;
@@ -238,6 +246,41 @@ pub fn verbs_match(req: &NodeData, endpoint: &NodeData) -> bool {
     }
 }
 
+pub fn is_ws(nd: &NodeData) -> bool {
+    nd.meta
+        .get("verb")
+        .map(|v| v.eq_ignore_ascii_case("WS"))
+        .unwrap_or(false)
+}
+
+// "ws://localhost:3000/ws?token=1" => "/ws", socket.io event names are kept as-is
+pub fn normalize_ws_name(name: &str) -> String {
+    let name = name.trim();
+    let Some(pos) = name.find("://") else {
+        return name.to_string();
+    };
+    let rest = &name[pos + 3..];
+    let path = match rest.find('/') {
+        Some(start) => &rest[start..],
+        None => "/",
+    };
+    let path = path.split(['?', '#']).next().unwrap_or("/");
+    if path.len() > 1 {
+        path.trim_end_matches('/').to_string()
+    } else {
+        path.to_string()
+    }
+}
+
+pub fn ws_names_match(frontend_name: &str, backend_name: &str) -> bool {
+    let backend_name = normalize_ws_name(backend_name);
+    if frontend_name.starts_with('/') && backend_name.starts_with('/') {
+        let frontend_path = normalize_frontend_path(frontend_name).unwrap_or_default();
+        return paths_match(&frontend_path, &backend_name);
+    }
+    frontend_name == backend_name
+}
+
 pub fn paths_match(frontend_path: &str, backend_path: &str) -> bool {
     let frontend_segments: Vec<&str> = frontend_path.split('/').filter(|s| !s.is_empty()).collect();
     let backend_segments: Vec<&str> = backend_path.split('/').filter(|s| !s.is_empty()).collect();
@@ -357,4 +400,51 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn test_normalize_ws_name() {
+        assert_eq!(normalize_ws_name("ws://localhost:3000/ws"), "/ws");
+        assert_eq!(normalize_ws_name("wss://example.com/cable/?t=1"), "/cable");
+        assert_eq!(normalize_ws_name("wss://example.com"), "/");
+        assert_eq!(normalize_ws_name("chat:message"), "chat:message");
+    }
+
+    #[test]
+    fn test_link_ws_nodes() -> Result<()> {
+        use crate::lang::graphs::ArrayGraph;
+        let mut graph = ArrayGraph::new(String::new(), Language::Typescript);
+
+        // socket.emit('chat:message') => socket.on('chat:message', ...)
+        let mut req1 = NodeData::name_file("chat:message", "src/components/Chat.tsx");
+        req1.add_verb("WS");
+        let mut endpoint1 = NodeData::name_file("chat:message", "server/socket.ts");
+        endpoint1.add_verb("WS");
+
+        // new WebSocket('ws://localhost:3000/ws') => axum .route("/ws", get(ws_handler))
+        let mut req2 = NodeData::name_file("ws://localhost:3000/ws", "src/hooks/useLive.ts");
+        req2.add_verb("WS");
+        let mut endpoint2 = NodeData::name_file("/ws", "src/routes.rs");
+        endpoint2.add_verb("WS");
+
+        // same path but a plain GET endpoint
+        let mut endpoint3 = NodeData::name_file("/ws", "src/other.rs");
+        endpoint3.add_verb("GET");
+
+        // different event
+        let mut req3 = NodeData::name_file("chat:typing", "src/components/Chat.tsx");
+        req3.add_verb("WS");
+
+        graph.nodes.push(Node::new(NodeType::Request, req1));
+        graph.nodes.push(Node::new(NodeType::Request, req2));
+        graph.nodes.push(Node::new(NodeType::Request, req3));
+        graph.nodes.push(Node::new(NodeType::Endpoint, endpoint1));
+        graph.nodes.push(Node::new(NodeType::Endpoint, endpoint2));
+        graph.nodes.push(Node::new(NodeType::Endpoint, endpoint3));
+
+        link_api_nodes(&mut graph)?;
+
+        assert_eq!(graph.edges.len(), 2);
+
+        Ok(())
+    }
 }
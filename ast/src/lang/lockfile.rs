@@ -0,0 +1,519 @@
+use serde_json::Value;
+use std::cmp::Ordering;
+use std::collections::HashMap;
+
+// a package pinned by a lockfile (Cargo.lock, package-lock.json, yarn.lock,
+// pnpm-lock.yaml, go.sum, Gemfile.lock, poetry.lock)
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct LockedPackage {
+    pub name: String,
+    pub version: String,
+    pub dependencies: Vec<String>,
+    pub line: usize,
+}
+
+pub fn parse_lockfile(file: &str, code: &str) -> Vec<LockedPackage> {
+    let name = file.rsplit('/').next().unwrap_or(file);
+    let pkgs = match name {
+        "Cargo.lock" => parse_toml_lock(code, true),
+        "poetry.lock" => parse_toml_lock(code, false),
+        "package-lock.json" => parse_package_lock(code),
+        "yarn.lock" => parse_yarn_lock(code),
+        "pnpm-lock.yaml" => parse_pnpm_lock(code),
+        "go.sum" => parse_go_sum(code),
+        "Gemfile.lock" => parse_gemfile_lock(code),
+        _ => Vec::new(),
+    };
+    dedup_by_name(pkgs)
+}
+
+// the first entry wins (hoisted / top-level packages come first). entries
+// without a version are workspace links or metadata, not packages
+fn dedup_by_name(pkgs: Vec<LockedPackage>) -> Vec<LockedPackage> {
+    let mut seen = std::collections::HashSet::new();
+    pkgs.into_iter()
+        .filter(|p| !p.name.is_empty() && !p.version.is_empty())
+        .filter(|p| seen.insert(p.name.clone()))
+        .collect()
+}
+
+fn unquote(s: &str) -> &str {
+    s.trim().trim_matches('"').trim_matches('\'')
+}
+
+// Cargo.lock and poetry.lock: [[package]] tables with name/version,
+// plus `dependencies = [...]` (cargo) or a [package.dependencies] table (poetry).
+// cargo workspace members have no `source`, they are not dependencies
+fn parse_toml_lock(code: &str, skip_local: bool) -> Vec<LockedPackage> {
+    let mut res = Vec::new();
+    let mut current: Option<LockedPackage> = None;
+    let mut has_source = false;
+    let mut in_dep_array = false;
+    let mut in_dep_table = false;
+    for (i, raw) in code.lines().enumerate() {
+        let line = raw.trim();
+        if line == "[[package]]" {
+            if !skip_local || has_source {
+                res.extend(current.take());
+            }
+            has_source = false;
+            current = Some(LockedPackage {
+                line: i,
+                ..Default::default()
+            });
+            in_dep_array = false;
+            in_dep_table = false;
+            continue;
+        }
+        let Some(pkg) = current.as_mut() else {
+            continue;
+        };
+        if line.starts_with('[') && !in_dep_array {
+            in_dep_table = line == "[package.dependencies]";
+            continue;
+        }
+        if in_dep_array {
+            if line.starts_with(']') {
+                in_dep_array = false;
+                continue;
+            }
+            // "serde", "serde 1.0.0" or "serde 1.0.0 (registry+...)"
+            let dep = unquote(line.trim_end_matches(','));
+            if let Some(name) = dep.split_whitespace().next() {
+                pkg.dependencies.push(name.to_string());
+            }
+            continue;
+        }
+        let Some((key, value)) = line.split_once('=') else {
+            continue;
+        };
+        let (key, value) = (key.trim(), value.trim());
+        if in_dep_table {
+            pkg.dependencies.push(unquote(key).to_string());
+        } else if key == "name" {
+            pkg.name = unquote(value).to_string();
+        } else if key == "version" {
+            pkg.version = unquote(value).to_string();
+        } else if key == "source" {
+            has_source = true;
+        } else if key == "dependencies" && value.starts_with('[') {
+            let inline = value.trim_start_matches('[');
+            for dep in inline.trim_end_matches(']').split(',') {
+                if let Some(name) = unquote(dep).split_whitespace().next() {
+                    pkg.dependencies.push(name.to_string());
+                }
+            }
+            in_dep_array = !value.ends_with(']');
+        }
+    }
+    if !skip_local || has_source {
+        res.extend(current);
+    }
+    res
+}
+
+fn line_of(code: &str, needle: &str) -> usize {
+    code.find(needle)
+        .map(|pos| code[..pos].matches('\n').count())
+        .unwrap_or(0)
+}
+
+fn json_keys(v: Option<&Value>) -> Vec<String> {
+    v.and_then(|d| d.as_object())
+        .map(|d| d.keys().cloned().collect())
+        .unwrap_or_default()
+}
+
+// lockfileVersion 2/3 "packages" map, falling back to the v1 "dependencies" tree
+fn parse_package_lock(code: &str) -> Vec<LockedPackage> {
+    let Ok(json) = serde_json::from_str::<Value>(code) else {
+        return Vec::new();
+    };
+    let mut res = Vec::new();
+    if let Some(packages) = json.get("packages").and_then(|p| p.as_object()) {
+        let mut entries: Vec<(&String, &Value)> = packages
+            .iter()
+            .filter(|(k, _)| k.contains("node_modules/"))
+            .collect();
+        // top-level node_modules/<name> before nested copies
+        entries.sort_by_key(|(k, _)| k.matches("node_modules/").count());
+        for (key, pkg) in entries {
+            let name = key.rsplit("node_modules/").next().unwrap_or(key);
+            let mut dependencies = json_keys(pkg.get("dependencies"));
+            dependencies.extend(json_keys(pkg.get("optionalDependencies")));
+            res.push(LockedPackage {
+                name: name.to_string(),
+                version: pkg["version"].as_str().unwrap_or_default().to_string(),
+                dependencies,
+                line: line_of(code, &format!("\"{}\"", key)),
+            });
+        }
+    } else if let Some(deps) = json.get("dependencies").and_then(|d| d.as_object()) {
+        for (name, pkg) in deps {
+            res.push(LockedPackage {
+                name: name.to_string(),
+                version: pkg["version"].as_str().unwrap_or_default().to_string(),
+                dependencies: json_keys(pkg.get("requires")),
+                line: line_of(code, &format!("\"{}\": {{", name)),
+            });
+        }
+    }
+    res
+}
+
+// "pkg@^1.0.0", "@scope/pkg@npm:^2" -> pkg, @scope/pkg
+fn strip_npm_spec(spec: &str) -> &str {
+    let spec = unquote(spec);
+    // a leading @ is the scope, not the version separator
+    let rest = spec.strip_prefix('@').unwrap_or(spec);
+    match rest.find('@') {
+        Some(i) => &spec[..spec.len() - rest.len() + i],
+        None => spec,
+    }
+}
+
+// classic (v1) and berry yarn.lock
+fn parse_yarn_lock(code: &str) -> Vec<LockedPackage> {
+    let mut res = Vec::new();
+    let mut current: Option<LockedPackage> = None;
+    let mut in_deps = false;
+    for (i, raw) in code.lines().enumerate() {
+        if raw.trim().is_empty() || raw.trim_start().starts_with('#') {
+            continue;
+        }
+        let indent = raw.len() - raw.trim_start().len();
+        let line = raw.trim();
+        if indent == 0 && line.ends_with(':') {
+            res.extend(current.take());
+            let first = line.trim_end_matches(':').split(',').next().unwrap_or("");
+            // berry lists the workspaces themselves as "name@workspace:."
+            let name = if first.contains("@workspace:") {
+                ""
+            } else {
+                strip_npm_spec(first)
+            };
+            current = Some(LockedPackage {
+                name: name.to_string(),
+                line: i,
+                ..Default::default()
+            });
+            in_deps = false;
+            continue;
+        }
+        let Some(pkg) = current.as_mut() else {
+            continue;
+        };
+        if indent == 2 {
+            in_deps = line == "dependencies:" || line == "optionalDependencies:";
+            if let Some(v) = line.strip_prefix("version") {
+                pkg.version = unquote(v.trim_start_matches(':')).to_string();
+            }
+        } else if indent > 2 && in_deps {
+            let dep = line.split_whitespace().next().unwrap_or("");
+            pkg.dependencies
+                .push(unquote(dep.trim_end_matches(':')).to_string());
+        }
+    }
+    res.extend(current);
+    res
+}
+
+// "/@scope/pkg@1.2.3(peer@1):", "pkg@1.2.3:" or the older "/pkg/1.2.3:"
+fn split_pnpm_key(key: &str) -> (String, String) {
+    let key = unquote(key.trim_end_matches(':'));
+    let key = key.trim_start_matches('/');
+    let key = key.split('(').next().unwrap_or(key);
+    let rest = key.strip_prefix('@').unwrap_or(key);
+    if let Some(i) = rest.find('@') {
+        let (name, version) = key.split_at(key.len() - rest.len() + i);
+        return (name.to_string(), version[1..].to_string());
+    }
+    match key.rsplit_once('/') {
+        Some((name, version)) => (name.to_string(), version.to_string()),
+        None => (key.to_string(), String::new()),
+    }
+}
+
+fn parse_pnpm_lock(code: &str) -> Vec<LockedPackage> {
+    let mut res: Vec<LockedPackage> = Vec::new();
+    let mut index: HashMap<String, usize> = HashMap::new();
+    let mut in_section = false;
+    let mut current: Option<usize> = None;
+    let mut in_deps = false;
+    for (i, raw) in code.lines().enumerate() {
+        if raw.trim().is_empty() {
+            continue;
+        }
+        let indent = raw.len() - raw.trim_start().len();
+        let line = raw.trim();
+        if indent == 0 {
+            // v9 keeps dependencies under "snapshots", older versions under "packages"
+            in_section = line == "packages:" || line == "snapshots:";
+            current = None;
+            continue;
+        }
+        if !in_section {
+            continue;
+        }
+        if indent == 2 && line.ends_with(':') {
+            let (name, version) = split_pnpm_key(line);
+            let idx = *index.entry(name.clone()).or_insert_with(|| {
+                res.push(LockedPackage {
+                    name,
+                    version,
+                    line: i,
+                    ..Default::default()
+                });
+                res.len() - 1
+            });
+            current = Some(idx);
+            in_deps = false;
+        } else if indent == 4 {
+            in_deps = line == "dependencies:" || line == "optionalDependencies:";
+        } else if indent > 4 && in_deps {
+            if let (Some(idx), Some((dep, _))) = (current, line.split_once(':')) {
+                let dep = unquote(dep).to_string();
+                if !res[idx].dependencies.contains(&dep) {
+                    res[idx].dependencies.push(dep);
+                }
+            }
+        }
+    }
+    res
+}
+
+// go.sum has no dependency graph, only the modules (and versions) in the build list
+fn parse_go_sum(code: &str) -> Vec<LockedPackage> {
+    let mut res: Vec<LockedPackage> = Vec::new();
+    let mut index: HashMap<&str, usize> = HashMap::new();
+    for (i, line) in code.lines().enumerate() {
+        let mut parts = line.split_whitespace();
+        let (Some(module), Some(version)) = (parts.next(), parts.next()) else {
+            continue;
+        };
+        if version.ends_with("/go.mod") {
+            continue;
+        }
+        // go.sum can keep old versions of a module around, the build uses the newest
+        if let Some(&idx) = index.get(module) {
+            if go_version_cmp(version, &res[idx].version).is_gt() {
+                res[idx].version = version.to_string();
+            }
+            continue;
+        }
+        index.insert(module, res.len());
+        res.push(LockedPackage {
+            name: module.to_string(),
+            version: version.to_string(),
+            dependencies: Vec::new(),
+            line: i,
+        });
+    }
+    res
+}
+
+// semver order for go versions: "v1.10.0" > "v1.9.0" > "v1.9.0-rc.1". pseudo-versions
+// ("v0.0.0-20230101000000-abcdef") are pre-releases that sort by their timestamp
+fn go_version_cmp(a: &str, b: &str) -> Ordering {
+    fn split(v: &str) -> (Vec<u64>, Option<&str>) {
+        let v = v.trim_start_matches('v');
+        let v = v.split('+').next().unwrap_or(v);
+        let (core, pre) = match v.split_once('-') {
+            Some((core, pre)) => (core, Some(pre)),
+            None => (v, None),
+        };
+        let nums = core.split('.').map(|n| n.parse().unwrap_or(0)).collect();
+        (nums, pre)
+    }
+    let (a_nums, a_pre) = split(a);
+    let (b_nums, b_pre) = split(b);
+    a_nums.cmp(&b_nums).then_with(|| match (a_pre, b_pre) {
+        (None, None) => Ordering::Equal,
+        (None, Some(_)) => Ordering::Greater,
+        (Some(_), None) => Ordering::Less,
+        (Some(a), Some(b)) => a.cmp(b),
+    })
+}
+
+// specs are indented 4 spaces ("rails (7.0.4)"), their dependencies 6 spaces
+fn parse_gemfile_lock(code: &str) -> Vec<LockedPackage> {
+    let mut res = Vec::new();
+    let mut current: Option<LockedPackage> = None;
+    let mut in_specs = false;
+    for (i, raw) in code.lines().enumerate() {
+        let indent = raw.len() - raw.trim_start().len();
+        let line = raw.trim();
+        if indent == 0 {
+            res.extend(current.take());
+            in_specs = false;
+            continue;
+        }
+        if indent == 2 {
+            in_specs = line == "specs:";
+            continue;
+        }
+        if !in_specs {
+            continue;
+        }
+        let name = line.split_whitespace().next().unwrap_or("").to_string();
+        if indent == 4 {
+            res.extend(current.take());
+            let version = line
+                .split_once('(')
+                .map(|(_, v)| v.trim_end_matches(')').to_string())
+                .unwrap_or_default();
+            current = Some(LockedPackage {
+                name,
+                version,
+                dependencies: Vec::new(),
+                line: i,
+            });
+        } else if let Some(pkg) = current.as_mut() {
+            pkg.dependencies.push(name);
+        }
+    }
+    res.extend(current);
+    res
+}
+
+// a lockfile resolves the manifests in its own directory and below
+pub fn lock_covers(lock_file: &str, manifest: &str) -> bool {
+    let lock_dir = lock_file.rsplit_once('/').map(|(d, _)| d).unwrap_or("");
+    let manifest_dir = manifest.rsplit_once('/').map(|(d, _)| d).unwrap_or("");
+    lock_dir.is_empty()
+        || manifest_dir == lock_dir
+        || manifest_dir.starts_with(&format!("{}/", lock_dir))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn find<'a>(pkgs: &'a [LockedPackage], name: &str) -> &'a LockedPackage {
+        pkgs.iter().find(|p| p.name == name).expect(name)
+    }
+
+    #[test]
+    fn test_parse_cargo_lock() {
+        let code = r#"version = 3
+
+[[package]]
+name = "axum"
+version = "0.7.5"
+source = "registry+https://github.com/rust-lang/crates.io-index"
+dependencies = [
+ "axum-core",
+ "bytes 1.6.0",
+]
+
+[[package]]
+name = "bytes"
+version = "1.6.0"
+source = "registry+https://github.com/rust-lang/crates.io-index"
+
+[[package]]
+name = "my-app"
+version = "0.1.0"
+dependencies = [
+ "axum",
+]
+"#;
+        let pkgs = parse_lockfile("x/Cargo.lock", code);
+        assert_eq!(pkgs.len(), 2);
+        let axum = find(&pkgs, "axum");
+        assert_eq!(axum.version, "0.7.5");
+        assert_eq!(axum.dependencies, vec!["axum-core", "bytes"]);
+        assert_eq!(axum.line, 2);
+
+        assert!(lock_covers("app/Cargo.lock", "app/crates/a/Cargo.toml"));
+        assert!(!lock_covers("app/Cargo.lock", "other/Cargo.toml"));
+    }
+
+    #[test]
+    fn test_parse_package_lock() {
+        let code = r#"{
+  "lockfileVersion": 3,
+  "packages": {
+    "": { "dependencies": { "express": "^4.18.0" } },
+    "node_modules/express": {
+      "version": "4.18.2",
+      "dependencies": { "body-parser": "1.20.1" }
+    },
+    "node_modules/body-parser": { "version": "1.20.1" },
+    "node_modules/express/node_modules/body-parser": { "version": "1.0.0" }
+  }
+}"#;
+        let pkgs = parse_lockfile("package-lock.json", code);
+        assert_eq!(pkgs.len(), 2);
+        assert_eq!(find(&pkgs, "express").dependencies, vec!["body-parser"]);
+        assert_eq!(find(&pkgs, "body-parser").version, "1.20.1");
+    }
+
+    #[test]
+    fn test_parse_yarn_and_pnpm_lock() {
+        let yarn = r#"# yarn lockfile v1
+
+"@babel/core@^7.0.0", "@babel/core@^7.1.0":
+  version "7.12.3"
+  dependencies:
+    "@babel/types" "^7.12.1"
+    debug "^4.1.0"
+
+debug@^4.1.0:
+  version "4.3.4"
+"#;
+        let pkgs = parse_lockfile("yarn.lock", yarn);
+        let core = find(&pkgs, "@babel/core");
+        assert_eq!(core.version, "7.12.3");
+        assert_eq!(core.dependencies, vec!["@babel/types", "debug"]);
+        assert_eq!(find(&pkgs, "debug").version, "4.3.4");
+
+        let pnpm = r#"lockfileVersion: '9.0'
+
+packages:
+
+  '@scope/pkg@1.2.3':
+    resolution: {integrity: sha512-x}
+
+  lodash@4.17.21:
+    resolution: {integrity: sha512-y}
+
+snapshots:
+
+  '@scope/pkg@1.2.3(react@18.2.0)':
+    dependencies:
+      lodash: 4.17.21
+"#;
+        let pkgs = parse_lockfile("pnpm-lock.yaml", pnpm);
+        assert_eq!(pkgs.len(), 2);
+        let scoped = find(&pkgs, "@scope/pkg");
+        assert_eq!(scoped.version, "1.2.3");
+        assert_eq!(scoped.dependencies, vec!["lodash"]);
+
+        // names are not always ascii
+        assert_eq!(strip_npm_spec("\"ü@^1.0.0\""), "ü");
+        assert_eq!(split_pnpm_key("/ü@1.0.0:"), ("ü".into(), "1.0.0".into()));
+    }
+
+    #[test]
+    fn test_parse_go_sum_gemfile_and_poetry_lock() {
+        let go_sum = "github.com/gin-gonic/gin v1.10.0 h1:abc=\ngithub.com/gin-gonic/gin v1.10.0/go.mod h1:def=\ngithub.com/gin-gonic/gin v1.9.0 h1:ghi=\ngithub.com/only/mod v1.0.0/go.mod h1:x=\n";
+        let pkgs = parse_lockfile("go.sum", go_sum);
+        assert_eq!(pkgs.len(), 1);
+        // go.sum is sorted as text, so v1.9.0 comes after the newer v1.10.0
+        assert_eq!(find(&pkgs, "github.com/gin-gonic/gin").version, "v1.10.0");
+
+        let gemfile = "GEM\n  remote: https://rubygems.org/\n  specs:\n    actioncable (7.0.4)\n      actionpack (= 7.0.4)\n      nio4r (~> 2.0)\n    nio4r (2.5.8)\n\nPLATFORMS\n  ruby\n";
+        let pkgs = parse_lockfile("Gemfile.lock", gemfile);
+        assert_eq!(pkgs.len(), 2);
+        let cable = find(&pkgs, "actioncable");
+        assert_eq!(cable.version, "7.0.4");
+        assert_eq!(cable.dependencies, vec!["actionpack", "nio4r"]);
+
+        let poetry = "[[package]]\nname = \"flask\"\nversion = \"2.3.2\"\n\n[package.dependencies]\nclick = \">=8.1.3\"\nWerkzeug = {version = \">=2.3.3\"}\n\n[package.extras]\ndotenv = [\"python-dotenv\"]\n";
+        let pkgs = parse_lockfile("poetry.lock", poetry);
+        let flask = find(&pkgs, "flask");
+        assert_eq!(flask.version, "2.3.2");
+        assert_eq!(flask.dependencies, vec!["click", "Werkzeug"]);
+    }
+}
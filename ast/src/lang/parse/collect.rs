@@ -1,8 +1,10 @@
-use super::utils::trim_quotes;
+use super::modules::ModuleIndex;
+use super::utils::{contains_identifier, imports_library, trim_quotes};
 use crate::lang::{graphs::Graph, *};
 use lsp::{Cmd as LspCmd, Position, Res as LspRes};
 use shared::error::{Error, Result};
 use streaming_iterator::StreamingIterator;
+use std::collections::HashSet;
 use tree_sitter::Node as TreeNode;
 impl Lang {
     pub fn collect<G: Graph>(
@@ -30,10 +32,17 @@ impl Lang {
                     .collect(),
                 NodeType::DataModel => vec![self.format_data_model(&m, code, file, q)?],
                 NodeType::Var => self.format_variables(&m, code, file, q)?,
+                NodeType::Enum | NodeType::Interface | NodeType::TypeAlias => {
+                    vec![self.format_type_definition(m, code, file, q)?]
+                }
+                NodeType::Const => self.format_variables(m, code, file, q)?,
                 _ => return Err(Error::Custom(format!("collect: {nt:?} not implemented"))),
             };
             res.extend(another);
         }
+        if !matches!(nt, NodeType::Library | NodeType::Import | NodeType::Instance | NodeType::Request) {
+            self.attach_docs(code, res.iter_mut())?;
+        }
         Ok(res)
     }
 
@@ -55,6 +64,7 @@ impl Lang {
                 res.push((cls, edges));
             }
         }
+        self.attach_docs(code, res.iter_mut().map(|(cls, _)| cls))?;
         Ok(res)
     }
     pub fn collect_implements_edges<G: Graph>(
@@ -119,6 +129,7 @@ impl Lang {
                 res.extend(endys);
             }
         }
+        self.attach_docs(code, res.iter_mut().map(|(nd, _)| nd))?;
         Ok(res)
     }
     pub fn collect_functions<G: Graph>(
@@ -132,9 +143,10 @@ impl Lang {
         let tree = self.lang.parse(&code, &NodeType::Function)?;
         let mut cursor = QueryCursor::new();
         let mut matches = cursor.matches(q, tree.root_node(), code.as_bytes());
+        let types = self.type_index(file, graph);
         let mut res = Vec::new();
         while let Some(m) = matches.next() {
-            if let Some(ff) = self.format_function(&m, code, file, &q, graph, lsp_tx)? {
+            if let Some(ff) = self.format_function(&m, code, file, &q, graph, lsp_tx, &types)? {
                 res.push(ff);
             }
         }
@@ -317,6 +329,7 @@ impl Lang {
         file: &str,
         graph: &G,
         lsp_tx: &Option<CmdSender>,
+        modules: &ModuleIndex,
     ) -> Result<Vec<Edge>> {
         if let Some(lsp) = lsp_tx {
             return self.collect_import_edges_with_lsp(code, file, graph, lsp);
@@ -343,6 +356,7 @@ impl Lang {
                 let resolved_path = self.lang.resolve_import_path(&source_path, file);
 
                 for import_name in &import_names {
+                    let mut found = false;
                     for nt in [
                         NodeType::Function,
                         NodeType::Class,
@@ -369,10 +383,23 @@ impl Lang {
                                 .unwrap_or_else(|| NodeData::in_file(file));
                             if let Some(target) = target {
                                 edges.push(Edge::file_imports(&file_node, nt, &target));
+                                found = true;
                                 break;
                             }
                         }
                     }
+                    if !found {
+                        let edge = self.module_import_edge(
+                            graph,
+                            modules,
+                            &source_path,
+                            import_name,
+                            file,
+                        );
+                        if let Some(edge) = edge.filter(|e| !edges.contains(e)) {
+                            edges.push(edge);
+                        }
+                    }
                 }
             }
         }
@@ -452,6 +479,81 @@ impl Lang {
 
         Ok(edges)
     }
+    // Function -> Library edges for functions using a symbol imported from a library
+    pub fn collect_library_uses<G: Graph>(
+        &self,
+        q: &Query,
+        code: &str,
+        file: &str,
+        libs: &[NodeData],
+        graph: &G,
+    ) -> Result<Vec<Edge>> {
+        const IMPORT_WORDS: [&str; 4] = ["require", "require_relative", "import", "include"];
+        let tree = self.lang.parse(code, &NodeType::Import)?;
+        let mut cursor = QueryCursor::new();
+        let mut matches = cursor.matches(q, tree.root_node(), code.as_bytes());
+        let mut provided: Vec<(NodeData, HashSet<String>)> = Vec::new();
+
+        while let Some(m) = matches.next() {
+            Self::loop_captures_multi(q, m, code, |body, node, o| {
+                if o != IMPORTS {
+                    return Ok(());
+                }
+                let mut found: Vec<&NodeData> = Vec::new();
+                for lib in libs {
+                    if imports_library(&body, &lib.name)
+                        && !found.iter().any(|l| l.name == lib.name)
+                    {
+                        found.push(lib);
+                    }
+                }
+                let idents: HashSet<String> = self
+                    .find_identifier_names(node, code)
+                    .into_iter()
+                    .filter(|i| !IMPORT_WORDS.contains(&i.as_str()))
+                    .collect();
+                for lib in &found {
+                    // one statement importing several libraries (go import blocks):
+                    // only keep the names on the library's own line
+                    let mut symbols: HashSet<String> = if found.len() == 1 {
+                        idents.clone()
+                    } else {
+                        body.lines()
+                            .filter(|l| imports_library(l, &lib.name))
+                            .flat_map(|l| idents.iter().filter(|i| contains_identifier(l, i)))
+                            .cloned()
+                            .collect()
+                    };
+                    if symbols.is_empty() {
+                        // go packages are referenced by the last path segment
+                        let last = lib.name.rsplit('/').next().unwrap_or(&lib.name);
+                        symbols.insert(last.replace('-', "_"));
+                    }
+                    match provided.iter_mut().find(|(l, _)| l.name == lib.name) {
+                        Some((_, syms)) => syms.extend(symbols),
+                        None => provided.push(((*lib).clone(), symbols)),
+                    }
+                }
+                Ok(())
+            })?;
+        }
+        if provided.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let mut edges = Vec::new();
+        for func in graph.find_nodes_by_file_ends_with(NodeType::Function, file) {
+            if func.file != file {
+                continue;
+            }
+            for (lib, symbols) in &provided {
+                if symbols.iter().any(|s| contains_identifier(&func.body, s)) {
+                    edges.push(Edge::function_uses(&func, NodeType::Library, lib));
+                }
+            }
+        }
+        Ok(edges)
+    }
     pub fn collect_var_call_in_function<G: Graph>(
         &self,
         func: &NodeData,
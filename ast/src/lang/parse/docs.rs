@@ -0,0 +1,335 @@
+use crate::lang::*;
+use shared::error::Result;
+use std::collections::BTreeMap;
+use streaming_iterator::StreamingIterator;
+
+struct DocComment {
+    start: usize,
+    end: usize,
+    text: String,
+}
+
+impl Lang {
+    // docs from a node's own docstring, else the comment block right above it.
+    // structured tags (@param, :raises:, # Errors ...) are copied into meta
+    pub fn attach_docs<'a>(
+        &self,
+        code: &str,
+        nodes: impl IntoIterator<Item = &'a mut NodeData>,
+    ) -> Result<()> {
+        let mut nodes = nodes.into_iter().peekable();
+        if nodes.peek().is_none() {
+            return Ok(());
+        }
+        let mut comments = self.collect_doc_comments(code)?;
+        comments.sort_by_key(|c| c.end);
+        let docstrings = self.collect_docstrings(code)?;
+        let lines: Vec<&str> = code.lines().collect();
+        for nd in nodes {
+            if nd.docs.is_none() {
+                let docs = match docstrings.iter().find(|(row, _)| *row == nd.start) {
+                    Some((_, text)) => self.clean_and_combine_comments(std::slice::from_ref(text)),
+                    None => self.preceding_comments(&comments, &lines, nd.start),
+                };
+                if !docs.trim().is_empty() {
+                    nd.docs = Some(docs);
+                }
+            }
+            if let Some(docs) = &nd.docs {
+                nd.meta.extend(doc_tags(docs));
+            }
+        }
+        Ok(())
+    }
+    // crate/module docs, module docstrings and package comments for a whole file
+    pub fn module_docs(&self, code: &str) -> Result<Option<String>> {
+        let Some(mq) = self.lang.module_doc_query() else {
+            return Ok(None);
+        };
+        let q = self.q(&mq, &NodeType::Function);
+        let tree = self.lang.parse(code, &NodeType::Function)?;
+        let mut cursor = QueryCursor::new();
+        let mut matches = cursor.matches(&q, tree.root_node(), code.as_bytes());
+        let mut found: Vec<DocComment> = Vec::new();
+        while let Some(m) = matches.next() {
+            for cap in m.captures.iter() {
+                if q.capture_names()[cap.index as usize] != MODULE_DOC {
+                    continue;
+                }
+                let start = cap.node.start_position().row;
+                if found.iter().any(|c| c.start == start) {
+                    continue;
+                }
+                found.push(DocComment {
+                    start,
+                    end: end_row(&cap.node),
+                    text: cap.node.utf8_text(code.as_bytes())?.to_string(),
+                });
+            }
+        }
+        found.sort_by_key(|c| c.start);
+        // only the run of comments touching the last one (skips license headers)
+        let mut first = found.len();
+        while first > 0 && (first == found.len() || found[first - 1].end + 1 >= found[first].start) {
+            first -= 1;
+        }
+        let texts: Vec<String> = found.drain(first..).map(|c| c.text).collect();
+        let docs = self.clean_and_combine_comments(&texts);
+        Ok((!docs.is_empty()).then_some(docs))
+    }
+    fn collect_doc_comments(&self, code: &str) -> Result<Vec<DocComment>> {
+        let mut out = Vec::new();
+        let Some(cq) = self.lang.comment_query() else {
+            return Ok(out);
+        };
+        let comment_q = self.q(&cq, &NodeType::Function);
+        let tree = self.lang.parse(code, &NodeType::Function)?;
+        let mut cursor = QueryCursor::new();
+        let mut matches = cursor.matches(&comment_q, tree.root_node(), code.as_bytes());
+        while let Some(m) = matches.next() {
+            for cap in m.captures.iter() {
+                let name = &comment_q.capture_names()[cap.index as usize];
+                if *name != FUNCTION_COMMENT {
+                    continue;
+                }
+                if let Ok(txt) = cap.node.utf8_text(code.as_bytes()) {
+                    // inner docs belong to the enclosing module, not the next item
+                    if txt.starts_with("//!") || txt.starts_with("/*!") {
+                        continue;
+                    }
+                    out.push(DocComment {
+                        start: cap.node.start_position().row,
+                        end: end_row(&cap.node),
+                        text: txt.to_string(),
+                    });
+                }
+            }
+        }
+        Ok(out)
+    }
+    // (row of the documented definition, docstring)
+    fn collect_docstrings(&self, code: &str) -> Result<Vec<(usize, String)>> {
+        let mut out = Vec::new();
+        let Some(dq) = self.lang.docstring_query() else {
+            return Ok(out);
+        };
+        let q = self.q(&dq, &NodeType::Function);
+        let tree = self.lang.parse(code, &NodeType::Function)?;
+        let mut cursor = QueryCursor::new();
+        let mut matches = cursor.matches(&q, tree.root_node(), code.as_bytes());
+        while let Some(m) = matches.next() {
+            let mut owner = None;
+            let mut docstring = None;
+            Self::loop_captures(&q, m, code, |body, node, o| {
+                if o == DOCSTRING_OWNER {
+                    owner = Some(node.start_position().row);
+                } else if o == DOCSTRING {
+                    docstring = Some(body);
+                }
+                Ok(())
+            })?;
+            if let (Some(owner), Some(docstring)) = (owner, docstring) {
+                out.push((owner, docstring));
+            }
+        }
+        Ok(out)
+    }
+    fn preceding_comments(&self, comments: &[DocComment], lines: &[&str], start: usize) -> String {
+        // look past attributes and decorators between the comment and the definition
+        let mut start = start;
+        while start > 0 && lines.get(start - 1).is_some_and(|l| is_attribute(l)) {
+            start -= 1;
+        }
+        let mut block: Vec<&DocComment> = Vec::new();
+        for c in comments.iter().rev() {
+            if c.end >= start {
+                continue;
+            }
+            let touches = match block.last() {
+                None => start - c.end <= 2,
+                Some(last) => last.start > 0 && last.start.saturating_sub(c.end) <= 2,
+            };
+            if !touches {
+                break;
+            }
+            block.push(c);
+        }
+        block.sort_by_key(|c| c.start);
+        let texts: Vec<String> = block.iter().map(|c| c.text.clone()).collect();
+        self.clean_and_combine_comments(&texts)
+    }
+}
+
+// line comments that swallow their newline end at column 0 of the next row
+fn end_row(node: &TreeNode) -> usize {
+    let end = node.end_position();
+    if end.column == 0 && end.row > node.start_position().row {
+        end.row - 1
+    } else {
+        end.row
+    }
+}
+
+fn is_attribute(line: &str) -> bool {
+    let line = line.trim_start();
+    line.starts_with("#[") || line.starts_with('@')
+}
+
+// structured tags from cleaned docs, as `doc_<tag>` meta entries
+pub fn doc_tags(docs: &str) -> BTreeMap<String, String> {
+    let mut tags: BTreeMap<&str, Vec<String>> = BTreeMap::new();
+    // (tag, whether following lines are separate entries)
+    let mut current: Option<(&str, bool)> = None;
+    for line in docs.lines().map(str::trim) {
+        if line.is_empty() || line.chars().all(|c| c == '-' || c == '=') {
+            continue;
+        }
+        if let Some((tag, entry)) = tag_line(line) {
+            current = (!tag.is_empty()).then_some((tag, false));
+            if !tag.is_empty() && !entry.is_empty() {
+                tags.entry(tag).or_default().push(entry);
+            }
+            continue;
+        }
+        if let Some(tag) = section_header(line) {
+            current = (!tag.is_empty()).then_some((tag, true));
+            continue;
+        }
+        match current {
+            Some((tag, true)) => tags.entry(tag).or_default().push(line.to_string()),
+            Some((tag, false)) => {
+                let entries = tags.entry(tag).or_default();
+                match entries.last_mut() {
+                    Some(last) => {
+                        last.push(' ');
+                        last.push_str(line);
+                    }
+                    None => entries.push(line.to_string()),
+                }
+            }
+            None => {}
+        }
+    }
+    tags.into_iter()
+        .filter(|(_, entries)| !entries.is_empty())
+        .map(|(tag, entries)| (format!("doc_{tag}"), entries.join("\n")))
+        .collect()
+}
+
+// `@param x desc` (jsdoc/javadoc) or `:param x: desc` (sphinx). unknown tags give ""
+fn tag_line(line: &str) -> Option<(&'static str, String)> {
+    if let Some(rest) = line.strip_prefix('@') {
+        let (name, rest) = rest.split_once(char::is_whitespace).unwrap_or((rest, ""));
+        let rest = rest.trim();
+        let tag = match name {
+            "param" | "arg" | "argument" => {
+                return Some(("params", named_entry(strip_type(rest))));
+            }
+            "returns" | "return" => "returns",
+            "throws" | "exception" | "raises" => "raises",
+            "deprecated" => "deprecated",
+            "example" => "examples",
+            _ => "",
+        };
+        return Some((tag, strip_type(rest).to_string()));
+    }
+    let rest = line.strip_prefix(':')?;
+    let (field, desc) = rest.split_once(':')?;
+    let mut words = field.split_whitespace();
+    let kind = words.next()?;
+    let target = words.last();
+    let desc = desc.trim();
+    let tag = match kind {
+        "param" | "parameter" | "arg" | "argument" | "key" | "keyword" => "params",
+        "returns" | "return" => "returns",
+        "raises" | "raise" | "except" | "exception" => "raises",
+        _ => return Some(("", String::new())),
+    };
+    let entry = match target {
+        Some(target) if desc.is_empty() => target.to_string(),
+        Some(target) => format!("{target}: {desc}"),
+        None => desc.to_string(),
+    };
+    Some((tag, entry))
+}
+
+// `# Errors` (rustdoc) or `Raises:` (google style). unknown headings give ""
+fn section_header(line: &str) -> Option<&'static str> {
+    let title = match line.strip_prefix('#') {
+        Some(heading) => heading.trim_start_matches('#').trim(),
+        None => line.strip_suffix(':')?.trim(),
+    };
+    let tag = match title.to_lowercase().as_str() {
+        "arguments" | "args" | "parameters" | "params" => "params",
+        "returns" | "return" | "yields" => "returns",
+        "raises" | "throws" | "exceptions" => "raises",
+        "errors" => "errors",
+        "panics" => "panics",
+        "safety" => "safety",
+        "examples" | "example" => "examples",
+        _ if line.starts_with('#') => "",
+        _ => return None,
+    };
+    Some(tag)
+}
+
+// `{string} name` -> `name`
+fn strip_type(s: &str) -> &str {
+    match s.strip_prefix('{').and_then(|r| r.split_once('}')) {
+        Some((_, rest)) => rest.trim(),
+        None => s,
+    }
+}
+
+// `name - desc` / `name desc` -> `name: desc`
+fn named_entry(s: &str) -> String {
+    match s.split_once(char::is_whitespace) {
+        Some((name, desc)) => {
+            let desc = desc.trim().trim_start_matches('-').trim();
+            if desc.is_empty() {
+                name.to_string()
+            } else {
+                format!("{name}: {desc}")
+            }
+        }
+        None => s.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_jsdoc_tags() {
+        let docs = "Create a user.\n@param {string} name - the user name\n@param age years\nmust be positive\n@returns the new user\n@throws Error when taken\n@since 1.0";
+        let tags = doc_tags(docs);
+        assert_eq!(tags["doc_params"], "name: the user name\nage: years must be positive");
+        assert_eq!(tags["doc_returns"], "the new user");
+        assert_eq!(tags["doc_raises"], "Error when taken");
+        assert_eq!(tags.len(), 3);
+    }
+
+    #[test]
+    fn test_sphinx_and_google_tags() {
+        let docs = "Load a file.\n:param str path: where to read\n:rtype: bytes\n:raises IOError: on failure";
+        let tags = doc_tags(docs);
+        assert_eq!(tags["doc_params"], "path: where to read");
+        assert_eq!(tags["doc_raises"], "IOError: on failure");
+        assert!(!tags.contains_key("doc_returns"));
+
+        let docs = "Load a file.\nArgs:\npath: where to read\nmode: open mode\nReturns:\nthe bytes";
+        let tags = doc_tags(docs);
+        assert_eq!(tags["doc_params"], "path: where to read\nmode: open mode");
+        assert_eq!(tags["doc_returns"], "the bytes");
+    }
+
+    #[test]
+    fn test_rustdoc_sections() {
+        let docs = "Open the db.\n# Errors\nwhen the file is missing\n# Notes\nnot a tag\n# Panics\non bad config";
+        let tags = doc_tags(docs);
+        assert_eq!(tags["doc_errors"], "when the file is missing");
+        assert_eq!(tags["doc_panics"], "on bad config");
+        assert_eq!(tags.len(), 2);
+    }
+}
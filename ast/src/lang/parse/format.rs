@@ -1,4 +1,4 @@
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 
 use crate::lang::call_finder::node_data_finder;
 use crate::lang::{graphs::Graph, *};
@@ -7,9 +7,56 @@ use shared::Result;
 use streaming_iterator::StreamingIterator;
 use tree_sitter::QueryMatch;
 
+use super::metrics::{count_parameters, function_metrics};
 use super::utils::{find_def, is_capitalized, log_cmd, trim_quotes};
 use super::super::queries::consts::FUNCTION_COMMENT;
 
+// type definitions visible to the functions of a file, by name
+#[derive(Default)]
+pub struct TypeIndex {
+    by_name: HashMap<String, Vec<(NodeType, NodeData)>>,
+}
+
+impl TypeIndex {
+    fn add(&mut self, node_type: NodeType, nd: NodeData) {
+        self.by_name
+            .entry(nd.name.clone())
+            .or_default()
+            .push((node_type, nd));
+    }
+}
+
+// the files, without extension, that relative `* as name` imports point at
+fn namespace_imports(file: &str, import_body: &str) -> Vec<String> {
+    let dir = std::path::Path::new(file)
+        .parent()
+        .unwrap_or(std::path::Path::new(""));
+    import_body
+        .lines()
+        .filter(|line| line.contains("* as "))
+        .filter_map(|line| {
+            let quote = line.rfind(['"', '\''])?;
+            let start = line[..quote].rfind(['"', '\''])?;
+            let spec = &line[start + 1..quote];
+            if !spec.starts_with('.') {
+                return None;
+            }
+            let mut parts: Vec<&str> = dir.to_str()?.split('/').filter(|p| !p.is_empty()).collect();
+            for part in spec.split('/') {
+                match part {
+                    "." | "" => {}
+                    ".." => {
+                        parts.pop()?;
+                    }
+                    part => parts.push(part),
+                }
+            }
+            let root = if file.starts_with('/') { "/" } else { "" };
+            Some(format!("{}{}", root, parts.join("/")))
+        })
+        .collect()
+}
+
 impl Lang {
     pub fn format_class_with_associations<G: Graph>(
         &self,
@@ -363,6 +410,22 @@ impl Lang {
             self.lang.add_endpoint_verb(&mut endp, &call);
         }
         self.lang.update_endpoint(&mut endp, &call);
+        // websocket upgrade routes are registered like regular GET routes
+        if let (Some(graph), Some(handler_name)) = (graph, endp.meta.get("handler").cloned()) {
+            let handler_fn = graph
+                .find_node_by_name_in_file(NodeType::Function, &handler_name, file)
+                .or_else(|| {
+                    graph
+                        .find_nodes_by_name(NodeType::Function, &handler_name)
+                        .first()
+                        .cloned()
+                });
+            if let Some(hf) = handler_fn {
+                if self.lang.is_websocket_handler(&hf) {
+                    endp.add_verb("WS");
+                }
+            }
+        }
         // for multi-handle endpoints with no "name:" (ENDPOINT)
         if endp.name.is_empty() {
             if let Some(handler) = endp.meta.get("handler") {
@@ -448,6 +511,144 @@ impl Lang {
         })?;
         Ok(inst)
     }
+    // enums, interfaces, type aliases and constants a function in `file` can see: the
+    // ones defined in the file, its package or module, and the ones its imports name,
+    // looked up once per file
+    pub fn type_index<G: Graph>(&self, file: &str, graph: &G) -> TypeIndex {
+        let mut index = TypeIndex::default();
+        let node_types: Vec<NodeType> = [
+            (NodeType::Enum, self.lang.enum_query()),
+            (NodeType::Interface, self.lang.interface_query()),
+            (NodeType::TypeAlias, self.lang.type_alias_query()),
+            (NodeType::Const, self.lang.const_query()),
+        ]
+        .into_iter()
+        .filter_map(|(nt, q)| q.map(|_| nt))
+        .collect();
+        if node_types.is_empty() {
+            return index;
+        }
+        let import_body = graph
+            .find_nodes_by_file_ends_with(NodeType::Import, file)
+            .into_iter()
+            .find(|i| i.file == file)
+            .map(|i| i.body)
+            .unwrap_or_default();
+        let imported: HashSet<&str> = import_body
+            .split(|c: char| !(c.is_alphanumeric() || c == '_' || c == '$'))
+            .filter(|w| !w.is_empty())
+            .collect();
+        // `import * as api from "../api"` brings in every name of that module
+        let mut modules: Vec<String> = namespace_imports(file, &import_body)
+            .into_iter()
+            .flat_map(|path| {
+                self.kind.exts().into_iter().flat_map(move |ext| {
+                    [
+                        format!("{}.{}", path, ext),
+                        format!("{}/index.{}", path, ext),
+                    ]
+                })
+            })
+            .collect();
+        // go and kotlin files see the rest of their package without importing it
+        modules.extend(self.package_files(file, graph));
+        // a swift module is the whole target, so every other file is in scope
+        let whole_module = matches!(self.kind, Language::Swift);
+        for node_type in node_types {
+            for nd in graph.find_nodes_by_file_ends_with(node_type.clone(), file) {
+                if nd.file == file {
+                    index.add(node_type.clone(), nd);
+                }
+            }
+            if whole_module {
+                for nd in graph.find_nodes_by_type(node_type.clone()) {
+                    if nd.file != file && self.kind.is_source_file(&nd.file) {
+                        index.add(node_type.clone(), nd);
+                    }
+                }
+                continue;
+            }
+            for module in &modules {
+                for nd in graph.find_nodes_by_file_ends_with(node_type.clone(), module) {
+                    if nd.file == *module {
+                        index.add(node_type.clone(), nd);
+                    }
+                }
+            }
+            for name in &imported {
+                for nd in graph.find_nodes_by_name(node_type.clone(), name) {
+                    if nd.file != file && !modules.contains(&nd.file) {
+                        index.add(node_type.clone(), nd);
+                    }
+                }
+            }
+        }
+        index
+    }
+    // the other files of the go or kotlin package `file` belongs to
+    fn package_files<G: Graph>(&self, file: &str, graph: &G) -> Vec<String> {
+        if !matches!(self.kind, Language::Go | Language::Kotlin) {
+            return Vec::new();
+        }
+        let members =
+            graph.find_nodes_with_edge_type(NodeType::Module, NodeType::File, EdgeType::Contains);
+        let Some((package, _)) = members.iter().find(|(_, f)| f.file == file) else {
+            return Vec::new();
+        };
+        members
+            .iter()
+            .filter(|(m, f)| m.name == package.name && m.file == package.file && f.file != file)
+            .map(|(_, f)| f.file.clone())
+            .collect()
+    }
+    fn type_usage_edges(
+        &self,
+        func: &NodeData,
+        node: TreeNode,
+        code: &str,
+        types: &TypeIndex,
+    ) -> Vec<Edge> {
+        if types.by_name.is_empty() {
+            return Vec::new();
+        }
+        let mut edges = Vec::new();
+        for name in self.find_identifier_names(node, code) {
+            for (node_type, target) in types.by_name.get(&name).into_iter().flatten() {
+                // skip the definition itself (const arrow fns, enum methods, etc)
+                if target.file == func.file && target.start <= func.start && target.end >= func.end
+                {
+                    continue;
+                }
+                edges.push(Edge::function_uses(func, node_type.clone(), target));
+            }
+        }
+        edges
+    }
+    pub fn format_type_definition(
+        &self,
+        m: &QueryMatch,
+        code: &str,
+        file: &str,
+        q: &Query,
+    ) -> Result<NodeData> {
+        let mut inst = NodeData::in_file(file);
+        let mut variants = Vec::new();
+        Self::loop_captures(q, m, code, |body, node, o| {
+            if o == TYPE_NAME {
+                inst.name = body;
+            } else if o == TYPE_DEFINITION {
+                inst.body = body;
+                inst.start = node.start_position().row;
+                inst.end = node.end_position().row;
+            } else if o == ENUM_VARIANTS {
+                variants = self.find_enum_variants(node, code)?;
+            }
+            Ok(())
+        })?;
+        inst.add_variants(&variants);
+        Ok(inst)
+    }
+    #[allow(clippy::too_many_arguments)]
     pub fn format_function<G: Graph>(
         &self,
         m: &QueryMatch,
@@ -456,6 +657,7 @@ impl Lang {
         q: &Query,
         graph: &G,
         lsp_tx: &Option<CmdSender>,
+        types: &TypeIndex,
     ) -> Result<Option<Function>> {
         let mut func = NodeData::in_file(file);
         let mut parent = None;
@@ -471,6 +673,8 @@ impl Lang {
         let mut def_start_byte: Option<usize> = None;
         let mut args_end_byte: Option<usize> = None;
         let mut return_end_byte: Option<usize> = None;
+        let mut metrics = None;
+        let mut parameter_count = None;
 
         Self::loop_captures(q, &m, code, |body, node, o| {
             if o == PARENT_TYPE {
@@ -485,6 +689,7 @@ impl Lang {
                 func.start = node.start_position().row;
                 func.end = node.end_position().row;
                 def_start_byte = Some(node.start_byte());
+                metrics = Some(function_metrics(node, code));
                 // parent
                 parent = self.lang.find_function_parent(
                     node,
@@ -615,9 +820,12 @@ impl Lang {
                         }
                     }
                 }
+                // enums, interfaces, type aliases and constants used in the function
+                models.extend(self.type_usage_edges(&func, node, code, types));
             } else if o == ARGUMENTS {
                 raw_args = Some(body.clone());
                 args_end_byte = Some(node.end_byte());
+                parameter_count = Some(count_parameters(node));
             } else if o == RETURN_TYPES {
                 raw_return = Some(body.clone());
                 return_end_byte = Some(node.end_byte());
@@ -655,6 +863,12 @@ impl Lang {
         if !comments.is_empty() {
             func.docs = Some(self.clean_and_combine_comments(&comments));
         }
+        if let Some(mut metrics) = metrics {
+            if let Some(count) = parameter_count {
+                metrics.parameter_count = count;
+            }
+            metrics.add_to(&mut func);
+        }
 
 
         if matches!(self.kind, Language::React) {
@@ -1057,13 +1271,13 @@ impl Lang {
             .lines()
             .map(|line| {
                 let trimmed = line.trim();
-                if let Some(stripped) = trimmed.strip_prefix("///") {
+                if let Some(stripped) = trimmed.strip_prefix("///").or_else(|| trimmed.strip_prefix("//!")) {
                     stripped.trim().to_string()
                 } else if let Some(stripped) = trimmed.strip_prefix("//") {
                     stripped.trim().to_string()
                 } else if let Some(stripped) = trimmed.strip_prefix("#") {
                     stripped.trim().to_string()
-                } else if let Some(stripped) = trimmed.strip_prefix("/*") {
+                } else if let Some(stripped) = ["/**", "/*!", "/*"].iter().find_map(|p| trimmed.strip_prefix(p)) {
                     let without_start = stripped.trim();
                     if let Some(without_end) = without_start.strip_suffix("*/") {
                         without_end.trim().to_string()
@@ -0,0 +1,266 @@
+use crate::lang::NodeData;
+use crate::utils::calculate_token_count;
+use tree_sitter::Node as TreeNode;
+
+// NodeData.meta keys of Function nodes
+pub const CYCLOMATIC_COMPLEXITY: &str = "cyclomatic_complexity";
+pub const NESTING_DEPTH: &str = "nesting_depth";
+pub const PARAMETER_COUNT: &str = "parameter_count";
+pub const LINES_OF_CODE: &str = "lines_of_code";
+pub const FN_TOKEN_COUNT: &str = "fn_token_count";
+pub const METRIC_KEYS: [&str; 5] = [
+    CYCLOMATIC_COMPLEXITY,
+    NESTING_DEPTH,
+    PARAMETER_COUNT,
+    LINES_OF_CODE,
+    FN_TOKEN_COUNT,
+];
+
+// named nodes that add a path through the function, across the grammars we parse
+const BRANCH_KINDS: [&str; 41] = [
+    "if_statement",
+    "if_expression",
+    "if",
+    "unless",
+    "if_modifier",
+    "unless_modifier",
+    "elif_clause",
+    "else_if_clause",
+    "while_statement",
+    "while_expression",
+    "while",
+    "until",
+    "while_modifier",
+    "until_modifier",
+    "for_statement",
+    "for_expression",
+    "for_in_statement",
+    "enhanced_for_statement",
+    "for",
+    "do_statement",
+    "repeat_while_statement",
+    "guard_statement",
+    "match_arm",
+    "case_clause",
+    "switch_case",
+    "switch_entry",
+    "switch_label",
+    "expression_case",
+    "type_case",
+    "communication_case",
+    "when_entry",
+    "when",
+    "catch_clause",
+    "catch_block",
+    "except_clause",
+    "rescue",
+    "conditional_expression",
+    "ternary_expression",
+    "conditional",
+    "for_in_clause",
+    "if_clause",
+];
+
+// anonymous operator tokens that short-circuit
+const BRANCH_OPERATORS: [&str; 4] = ["&&", "||", "and", "or"];
+
+// named nodes that open a nested block of control flow
+const NESTING_KINDS: [&str; 30] = [
+    "if_statement",
+    "if_expression",
+    "if",
+    "unless",
+    "while_statement",
+    "while_expression",
+    "while",
+    "until",
+    "for_statement",
+    "for_expression",
+    "for_in_statement",
+    "enhanced_for_statement",
+    "for",
+    "loop_expression",
+    "do_statement",
+    "repeat_while_statement",
+    "switch_statement",
+    "switch_expression",
+    "expression_switch_statement",
+    "type_switch_statement",
+    "select_statement",
+    "match_expression",
+    "when_expression",
+    "case",
+    "try_statement",
+    "try_expression",
+    "with_statement",
+    "begin",
+    "do_block",
+    "guard_statement",
+];
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct FunctionMetrics {
+    pub cyclomatic_complexity: usize,
+    pub nesting_depth: usize,
+    pub parameter_count: usize,
+    // lines holding code, not just comments or whitespace
+    pub lines_of_code: usize,
+    pub token_count: usize,
+}
+
+impl FunctionMetrics {
+    pub fn add_to(&self, nd: &mut NodeData) {
+        let values = [
+            (CYCLOMATIC_COMPLEXITY, self.cyclomatic_complexity),
+            (NESTING_DEPTH, self.nesting_depth),
+            (PARAMETER_COUNT, self.parameter_count),
+            (LINES_OF_CODE, self.lines_of_code),
+            (FN_TOKEN_COUNT, self.token_count),
+        ];
+        for (key, value) in values {
+            nd.meta.insert(key.to_string(), value.to_string());
+        }
+    }
+}
+
+// metrics of a function definition. parameters come from its `parameters` field,
+// callers with an arguments capture can overwrite them with count_parameters
+pub fn function_metrics(def: TreeNode, code: &str) -> FunctionMetrics {
+    let mut walk = Walk::default();
+    walk.visit(def, 0);
+    let body = def.utf8_text(code.as_bytes()).unwrap_or_default();
+    FunctionMetrics {
+        cyclomatic_complexity: 1 + walk.branches,
+        nesting_depth: walk.max_depth,
+        parameter_count: def
+            .child_by_field_name("parameters")
+            .map(count_parameters)
+            .unwrap_or(0),
+        lines_of_code: walk.rows.len(),
+        token_count: calculate_token_count(body).unwrap_or(0).max(0) as usize,
+    }
+}
+
+// named children of a parameter list, counting every name of a grouped go
+// declaration like `a, b int`
+pub fn count_parameters(params: TreeNode) -> usize {
+    let mut cursor = params.walk();
+    params
+        .named_children(&mut cursor)
+        .filter(|p| !p.kind().contains("comment"))
+        .map(|p| {
+            let mut names = p.walk();
+            p.children_by_field_name("name", &mut names).count().max(1)
+        })
+        .sum()
+}
+
+#[derive(Default)]
+struct Walk {
+    branches: usize,
+    max_depth: usize,
+    rows: std::collections::BTreeSet<usize>,
+}
+
+impl Walk {
+    fn visit(&mut self, node: TreeNode, depth: usize) {
+        let kind = node.kind();
+        if kind.contains("comment") {
+            return;
+        }
+        if node.child_count() == 0 {
+            if !node.is_named() && BRANCH_OPERATORS.contains(&kind) {
+                self.branches += 1;
+            }
+            self.rows
+                .extend(node.start_position().row..=node.end_position().row);
+            return;
+        }
+        if node.is_named() && BRANCH_KINDS.contains(&kind) {
+            self.branches += 1;
+        }
+        let depth = if node.is_named() && NESTING_KINDS.contains(&kind) && !is_else_if(node) {
+            depth + 1
+        } else {
+            depth
+        };
+        self.max_depth = self.max_depth.max(depth);
+        let mut cursor = node.walk();
+        for child in node.children(&mut cursor) {
+            self.visit(child, depth);
+        }
+    }
+}
+
+// an `else if` continues the chain it belongs to rather than nesting inside it
+fn is_else_if(node: TreeNode) -> bool {
+    let Some(parent) = node.parent() else {
+        return false;
+    };
+    parent.kind().contains("else") || parent.child_by_field_name("alternative") == Some(node)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tree_sitter::Parser;
+
+    fn metrics(language: tree_sitter::Language, code: &str) -> FunctionMetrics {
+        let mut parser = Parser::new();
+        parser.set_language(&language).unwrap();
+        let tree = parser.parse(code, None).unwrap();
+        let def = tree.root_node().named_child(0).unwrap();
+        function_metrics(def, code)
+    }
+
+    #[test]
+    fn test_function_metrics() {
+        let code = "fn pick(items: &[Item], limit: usize) -> Vec<Item> {
+    // keep the active ones
+    let mut out = Vec::new();
+    for item in items {
+        if item.active && out.len() < limit {
+            out.push(item.clone());
+        } else if item.pinned {
+            out.insert(0, item.clone());
+        }
+    }
+
+    out
+}";
+        let m = metrics(tree_sitter_rust::LANGUAGE.into(), code);
+        // for, if, &&, else if
+        assert_eq!(m.cyclomatic_complexity, 5);
+        assert_eq!(m.nesting_depth, 2);
+        assert_eq!(m.parameter_count, 2);
+        assert_eq!(m.lines_of_code, 11);
+        assert!(m.token_count > 0);
+
+        let code = "def load(path, retries=3):
+    try:
+        with open(path) as f:
+            return f.read()
+    except OSError:
+        return None if retries == 0 else load(path, retries - 1)
+";
+        let m = metrics(tree_sitter_python::LANGUAGE.into(), code);
+        // except, conditional
+        assert_eq!(m.cyclomatic_complexity, 3);
+        assert_eq!(m.nesting_depth, 2);
+        assert_eq!(m.parameter_count, 2);
+
+        let code = "func sum(a, b int, c float64) float64 { return float64(a+b) + c }";
+        let m = metrics(tree_sitter_go::LANGUAGE.into(), code);
+        assert_eq!(m.parameter_count, 3);
+        assert_eq!(m.cyclomatic_complexity, 1);
+        assert_eq!(m.nesting_depth, 0);
+        assert_eq!(m.lines_of_code, 1);
+
+        let mut nd = NodeData::name_file("sum", "main.go");
+        m.add_to(&mut nd);
+        assert_eq!(nd.meta[CYCLOMATIC_COMPLEXITY], "1");
+        assert_eq!(nd.meta[PARAMETER_COUNT], "3");
+        // neo4j already stores token_count on every node, so the metric gets its own key
+        assert!(nd.meta.contains_key(FN_TOKEN_COUNT) && !nd.meta.contains_key("token_count"));
+    }
+}
@@ -1,4 +1,7 @@
 pub mod collect;
+pub mod docs;
 pub mod format;
+pub mod metrics;
+pub mod modules;
 pub mod utils;
 pub use utils::*;
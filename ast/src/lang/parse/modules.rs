@@ -0,0 +1,533 @@
+use super::docs::doc_tags;
+use super::utils::trim_quotes;
+use crate::lang::{graphs::Graph, *};
+use shared::error::Result;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use streaming_iterator::StreamingIterator;
+
+// node types a module can re-export, and an import can resolve to through a module
+const EXPORTABLE: [NodeType; 9] = [
+    NodeType::Function,
+    NodeType::Class,
+    NodeType::DataModel,
+    NodeType::Var,
+    NodeType::Trait,
+    NodeType::Enum,
+    NodeType::Interface,
+    NodeType::TypeAlias,
+    NodeType::Const,
+];
+
+// Module nodes of a graph, with the files they own and what they re-export
+pub struct ModuleIndex {
+    sep: &'static str,
+    modules: Vec<NodeData>,
+    keys: HashMap<(String, String, usize), usize>,
+    file_modules: HashMap<String, usize>,
+    members: HashMap<usize, HashSet<String>>,
+    exports: HashMap<usize, Vec<(NodeType, NodeData)>>,
+}
+
+impl ModuleIndex {
+    pub fn new<G: Graph>(graph: &G, sep: &'static str) -> Self {
+        let modules = graph.find_nodes_by_type(NodeType::Module);
+        let keys = modules
+            .iter()
+            .enumerate()
+            .map(|(i, m)| ((m.name.clone(), m.file.clone(), m.start), i))
+            .collect();
+        let mut index = Self {
+            sep,
+            modules,
+            keys,
+            file_modules: HashMap::new(),
+            members: HashMap::new(),
+            exports: HashMap::new(),
+        };
+        if index.is_empty() {
+            return index;
+        }
+        for (m, f) in
+            graph.find_nodes_with_edge_type(NodeType::Module, NodeType::File, EdgeType::Contains)
+        {
+            if let Some(i) = index.position(&m) {
+                index.file_modules.insert(f.file.clone(), i);
+                index.members.entry(i).or_default().insert(f.file);
+            }
+        }
+        for nt in EXPORTABLE.iter().chain([NodeType::Module].iter()) {
+            for (m, target) in
+                graph.find_nodes_with_edge_type(NodeType::Module, nt.clone(), EdgeType::Exports)
+            {
+                if let Some(i) = index.position(&m) {
+                    index.add_export(i, nt.clone(), target);
+                }
+            }
+        }
+        index
+    }
+    pub fn is_empty(&self) -> bool {
+        self.modules.is_empty()
+    }
+    fn position(&self, m: &NodeData) -> Option<usize> {
+        self.keys
+            .get(&(m.name.clone(), m.file.clone(), m.start))
+            .copied()
+    }
+    fn add_export(&mut self, i: usize, nt: NodeType, target: NodeData) {
+        self.exports.entry(i).or_default().push((nt, target));
+    }
+    // the module a file belongs to
+    pub fn module_of(&self, file: &str) -> Option<&NodeData> {
+        self.file_modules.get(file).map(|i| &self.modules[*i])
+    }
+    // a module by qualified path (or by its trailing segments), closest to `near`
+    fn find(&self, path: &str, near: &str) -> Option<usize> {
+        if path.is_empty() {
+            return None;
+        }
+        let mut found: Vec<usize> = (0..self.modules.len())
+            .filter(|i| self.modules[*i].name == path)
+            .collect();
+        if found.is_empty() {
+            let suffix = format!("{}{}", self.sep, path);
+            found = (0..self.modules.len())
+                .filter(|i| self.modules[*i].name.ends_with(&suffix))
+                .collect();
+        }
+        found
+            .into_iter()
+            .max_by_key(|i| common_prefix_len(&self.modules[*i].file, near))
+    }
+    // follows re-exports, including modules re-exported as a whole
+    fn exported(&self, i: usize, name: &str, depth: usize) -> Option<(NodeType, NodeData)> {
+        let exports = self.exports.get(&i)?;
+        if let Some(found) = exports.iter().find(|(_, nd)| nd.name == name) {
+            if found.0 != NodeType::Module {
+                return Some(found.clone());
+            }
+        }
+        if depth > 3 {
+            return None;
+        }
+        exports
+            .iter()
+            .filter(|(nt, _)| *nt == NodeType::Module)
+            .filter_map(|(_, m)| self.position(m))
+            .find_map(|j| self.exported(j, name, depth + 1))
+    }
+    // an item `name` defined or re-exported by the module at `path`, or
+    // defined in the file `path` points at (`./button`, `pkg.routes`)
+    fn find_item<G: Graph>(
+        &self,
+        graph: &G,
+        path: &str,
+        name: &str,
+        near: &str,
+    ) -> Option<(NodeType, NodeData)> {
+        if let Some(i) = self.find(path, near) {
+            if let Some(found) = self.exported(i, name, 0) {
+                return Some(found);
+            }
+            if let Some(members) = self.members.get(&i) {
+                for nt in EXPORTABLE.iter() {
+                    let found = graph
+                        .find_nodes_by_name(nt.clone(), name)
+                        .into_iter()
+                        .find(|nd| members.contains(&nd.file) && is_top_level(nd));
+                    if let Some(nd) = found {
+                        return Some((nt.clone(), nd));
+                    }
+                }
+            }
+        }
+        let file_path = path.replace(self.sep, "/");
+        for nt in EXPORTABLE.iter() {
+            let found = graph
+                .find_nodes_by_name(nt.clone(), name)
+                .into_iter()
+                .find(|nd| file_matches(&nd.file, &file_path) && is_top_level(nd));
+            if let Some(nd) = found {
+                return Some((nt.clone(), nd));
+            }
+        }
+        None
+    }
+    // every top-level item in the file `path` points at, for `export * from`
+    fn file_items<G: Graph>(&self, graph: &G, path: &str) -> Vec<(NodeType, NodeData)> {
+        let file_path = path.replace(self.sep, "/");
+        EXPORTABLE
+            .iter()
+            .flat_map(|nt| {
+                graph
+                    .find_nodes_by_type(nt.clone())
+                    .into_iter()
+                    .filter(|nd| file_matches(&nd.file, &file_path) && is_top_level(nd))
+                    .map(|nd| (nt.clone(), nd))
+                    .collect::<Vec<_>>()
+            })
+            .collect()
+    }
+}
+
+impl Lang {
+    pub fn module_index<G: Graph>(&self, graph: &G) -> ModuleIndex {
+        ModuleIndex::new(graph, self.lang.module_separator())
+    }
+    // Module nodes for the packages/modules the source files belong to, with
+    // Contains edges to their submodules and member files
+    pub fn collect_modules(&self, filez: &[(String, String)]) -> Result<(Vec<NodeData>, Vec<Edge>)> {
+        let sep = self.lang.module_separator();
+        let files: HashSet<String> = filez.iter().map(|(f, _)| f.clone()).collect();
+        let module_q = self.lang.module_query().map(|q| self.q(&q, &NodeType::Module));
+        let reexports_q = self
+            .lang
+            .reexports_query()
+            .map(|q| self.q(&q, &NodeType::Import));
+
+        // qualified name -> member files
+        let mut grouped: BTreeMap<String, Vec<String>> = BTreeMap::new();
+        let mut inline = Vec::new();
+        let mut file_docs: HashMap<&str, String> = HashMap::new();
+        for (file, code) in filez {
+            if !self.kind.is_source_file(file) {
+                continue;
+            }
+            if let Some(docs) = self.module_docs(code)? {
+                file_docs.insert(file, docs);
+            }
+            let mut package = None;
+            let mut blocks = Vec::new();
+            if let Some(q) = &module_q {
+                let tree = self.lang.parse(code, &NodeType::Module)?;
+                let mut cursor = QueryCursor::new();
+                let mut matches = cursor.matches(q, tree.root_node(), code.as_bytes());
+                while let Some(m) = matches.next() {
+                    let mut name = None;
+                    let mut block = None;
+                    Self::loop_captures(q, m, code, |body, node, o| {
+                        if o == MODULE_NAME {
+                            name = Some(body);
+                        } else if o == MODULE {
+                            block = Some((inline_module_path(node, code), node_data(node, code, file)));
+                        }
+                        Ok(())
+                    })?;
+                    match (name, block) {
+                        (_, Some(block)) => blocks.push(block),
+                        (Some(name), None) if package.is_none() => package = Some(name),
+                        _ => {}
+                    }
+                }
+            }
+            let Some(name) = self.lang.file_module(file, package.as_deref(), &files) else {
+                continue;
+            };
+            if self.lang.barrel_modules() && self.lang.is_module_root(file) {
+                let has_reexports = match &reexports_q {
+                    Some(q) => !self.reexport_statements(q, code)?.is_empty(),
+                    None => false,
+                };
+                if !has_reexports {
+                    continue;
+                }
+            }
+            self.attach_docs(code, blocks.iter_mut().map(|(_, nd)| nd))?;
+            for (path, mut nd) in blocks {
+                let mut qualified = vec![name.as_str()];
+                qualified.extend(path.iter().map(String::as_str));
+                nd.name = qualified.join(sep);
+                let parent = qualified[..qualified.len() - 1].join(sep);
+                inline.push((nd, parent));
+            }
+            grouped.entry(name).or_default().push(file.clone());
+        }
+
+        let mut modules: Vec<(NodeData, Vec<String>)> = Vec::new();
+        for (name, members) in grouped {
+            let roots: Vec<&String> = members
+                .iter()
+                .filter(|f| self.lang.is_module_root(f))
+                .collect();
+            if roots.len() <= 1 {
+                let anchor = match roots.first() {
+                    Some(root) => root.to_string(),
+                    None => common_dir(&members),
+                };
+                modules.push((module_node(&name, &anchor), members));
+                continue;
+            }
+            // the same path in several crates/projects: each root takes the files closest to it
+            let mut split: Vec<(NodeData, Vec<String>)> = roots
+                .iter()
+                .map(|root| (module_node(&name, root), Vec::new()))
+                .collect();
+            for member in &members {
+                let closest = (0..split.len())
+                    .max_by_key(|i| common_prefix_len(&split[*i].0.file, member))
+                    .unwrap_or(0);
+                split[closest].1.push(member.clone());
+            }
+            modules.extend(split);
+        }
+
+        // the root file documents the module, else any member (like go's doc.go)
+        for (module, members) in modules.iter_mut() {
+            let docs = file_docs.get(module.file.as_str()).or_else(|| {
+                let mut documented = members.iter().filter_map(|m| file_docs.get(m.as_str()));
+                documented.next()
+            });
+            if let Some(docs) = docs {
+                module.meta.extend(doc_tags(docs));
+                module.docs = Some(docs.clone());
+            }
+        }
+
+        let mut edges = Vec::new();
+        for (module, members) in &modules {
+            for member in members {
+                edges.push(Edge::contains(
+                    NodeType::Module,
+                    module,
+                    NodeType::File,
+                    &file_node(member),
+                ));
+            }
+            let mut parent = module.name.as_str();
+            while let Some((prefix, _)) = parent.rsplit_once(sep) {
+                parent = prefix;
+                if let Some(p) = closest_module(&modules, parent, &module.file) {
+                    edges.push(Edge::contains(NodeType::Module, p, NodeType::Module, module));
+                    break;
+                }
+            }
+        }
+        let mut nodes: Vec<NodeData> = modules.into_iter().map(|(m, _)| m).collect();
+        for (nd, parent) in inline {
+            let parent_module = nodes.iter().find(|m| m.name == parent && m.file == nd.file);
+            if let Some(p) = parent_module {
+                edges.push(Edge::contains(NodeType::Module, p, NodeType::Module, &nd));
+            }
+            nodes.push(nd);
+        }
+        Ok((nodes, edges))
+    }
+    // Exports edges for `pub use`, `export ... from` and package `__init__` imports,
+    // innermost modules first so barrels can re-export other barrels
+    pub fn collect_reexports<G: Graph>(
+        &self,
+        filez: &[(String, String)],
+        graph: &G,
+        modules: &mut ModuleIndex,
+    ) -> Result<Vec<Edge>> {
+        let mut edges = Vec::new();
+        let Some(query) = self.lang.reexports_query() else {
+            return Ok(edges);
+        };
+        let q = self.q(&query, &NodeType::Import);
+        let sep = self.lang.module_separator();
+        let mut roots: Vec<(&String, &String, usize)> = filez
+            .iter()
+            .filter(|(f, _)| self.lang.is_module_root(f))
+            .filter_map(|(f, code)| {
+                let i = *modules.file_modules.get(f)?;
+                (modules.modules[i].file == *f).then_some((f, code, i))
+            })
+            .collect();
+        roots.sort_by_key(|(f, _, _)| std::cmp::Reverse(f.matches('/').count()));
+
+        for (file, code, i) in roots {
+            let module = modules.modules[i].clone();
+            for (source, names) in self.reexport_statements(&q, code)? {
+                let Some(path) = self.lang.import_module(&source, Some(&module.name), file) else {
+                    continue;
+                };
+                let mut targets = Vec::new();
+                if names.is_empty() {
+                    match modules.find(&path, file) {
+                        Some(j) if j != i => {
+                            targets.push((NodeType::Module, modules.modules[j].clone()))
+                        }
+                        Some(_) => {}
+                        None => targets.extend(modules.file_items(graph, &path)),
+                    }
+                }
+                for name in &names {
+                    let submodule = modules.find(&format!("{}{}{}", path, sep, name), file);
+                    if let Some(j) = submodule.filter(|j| *j != i) {
+                        targets.push((NodeType::Module, modules.modules[j].clone()));
+                    } else if let Some(found) = modules.find_item(graph, &path, name, file) {
+                        targets.push(found);
+                    }
+                }
+                for (nt, target) in targets {
+                    edges.push(Edge::module_exports(&module, nt.clone(), &target));
+                    modules.add_export(i, nt, target);
+                }
+            }
+        }
+        Ok(edges)
+    }
+    // an Imports edge through the module graph, for imports that didn't resolve
+    // to an item directly: a submodule, a re-exported item, or the module itself
+    pub fn module_import_edge<G: Graph>(
+        &self,
+        graph: &G,
+        modules: &ModuleIndex,
+        import_source: &str,
+        import_name: &str,
+        file: &str,
+    ) -> Option<Edge> {
+        if modules.is_empty() {
+            return None;
+        }
+        let sep = self.lang.module_separator();
+        let current = modules.module_of(file).map(|m| m.name.as_str());
+        let path = self.lang.import_module(import_source, current, file)?;
+        let name = self.lang.resolve_import_name(import_name);
+        // `import com.x.Y`: the source already ends with the imported name
+        let parent = path
+            .strip_suffix(name.as_str())
+            .and_then(|p| p.strip_suffix(sep))
+            .filter(|_| !name.is_empty());
+
+        let file_node = graph
+            .find_nodes_by_file_ends_with(NodeType::File, file)
+            .first()
+            .cloned()
+            .unwrap_or_else(|| NodeData::in_file(file));
+        if !name.is_empty() {
+            if let Some(j) = modules.find(&format!("{}{}{}", path, sep, name), file) {
+                return Some(Edge::file_imports(&file_node, NodeType::Module, &modules.modules[j]));
+            }
+            for p in [Some(path.as_str()), parent].into_iter().flatten() {
+                if let Some((nt, target)) = modules.find_item(graph, p, &name, file) {
+                    if target.file == file {
+                        return None;
+                    }
+                    return Some(Edge::file_imports(&file_node, nt, &target));
+                }
+            }
+        }
+        let j = modules
+            .find(&path, file)
+            .or_else(|| parent.and_then(|p| modules.find(p, file)))?;
+        if modules.module_of(file).map(|m| &m.name) == Some(&modules.modules[j].name) {
+            return None;
+        }
+        Some(Edge::file_imports(&file_node, NodeType::Module, &modules.modules[j]))
+    }
+    // (source, names) of each re-export statement; no names means everything
+    fn reexport_statements(&self, q: &Query, code: &str) -> Result<Vec<(String, Vec<String>)>> {
+        let tree = self.lang.parse(code, &NodeType::Import)?;
+        let mut cursor = QueryCursor::new();
+        let mut matches = cursor.matches(q, tree.root_node(), code.as_bytes());
+        let mut statements: BTreeMap<usize, (String, Vec<String>)> = BTreeMap::new();
+        while let Some(m) = matches.next() {
+            let mut start = None;
+            let mut source = String::new();
+            let mut names = Vec::new();
+            Self::loop_captures_multi(q, m, code, |body, node, o| {
+                if o == IMPORTS {
+                    start = Some(node.start_byte());
+                } else if o == IMPORTS_FROM {
+                    source = trim_quotes(&body).to_string();
+                } else if o == IMPORTS_NAME {
+                    names.push(body);
+                }
+                Ok(())
+            })?;
+            let Some(start) = start else {
+                continue;
+            };
+            let entry = statements
+                .entry(start)
+                .or_insert_with(|| (source, Vec::new()));
+            for name in names {
+                if !entry.1.contains(&name) {
+                    entry.1.push(name);
+                }
+            }
+        }
+        Ok(statements.into_values().collect())
+    }
+}
+
+// names of the inline modules enclosing `node` (outermost first) and its own
+fn inline_module_path(node: TreeNode, code: &str) -> Vec<String> {
+    let mut path = Vec::new();
+    let mut current = Some(node);
+    while let Some(n) = current {
+        if n.kind() == node.kind() {
+            if let Some(Ok(name)) = n
+                .child_by_field_name("name")
+                .map(|name| name.utf8_text(code.as_bytes()))
+            {
+                path.insert(0, name.to_string());
+            }
+        }
+        current = n.parent();
+    }
+    path
+}
+
+fn node_data(node: TreeNode, code: &str, file: &str) -> NodeData {
+    let mut nd = NodeData::in_file(file);
+    nd.start = node.start_position().row;
+    nd.end = node.end_position().row;
+    nd.body = node.utf8_text(code.as_bytes()).unwrap_or_default().to_string();
+    nd
+}
+
+fn module_node(name: &str, file: &str) -> NodeData {
+    let mut nd = NodeData::in_file(file);
+    nd.name = name.to_string();
+    nd
+}
+
+fn file_node(file: &str) -> NodeData {
+    let mut nd = NodeData::in_file(file);
+    nd.name = file.rsplit('/').next().unwrap_or(file).to_string();
+    nd
+}
+
+fn closest_module<'a>(
+    modules: &'a [(NodeData, Vec<String>)],
+    name: &str,
+    near: &str,
+) -> Option<&'a NodeData> {
+    modules
+        .iter()
+        .map(|(m, _)| m)
+        .filter(|m| m.name == name)
+        .max_by_key(|m| common_prefix_len(&m.file, near))
+}
+
+fn common_dir(files: &[String]) -> String {
+    let mut dir: Vec<&str> = files[0].split('/').collect();
+    dir.pop();
+    for f in &files[1..] {
+        let parts: Vec<&str> = f.split('/').collect();
+        let same = dir.iter().zip(&parts).take_while(|(a, b)| a == b).count();
+        dir.truncate(same);
+    }
+    dir.join("/")
+}
+
+fn common_prefix_len(a: &str, b: &str) -> usize {
+    a.split('/')
+        .zip(b.split('/'))
+        .take_while(|(x, y)| x == y)
+        .count()
+}
+
+// `file` without extension is `path`, or `path` is its directory's index file
+fn file_matches(file: &str, path: &str) -> bool {
+    let stem = file.rsplit_once('.').map(|(s, _)| s).unwrap_or(file);
+    let stem = stem.strip_suffix("/index").unwrap_or(stem);
+    stem == path || stem.ends_with(&format!("/{}", path))
+}
+
+fn is_top_level(nd: &NodeData) -> bool {
+    !nd.meta.contains_key("operand")
+}
@@ -1,6 +1,7 @@
 use crate::lang::{graphs::Graph, *};
 use lsp::{Cmd as LspCmd, Position, Res as LspRes};
 use shared::Result;
+use std::collections::HashSet;
 use tracing::debug;
 use tree_sitter::{Node as TreeNode, QueryMatch};
 
@@ -32,6 +33,37 @@ pub fn is_capitalized(name: &str) -> bool {
     name.chars().next().unwrap().is_uppercase()
 }
 
+fn is_ident_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
+// `name` appears in `text` as a whole identifier
+pub fn contains_identifier(text: &str, name: &str) -> bool {
+    if name.is_empty() {
+        return false;
+    }
+    text.match_indices(name).any(|(i, _)| {
+        let before = text[..i].chars().next_back();
+        let after = text[i + name.len()..].chars().next();
+        !before.is_some_and(is_ident_char) && !after.is_some_and(is_ident_char)
+    })
+}
+
+// an import statement pulls from library `lib` (or one of its subpaths):
+// "express" matches 'express' and 'express/lib' but not 'express-session' or '@types/express'
+pub fn imports_library(stmt: &str, lib: &str) -> bool {
+    let underscored = lib.replace('-', "_");
+    [lib, underscored.as_str()].iter().any(|name| {
+        !name.is_empty()
+            && stmt.match_indices(*name).any(|(i, _)| {
+                let before = stmt[..i].chars().next_back();
+                let after = stmt[i + name.len()..].chars().next();
+                !before.is_some_and(|c| is_ident_char(c) || "-./@".contains(c))
+                    && !after.is_some_and(|c| is_ident_char(c) || c == '-')
+            })
+    })
+}
+
 // FIXME also find it its in range!!! not just on the line!!!
 pub fn find_def<G: Graph>(
     pos: Option<Position>,
@@ -111,6 +143,56 @@ impl Lang {
         }
         Ok(results)
     }
+    // every identifier-like leaf (identifier, type_identifier, simple_identifier, ...)
+    pub fn find_identifier_names(&self, node: TreeNode, code: &str) -> HashSet<String> {
+        let mut names = HashSet::new();
+        let mut stack = vec![node];
+        while let Some(n) = stack.pop() {
+            if n.named_child_count() == 0 {
+                if n.kind().ends_with("identifier") {
+                    if let Ok(text) = n.utf8_text(code.as_bytes()) {
+                        names.insert(text.to_string());
+                    }
+                }
+                continue;
+            }
+            for i in 0..n.named_child_count() {
+                if let Some(child) = n.named_child(i) {
+                    stack.push(child);
+                }
+            }
+        }
+        names
+    }
+    // variant names from an enum body (enum_body, enum_variant_list, enum_class_body)
+    pub fn find_enum_variants(&self, body: TreeNode, code: &str) -> Result<Vec<String>> {
+        let mut variants = Vec::new();
+        let mut cursor = body.walk();
+        for child in body.named_children(&mut cursor) {
+            let mut names = Vec::new();
+            if child.kind().ends_with("identifier") {
+                names.push(child);
+            } else if child.kind().contains("enum") {
+                let mut c = child.walk();
+                names.extend(child.children_by_field_name("name", &mut c));
+                if names.is_empty() {
+                    let mut c = child.walk();
+                    names.extend(
+                        child
+                            .named_children(&mut c)
+                            .find(|n| n.kind().ends_with("identifier")),
+                    );
+                }
+            }
+            for n in names {
+                let name = n.utf8_text(code.as_bytes())?.to_string();
+                if !variants.contains(&name) {
+                    variants.push(name);
+                }
+            }
+        }
+        Ok(variants)
+    }
     pub fn loop_captures<F>(q: &Query, m: &QueryMatch, code: &str, mut cb: F) -> Result<()>
     where
         F: FnMut(String, TreeNode, String) -> Result<()>,
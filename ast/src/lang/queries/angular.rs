@@ -12,6 +12,26 @@ impl Angular {
 }
 
 impl Stack for Angular {
+    fn lifecycle_hooks(&self) -> &'static [&'static str] {
+        &[
+            "constructor",
+            "ngOnChanges",
+            "ngOnInit",
+            "ngDoCheck",
+            "ngAfterContentInit",
+            "ngAfterContentChecked",
+            "ngAfterViewInit",
+            "ngAfterViewChecked",
+            "ngOnDestroy",
+            "canActivate",
+            "resolve",
+            "intercept",
+            "transform",
+        ]
+    }
+    fn is_exported(&self, nd: &NodeData, source: &str) -> bool {
+        super::is_js_export(&nd.name, source)
+    }
     fn q(&self, q: &str, _nt: &NodeType) -> Query {
         Query::new(&self.0, q).unwrap()
     }
@@ -239,6 +259,37 @@ impl Stack for Angular {
 
         path
     }
+    fn module_separator(&self) -> &'static str {
+        "/"
+    }
+    fn file_module(
+        &self,
+        file: &str,
+        _package: Option<&str>,
+        _files: &std::collections::HashSet<String>,
+    ) -> Option<String> {
+        super::typescript::barrel_dir(file)
+    }
+    fn is_module_root(&self, file: &str) -> bool {
+        super::typescript::barrel_dir(file).is_some()
+    }
+    fn barrel_modules(&self) -> bool {
+        true
+    }
+    fn reexports_query(&self) -> Option<String> {
+        Some(super::typescript::reexports_query())
+    }
+    fn module_doc_query(&self) -> Option<String> {
+        Some(super::typescript::module_doc_query())
+    }
+    fn import_module(
+        &self,
+        import_source: &str,
+        _current_module: Option<&str>,
+        current_file: &str,
+    ) -> Option<String> {
+        super::typescript::relative_module_path(import_source, current_file)
+    }
     fn is_extra_page(&self, file_name: &str) -> bool {
         file_name.ends_with(".html")
             || file_name.ends_with(".css")
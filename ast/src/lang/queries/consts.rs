@@ -8,6 +8,7 @@ pub const IMPORTS_NAME: &str = "imports-name";
 pub const TRAIT: &str = "trait";
 pub const TRAIT_NAME: &str = "trait-name";
 pub const TRAIT_METHOD_NAME: &str = "trait-method-name";
+pub const MODULE: &str = "module";
 pub const MODULE_NAME: &str = "module-name";
 pub const CLASS_NAME: &str = "class-name";
 pub const CLASS_PARENT: &str = "class-parent";
@@ -23,6 +24,9 @@ pub const PARENT_NAME: &str = "parent-name";
 pub const PARENT_TYPE: &str = "parent-type";
 pub const FUNCTION_CALL: &str = "function-call";
 pub const FUNCTION_COMMENT: &str = "function-comment";
+pub const DOCSTRING: &str = "docstring";
+pub const DOCSTRING_OWNER: &str = "docstring-owner";
+pub const MODULE_DOC: &str = "module-doc";
 pub const OPERAND: &str = "operand";
 pub const ASSOCIATION_TYPE: &str = "association-type";
 pub const ASSOCIATION_TARGET: &str = "association-target";
@@ -48,6 +52,9 @@ pub const TEST_NAME: &str = "test-name";
 
 pub const STRUCT: &str = "struct";
 pub const STRUCT_NAME: &str = "struct-name";
+pub const TYPE_DEFINITION: &str = "type-definition";
+pub const TYPE_NAME: &str = "type-name";
+pub const ENUM_VARIANTS: &str = "enum-variants";
 pub const PAGE: &str = "page";
 pub const PAGE_COMPONENT: &str = "page-component";
 pub const PAGE_PATHS: &str = "page-paths";
@@ -46,7 +46,27 @@ impl Stack for Go {
         ))
     }
     fn module_query(&self) -> Option<String> {
-        Some(format!("(package_identifier) @{MODULE_NAME}"))
+        Some(format!(
+            "(package_clause
+                (package_identifier) @{MODULE_NAME}
+            )"
+        ))
+    }
+    fn module_separator(&self) -> &'static str {
+        "/"
+    }
+    // go packages are identified by their directory, like import paths
+    fn file_module(
+        &self,
+        file: &str,
+        package: Option<&str>,
+        _files: &std::collections::HashSet<String>,
+    ) -> Option<String> {
+        let package = package?;
+        match file.rsplit_once('/') {
+            Some((dir, _)) => Some(dir.to_string()),
+            None => Some(package.to_string()),
+        }
     }
     fn imports_query(&self) -> Option<String> {
         Some(format!(
@@ -77,6 +97,31 @@ impl Stack for Go {
             "#
         ))
     }
+    fn type_alias_query(&self) -> Option<String> {
+        Some(format!(
+            r#"
+            (type_declaration
+                (type_alias
+                    name: (type_identifier) @{TYPE_NAME}
+                )
+            ) @{TYPE_DEFINITION}
+            "#
+        ))
+    }
+    fn const_query(&self) -> Option<String> {
+        Some(format!(
+            r#"
+            (source_file
+                (const_declaration
+                    (const_spec
+                        name: (identifier) @{VARIABLE_NAME}
+                        type: (_)? @{VARIABLE_TYPE}
+                    ) @{VARIABLE_DECLARATION}
+                )
+            )
+            "#
+        ))
+    }
     fn trait_query(&self) -> Option<String> {
         Some(format!(
             r#"(type_declaration
@@ -137,6 +182,9 @@ impl Stack for Go {
     fn comment_query(&self) -> Option<String> {
         Some(format!(r#"(comment) @{FUNCTION_COMMENT}"#))
     }
+    fn module_doc_query(&self) -> Option<String> {
+        Some(format!(r#"((comment)+ @{MODULE_DOC} . (package_clause))"#))
+    }
     fn function_call_query(&self) -> String {
         format!(
             "(call_expression
@@ -187,6 +235,12 @@ impl Stack for Go {
             ) @{ROUTE}"#
         )]
     }
+    fn is_websocket_handler(&self, handler: &NodeData) -> bool {
+        // gorilla: conn, err := upgrader.Upgrade(w, r, nil)
+        handler.body.contains("upgrader.Upgrade(")
+            || handler.body.contains("websocket.Upgrade(")
+            || handler.body.contains("websocket.Upgrader")
+    }
     fn endpoint_group_find(&self) -> Option<String> {
         Some(format!(
             r#"(call_expression
@@ -279,6 +333,12 @@ impl Stack for Go {
         );
         Some(type_finder)
     }
+    fn lifecycle_hooks(&self) -> &'static [&'static str] {
+        &["init"]
+    }
+    fn is_exported(&self, nd: &NodeData, _source: &str) -> bool {
+        nd.name.starts_with(|c: char| c.is_uppercase())
+    }
     fn is_test(&self, func_name: &str, _func_file: &str) -> bool {
         func_name.starts_with("Test")
     }
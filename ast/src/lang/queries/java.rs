@@ -12,6 +12,22 @@ impl Java {
 }
 
 impl Stack for Java {
+    fn lifecycle_hooks(&self) -> &'static [&'static str] {
+        &[
+            "run",
+            "call",
+            "toString",
+            "equals",
+            "hashCode",
+            "compareTo",
+            "init",
+            "destroy",
+        ]
+    }
+    fn is_exported(&self, nd: &NodeData, _source: &str) -> bool {
+        let head = nd.body.split(['(', '{']).next().unwrap_or_default();
+        head.split_whitespace().any(|w| w == "public")
+    }
     fn q(&self, q: &str, _nt: &NodeType) -> Query {
         Query::new(&self.0, q).unwrap()
     }
@@ -234,6 +250,13 @@ impl Stack for Java {
             .to_string();
         name
     }
+    fn module_query(&self) -> Option<String> {
+        Some(format!(
+            r#"(package_declaration
+                [(scoped_identifier) (identifier)] @{MODULE_NAME}
+            )"#
+        ))
+    }
     fn resolve_import_path(&self, import_path: &str, _current_file: &str) -> String {
         let import_path = import_path.to_string();
 
@@ -69,6 +69,47 @@ impl Stack for Kotlin {
         ))
     }
 
+    fn enum_query(&self) -> Option<String> {
+        Some(format!(
+            r#"
+            (class_declaration
+                (modifiers
+                    (class_modifier) @modifier (#eq? @modifier "enum")
+                )
+                (type_identifier) @{TYPE_NAME}
+                (enum_class_body) @{ENUM_VARIANTS}
+            ) @{TYPE_DEFINITION}
+            "#
+        ))
+    }
+    fn type_alias_query(&self) -> Option<String> {
+        Some(format!(
+            r#"
+            (type_alias
+                (type_identifier) @{TYPE_NAME}
+            ) @{TYPE_DEFINITION}
+            "#
+        ))
+    }
+    fn const_query(&self) -> Option<String> {
+        Some(format!(
+            r#"
+            (source_file
+                (property_declaration
+                    (modifiers
+                        (property_modifier) @modifier (#eq? @modifier "const")
+                    )
+                    (binding_pattern_kind)
+                    (variable_declaration
+                        (simple_identifier) @{VARIABLE_NAME}
+                        (user_type)? @{VARIABLE_TYPE}
+                    )
+                ) @{VARIABLE_DECLARATION}
+            )
+            "#
+        ))
+    }
+
     fn class_definition_query(&self) -> String {
         format!(
             r#"
@@ -249,6 +290,29 @@ impl Stack for Kotlin {
         ))
     }
 
+    fn lifecycle_hooks(&self) -> &'static [&'static str] {
+        &[
+            "onCreate",
+            "onStart",
+            "onResume",
+            "onPause",
+            "onStop",
+            "onDestroy",
+            "onCreateView",
+            "onViewCreated",
+            "onDestroyView",
+            "onCleared",
+            "onReceive",
+            "onBind",
+        ]
+    }
+    // kotlin declarations are public unless marked otherwise
+    fn is_exported(&self, nd: &NodeData, _source: &str) -> bool {
+        let head = nd.body.split(['(', '{']).next().unwrap_or_default();
+        !head
+            .split_whitespace()
+            .any(|w| matches!(w, "private" | "internal" | "protected"))
+    }
     fn is_test(&self, func_name: &str, _func_file: &str) -> bool {
         func_name.starts_with("test")
     }
@@ -263,6 +327,13 @@ impl Stack for Kotlin {
         name
     }
 
+    fn module_query(&self) -> Option<String> {
+        Some(format!(
+            r#"(package_header
+                (identifier) @{MODULE_NAME}
+            )"#
+        ))
+    }
     fn resolve_import_path(&self, import_path: &str, _current_file: &str) -> String {
         let import_path = import_path.to_string();
 
@@ -23,7 +23,7 @@ use crate::lang::{Function, NodeData, NodeType};
 use lsp::Language as LspLanguage;
 use lsp::{CmdSender, Position};
 use shared::Result;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use tree_sitter::{Node as TreeNode, Query, Tree};
 
 #[derive(Default, Debug)]
@@ -52,9 +52,44 @@ pub trait Stack {
     fn q(&self, q: &str, nt: &NodeType) -> Query;
     // use different parser for pkg files
     fn parse(&self, code: &str, nt: &NodeType) -> Result<Tree>;
+    // package declarations (MODULE_NAME) or inline module blocks (MODULE)
     fn module_query(&self) -> Option<String> {
         None
     }
+    fn module_separator(&self) -> &'static str {
+        "."
+    }
+    // qualified module (package) a source file belongs to. `package` is the
+    // name declared in the file, `files` every file in the repo
+    fn file_module(
+        &self,
+        _file: &str,
+        package: Option<&str>,
+        _files: &HashSet<String>,
+    ) -> Option<String> {
+        package.map(|p| p.to_string())
+    }
+    // the file that defines its module (mod.rs, __init__.py, index.ts)
+    fn is_module_root(&self, _file: &str) -> bool {
+        false
+    }
+    // a module only exists if its root file re-exports something (TS barrels)
+    fn barrel_modules(&self) -> bool {
+        false
+    }
+    // `pub use`, `export ... from` and package-level imports that re-export items
+    fn reexports_query(&self) -> Option<String> {
+        None
+    }
+    // qualified module an import source points at, like file_module
+    fn import_module(
+        &self,
+        import_source: &str,
+        _current_module: Option<&str>,
+        _current_file: &str,
+    ) -> Option<String> {
+        Some(import_source.to_string())
+    }
     fn lib_query(&self) -> Option<String> {
         None
     }
@@ -96,6 +131,14 @@ pub trait Stack {
         format!("(identifier) @identifier")
     }
     fn comment_query(&self) -> Option<String> { None }
+    // string literals documenting their enclosing definition (DOCSTRING, DOCSTRING_OWNER)
+    fn docstring_query(&self) -> Option<String> {
+        None
+    }
+    // file-level docs like `//!`, module docstrings or package comments (MODULE_DOC)
+    fn module_doc_query(&self) -> Option<String> {
+        None
+    }
     fn type_identifier_node_name(&self) -> String {
         "type_identifier".to_string()
     }
@@ -116,6 +159,20 @@ pub trait Stack {
     fn data_model_path_filter(&self) -> Option<String> {
         None
     }
+    // enums (variant list captured with ENUM_VARIANTS), interfaces and type aliases
+    fn enum_query(&self) -> Option<String> {
+        None
+    }
+    fn interface_query(&self) -> Option<String> {
+        None
+    }
+    fn type_alias_query(&self) -> Option<String> {
+        None
+    }
+    // top-level constants, using the VARIABLE_* captures
+    fn const_query(&self) -> Option<String> {
+        None
+    }
     fn use_data_model_within_finder(&self) -> bool {
         false
     }
@@ -180,9 +237,25 @@ pub trait Stack {
     fn is_test_file(&self, _filename: &str) -> bool {
         false
     }
+    // functions the framework calls by name, so they never show up as called in the graph
+    fn lifecycle_hooks(&self) -> &'static [&'static str] {
+        &[]
+    }
+    // public api of the file (`source` is its code), callers may live outside the repo
+    fn is_exported(&self, _nd: &NodeData, _source: &str) -> bool {
+        false
+    }
     fn classify_test(&self, _name: &str, _file: &str, _body: &str) -> NodeType { NodeType::UnitTest }
     fn add_endpoint_verb(&self, _nd: &mut NodeData, _call: &Option<String>) {}
     fn update_endpoint(&self, _nd: &mut NodeData, _call: &Option<String>) {}
+    // websocket upgrade handlers (axum WebSocketUpgrade, gorilla Upgrader, etc)
+    fn is_websocket_handler(&self, _handler: &NodeData) -> bool {
+        false
+    }
+    // files outside of endpoint_path_filter that still declare realtime endpoints
+    fn is_channel_file(&self, _file_name: &str) -> bool {
+        false
+    }
     // this one should be the same for all langs?
     fn filter_tests(&self, funcs: Vec<Function>) -> (Vec<Function>, Vec<Function>) {
         let mut fs = Vec::new();
@@ -322,6 +395,36 @@ pub fn treesitter_from_lsp_language(ll: LspLanguage) -> tree_sitter::Language {
     }
 }
 
+// `export function name`, `export const name`, `export default name` or `export { name }`
+pub fn is_js_export(name: &str, source: &str) -> bool {
+    let is_name = |word: &str| {
+        word.split(|c: char| !(c.is_alphanumeric() || c == '_' || c == '$'))
+            .next()
+            == Some(name)
+    };
+    source.lines().any(|line| {
+        let Some(rest) = line.trim_start().strip_prefix("export ") else {
+            return false;
+        };
+        if let Some(list) = rest.trim_start().strip_prefix('{') {
+            let list = list.split('}').next().unwrap_or(list);
+            return list.split(',').any(|item| {
+                let mut words = item.split_whitespace();
+                words.next() == Some(name)
+            });
+        }
+        rest.split_whitespace()
+            .find(|w| {
+                !matches!(
+                    *w,
+                    "default" | "async" | "function" | "function*" | "const" | "let" | "var"
+                        | "class" | "abstract" | "interface" | "type" | "enum"
+                )
+            })
+            .is_some_and(is_name)
+    })
+}
+
 impl HandlerItem {
     pub fn new_member(name: &str) -> Self {
         HandlerItem {
@@ -102,6 +102,23 @@ impl Stack for Python {
     fn comment_query(&self) -> Option<String> {
         Some(format!(r#"(comment)+ @{FUNCTION_COMMENT}"#))
     }
+    fn docstring_query(&self) -> Option<String> {
+        Some(format!(
+            r#"[
+                (class_definition
+                    body: (block . (expression_statement (string) @{DOCSTRING}))
+                )
+                (function_definition
+                    body: (block . (expression_statement (string) @{DOCSTRING}))
+                )
+            ] @{DOCSTRING_OWNER}"#
+        ))
+    }
+    fn module_doc_query(&self) -> Option<String> {
+        Some(format!(
+            r#"(module . (comment)* . (expression_statement (string) @{MODULE_DOC}))"#
+        ))
+    }
     fn find_function_parent(
         &self,
         node: TreeNode,
@@ -314,6 +331,40 @@ impl Stack for Python {
         ))
     }
 
+    fn lifecycle_hooks(&self) -> &'static [&'static str] {
+        &[
+            "__init__",
+            "__new__",
+            "__post_init__",
+            "__call__",
+            "__str__",
+            "__repr__",
+            "__eq__",
+            "__hash__",
+            "__len__",
+            "__iter__",
+            "__next__",
+            "__getitem__",
+            "__enter__",
+            "__exit__",
+            "setUp",
+            "tearDown",
+            "ready",
+        ]
+    }
+    // what the module lists in __all__, or without one its public module level names
+    fn is_exported(&self, nd: &NodeData, source: &str) -> bool {
+        match module_all(source) {
+            Some(names) => names.contains(&nd.name),
+            None => {
+                !nd.name.starts_with('_')
+                    && source
+                        .lines()
+                        .nth(nd.start)
+                        .is_some_and(|line| line.starts_with(|c: char| !c.is_whitespace()))
+            }
+        }
+    }
     fn is_test(&self, func_name: &str, _func_file: &str) -> bool {
         func_name.starts_with("test_")
     }
@@ -366,4 +417,127 @@ impl Stack for Python {
     fn use_handler_finder(&self) -> bool {
         true
     }
+    fn file_module(
+        &self,
+        file: &str,
+        _package: Option<&str>,
+        files: &std::collections::HashSet<String>,
+    ) -> Option<String> {
+        if !file.ends_with(".py") {
+            return None;
+        }
+        // a package is a directory with an __init__.py, nested in its parent package
+        let (mut dir, _) = file.rsplit_once('/')?;
+        let mut parts = Vec::new();
+        while files.contains(&format!("{}/__init__.py", dir)) {
+            let (parent, name) = dir.rsplit_once('/').unwrap_or(("", dir));
+            parts.push(name);
+            if parent.is_empty() {
+                break;
+            }
+            dir = parent;
+        }
+        if parts.is_empty() {
+            return None;
+        }
+        parts.reverse();
+        Some(parts.join("."))
+    }
+    fn is_module_root(&self, file: &str) -> bool {
+        file == "__init__.py" || file.ends_with("/__init__.py")
+    }
+    fn reexports_query(&self) -> Option<String> {
+        Some(format!(
+            r#"(import_from_statement
+                module_name: (_) @{IMPORTS_FROM}
+                name: (dotted_name) @{IMPORTS_NAME}
+            ) @{IMPORTS}
+            (import_from_statement
+                module_name: (_) @{IMPORTS_FROM}
+                (wildcard_import)
+            ) @{IMPORTS}"#
+        ))
+    }
+    fn import_module(
+        &self,
+        import_source: &str,
+        current_module: Option<&str>,
+        _current_file: &str,
+    ) -> Option<String> {
+        let rest = import_source.trim_start_matches('.');
+        let dots = import_source.len() - rest.len();
+        if dots == 0 {
+            return Some(import_source.to_string());
+        }
+        // `.x` is relative to the current package, every extra dot goes one level up
+        let mut base: Vec<&str> = current_module?.split('.').collect();
+        for _ in 1..dots {
+            base.pop();
+        }
+        if !rest.is_empty() {
+            base.push(rest);
+        }
+        if base.is_empty() {
+            return None;
+        }
+        Some(base.join("."))
+    }
+}
+
+// the names of every module level `__all__ = [...]` or `__all__ += [...]`, None when
+// the module never sets it
+fn module_all(source: &str) -> Option<Vec<String>> {
+    let mut names: Option<Vec<String>> = None;
+    let mut lines = source.lines();
+    while let Some(line) = lines.next() {
+        // indented assignments are not module level
+        let Some(rest) = line.strip_prefix("__all__") else {
+            continue;
+        };
+        let rest = rest.trim_start();
+        // `__all__: list[str] = ...`
+        let rest = match rest.strip_prefix(':') {
+            Some(annotated) => annotated.split_once('=').map(|(_, v)| v).unwrap_or(""),
+            None => match rest.strip_prefix("+=").or_else(|| rest.strip_prefix('=')) {
+                Some(value) if !value.starts_with('=') => value,
+                _ => continue,
+            },
+        };
+        let mut value = rest.to_string();
+        while bracket_depth(&value) > 0 {
+            let Some(next) = lines.next() else {
+                break;
+            };
+            value.push('\n');
+            value.push_str(next);
+        }
+        names
+            .get_or_insert_with(Vec::new)
+            .extend(string_literals(&value));
+    }
+    names
+}
+
+fn bracket_depth(value: &str) -> i32 {
+    value.chars().fold(0, |depth, c| match c {
+        '[' | '(' => depth + 1,
+        ']' | ')' => depth - 1,
+        _ => depth,
+    })
+}
+
+// quoted strings outside comments
+fn string_literals(value: &str) -> Vec<String> {
+    let mut literals = Vec::new();
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '#' => {
+                chars.by_ref().take_while(|c| *c != '\n').for_each(drop);
+            }
+            '"' | '\'' => literals.push(chars.by_ref().take_while(|q| *q != c).collect()),
+            _ => {}
+        }
+    }
+    literals
 }
@@ -173,6 +173,49 @@ impl Stack for ReactTs {
         }
         func_name.chars().next().unwrap().is_uppercase()
     }
+    fn enum_query(&self) -> Option<String> {
+        Some(super::typescript::enum_query())
+    }
+    fn interface_query(&self) -> Option<String> {
+        Some(super::typescript::interface_query())
+    }
+    fn type_alias_query(&self) -> Option<String> {
+        Some(super::typescript::type_alias_query())
+    }
+    fn const_query(&self) -> Option<String> {
+        Some(super::typescript::const_query())
+    }
+    fn module_separator(&self) -> &'static str {
+        "/"
+    }
+    fn file_module(
+        &self,
+        file: &str,
+        _package: Option<&str>,
+        _files: &std::collections::HashSet<String>,
+    ) -> Option<String> {
+        super::typescript::barrel_dir(file)
+    }
+    fn is_module_root(&self, file: &str) -> bool {
+        super::typescript::barrel_dir(file).is_some()
+    }
+    fn barrel_modules(&self) -> bool {
+        true
+    }
+    fn reexports_query(&self) -> Option<String> {
+        Some(super::typescript::reexports_query())
+    }
+    fn module_doc_query(&self) -> Option<String> {
+        Some(super::typescript::module_doc_query())
+    }
+    fn import_module(
+        &self,
+        import_source: &str,
+        _current_module: Option<&str>,
+        current_file: &str,
+    ) -> Option<String> {
+        super::typescript::relative_module_path(import_source, current_file)
+    }
     fn class_definition_query(&self) -> String {
         format!(
             "(class_declaration
@@ -511,6 +554,48 @@ impl Stack for ReactTs {
                         )
                     )
                 ) @{ROUTE}
+
+                ;; Matches: new WebSocket('wss://.../ws')
+                (new_expression
+                    constructor: (identifier) @{REQUEST_CALL} (#eq? @{REQUEST_CALL} "WebSocket")
+                    arguments: (arguments . [ (string) (template_string) ] @{ENDPOINT})
+                ) @{ROUTE}
+
+                ;; Matches: socket.emit('event', ...), this.socket.emit('event', ...), io.emit(...)
+                ;; only on socket-like receivers, so event emitters and streams are left out
+                (call_expression
+                    function: (member_expression
+                        object: [
+                            (identifier) @socket
+                            (member_expression property: (property_identifier) @socket)
+                        ]
+                        property: (property_identifier) @{REQUEST_CALL} (#eq? @{REQUEST_CALL} "emit")
+                    )
+                    arguments: (arguments . [ (string) (template_string) ] @{ENDPOINT})
+                    (#match? @socket "^(io|ws|socket|[A-Za-z_]*Socket)$")
+                ) @{ROUTE}
+
+                ;; Matches: consumer.subscriptions.create('ChatChannel') or create({{ channel: 'ChatChannel' }})
+                (call_expression
+                    function: (member_expression
+                        object: (member_expression
+                            property: (property_identifier) @subs (#eq? @subs "subscriptions")
+                        )
+                        property: (property_identifier) @{REQUEST_CALL} (#eq? @{REQUEST_CALL} "create")
+                    )
+                    arguments: (arguments
+                        .
+                        [
+                            (string) @{ENDPOINT}
+                            (object
+                                (pair
+                                    key: (property_identifier) @channel_key (#eq? @channel_key "channel")
+                                    value: (string) @{ENDPOINT}
+                                )
+                            )
+                        ]
+                    )
+                ) @{ROUTE}
             "#
         ))
     }
@@ -548,6 +633,8 @@ impl Stack for ReactTs {
                     "post" => inst.add_verb("POST"),
                     "put" => inst.add_verb("PUT"),
                     "delete" => inst.add_verb("DELETE"),
+                    "WebSocket" | "emit" => inst.add_verb("WS"),
+                    "create" | "on" if is_socket_call(&inst.body, call) => inst.add_verb("WS"),
                     "fetch" => {
                         inst.body.find("GET").map(|_| inst.add_verb("GET"));
                         inst.body.find("POST").map(|_| inst.add_verb("POST"));
@@ -571,7 +658,13 @@ impl Stack for ReactTs {
         ) {
             nd.name = endpoint_name_from_file(&nd.file);
         }
-        if let Some(verb) = nd.meta.get("verb") {
+        if nd.meta.get("verb").map(|v| v == "WS").unwrap_or(false) {
+            // socket events keep their callback name, or the event name if inline
+            if !nd.meta.contains_key("handler") {
+                let name = nd.name.clone();
+                nd.add_handler(&name);
+            }
+        } else if let Some(verb) = nd.meta.get("verb") {
             nd.meta.insert("handler".to_string(), verb.to_string());
         } else {
             nd.meta.insert("handler".to_string(), "GET".to_string());
@@ -588,7 +681,11 @@ impl Stack for ReactTs {
         _handler_params: HandlerParams,
     ) -> Vec<(NodeData, Option<Edge>)> {
         if let Some(verb) = endpoint.meta.get("verb") {
-            let handler_name = verb;
+            let handler_name = if verb == "WS" {
+                endpoint.meta.get("handler").unwrap_or(verb)
+            } else {
+                verb
+            };
             if let Some(handler_node) = find_fn(handler_name, &endpoint.file) {
                 let edge = Edge::handler(&endpoint, &handler_node);
                 return vec![(endpoint, Some(edge))];
@@ -865,6 +962,26 @@ impl Stack for ReactTs {
             || file_name.ends_with(".spec.js")
     }
 
+    fn lifecycle_hooks(&self) -> &'static [&'static str] {
+        &[
+            "constructor",
+            "render",
+            "componentDidMount",
+            "componentDidUpdate",
+            "componentWillUnmount",
+            "shouldComponentUpdate",
+            "getDerivedStateFromProps",
+            "getSnapshotBeforeUpdate",
+            "componentDidCatch",
+            "getServerSideProps",
+            "getStaticProps",
+            "getStaticPaths",
+            "generateMetadata",
+        ]
+    }
+    fn is_exported(&self, nd: &NodeData, source: &str) -> bool {
+        super::is_js_export(&nd.name, source)
+    }
     fn is_test(&self, _func_name: &str, func_file: &str) -> bool {
         if self.is_test_file(func_file) {
             true
@@ -873,6 +990,19 @@ impl Stack for ReactTs {
         }
     }
 }
+// `create`/`on` only count on a socket or an ActionCable consumer's subscriptions,
+// the receivers the emit query allows
+fn is_socket_call(body: &str, call: &str) -> bool {
+    let Some(end) = body.find(&format!(".{}(", call)) else {
+        return false;
+    };
+    let receiver = body[..end]
+        .rsplit(|c: char| !(c.is_alphanumeric() || c == '_'))
+        .next()
+        .unwrap_or("");
+    matches!(receiver, "io" | "ws" | "socket" | "subscriptions") || receiver.ends_with("Socket")
+}
+
 pub fn endpoint_name_from_file(file: &str) -> String {
     let path = file.replace('\\', "/");
     let route_path = if let Some(idx) = path.find("/api/") {
@@ -148,11 +148,34 @@ impl Stack for Ruby {
         )
     }
     fn endpoint_finders(&self) -> Vec<String> {
-        super::rails_routes::ruby_endpoint_finders_func()
+        let mut finders = super::rails_routes::ruby_endpoint_finders_func();
+        // ActionCable: class ChatChannel < ApplicationCable::Channel; def subscribed
+        finders.push(format!(
+            r#"(class
+                name: (constant) @{ENDPOINT}
+                superclass: (superclass
+                    (scope_resolution) @{REQUEST_CALL} (#eq? @{REQUEST_CALL} "ApplicationCable::Channel")
+                )
+                body: (body_statement
+                    (method
+                        name: (identifier) @{HANDLER} (#eq? @{HANDLER} "subscribed")
+                    )
+                )
+            ) @{ROUTE}"#
+        ));
+        finders
     }
     fn endpoint_path_filter(&self) -> Option<String> {
         Some("routes.rb".to_string())
     }
+    fn is_channel_file(&self, file_name: &str) -> bool {
+        file_name.contains("app/channels/") && file_name.ends_with("_channel.rb")
+    }
+    fn add_endpoint_verb(&self, nd: &mut NodeData, call: &Option<String>) {
+        if call.as_deref() == Some("ApplicationCable::Channel") {
+            nd.add_verb("WS");
+        }
+    }
     fn find_function_parent(
         &self,
         node: TreeNode,
@@ -247,6 +270,16 @@ impl Stack for Ruby {
         // "advisor_groups"
         models
     }
+    fn lifecycle_hooks(&self) -> &'static [&'static str] {
+        &[
+            "initialize",
+            "call",
+            "perform",
+            "method_missing",
+            "respond_to_missing?",
+            "to_s",
+        ]
+    }
     fn is_test(&self, _func_name: &str, func_file: &str) -> bool {
         self.is_test_file(func_file)
     }
@@ -317,6 +350,18 @@ impl Stack for Ruby {
             return Vec::new();
         }
 
+        // ActionCable channels: handler lives in the channel file itself
+        if endpoint.meta.get("verb").map(|v| v == "WS").unwrap_or(false) {
+            let handler = endpoint.meta.get("handler").unwrap();
+            return match find_fn(handler, &endpoint.file) {
+                Some(nd) => {
+                    let edge = Edge::handler(&endpoint, &nd);
+                    vec![(endpoint, Some(edge))]
+                }
+                None => vec![(endpoint, None)],
+            };
+        }
+
         let handler_string = endpoint.meta.get("handler").unwrap();
         // tracing::info!("handler_finder: {} {:?}", handler_string, params);
         let mut explicit_path = false;
@@ -11,6 +11,31 @@ impl Rust {
 }
 
 impl Stack for Rust {
+    // std trait methods the compiler calls for us (Display, From, Drop, Iterator...)
+    fn lifecycle_hooks(&self) -> &'static [&'static str] {
+        &[
+            "fmt",
+            "from",
+            "try_from",
+            "from_str",
+            "drop",
+            "default",
+            "clone",
+            "eq",
+            "cmp",
+            "partial_cmp",
+            "hash",
+            "deref",
+            "deref_mut",
+            "next",
+            "into_iter",
+            "as_ref",
+        ]
+    }
+    fn is_exported(&self, nd: &NodeData, _source: &str) -> bool {
+        // pub(crate) and friends stay inside the repo
+        nd.body.trim_start().starts_with("pub ")
+    }
     fn q(&self, q: &str, nt: &NodeType) -> Query {
         if matches!(nt, NodeType::Library) {
             Query::new(&tree_sitter_toml_ng::LANGUAGE.into(), q).unwrap()
@@ -114,6 +139,43 @@ impl Stack for Rust {
                 "#
         ))
     }
+    fn enum_query(&self) -> Option<String> {
+        Some(format!(
+            r#"
+            (enum_item
+                name: (type_identifier) @{TYPE_NAME}
+                body: (enum_variant_list) @{ENUM_VARIANTS}
+            ) @{TYPE_DEFINITION}
+            "#
+        ))
+    }
+    fn type_alias_query(&self) -> Option<String> {
+        Some(format!(
+            r#"
+            (type_item
+                name: (type_identifier) @{TYPE_NAME}
+            ) @{TYPE_DEFINITION}
+            "#
+        ))
+    }
+    fn const_query(&self) -> Option<String> {
+        Some(format!(
+            r#"
+            (source_file
+                [
+                    (const_item
+                        name: (identifier) @{VARIABLE_NAME}
+                        type: (_)? @{VARIABLE_TYPE}
+                    )
+                    (static_item
+                        name: (identifier) @{VARIABLE_NAME}
+                        type: (_)? @{VARIABLE_TYPE}
+                    )
+                ] @{VARIABLE_DECLARATION}
+            )
+            "#
+        ))
+    }
     fn trait_query(&self) -> Option<String> {
         Some(
             r#"
@@ -187,6 +249,16 @@ impl Stack for Rust {
             ] @{FUNCTION_COMMENT}
         "#))
     }
+    fn module_doc_query(&self) -> Option<String> {
+        Some(format!(
+            r#"(source_file
+                [
+                    (line_comment inner: (inner_doc_comment_marker))
+                    (block_comment inner: (inner_doc_comment_marker))
+                ] @{MODULE_DOC}
+            )"#
+        ))
+    }
     fn function_call_query(&self) -> String {
         format!(
             r#"
@@ -343,12 +415,115 @@ impl Stack for Rust {
         }
     }
 
+    fn is_websocket_handler(&self, handler: &NodeData) -> bool {
+        // axum: async fn ws_handler(ws: WebSocketUpgrade) -> impl IntoResponse
+        handler.body.contains("WebSocketUpgrade")
+    }
+
     fn resolve_import_path(&self, import_path: &str, _current_file: &str) -> String {
         let mut path = import_path.to_string();
         path = path.replace("::", "/");
         path
     }
+    fn module_query(&self) -> Option<String> {
+        Some(format!(
+            r#"(mod_item
+                name: (identifier) @{MODULE_NAME}
+                body: (declaration_list)
+            ) @{MODULE}"#
+        ))
+    }
+    fn module_separator(&self) -> &'static str {
+        "::"
+    }
+    fn file_module(
+        &self,
+        file: &str,
+        _package: Option<&str>,
+        _files: &std::collections::HashSet<String>,
+    ) -> Option<String> {
+        rust_module_path(file)
+    }
+    fn is_module_root(&self, file: &str) -> bool {
+        file.ends_with(".rs")
+    }
+    fn reexports_query(&self) -> Option<String> {
+        Some(format!(
+            r#"(use_declaration
+                (visibility_modifier)
+                argument: [
+                    (scoped_identifier
+                        path: (_) @{IMPORTS_FROM}
+                        name: (identifier) @{IMPORTS_NAME}
+                    )
+                    (scoped_use_list
+                        path: (_) @{IMPORTS_FROM}
+                        list: (use_list
+                            (identifier) @{IMPORTS_NAME}
+                        )
+                    )
+                    (use_wildcard
+                        (_) @{IMPORTS_FROM}
+                    )
+                ]
+            ) @{IMPORTS}"#
+        ))
+    }
+    fn import_module(
+        &self,
+        import_source: &str,
+        current_module: Option<&str>,
+        _current_file: &str,
+    ) -> Option<String> {
+        let mut parts: Vec<&str> = import_source
+            .split("::")
+            .map(str::trim)
+            .filter(|p| !p.is_empty())
+            .collect();
+        if parts.first() == Some(&"crate") {
+            return Some(parts.join("::"));
+        }
+        // self:: and super:: (and bare paths) are relative to the importing module
+        let mut base: Vec<&str> = current_module.unwrap_or("crate").split("::").collect();
+        while let Some(first) = parts.first() {
+            match *first {
+                "self" => {}
+                "super" => {
+                    base.pop();
+                }
+                _ => break,
+            }
+            parts.remove(0);
+        }
+        base.extend(parts);
+        if base.is_empty() {
+            return None;
+        }
+        Some(base.join("::"))
+    }
     fn filter_by_implements(&self) -> bool {
         true
     }
 }
+
+// crate::a::b for src/a/b.rs or src/a/b/mod.rs, crate for src/lib.rs and src/main.rs
+fn rust_module_path(file: &str) -> Option<String> {
+    let rel = match file.rfind("/src/") {
+        Some(i) => &file[i + "/src/".len()..],
+        None => file.strip_prefix("src/")?,
+    };
+    let mut parts: Vec<&str> = rel.strip_suffix(".rs")?.split('/').collect();
+    // every file under src/bin is its own crate
+    if parts.len() > 1 && parts[0] == "bin" {
+        return None;
+    }
+    if parts.last() == Some(&"mod") {
+        parts.pop();
+    }
+    if parts == ["lib"] || parts == ["main"] {
+        parts.clear();
+    }
+    let mut path = vec!["crate"];
+    path.extend(parts);
+    Some(path.join("::"))
+}
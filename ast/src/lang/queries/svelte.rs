@@ -148,6 +148,18 @@ impl Stack for Svelte {
         ))
     }
 
+    fn lifecycle_hooks(&self) -> &'static [&'static str] {
+        &[
+            "onMount",
+            "onDestroy",
+            "beforeUpdate",
+            "afterUpdate",
+            "load",
+        ]
+    }
+    fn is_exported(&self, nd: &NodeData, source: &str) -> bool {
+        super::is_js_export(&nd.name, source)
+    }
     fn is_test(&self, func_name: &str, _func_file: &str) -> bool {
         func_name.starts_with("test")
     }
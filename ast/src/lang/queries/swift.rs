@@ -45,6 +45,43 @@ impl Stack for Swift {
             "#
         ))
     }
+    fn enum_query(&self) -> Option<String> {
+        Some(format!(
+            r#"
+            (class_declaration
+                declaration_kind: "enum"
+                name: (type_identifier) @{TYPE_NAME}
+                body: (enum_class_body) @{ENUM_VARIANTS}
+            ) @{TYPE_DEFINITION}
+            "#
+        ))
+    }
+    fn type_alias_query(&self) -> Option<String> {
+        Some(format!(
+            r#"
+            (typealias_declaration
+                name: (type_identifier) @{TYPE_NAME}
+            ) @{TYPE_DEFINITION}
+            "#
+        ))
+    }
+    fn const_query(&self) -> Option<String> {
+        Some(format!(
+            r#"
+            (source_file
+                (property_declaration
+                    (value_binding_pattern
+                        mutability: "let"
+                    )
+                    name: (pattern
+                        bound_identifier: (simple_identifier) @{VARIABLE_NAME}
+                    )
+                    (type_annotation)? @{VARIABLE_TYPE}
+                )@{VARIABLE_DECLARATION}
+            )
+            "#
+        ))
+    }
     fn class_definition_query(&self) -> String {
         format!(
             r#"
@@ -189,6 +226,27 @@ impl Stack for Swift {
         ))
     }
 
+    fn lifecycle_hooks(&self) -> &'static [&'static str] {
+        &[
+            "init",
+            "deinit",
+            "viewDidLoad",
+            "viewWillAppear",
+            "viewDidAppear",
+            "viewWillDisappear",
+            "viewDidDisappear",
+            "viewDidLayoutSubviews",
+            "awakeFromNib",
+            "application",
+            "scene",
+            "body",
+        ]
+    }
+    fn is_exported(&self, nd: &NodeData, _source: &str) -> bool {
+        let head = nd.body.split(['(', '{']).next().unwrap_or_default();
+        head.split_whitespace()
+            .any(|w| w == "public" || w == "open")
+    }
     fn is_test(&self, func_name: &str, _func_file: &str) -> bool {
         func_name.starts_with("test")
     }
@@ -12,6 +12,12 @@ impl TypeScript {
 }
 
 impl Stack for TypeScript {
+    fn lifecycle_hooks(&self) -> &'static [&'static str] {
+        &["constructor"]
+    }
+    fn is_exported(&self, nd: &NodeData, source: &str) -> bool {
+        super::is_js_export(&nd.name, source)
+    }
     fn q(&self, q: &str, _nt: &NodeType) -> Query {
         Query::new(&self.0, q).unwrap()
     }
@@ -118,6 +124,18 @@ impl Stack for TypeScript {
         ))
     }
 
+    fn enum_query(&self) -> Option<String> {
+        Some(enum_query())
+    }
+    fn interface_query(&self) -> Option<String> {
+        Some(interface_query())
+    }
+    fn type_alias_query(&self) -> Option<String> {
+        Some(type_alias_query())
+    }
+    fn const_query(&self) -> Option<String> {
+        Some(const_query())
+    }
     fn class_definition_query(&self) -> String {
         format!(
             r#"
@@ -148,6 +166,9 @@ impl Stack for TypeScript {
     fn comment_query(&self) -> Option<String> {
         Some(format!(r#"(comment) @{FUNCTION_COMMENT}"#))
     }
+    fn module_doc_query(&self) -> Option<String> {
+        Some(module_doc_query())
+    }
 
     fn function_call_query(&self) -> String {
         format!(
@@ -169,8 +190,9 @@ impl Stack for TypeScript {
     }
 
     fn endpoint_finders(&self) -> Vec<String> {
-        vec![format!(
-            r#"(call_expression
+        vec![
+            format!(
+                r#"(call_expression
                 function: (member_expression
                     object: (identifier)
                     property: (property_identifier) @{ENDPOINT_VERB} (#match? @{ENDPOINT_VERB} "^get$|^post$|^put$|^delete$")
@@ -181,7 +203,9 @@ impl Stack for TypeScript {
                 )
                 ) @{ROUTE}
             "#
-        )]
+            ),
+            socket_io_endpoint_finder(),
+        ]
     }
     fn add_endpoint_verb(&self, inst: &mut NodeData, call: &Option<String>) {
         if let Some(c) = call {
@@ -190,6 +214,7 @@ impl Stack for TypeScript {
                 "post" => "POST",
                 "put" => "PUT",
                 "delete" => "DELETE",
+                "on" => "WS",
                 _ => "",
             };
 
@@ -198,6 +223,34 @@ impl Stack for TypeScript {
             }
         }
     }
+    fn update_endpoint(&self, nd: &mut NodeData, _call: &Option<String>) {
+        // inline socket event callbacks have no named handler
+        if nd.meta.get("verb").map(|v| v == "WS").unwrap_or(false)
+            && !nd.meta.contains_key("handler")
+        {
+            let name = nd.name.clone();
+            nd.add_handler(&name);
+        }
+    }
+    fn handler_finder(
+        &self,
+        endpoint: NodeData,
+        find_fn: &dyn Fn(&str, &str) -> Option<NodeData>,
+        _find_fns_in: &dyn Fn(&str) -> Vec<NodeData>,
+        _handler_params: HandlerParams,
+    ) -> Vec<(NodeData, Option<Edge>)> {
+        if let Some(handler) = endpoint.meta.get("handler") {
+            if let Some(nd) = find_fn(handler, &endpoint.file) {
+                let edge = Edge::handler(&endpoint, &nd);
+                return vec![(endpoint, Some(edge))];
+            }
+        }
+        // keep socket events even when the callback is inline
+        if endpoint.meta.get("verb").map(|v| v == "WS").unwrap_or(false) {
+            return vec![(endpoint, None)];
+        }
+        Vec::new()
+    }
 
     /*
     POSSIBLE QUERY FOR DATA MODEL that picks up interfaces without methods -- needs work
@@ -312,4 +365,182 @@ impl Stack for TypeScript {
 
         path
     }
+    fn module_separator(&self) -> &'static str {
+        "/"
+    }
+    fn file_module(
+        &self,
+        file: &str,
+        _package: Option<&str>,
+        _files: &std::collections::HashSet<String>,
+    ) -> Option<String> {
+        barrel_dir(file)
+    }
+    fn is_module_root(&self, file: &str) -> bool {
+        barrel_dir(file).is_some()
+    }
+    fn barrel_modules(&self) -> bool {
+        true
+    }
+    fn reexports_query(&self) -> Option<String> {
+        Some(reexports_query())
+    }
+    fn import_module(
+        &self,
+        import_source: &str,
+        _current_module: Option<&str>,
+        current_file: &str,
+    ) -> Option<String> {
+        relative_module_path(import_source, current_file)
+    }
+}
+
+// socket.io / ws: io.on('connection', ...), socket.on('event', handler)
+pub fn socket_io_endpoint_finder() -> String {
+    format!(
+        r#"(call_expression
+            function: (member_expression
+                object: (identifier) @socket (#match? @socket "^(io|socket|client|ws|nsp)$")
+                property: (property_identifier) @{REQUEST_CALL} (#eq? @{REQUEST_CALL} "on")
+            )
+            arguments: (arguments
+                .
+                (string) @{ENDPOINT}
+                .
+                [
+                    (identifier) @{HANDLER}
+                    (arrow_function)
+                    (function_expression)
+                ]
+            )
+        ) @{ROUTE}
+        "#
+    )
+}
+
+pub fn enum_query() -> String {
+    format!(
+        r#"(enum_declaration
+            name: (identifier) @{TYPE_NAME}
+            body: (enum_body) @{ENUM_VARIANTS}
+        ) @{TYPE_DEFINITION}
+        "#
+    )
+}
+
+pub fn interface_query() -> String {
+    format!(
+        r#"(interface_declaration
+            name: (type_identifier) @{TYPE_NAME}
+        ) @{TYPE_DEFINITION}
+        "#
+    )
+}
+
+pub fn type_alias_query() -> String {
+    format!(
+        r#"(type_alias_declaration
+            name: (type_identifier) @{TYPE_NAME}
+        ) @{TYPE_DEFINITION}
+        "#
+    )
+}
+
+// top-level `const X = <literal>`, exported or not
+pub fn const_query() -> String {
+    let types = "(string)(template_string)(number)(object)(array)(true)(false)(unary_expression)";
+    format!(
+        r#"(program
+            (lexical_declaration
+                kind: "const"
+                (variable_declarator
+                    name: (identifier) @{VARIABLE_NAME}
+                    type: (_)? @{VARIABLE_TYPE}
+                    value: [{types}]
+                )
+            ) @{VARIABLE_DECLARATION}
+        )
+        (program
+            (export_statement
+                declaration: (lexical_declaration
+                    kind: "const"
+                    (variable_declarator
+                        name: (identifier) @{VARIABLE_NAME}
+                        type: (_)? @{VARIABLE_TYPE}
+                        value: [{types}]
+                    )
+                )
+            ) @{VARIABLE_DECLARATION}
+        )
+        "#
+    )
+}
+
+// leading `/** @module */`, `@file` or `@packageDocumentation` block
+pub fn module_doc_query() -> String {
+    format!(
+        r#"(program . (comment) @{MODULE_DOC}
+            (#match? @{MODULE_DOC} "@(module|file|fileoverview|packageDocumentation)")
+        )"#
+    )
+}
+
+// `export { a, b } from './x'` and `export * from './x'`
+pub fn reexports_query() -> String {
+    format!(
+        r#"(export_statement
+            (export_clause
+                (export_specifier
+                    name: (identifier) @{IMPORTS_NAME}
+                )
+            )
+            source: (string) @{IMPORTS_FROM}
+        ) @{IMPORTS}
+        (export_statement
+            "*"
+            source: (string) @{IMPORTS_FROM}
+        ) @{IMPORTS}
+        "#
+    )
+}
+
+// an index file is the barrel for its directory
+pub fn barrel_dir(file: &str) -> Option<String> {
+    let (dir, name) = file.rsplit_once('/')?;
+    let (stem, ext) = name.rsplit_once('.')?;
+    let is_script = ["ts", "tsx", "js", "jsx", "mjs"].contains(&ext);
+    if stem == "index" && is_script && !dir.is_empty() {
+        Some(dir.to_string())
+    } else {
+        None
+    }
+}
+
+// `./x`, `../x/index.js` resolved against the importing file, without extension or index
+pub fn relative_module_path(source: &str, current_file: &str) -> Option<String> {
+    if !source.starts_with('.') {
+        return None;
+    }
+    let mut parts: Vec<&str> = current_file.split('/').collect();
+    parts.pop();
+    for seg in source.split('/') {
+        match seg {
+            "" | "." => {}
+            ".." => {
+                parts.pop()?;
+            }
+            seg => parts.push(seg),
+        }
+    }
+    let mut path = parts.join("/");
+    for ext in [".ts", ".tsx", ".js", ".jsx", ".mjs"] {
+        if let Some(p) = path.strip_suffix(ext) {
+            path = p.to_string();
+            break;
+        }
+    }
+    if let Some(p) = path.strip_suffix("/index") {
+        path = p.to_string();
+    }
+    Some(path)
 }
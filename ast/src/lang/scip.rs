@@ -0,0 +1,546 @@
+// the subset of the SCIP protobuf schema (github.com/sourcegraph/scip, scip.proto) that
+// stakgraph reads and writes, with a small hand written codec. field numbers follow the
+// schema, unknown fields are skipped when decoding
+use shared::{Error, Result};
+
+pub mod role {
+    pub const DEFINITION: i32 = 0x1;
+    pub const IMPORT: i32 = 0x2;
+    pub const WRITE_ACCESS: i32 = 0x4;
+    pub const READ_ACCESS: i32 = 0x8;
+    pub const GENERATED: i32 = 0x10;
+    pub const TEST: i32 = 0x20;
+}
+
+// SymbolInformation.Kind values for the node types stakgraph produces
+pub mod kind {
+    pub const CLASS: i32 = 7;
+    pub const CONSTANT: i32 = 8;
+    pub const ENUM: i32 = 11;
+    pub const FUNCTION: i32 = 17;
+    pub const INTERFACE: i32 = 21;
+    pub const METHOD: i32 = 26;
+    pub const MODULE: i32 = 29;
+    pub const STRUCT: i32 = 49;
+    pub const TRAIT: i32 = 53;
+    pub const TYPE_ALIAS: i32 = 55;
+    pub const VARIABLE: i32 = 61;
+}
+
+pub const TEXT_ENCODING_UTF8: i32 = 1;
+pub const POSITION_ENCODING_UTF8: i32 = 1;
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Index {
+    pub metadata: Metadata,
+    pub documents: Vec<Document>,
+    pub external_symbols: Vec<SymbolInformation>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Metadata {
+    pub version: i32,
+    pub tool_info: ToolInfo,
+    pub project_root: String,
+    pub text_document_encoding: i32,
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ToolInfo {
+    pub name: String,
+    pub version: String,
+    pub arguments: Vec<String>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Document {
+    pub relative_path: String,
+    pub occurrences: Vec<Occurrence>,
+    pub symbols: Vec<SymbolInformation>,
+    pub language: String,
+    pub position_encoding: i32,
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Occurrence {
+    // [start line, start char, end line, end char], or three items when on one line
+    pub range: Vec<i32>,
+    pub symbol: String,
+    pub symbol_roles: i32,
+    pub enclosing_range: Vec<i32>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SymbolInformation {
+    pub symbol: String,
+    pub documentation: Vec<String>,
+    pub relationships: Vec<Relationship>,
+    pub kind: i32,
+    pub display_name: String,
+    pub enclosing_symbol: String,
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Relationship {
+    pub symbol: String,
+    pub is_reference: bool,
+    pub is_implementation: bool,
+    pub is_type_definition: bool,
+    pub is_definition: bool,
+}
+
+impl Occurrence {
+    pub fn is_definition(&self) -> bool {
+        self.symbol_roles & role::DEFINITION != 0
+    }
+
+    // (start line, start char, end line, end char)
+    pub fn span(&self) -> Option<(i32, i32, i32, i32)> {
+        match self.range[..] {
+            [line, start, end] => Some((line, start, line, end)),
+            [start_line, start, end_line, end] => Some((start_line, start, end_line, end)),
+            _ => None,
+        }
+    }
+}
+
+const VARINT: u8 = 0;
+const FIXED64: u8 = 1;
+const LEN: u8 = 2;
+const FIXED32: u8 = 5;
+
+#[derive(Default)]
+struct Writer {
+    buf: Vec<u8>,
+}
+
+impl Writer {
+    fn varint(&mut self, mut v: u64) {
+        while v >= 0x80 {
+            self.buf.push((v as u8) | 0x80);
+            v >>= 7;
+        }
+        self.buf.push(v as u8);
+    }
+
+    fn key(&mut self, field: u32, wire: u8) {
+        self.varint(((field as u64) << 3) | wire as u64);
+    }
+
+    // proto3 leaves default values off the wire
+    fn int32(&mut self, field: u32, v: i32) {
+        if v != 0 {
+            self.key(field, VARINT);
+            self.varint(v as i64 as u64);
+        }
+    }
+
+    fn bool(&mut self, field: u32, v: bool) {
+        if v {
+            self.key(field, VARINT);
+            self.varint(1);
+        }
+    }
+
+    fn bytes(&mut self, field: u32, v: &[u8]) {
+        self.key(field, LEN);
+        self.varint(v.len() as u64);
+        self.buf.extend_from_slice(v);
+    }
+
+    fn string(&mut self, field: u32, v: &str) {
+        if !v.is_empty() {
+            self.bytes(field, v.as_bytes());
+        }
+    }
+
+    fn strings(&mut self, field: u32, v: &[String]) {
+        for s in v {
+            self.bytes(field, s.as_bytes());
+        }
+    }
+
+    fn packed_int32(&mut self, field: u32, v: &[i32]) {
+        if v.is_empty() {
+            return;
+        }
+        let mut packed = Writer::default();
+        for i in v {
+            packed.varint(*i as i64 as u64);
+        }
+        self.bytes(field, &packed.buf);
+    }
+
+    fn message(&mut self, field: u32, encode: impl FnOnce(&mut Writer)) {
+        let mut inner = Writer::default();
+        encode(&mut inner);
+        self.bytes(field, &inner.buf);
+    }
+}
+
+struct Reader<'a> {
+    buf: &'a [u8],
+    pos: usize,
+}
+
+enum Value<'a> {
+    Varint(u64),
+    Bytes(&'a [u8]),
+    Skipped,
+}
+
+impl<'a> Reader<'a> {
+    fn new(buf: &'a [u8]) -> Self {
+        Reader { buf, pos: 0 }
+    }
+
+    fn varint(&mut self) -> Result<u64> {
+        let mut v = 0u64;
+        for shift in (0..64).step_by(7) {
+            let byte = *self
+                .buf
+                .get(self.pos)
+                .ok_or_else(|| Error::Custom("scip: truncated varint".into()))?;
+            self.pos += 1;
+            v |= ((byte & 0x7f) as u64) << shift;
+            if byte & 0x80 == 0 {
+                return Ok(v);
+            }
+        }
+        Err(Error::Custom("scip: varint too long".into()))
+    }
+
+    fn take(&mut self, len: usize) -> Result<&'a [u8]> {
+        let end = self
+            .pos
+            .checked_add(len)
+            .filter(|end| *end <= self.buf.len())
+            .ok_or_else(|| Error::Custom("scip: truncated field".into()))?;
+        let out = &self.buf[self.pos..end];
+        self.pos = end;
+        Ok(out)
+    }
+
+    fn field(&mut self) -> Result<Option<(u32, Value<'a>)>> {
+        if self.pos >= self.buf.len() {
+            return Ok(None);
+        }
+        let key = self.varint()?;
+        let field = (key >> 3) as u32;
+        let value = match (key & 0x7) as u8 {
+            VARINT => Value::Varint(self.varint()?),
+            LEN => {
+                let len = self.varint()? as usize;
+                Value::Bytes(self.take(len)?)
+            }
+            FIXED64 => {
+                self.take(8)?;
+                Value::Skipped
+            }
+            FIXED32 => {
+                self.take(4)?;
+                Value::Skipped
+            }
+            wire => return Err(Error::Custom(format!("scip: unsupported wire type {wire}"))),
+        };
+        Ok(Some((field, value)))
+    }
+}
+
+impl Value<'_> {
+    fn int32(&self) -> i32 {
+        match self {
+            Value::Varint(v) => *v as i32,
+            _ => 0,
+        }
+    }
+
+    fn bool(&self) -> bool {
+        self.int32() != 0
+    }
+
+    fn string(&self) -> Result<String> {
+        match self {
+            Value::Bytes(b) => String::from_utf8(b.to_vec())
+                .map_err(|_| Error::Custom("scip: invalid utf-8 string".into())),
+            _ => Ok(String::new()),
+        }
+    }
+
+    fn bytes(&self) -> &[u8] {
+        match self {
+            Value::Bytes(b) => b,
+            _ => &[],
+        }
+    }
+
+    // repeated int32 may arrive packed or one value per field
+    fn push_int32s(&self, out: &mut Vec<i32>) -> Result<()> {
+        match self {
+            Value::Varint(v) => out.push(*v as i32),
+            Value::Bytes(b) => {
+                let mut r = Reader::new(b);
+                while r.pos < b.len() {
+                    out.push(r.varint()? as i32);
+                }
+            }
+            Value::Skipped => {}
+        }
+        Ok(())
+    }
+}
+
+impl Index {
+    pub fn encode(&self) -> Vec<u8> {
+        let mut w = Writer::default();
+        w.message(1, |w| self.metadata.encode(w));
+        for doc in &self.documents {
+            w.message(2, |w| doc.encode(w));
+        }
+        for sym in &self.external_symbols {
+            w.message(3, |w| sym.encode(w));
+        }
+        w.buf
+    }
+
+    pub fn decode(buf: &[u8]) -> Result<Self> {
+        let mut index = Index::default();
+        let mut r = Reader::new(buf);
+        while let Some((field, value)) = r.field()? {
+            match field {
+                1 => index.metadata = Metadata::decode(value.bytes())?,
+                2 => index.documents.push(Document::decode(value.bytes())?),
+                3 => index
+                    .external_symbols
+                    .push(SymbolInformation::decode(value.bytes())?),
+                _ => {}
+            }
+        }
+        Ok(index)
+    }
+}
+
+impl Metadata {
+    fn encode(&self, w: &mut Writer) {
+        w.int32(1, self.version);
+        w.message(2, |w| self.tool_info.encode(w));
+        w.string(3, &self.project_root);
+        w.int32(4, self.text_document_encoding);
+    }
+
+    fn decode(buf: &[u8]) -> Result<Self> {
+        let mut m = Metadata::default();
+        let mut r = Reader::new(buf);
+        while let Some((field, value)) = r.field()? {
+            match field {
+                1 => m.version = value.int32(),
+                2 => m.tool_info = ToolInfo::decode(value.bytes())?,
+                3 => m.project_root = value.string()?,
+                4 => m.text_document_encoding = value.int32(),
+                _ => {}
+            }
+        }
+        Ok(m)
+    }
+}
+
+impl ToolInfo {
+    fn encode(&self, w: &mut Writer) {
+        w.string(1, &self.name);
+        w.string(2, &self.version);
+        w.strings(3, &self.arguments);
+    }
+
+    fn decode(buf: &[u8]) -> Result<Self> {
+        let mut t = ToolInfo::default();
+        let mut r = Reader::new(buf);
+        while let Some((field, value)) = r.field()? {
+            match field {
+                1 => t.name = value.string()?,
+                2 => t.version = value.string()?,
+                3 => t.arguments.push(value.string()?),
+                _ => {}
+            }
+        }
+        Ok(t)
+    }
+}
+
+impl Document {
+    fn encode(&self, w: &mut Writer) {
+        w.string(1, &self.relative_path);
+        for occ in &self.occurrences {
+            w.message(2, |w| occ.encode(w));
+        }
+        for sym in &self.symbols {
+            w.message(3, |w| sym.encode(w));
+        }
+        w.string(4, &self.language);
+        w.int32(6, self.position_encoding);
+    }
+
+    fn decode(buf: &[u8]) -> Result<Self> {
+        let mut d = Document::default();
+        let mut r = Reader::new(buf);
+        while let Some((field, value)) = r.field()? {
+            match field {
+                1 => d.relative_path = value.string()?,
+                2 => d.occurrences.push(Occurrence::decode(value.bytes())?),
+                3 => d.symbols.push(SymbolInformation::decode(value.bytes())?),
+                4 => d.language = value.string()?,
+                6 => d.position_encoding = value.int32(),
+                _ => {}
+            }
+        }
+        Ok(d)
+    }
+}
+
+impl Occurrence {
+    fn encode(&self, w: &mut Writer) {
+        w.packed_int32(1, &self.range);
+        w.string(2, &self.symbol);
+        w.int32(3, self.symbol_roles);
+        w.packed_int32(7, &self.enclosing_range);
+    }
+
+    fn decode(buf: &[u8]) -> Result<Self> {
+        let mut o = Occurrence::default();
+        let mut r = Reader::new(buf);
+        while let Some((field, value)) = r.field()? {
+            match field {
+                1 => value.push_int32s(&mut o.range)?,
+                2 => o.symbol = value.string()?,
+                3 => o.symbol_roles = value.int32(),
+                7 => value.push_int32s(&mut o.enclosing_range)?,
+                _ => {}
+            }
+        }
+        Ok(o)
+    }
+}
+
+impl SymbolInformation {
+    fn encode(&self, w: &mut Writer) {
+        w.string(1, &self.symbol);
+        w.strings(3, &self.documentation);
+        for rel in &self.relationships {
+            w.message(4, |w| rel.encode(w));
+        }
+        w.int32(5, self.kind);
+        w.string(6, &self.display_name);
+        w.string(8, &self.enclosing_symbol);
+    }
+
+    fn decode(buf: &[u8]) -> Result<Self> {
+        let mut s = SymbolInformation::default();
+        let mut r = Reader::new(buf);
+        while let Some((field, value)) = r.field()? {
+            match field {
+                1 => s.symbol = value.string()?,
+                3 => s.documentation.push(value.string()?),
+                4 => s.relationships.push(Relationship::decode(value.bytes())?),
+                5 => s.kind = value.int32(),
+                6 => s.display_name = value.string()?,
+                8 => s.enclosing_symbol = value.string()?,
+                _ => {}
+            }
+        }
+        Ok(s)
+    }
+}
+
+impl Relationship {
+    fn encode(&self, w: &mut Writer) {
+        w.string(1, &self.symbol);
+        w.bool(2, self.is_reference);
+        w.bool(3, self.is_implementation);
+        w.bool(4, self.is_type_definition);
+        w.bool(5, self.is_definition);
+    }
+
+    fn decode(buf: &[u8]) -> Result<Self> {
+        let mut rel = Relationship::default();
+        let mut r = Reader::new(buf);
+        while let Some((field, value)) = r.field()? {
+            match field {
+                1 => rel.symbol = value.string()?,
+                2 => rel.is_reference = value.bool(),
+                3 => rel.is_implementation = value.bool(),
+                4 => rel.is_type_definition = value.bool(),
+                5 => rel.is_definition = value.bool(),
+                _ => {}
+            }
+        }
+        Ok(rel)
+    }
+}
+
+// a descriptor name is written bare when it only has identifier characters, otherwise
+// between backticks with inner backticks doubled
+pub fn escape_name(name: &str) -> String {
+    if !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_alphanumeric() || matches!(c, '_' | '+' | '-' | '$'))
+    {
+        name.to_string()
+    } else {
+        format!("`{}`", name.replace('`', "``"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scip_roundtrip() {
+        let index = Index {
+            metadata: Metadata {
+                version: 0,
+                tool_info: ToolInfo {
+                    name: "stakgraph".into(),
+                    version: "0.1.0".into(),
+                    arguments: vec!["--x".into()],
+                },
+                project_root: "file:///repo".into(),
+                text_document_encoding: TEXT_ENCODING_UTF8,
+            },
+            documents: vec![Document {
+                relative_path: "src/main.rs".into(),
+                occurrences: vec![Occurrence {
+                    range: vec![3, 4, 7],
+                    symbol: "stakgraph . . . `main.rs`/run().".into(),
+                    symbol_roles: role::DEFINITION,
+                    enclosing_range: vec![3, 0, 9, 1],
+                }],
+                symbols: vec![SymbolInformation {
+                    symbol: "stakgraph . . . `main.rs`/run().".into(),
+                    documentation: vec!["runs it".into()],
+                    relationships: vec![Relationship {
+                        symbol: "stakgraph . . . `main.rs`/Run#".into(),
+                        is_implementation: true,
+                        ..Default::default()
+                    }],
+                    kind: kind::FUNCTION,
+                    display_name: "run".into(),
+                    enclosing_symbol: String::new(),
+                }],
+                language: "rust".into(),
+                position_encoding: POSITION_ENCODING_UTF8,
+            }],
+            external_symbols: vec![],
+        };
+        let decoded = Index::decode(&index.encode()).unwrap();
+        assert_eq!(decoded, index);
+        assert_eq!(
+            decoded.documents[0].occurrences[0].span(),
+            Some((3, 4, 3, 7))
+        );
+
+        assert!(Index::decode(&[0x0a, 0x05, 0x01]).is_err());
+        assert_eq!(escape_name("run"), "run");
+        assert_eq!(escape_name("main.rs"), "`main.rs`");
+    }
+}
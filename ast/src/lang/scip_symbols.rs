@@ -0,0 +1,304 @@
+use super::scip::{Document, Index};
+use lsp::{strip_tmp, Cmd, CmdReceiver, Position, Res};
+use shared::{Context, Result};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use tracing::{debug, info};
+
+// default location of a precomputed index, relative to the repo root
+pub const DEFAULT_SCIP_INDEX: &str = "index.scip";
+
+#[derive(Clone, Debug)]
+struct Span {
+    line: u32,
+    start: u32,
+    end_line: u32,
+    end: u32,
+}
+
+impl Span {
+    fn from_range(range: &[i32]) -> Option<Self> {
+        let n = |i: usize| range.get(i).map(|v| *v.max(&0) as u32);
+        Some(match range.len() {
+            3 => Span {
+                line: n(0)?,
+                start: n(1)?,
+                end_line: n(0)?,
+                end: n(2)?,
+            },
+            4 => Span {
+                line: n(0)?,
+                start: n(1)?,
+                end_line: n(2)?,
+                end: n(3)?,
+            },
+            _ => return None,
+        })
+    }
+
+    fn contains(&self, line: u32, col: u32) -> bool {
+        (line, col) >= (self.line, self.start) && (line, col) <= (self.end_line, self.end)
+    }
+}
+
+// the symbol table of one or more SCIP indexes, answering the same questions the
+// language servers answer during the build: definition, implementation and hover
+#[derive(Debug, Default)]
+pub struct ScipSymbols {
+    // document path -> occurrences (span, symbol), sorted by position
+    occurrences: HashMap<String, Vec<(Span, String)>>,
+    // symbol -> (document path, span) of its definition
+    definitions: HashMap<String, (String, Span)>,
+    // symbol -> symbols that implement it
+    implementations: HashMap<String, Vec<String>>,
+    docs: HashMap<String, String>,
+}
+
+// `local` symbols are only unique within their document
+fn scoped(symbol: &str, path: &str) -> String {
+    if symbol.starts_with("local ") {
+        format!("{path}#{symbol}")
+    } else {
+        symbol.to_string()
+    }
+}
+
+impl ScipSymbols {
+    pub fn from_index(index: &Index) -> Self {
+        let mut table = ScipSymbols::default();
+        table.add_index(index);
+        table
+    }
+
+    pub fn load(paths: &[PathBuf]) -> Result<Self> {
+        let mut table = ScipSymbols::default();
+        for path in paths {
+            let bytes = std::fs::read(path)
+                .context(&format!("failed to read scip index {}", path.display()))?;
+            let index = Index::decode(&bytes)?;
+            info!(
+                "loaded scip index {} ({} documents)",
+                path.display(),
+                index.documents.len()
+            );
+            table.add_index(&index);
+        }
+        Ok(table)
+    }
+
+    fn add_index(&mut self, index: &Index) {
+        for doc in &index.documents {
+            self.add_document(doc);
+        }
+        for info in &index.external_symbols {
+            if !info.documentation.is_empty() {
+                self.docs
+                    .insert(info.symbol.clone(), info.documentation.join("\n"));
+            }
+        }
+    }
+
+    fn add_document(&mut self, doc: &Document) {
+        let path = doc.relative_path.trim_start_matches("./").to_string();
+        let occurrences = self.occurrences.entry(path.clone()).or_default();
+        for occ in &doc.occurrences {
+            let Some(span) = Span::from_range(&occ.range) else {
+                continue;
+            };
+            let symbol = scoped(&occ.symbol, &path);
+            if occ.is_definition() {
+                self.definitions
+                    .entry(symbol.clone())
+                    .or_insert_with(|| (path.clone(), span.clone()));
+            }
+            occurrences.push((span, symbol));
+        }
+        occurrences.sort_by_key(|(span, _)| (span.line, span.start));
+        for info in &doc.symbols {
+            let symbol = scoped(&info.symbol, &path);
+            if !info.documentation.is_empty() {
+                self.docs
+                    .insert(symbol.clone(), info.documentation.join("\n"));
+            }
+            for rel in info.relationships.iter().filter(|r| r.is_implementation) {
+                self.implementations
+                    .entry(scoped(&rel.symbol, &path))
+                    .or_default()
+                    .push(symbol.clone());
+            }
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.occurrences.is_empty()
+    }
+
+    // the symbol of the occurrence under (line, col), the narrowest one if they nest
+    pub fn symbol_at(&self, path: &str, line: u32, col: u32) -> Option<&str> {
+        self.occurrences
+            .get(path)?
+            .iter()
+            .filter(|(span, _)| span.contains(line, col))
+            .min_by_key(|(span, _)| (span.end_line - span.line, span.end.abs_diff(span.start)))
+            .map(|(_, symbol)| symbol.as_str())
+    }
+
+    // (document path, line, col) of the definition of the symbol at the position
+    pub fn definition(&self, path: &str, line: u32, col: u32) -> Option<(&str, u32, u32)> {
+        let symbol = self.symbol_at(path, line, col)?;
+        let (file, span) = self.definitions.get(symbol)?;
+        Some((file, span.line, span.start))
+    }
+
+    pub fn implementation(&self, path: &str, line: u32, col: u32) -> Option<(&str, u32, u32)> {
+        let symbol = self.symbol_at(path, line, col)?;
+        self.implementations
+            .get(symbol)?
+            .iter()
+            .find_map(|imp| self.definitions.get(imp))
+            .map(|(file, span)| (file.as_str(), span.line, span.start))
+    }
+
+    pub fn hover(&self, path: &str, line: u32, col: u32) -> Option<&str> {
+        let symbol = self.symbol_at(path, line, col)?;
+        self.docs.get(symbol).map(|s| s.as_str())
+    }
+}
+
+// index files for a repo: the configured ones, or index.scip at the root if it exists
+pub fn find_scip_indexes(root: &Path, configured: &[String]) -> Vec<PathBuf> {
+    if !configured.is_empty() {
+        return configured.iter().map(|p| root.join(p)).collect();
+    }
+    let default = root.join(DEFAULT_SCIP_INDEX);
+    if default.is_file() {
+        vec![default]
+    } else {
+        Vec::new()
+    }
+}
+
+// serves lsp commands from the scip table, so the build resolves definitions the same
+// way it does with a language server but without starting one
+pub fn spawn_scip_analyzer(root: &Path, symbols: ScipSymbols, mut cmd_rx: CmdReceiver) {
+    let relative_root = strip_tmp(root);
+    tokio::spawn(async move {
+        let to_path = |pos: &Position| {
+            pos.file
+                .strip_prefix(&relative_root)
+                .unwrap_or(&pos.file)
+                .display()
+                .to_string()
+        };
+        let to_pos = |(file, line, col): (&str, u32, u32)| Position {
+            file: relative_root.join(file),
+            line,
+            col,
+        };
+        while let Some((cmd, res_tx)) = cmd_rx.recv().await {
+            let res = match cmd {
+                Cmd::DidOpen(di) => Res::Opened(di.file.display().to_string()),
+                Cmd::GotoDefinition(pos) => Res::GotoDefinition(
+                    symbols
+                        .definition(&to_path(&pos), pos.line, pos.col)
+                        .map(to_pos),
+                ),
+                Cmd::GotoImplementations(pos) => Res::GotoImplementations(
+                    symbols
+                        .implementation(&to_path(&pos), pos.line, pos.col)
+                        .map(to_pos),
+                ),
+                Cmd::Hover(pos) => Res::Hover(
+                    symbols
+                        .hover(&to_path(&pos), pos.line, pos.col)
+                        .map(|s| s.to_string()),
+                ),
+                Cmd::Stop => break,
+            };
+            let _ = res_tx.send(res);
+        }
+        debug!("scip analyzer stopped");
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lang::scip::{role, Occurrence, Relationship, SymbolInformation};
+
+    fn occ(range: Vec<i32>, symbol: &str, roles: i32) -> Occurrence {
+        Occurrence {
+            range,
+            symbol: symbol.to_string(),
+            symbol_roles: roles,
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_scip_analyzer() {
+        let greet = "scip-go gomod example v1 `main.go`/greet().";
+        let iface = "scip-go gomod example v1 `main.go`/Greeter#Greet().";
+        let imp = "scip-go gomod example v1 `main.go`/English#Greet().";
+        let index = Index {
+            documents: vec![Document {
+                relative_path: "main.go".into(),
+                occurrences: vec![
+                    occ(vec![2, 5, 10], greet, role::DEFINITION),
+                    occ(vec![8, 1, 6], greet, 0),
+                    occ(vec![12, 10, 15], iface, role::DEFINITION),
+                    occ(vec![16, 20, 25], imp, role::DEFINITION),
+                    occ(vec![20, 4, 5], "local 0", role::DEFINITION),
+                    occ(vec![21, 8, 9], "local 0", 0),
+                ],
+                symbols: vec![
+                    SymbolInformation {
+                        symbol: greet.into(),
+                        documentation: vec!["says hi".into()],
+                        ..Default::default()
+                    },
+                    SymbolInformation {
+                        symbol: imp.into(),
+                        relationships: vec![Relationship {
+                            symbol: iface.into(),
+                            is_implementation: true,
+                            ..Default::default()
+                        }],
+                        ..Default::default()
+                    },
+                ],
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+        let symbols = ScipSymbols::from_index(&index);
+        assert_eq!(symbols.definition("main.go", 8, 3), Some(("main.go", 2, 5)));
+        assert_eq!(symbols.definition("main.go", 8, 7), None);
+        assert_eq!(
+            symbols.definition("main.go", 21, 8),
+            Some(("main.go", 20, 4))
+        );
+        assert_eq!(symbols.definition("other.go", 21, 8), None);
+
+        let (tx, rx) = tokio::sync::mpsc::channel(10);
+        spawn_scip_analyzer(Path::new("/tmp/example"), symbols, rx);
+        let res = Cmd::GotoDefinition(Position::new("example/main.go", 8, 1).unwrap())
+            .send(&tx)
+            .unwrap();
+        match res {
+            Res::GotoDefinition(Some(pos)) => {
+                assert_eq!(pos.file, PathBuf::from("example/main.go"));
+                assert_eq!((pos.line, pos.col), (2, 5));
+            }
+            other => panic!("unexpected {:?}", other),
+        }
+        let res = Cmd::GotoImplementations(Position::new("example/main.go", 12, 12).unwrap())
+            .send(&tx)
+            .unwrap();
+        assert!(matches!(res, Res::GotoImplementations(Some(pos)) if pos.line == 16));
+        let res = Cmd::Hover(Position::new("example/main.go", 2, 6).unwrap())
+            .send(&tx)
+            .unwrap();
+        assert!(matches!(res, Res::Hover(Some(h)) if h == "says hi"));
+    }
+}
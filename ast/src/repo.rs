@@ -1,6 +1,11 @@
 pub use crate::builder::progress::StatusUpdate;
+use crate::lang::graphs::architecture::Layer;
+use crate::lang::graphs::impact::DiffHunk;
+use crate::lang::graphs::clones;
+use crate::lang::graphs::importance;
 use crate::lang::graphs::Graph;
-use crate::lang::{linker, ArrayGraph, BTreeMapGraph, Lang};
+use crate::lang::scip_symbols::{find_scip_indexes, spawn_scip_analyzer, ScipSymbols};
+use crate::lang::{linker, ArrayGraph, BTreeMapGraph, IndexedGraph, Lang};
 #[cfg(feature = "neo4j")]
 use crate::builder::streaming::{GraphStreamingUploader, drain_deltas};
 #[cfg(feature = "neo4j")]
@@ -11,13 +16,21 @@ use lsp::language::{Language, PROGRAMMING_LANGUAGES};
 use lsp::{git::git_clone, spawn_analyzer, strip_tmp, CmdSender};
 use shared::{Context, Error, Result};
 use std::str::FromStr;
-use std::{fs, path::PathBuf};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
 use tokio::sync::broadcast::Sender;
 use tracing::{info, warn};
 use walkdir::{DirEntry, WalkDir};
 
 const CONF_FILE_PATH: &str = ".ast.json";
 
+// optional build passes are switched on with NAME=true or NAME=1
+fn env_flag(name: &str) -> bool {
+    std::env::var(name).is_ok_and(|v| v == "true" || v == "1")
+}
+
 pub async fn clone_repo(
     url: &str,
     path: &str,
@@ -60,7 +73,11 @@ impl Repos {
             repo.status_tx = Some(status_tx.clone());
         }
     }
+    // builds on the indexed backend, then hands back the plain BTreeMapGraph
     pub async fn build_graphs(&self) -> Result<BTreeMapGraph> {
+        Ok(self.build_graphs_indexed().await?.into_inner())
+    }
+    pub async fn build_graphs_indexed(&self) -> Result<IndexedGraph> {
         self.build_graphs_inner().await
     }
     pub async fn build_graphs_array(&self) -> Result<ArrayGraph> {
@@ -94,18 +111,34 @@ impl Repos {
         }
 
         if let Some(first_repo) = &self.0.get(0) {
-            first_repo.send_status_update("linking_graphs", 14);
+            first_repo.send_status_update("linking_graphs", 16);
         }
         info!("linking e2e tests");
         linker::link_e2e_tests(&mut graph)?;
         info!("linking api nodes");
         linker::link_api_nodes(&mut graph)?;
+        // before the flush below, so streamed uploads get the scores too
+        if env_flag("RANK_NODES") {
+            info!("ranking nodes");
+            importance::store_importance(&mut graph);
+        }
+        #[cfg_attr(not(feature = "neo4j"), allow(unused_variables))]
+        let clone_report = if env_flag("DETECT_CLONES") {
+            info!("detecting clones");
+            Some(clones::store_clones(&mut graph, clones::DEFAULT_THRESHOLD))
+        } else {
+            None
+        };
         #[cfg(feature = "neo4j")]
         if let Some((neo, uploader)) = &mut streaming {
             let (dn,de) = drain_deltas();
             if !(dn.is_empty() && de.is_empty()) {
                 let _ = uploader.flush_stage(neo, "cross_repo_linking", &dn, &de).await;
             }
+            // the streamed SIMILAR_TO edges carry no similarity
+            if let Some(report) = &clone_report {
+                let _ = neo.execute_batch(clones::scored_edge_queries(report)).await;
+            }
         }
 
         let (nodes_size, edges_size) = graph.get_graph_size();
@@ -123,6 +156,12 @@ pub struct AstConfig {
     pub only_include_files: Option<Vec<String>>,
     #[serde(skip_serializing_if = "Option::is_empty")]
     pub skip_file_ends: Option<Vec<String>>,
+    // precomputed scip indexes, relative to the repo root. defaults to index.scip
+    #[serde(skip_serializing_if = "Option::is_empty")]
+    pub scip_indexes: Option<Vec<String>>,
+    // architecture layers and what each may depend on
+    #[serde(skip_serializing_if = "Option::is_empty")]
+    pub layers: Option<Vec<Layer>>,
 }
 
 // actual config (merged with lang-specific configs)
@@ -346,6 +385,25 @@ impl Repo {
         Ok(())
     }
     fn start_lsp(root: &str, lang: &Lang, lsp: bool) -> Result<Option<CmdSender>> {
+        // a precomputed scip index answers the same lookups without a language server. it is
+        // only picked up when lsp is on, or when the config names indexes explicitly
+        let configured = Self::read_config_at(Path::new(root))
+            .and_then(|c| c.scip_indexes)
+            .unwrap_or_default();
+        let indexes = if lsp || !configured.is_empty() {
+            find_scip_indexes(Path::new(root), &configured)
+        } else {
+            Vec::new()
+        };
+        if !indexes.is_empty() {
+            let symbols = ScipSymbols::load(&indexes)?;
+            if !symbols.is_empty() {
+                info!("resolving with scip indexes {:?} instead of lsp", indexes);
+                let (tx, rx) = tokio::sync::mpsc::channel(10000);
+                spawn_scip_analyzer(Path::new(root), symbols, rx);
+                return Ok(Some(tx));
+            }
+        }
         Ok(if lsp {
             let (tx, rx) = tokio::sync::mpsc::channel(10000);
             spawn_analyzer(&root.into(), &lang.kind, rx)?;
@@ -397,8 +455,17 @@ impl Repo {
         let dirs = walk_dirs(&self.root, &conf)?;
         Ok(dirs)
     }
+    // the architecture layers declared in .ast.json
+    pub fn layers(&self) -> Vec<Layer> {
+        self.read_config_file()
+            .and_then(|c| c.layers)
+            .unwrap_or_default()
+    }
     fn read_config_file(&self) -> Option<AstConfig> {
-        let config_path = self.root.join(CONF_FILE_PATH);
+        Self::read_config_at(&self.root)
+    }
+    fn read_config_at(root: &Path) -> Option<AstConfig> {
+        let config_path = root.join(CONF_FILE_PATH);
         match std::fs::read_to_string(&config_path) {
             Ok(s) => match serde_json::from_str::<AstConfig>(&s) {
                 Ok(c) => Some(c),
@@ -665,6 +732,11 @@ pub fn check_revs_files(repo_path: &str, mut revs: Vec<String>) -> Option<Vec<St
     crate::gat::get_changed_files(repo_path, old_rev, new_rev).ok()
 }
 
+// hunks changed between two revisions, for impact::analyze
+pub fn changed_hunks(repo_path: &str, old_rev: &str, new_rev: &str) -> Result<Vec<DiffHunk>> {
+    crate::gat::get_changed_hunks(repo_path, old_rev, new_rev)
+}
+
 fn walk_files_arbitrary(dir: &PathBuf, directive: impl Fn(&str) -> bool) -> Result<Vec<String>> {
     let mut source_files: Vec<String> = Vec::new();
     for entry in WalkDir::new(dir).min_depth(1).into_iter() {
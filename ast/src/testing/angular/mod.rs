@@ -49,6 +49,10 @@ pub async fn test_angular_generic<G: Graph>() -> Result<()> {
     nodes += libraries.len();
     assert_eq!(libraries.len(), 26, "Expected 26 library node");
 
+    let uses = graph.count_edges_of_type(EdgeType::Uses);
+    edges += uses;
+    assert_eq!(uses, 1, "Expected 1 uses edge");
+
     let calls = graph.count_edges_of_type(EdgeType::Calls);
     edges += calls;
     assert_eq!(calls, 8, "Expected 8 call edges");
@@ -522,6 +526,12 @@ async fn test_angular() {
     test_angular_generic::<ArrayGraph>().await.unwrap();
     test_angular_generic::<BTreeMapGraph>().await.unwrap();
 
+    #[cfg(feature = "sqlite")]
+    {
+        use crate::lang::graphs::SqliteGraph;
+        test_angular_generic::<SqliteGraph>().await.unwrap();
+    }
+
     #[cfg(feature = "neo4j")]
     {
         use crate::lang::graphs::Neo4jGraph;
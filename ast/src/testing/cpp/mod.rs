@@ -243,6 +243,12 @@ async fn test_cpp() {
     test_cpp_generic::<ArrayGraph>().await.unwrap();
     test_cpp_generic::<BTreeMapGraph>().await.unwrap();
 
+    #[cfg(feature = "sqlite")]
+    {
+        use crate::lang::graphs::SqliteGraph;
+        test_cpp_generic::<SqliteGraph>().await.unwrap();
+    }
+
     #[cfg(feature = "neo4j")]
     {
         use crate::lang::graphs::Neo4jGraph;
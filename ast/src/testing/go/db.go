@@ -1,3 +1,4 @@
+// Package main serves the person api.
 package main
 
 import (
@@ -16,6 +17,7 @@ type database struct {
 // DB is the object
 var DB database
 
+// Person is a row in the people table.
 type Person struct {
 	ID    int    `json:"id"`
 	Name  string `json:"name"`
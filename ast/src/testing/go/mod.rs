@@ -43,7 +43,13 @@ pub async fn test_go_generic<G: Graph>() -> Result<()> {
 
     let libraries = graph.find_nodes_by_type(NodeType::Library);
     nodes_count += libraries.len();
-    assert_eq!(libraries.len(), 4, "Expected 4 library nodes");
+    assert_eq!(libraries.len(), 21, "Expected 21 library nodes");
+
+    let direct_libraries = libraries
+        .iter()
+        .filter(|l| l.meta.get("direct").map(String::as_str) == Some("true"))
+        .count();
+    assert_eq!(direct_libraries, 4, "Expected 4 direct libraries");
 
     let files = graph.find_nodes_by_type(NodeType::File);
     nodes_count += files.len();
@@ -272,7 +278,15 @@ pub async fn test_go_generic<G: Graph>() -> Result<()> {
 
     let contains = graph.count_edges_of_type(EdgeType::Contains);
     edges_count += contains;
-    assert_eq!(contains, 50, "Expected 50 contains edges");
+    assert_eq!(contains, 70, "Expected 70 contains edges");
+
+    let packages = graph.find_nodes_by_type(NodeType::Module);
+    nodes_count += packages.len();
+    assert_eq!(packages.len(), 1, "Expected 1 package");
+    assert_eq!(
+        packages[0].name, "src/testing/go",
+        "Package should be named by its directory"
+    );
 
     let variables = graph.find_nodes_by_type(NodeType::Var);
     nodes_count += variables.len();
@@ -287,7 +301,9 @@ pub async fn test_go_generic<G: Graph>() -> Result<()> {
     let uses = graph.count_edges_of_type(EdgeType::Uses);
     edges_count += uses;
     if use_lsp {
-        assert_eq!(uses, 50, "Expected 50 uses edges with lsp");
+        assert_eq!(uses, 56, "Expected 56 uses edges with lsp");
+    } else {
+        assert_eq!(uses, 6, "Expected 6 uses edges");
     }
 
     let handler_fn = graph
@@ -324,6 +340,26 @@ pub async fn test_go_generic<G: Graph>() -> Result<()> {
         "DB variable should have correct declaration"
     );
 
+    let db_file = graph
+        .find_nodes_by_type(NodeType::File)
+        .into_iter()
+        .find(|n| n.file.ends_with("go/db.go"))
+        .expect("db.go file not found");
+    assert_eq!(
+        db_file.docs.as_deref(),
+        Some("Package main serves the person api."),
+        "package comment should document the file"
+    );
+    let person = graph
+        .find_nodes_by_type(NodeType::DataModel)
+        .into_iter()
+        .find(|n| n.name == "Person")
+        .expect("Person data model not found");
+    assert_eq!(
+        person.docs.as_deref(),
+        Some("Person is a row in the people table.")
+    );
+
     let (nodes, edges) = graph.get_graph_size();
     assert_eq!(
         nodes as usize, nodes_count,
@@ -341,9 +377,16 @@ pub async fn test_go_generic<G: Graph>() -> Result<()> {
 
 #[test(tokio::test(flavor = "multi_thread", worker_threads = 2))]
 async fn test_go() {
-    use crate::lang::graphs::{ArrayGraph, BTreeMapGraph};
+    use crate::lang::graphs::{ArrayGraph, BTreeMapGraph, IndexedGraph};
     test_go_generic::<ArrayGraph>().await.unwrap();
     test_go_generic::<BTreeMapGraph>().await.unwrap();
+    test_go_generic::<IndexedGraph>().await.unwrap();
+
+    #[cfg(feature = "sqlite")]
+    {
+        use crate::lang::graphs::SqliteGraph;
+        test_go_generic::<SqliteGraph>().await.unwrap();
+    }
 
     #[cfg(feature = "neo4j")]
     {
@@ -353,3 +396,57 @@ async fn test_go() {
         test_go_generic::<Neo4jGraph>().await.unwrap();
     }
 }
+
+pub async fn test_go_package_generic<G: Graph>() -> Result<()> {
+    let repo = Repo::new(
+        "src/testing/go_package",
+        Lang::from_str("go").unwrap(),
+        false,
+        Vec::new(),
+        Vec::new(),
+    )?;
+    let graph = repo.build_graph_inner::<G>().await?;
+
+    let func = |name: &str| {
+        let nd = graph.find_nodes_by_name(NodeType::Function, name).pop();
+        Node::new(NodeType::Function, nd.expect(name))
+    };
+    let in_file = |node_type: NodeType, name: &str, file: &str| {
+        let nd = graph.find_node_by_name_in_file(node_type.clone(), name, file);
+        Node::new(node_type, nd.expect(name))
+    };
+
+    // same package, so MaxItems and Label need no import in handler.go
+    let status = "src/testing/go_package/status.go";
+    let max_items = in_file(NodeType::Const, "MaxItems", status);
+    let label = in_file(NodeType::TypeAlias, "Label", status);
+    assert!(graph.has_edge(&func("CheckOrder"), &max_items, EdgeType::Uses));
+    assert!(graph.has_edge(&func("Describe"), &label, EdgeType::Uses));
+
+    // and those are the only ones: the MaxItems of the other package is not visible
+    assert_eq!(graph.count_edges_of_type(EdgeType::Uses), 2);
+
+    Ok(())
+}
+
+#[test(tokio::test(flavor = "multi_thread", worker_threads = 2))]
+async fn test_go_package() {
+    use crate::lang::graphs::{ArrayGraph, BTreeMapGraph, IndexedGraph};
+    test_go_package_generic::<ArrayGraph>().await.unwrap();
+    test_go_package_generic::<BTreeMapGraph>().await.unwrap();
+    test_go_package_generic::<IndexedGraph>().await.unwrap();
+
+    #[cfg(feature = "sqlite")]
+    {
+        use crate::lang::graphs::SqliteGraph;
+        test_go_package_generic::<SqliteGraph>().await.unwrap();
+    }
+
+    #[cfg(feature = "neo4j")]
+    {
+        use crate::lang::graphs::Neo4jGraph;
+        let graph = Neo4jGraph::default();
+        graph.clear().await.unwrap();
+        test_go_package_generic::<Neo4jGraph>().await.unwrap();
+    }
+}
@@ -0,0 +1,9 @@
+package shop
+
+func CheckOrder(count int) bool {
+	return count <= MaxItems
+}
+
+func Describe(name string) Label {
+	return Label(name)
+}
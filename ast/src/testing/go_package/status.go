@@ -0,0 +1,6 @@
+package shop
+
+// MaxItems caps the size of an order
+const MaxItems = 10
+
+type Label = string
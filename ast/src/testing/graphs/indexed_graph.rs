@@ -0,0 +1,105 @@
+use super::queries::{function, service_graph};
+use crate::lang::graphs::{BTreeMapGraph, Graph, IndexedGraph, NodeType};
+use crate::lang::Lang;
+use crate::repo::Repo;
+use std::str::FromStr;
+
+// the indexed backend has to build exactly the graph BTreeMapGraph builds
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_indexed_graph_matches_btreemap() {
+    for (lang, root) in [
+        ("rust", "src/testing/rust"),
+        ("go", "src/testing/go"),
+        ("typescript", "src/testing/typescript"),
+    ] {
+        let repo = Repo::new(
+            root,
+            Lang::from_str(lang).unwrap(),
+            false,
+            Vec::new(),
+            Vec::new(),
+        )
+        .unwrap();
+        let btree = repo.build_graph_inner::<BTreeMapGraph>().await.unwrap();
+        let indexed = repo.build_graph_inner::<IndexedGraph>().await.unwrap();
+        assert_eq!(indexed.into_inner(), btree, "{lang}");
+    }
+}
+
+// the fixture plus an outer function around an inner one and a later one in src/util.rs,
+// after log (0-2) and unused (4)
+fn nested_graph() -> IndexedGraph {
+    let mut graph: IndexedGraph = service_graph();
+    let outer = format!("fn outer() {{\n{}}}", "    step();\n".repeat(19));
+    function(&mut graph, "outer", "src/util.rs", 10, &outer);
+    let inner = "fn inner() {\n    a();\n    b();\n}";
+    function(&mut graph, "inner", "src/util.rs", 15, inner);
+    let later = format!("fn later() {{\n{}}}", "    step();\n".repeat(9));
+    function(&mut graph, "later", "src/util.rs", 40, &later);
+    graph
+}
+
+#[test]
+fn test_indexed_range_lookups() {
+    let graph = nested_graph();
+    let btree = graph.inner().clone();
+    for file in ["src/util.rs", "src/routes.rs", "src/service.rs"] {
+        for row in [0, 1, 3, 4, 12, 13, 16, 19, 31, 35, 45, 51] {
+            assert_eq!(
+                graph.find_node_in_range(NodeType::Function, row, file),
+                btree.find_node_in_range(NodeType::Function, row, file),
+                "{file} row {row}"
+            );
+        }
+    }
+    // the innermost function wins
+    let at = |row| {
+        graph
+            .find_node_in_range(NodeType::Function, row, "src/util.rs")
+            .map(|n| n.name)
+    };
+    assert_eq!(at(16).as_deref(), Some("inner"));
+    assert_eq!(at(12).as_deref(), Some("outer"));
+    assert_eq!(at(35), None);
+    assert_eq!(
+        graph
+            .find_node_at(NodeType::Function, "src/util.rs", 40)
+            .map(|n| n.name),
+        Some("later".to_string())
+    );
+}
+
+#[test]
+fn test_indexed_name_and_file_lookups() {
+    let mut graph = nested_graph();
+    function(&mut graph, "log", "src/audit.rs", 5, "fn log() {}");
+    assert_eq!(graph.find_nodes_by_name(NodeType::Function, "log").len(), 2);
+    for file in ["util.rs", "src/audit.rs", "missing.rs"] {
+        assert_eq!(
+            graph.find_nodes_by_file_ends_with(NodeType::Function, file),
+            graph
+                .inner()
+                .find_nodes_by_file_ends_with(NodeType::Function, file),
+            "{file}"
+        );
+    }
+}
+
+#[test]
+fn test_indexed_updates() {
+    let mut graph = nested_graph();
+    // re-adding a key moves its interval
+    let later = format!("fn later() {{\n{}}}", "    step();\n".repeat(19));
+    function(&mut graph, "later", "src/util.rs", 40, &later);
+    assert!(graph
+        .find_node_in_range(NodeType::Function, 55, "src/util.rs")
+        .is_some());
+    // and removal drops it. no function has a parent class here
+    graph.filter_out_nodes_without_children(NodeType::Function, NodeType::Class, "parent");
+    assert!(graph
+        .find_nodes_by_name(NodeType::Function, "outer")
+        .is_empty());
+    assert!(graph
+        .find_node_in_range(NodeType::Function, 55, "src/util.rs")
+        .is_none());
+}
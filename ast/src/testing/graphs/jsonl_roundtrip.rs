@@ -0,0 +1,49 @@
+use crate::lang::graphs::BTreeMapGraph;
+use crate::lang::{ArrayGraph, Graph, Lang};
+use crate::repo::Repo;
+use crate::utils::read_jsonl;
+use serde_jsonlines::write_json_lines;
+use std::io::Write;
+use std::str::FromStr;
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_jsonl_roundtrip() {
+    let repo = Repo::new(
+        "src/testing/rust",
+        Lang::from_str("rust").unwrap(),
+        false,
+        Vec::new(),
+        Vec::new(),
+    )
+    .unwrap();
+    let graph = repo.build_graph_inner::<BTreeMapGraph>().await.unwrap();
+
+    let dir = std::env::temp_dir().join(uuid::Uuid::new_v4().to_string());
+    std::fs::create_dir_all(&dir).unwrap();
+    let root = dir.to_str().unwrap();
+    let nodes: Vec<_> = graph.nodes.values().collect();
+    write_json_lines(format!("{root}/rust-nodes.jsonl"), &nodes).unwrap();
+    let edges = graph.to_array_graph_edges();
+    write_json_lines(format!("{root}/rust-edges.jsonl"), &edges).unwrap();
+
+    let btree = read_jsonl::<BTreeMapGraph>(root, "rust", false).unwrap();
+    assert_eq!(btree.get_graph_keys(), graph.get_graph_keys());
+    let array = read_jsonl::<ArrayGraph>(root, "rust", false).unwrap();
+    assert_eq!(array.get_graph_size(), graph.get_graph_size());
+
+    // an edge to a node that is not in the dump
+    let mut dangling = edges[0].clone();
+    dangling.target.node_data.name = "missing_function".to_string();
+    let mut file = std::fs::OpenOptions::new()
+        .append(true)
+        .open(format!("{root}/rust-edges.jsonl"))
+        .unwrap();
+    writeln!(file, "{}", serde_json::to_string(&dangling).unwrap()).unwrap();
+
+    let err = read_jsonl::<BTreeMapGraph>(root, "rust", false).unwrap_err();
+    assert!(err.to_string().contains("missingfunction"), "{}", err);
+    let lenient = read_jsonl::<BTreeMapGraph>(root, "rust", true).unwrap();
+    assert_eq!(lenient.get_graph_size(), graph.get_graph_size());
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
@@ -1,4 +1,7 @@
 pub mod compare_graphs;
+pub mod indexed_graph;
+pub mod jsonl_roundtrip;
+pub mod queries;
 
 #[cfg(feature = "fulltest")]
 pub mod demorepo_test;
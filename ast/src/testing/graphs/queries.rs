@@ -0,0 +1,987 @@
+use crate::lang::graphs::call_graph::MAX_CALL_DEPTH;
+use crate::lang::graphs::clones::{find_clones, store_clones, CloneReport};
+use crate::lang::graphs::context_pack::{build_context_pack, ContextPack, Seed};
+use crate::lang::graphs::dead_code::{find_dead_code, DeadCodeReport, DeadKind};
+use crate::lang::graphs::impact::{analyze, parse_unified_diff, DiffHunk, ImpactReport};
+use crate::lang::graphs::importance::{store_importance, IN_DEGREE, OUT_DEGREE, PAGERANK};
+use crate::lang::graphs::{
+    ArrayGraph, BTreeMapGraph, Edge, EdgeType, Graph, IndexedGraph, Node, NodeRef, NodeType,
+};
+use crate::lang::NodeData;
+
+// a small service every backend builds the same way:
+//   POST /users -> create_user -> save_user -> audit, create_user and save_user -> log
+//   create_user and save_user use User, audit uses AuditEvent
+//   GET /health -> ping, and unused is called by nothing
+pub(super) fn service_graph<G: Graph>() -> G {
+    let mut graph = G::default();
+    let f = NodeType::Function;
+    let dm = NodeType::DataModel;
+    let mut create = NodeData::name_file("/users", "src/routes.rs");
+    create.add_verb("POST");
+    let mut health = NodeData::name_file("/health", "src/routes.rs");
+    health.add_verb("GET");
+    health.start = 10;
+    graph.add_node(NodeType::Endpoint, create.clone());
+    graph.add_node(NodeType::Endpoint, health.clone());
+
+    let create_user = function(
+        &mut graph,
+        "create_user",
+        "src/routes.rs",
+        2,
+        "fn create_user() {\n    save_user();\n    log();\n}",
+    );
+    let ping = function(
+        &mut graph,
+        "ping",
+        "src/routes.rs",
+        12,
+        "fn ping() -> &'static str {\n    \"ok\"\n}",
+    );
+    let save_user = function(
+        &mut graph,
+        "save_user",
+        "src/service.rs",
+        0,
+        "fn save_user() {\n    audit();\n    log();\n}",
+    );
+    let audit = function(
+        &mut graph,
+        "audit",
+        "src/audit.rs",
+        0,
+        "fn audit() {\n    AuditEvent::record();\n}",
+    );
+    let log = function(
+        &mut graph,
+        "log",
+        "src/util.rs",
+        0,
+        "fn log() {\n    eprintln!(\"called\");\n}",
+    );
+    function(&mut graph, "unused", "src/util.rs", 4, "fn unused() {}");
+    let user = NodeData::name_file("User", "src/models.rs");
+    let event = NodeData::name_file("AuditEvent", "src/models.rs");
+    graph.add_node(dm.clone(), user.clone());
+    graph.add_node(dm.clone(), event.clone());
+    let file = NodeData::name_file("routes.rs", "src/routes.rs");
+    graph.add_node(NodeType::File, file.clone());
+
+    graph.add_edge(Edge::handler(&create, &create_user));
+    graph.add_edge(Edge::handler(&health, &ping));
+    for (source, target) in [
+        (&create_user, &save_user),
+        (&create_user, &log),
+        (&save_user, &audit),
+        (&save_user, &log),
+    ] {
+        graph.add_edge(Edge::calls(f.clone(), source, f.clone(), target));
+    }
+    for (source, target) in [(&create_user, &user), (&save_user, &user), (&audit, &event)] {
+        graph.add_edge(Edge::contains(f.clone(), source, dm.clone(), target));
+    }
+    for nd in [&create_user, &ping] {
+        graph.add_edge(Edge::contains(NodeType::File, &file, f.clone(), nd));
+    }
+    graph
+}
+
+fn node<G: Graph>(graph: &G, node_type: NodeType, name: &str) -> Node {
+    let nd = graph
+        .find_nodes_by_name(node_type.clone(), name)
+        .pop()
+        .unwrap_or_else(|| panic!("{name} not found"));
+    Node::new(node_type, nd)
+}
+
+// a function spanning the lines of its body
+pub(super) fn function<G: Graph>(
+    graph: &mut G,
+    name: &str,
+    file: &str,
+    start: usize,
+    body: &str,
+) -> NodeData {
+    let mut nd = NodeData::name_file(name, file);
+    nd.start = start;
+    nd.end = start + body.lines().count().saturating_sub(1);
+    nd.body = body.to_string();
+    graph.add_node(NodeType::Function, nd.clone());
+    nd
+}
+
+fn names_of(graph: &BTreeMapGraph) -> Vec<String> {
+    let mut names: Vec<_> = graph
+        .find_all_nodes()
+        .into_iter()
+        .map(|n| n.node_data.name)
+        .collect();
+    names.sort();
+    names
+}
+
+fn check_subgraph<G: Graph>() {
+    let graph: G = service_graph();
+    let seed = node(&graph, NodeType::Function, "save_user");
+
+    // create_user -> log joins two nodes at 1 hop, so it is past the bound
+    let sub: BTreeMapGraph = graph.subgraph(&seed, 1, &[EdgeType::Calls], &[]);
+    assert_eq!(names_of(&sub), ["audit", "create_user", "log", "save_user"]);
+    assert_eq!(sub.get_graph_size(), (4, 3));
+
+    // the endpoint comes in over the handler edge at 2 hops, ping and /health do not
+    let sub: BTreeMapGraph = graph.subgraph(&seed, 2, &[EdgeType::Calls, EdgeType::Handler], &[]);
+    assert_eq!(
+        names_of(&sub),
+        ["/users", "audit", "create_user", "log", "save_user"]
+    );
+    assert_eq!(sub.get_graph_size(), (5, 5));
+
+    // create_user links User to more functions, but only data models are let in
+    let sub: BTreeMapGraph = graph.subgraph(&seed, 2, &[], &[NodeType::DataModel]);
+    assert_eq!(names_of(&sub), ["User", "save_user"]);
+    assert_eq!(sub.get_graph_size(), (2, 1));
+
+    let (nodes, edges) = graph.get_graph_size();
+    let sub: BTreeMapGraph = graph.subgraph(&seed, 1, &[], &[]);
+    let (sub_nodes, sub_edges) = sub.get_graph_size();
+    assert!(sub_nodes < nodes && sub_edges < edges);
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_subgraph() {
+    check_subgraph::<ArrayGraph>();
+    check_subgraph::<BTreeMapGraph>();
+    check_subgraph::<IndexedGraph>();
+
+    #[cfg(feature = "sqlite")]
+    {
+        use crate::lang::graphs::SqliteGraph;
+        check_subgraph::<SqliteGraph>();
+    }
+
+    #[cfg(feature = "neo4j")]
+    {
+        use crate::lang::graphs::Neo4jGraph;
+        let graph = Neo4jGraph::default();
+        graph.clear().await.unwrap();
+        check_subgraph::<Neo4jGraph>();
+    }
+}
+
+fn check_call_graph<G: Graph>() {
+    let graph: G = service_graph();
+    let log = node(&graph, NodeType::Function, "log");
+    let create_user = node(&graph, NodeType::Function, "create_user");
+    let users = node(&graph, NodeType::Endpoint, "/users");
+
+    let mut callers: Vec<_> = graph
+        .find_callers(&log.node_data)
+        .into_iter()
+        .map(|n| n.name)
+        .collect();
+    callers.sort();
+    assert_eq!(callers, ["create_user", "save_user"]);
+    // the endpoint calls its handler, the file holding it does not
+    let callers: Vec<_> = graph
+        .find_callers(&create_user.node_data)
+        .into_iter()
+        .map(|n| n.name)
+        .collect();
+    assert_eq!(callers, ["/users"]);
+
+    let distances = |found: Vec<(Node, usize)>| {
+        let mut found: Vec<_> = found
+            .into_iter()
+            .map(|(n, d)| (n.node_data.name, d))
+            .collect();
+        found.sort();
+        found
+    };
+    let up = distances(graph.transitive_callers(&log, 5));
+    let expected = [("/users", 2), ("create_user", 1), ("save_user", 1)];
+    assert_eq!(up, expected.map(|(n, d)| (n.to_string(), d)));
+    assert_eq!(graph.transitive_callers(&log, 1).len(), 2);
+
+    // a function using a data model is a hop too
+    let down = distances(graph.transitive_callees(&users, 2));
+    let expected = [
+        ("User", 2),
+        ("create_user", 1),
+        ("log", 2),
+        ("save_user", 2),
+    ];
+    assert_eq!(down, expected.map(|(n, d)| (n.to_string(), d)));
+
+    let event = node(&graph, NodeType::DataModel, "AuditEvent");
+    let path: Vec<_> = graph
+        .shortest_call_path(&users, &event)
+        .unwrap()
+        .into_iter()
+        .map(|n| n.node_data.name)
+        .collect();
+    assert_eq!(
+        path,
+        ["/users", "create_user", "save_user", "audit", "AuditEvent"]
+    );
+    assert!(graph.shortest_call_path(&event, &users).is_none());
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_call_graph_queries() {
+    check_call_graph::<ArrayGraph>();
+    check_call_graph::<BTreeMapGraph>();
+    check_call_graph::<IndexedGraph>();
+
+    #[cfg(feature = "sqlite")]
+    {
+        use crate::lang::graphs::SqliteGraph;
+        check_call_graph::<SqliteGraph>();
+    }
+
+    #[cfg(feature = "neo4j")]
+    {
+        use crate::lang::graphs::Neo4jGraph;
+        let graph = Neo4jGraph::default();
+        graph.clear().await.unwrap();
+        check_call_graph::<Neo4jGraph>();
+    }
+}
+
+fn check_importance<G: Graph>() {
+    let mut graph: G = service_graph();
+    store_importance(&mut graph);
+    let meta = |node_type: NodeType, name: &str| node(&graph, node_type, name).node_data.meta;
+    let rank = |name: &str| {
+        meta(NodeType::Function, name)[PAGERANK]
+            .parse::<f64>()
+            .unwrap()
+    };
+
+    // log is called from both steps of the create path, so it ranks highest
+    assert_eq!(rank("log"), 1.0);
+    assert!(rank("audit") < rank("log"));
+    assert_eq!(meta(NodeType::Function, "log")[IN_DEGREE], "2");
+    assert_eq!(meta(NodeType::Function, "save_user")[OUT_DEGREE], "2");
+    // handler edges rank, file containment and data model use do not
+    assert_eq!(meta(NodeType::Endpoint, "/users")[OUT_DEGREE], "1");
+    assert_eq!(meta(NodeType::Function, "create_user")[IN_DEGREE], "1");
+    assert!(!meta(NodeType::DataModel, "User").contains_key(PAGERANK));
+    assert!(!meta(NodeType::Function, "unused").contains_key(PAGERANK));
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_importance() {
+    check_importance::<ArrayGraph>();
+    check_importance::<BTreeMapGraph>();
+    check_importance::<IndexedGraph>();
+
+    #[cfg(feature = "sqlite")]
+    {
+        use crate::lang::graphs::SqliteGraph;
+        check_importance::<SqliteGraph>();
+    }
+
+    #[cfg(feature = "neo4j")]
+    {
+        use crate::lang::graphs::Neo4jGraph;
+        let graph = Neo4jGraph::default();
+        graph.clear().await.unwrap();
+        check_importance::<Neo4jGraph>();
+    }
+}
+
+fn check_trace_endpoints<G: Graph>() {
+    let mut graph: G = service_graph();
+    // a same-named function elsewhere, using a model whose name contains User
+    let legacy = NodeData::name_file("create_user", "src/legacy.rs");
+    let profile = NodeData::name_file("UserProfile", "src/models.rs");
+    graph.add_node(NodeType::Function, legacy.clone());
+    graph.add_node(NodeType::DataModel, profile.clone());
+    graph.add_edge(Edge::contains(
+        NodeType::Function,
+        &legacy,
+        NodeType::DataModel,
+        &profile,
+    ));
+
+    let report = graph.endpoint_data_model_report(MAX_CALL_DEPTH);
+    let endpoints: Vec<_> = report
+        .iter()
+        .map(|t| t.endpoint.node_data.name.as_str())
+        .collect();
+    assert_eq!(endpoints, ["/users", "/health"]);
+    let paths: Vec<Vec<&str>> = report[0]
+        .data_models
+        .iter()
+        .map(|t| t.path.iter().map(|n| n.node_data.name.as_str()).collect())
+        .collect();
+    // the handler uses User directly, so save_user using it too adds no second trace,
+    // and the uses of the other create_user are not the handler's
+    assert_eq!(
+        paths,
+        [
+            vec!["/users", "create_user", "User"],
+            vec!["/users", "create_user", "save_user", "audit", "AuditEvent"],
+        ]
+    );
+    assert!(report[1].data_models.is_empty());
+
+    // the data model counts as the last hop
+    let users = node(&graph, NodeType::Endpoint, "/users");
+    assert_eq!(graph.endpoint_data_models(&users, 2).len(), 1);
+    assert_eq!(graph.endpoint_data_models(&users, 4).len(), 2);
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_trace_endpoints() {
+    check_trace_endpoints::<ArrayGraph>();
+    check_trace_endpoints::<BTreeMapGraph>();
+    check_trace_endpoints::<IndexedGraph>();
+
+    #[cfg(feature = "sqlite")]
+    {
+        use crate::lang::graphs::SqliteGraph;
+        check_trace_endpoints::<SqliteGraph>();
+    }
+
+    #[cfg(feature = "neo4j")]
+    {
+        use crate::lang::graphs::Neo4jGraph;
+        let graph = Neo4jGraph::default();
+        graph.clear().await.unwrap();
+        check_trace_endpoints::<Neo4jGraph>();
+    }
+}
+
+fn dead_of(report: &DeadCodeReport) -> Vec<(&str, DeadKind)> {
+    report
+        .dead
+        .iter()
+        .map(|d| (d.node.node_data.name.as_str(), d.kind))
+        .collect()
+}
+
+fn check_dead_code<G: Graph>() {
+    let mut graph: G = service_graph();
+    // old_helper calls legacy, but nothing calls old_helper
+    let f = NodeType::Function;
+    let old = function(
+        &mut graph,
+        "old_helper",
+        "src/util.rs",
+        5,
+        "fn old_helper() {}",
+    );
+    let legacy = function(&mut graph, "legacy", "src/util.rs", 10, "fn legacy() {}");
+    graph.add_edge(Edge::calls(f.clone(), &old, f.clone(), &legacy));
+
+    // everything the endpoints reach is live
+    let report = find_dead_code(&graph);
+    assert_eq!(
+        dead_of(&report),
+        [
+            ("unused", DeadKind::Unused),
+            ("old_helper", DeadKind::Unused),
+            ("legacy", DeadKind::Unreachable),
+        ]
+    );
+    assert_eq!(report.of_kind(DeadKind::Unused).count(), 2);
+    assert!(report.in_path("src/routes.rs").dead.is_empty());
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_dead_code() {
+    check_dead_code::<ArrayGraph>();
+    check_dead_code::<BTreeMapGraph>();
+    check_dead_code::<IndexedGraph>();
+
+    #[cfg(feature = "sqlite")]
+    {
+        use crate::lang::graphs::SqliteGraph;
+        check_dead_code::<SqliteGraph>();
+    }
+
+    #[cfg(feature = "neo4j")]
+    {
+        use crate::lang::graphs::Neo4jGraph;
+        let graph = Neo4jGraph::default();
+        graph.clear().await.unwrap();
+        check_dead_code::<Neo4jGraph>();
+    }
+}
+
+fn check_dead_code_methods<G: Graph>() {
+    let mut graph: G = service_graph();
+    let save_user = node(&graph, NodeType::Function, "save_user").node_data;
+    let drop_all = function(
+        &mut graph,
+        "drop_all",
+        "src/service.rs",
+        10,
+        "fn drop_all() {}",
+    );
+    let get = function(&mut graph, "get", "src/cache.rs", 2, "fn get() {}");
+    let store = NodeData::name_file("Store", "src/service.rs");
+    let cache = NodeData::name_file("Cache", "src/cache.rs");
+    graph.add_node(NodeType::Class, store.clone());
+    graph.add_node(NodeType::Class, cache.clone());
+    for (class, method) in [(&store, &save_user), (&store, &drop_all), (&cache, &get)] {
+        graph.add_edge(Edge::new(
+            EdgeType::Operand,
+            NodeRef::from(class.into(), NodeType::Class),
+            NodeRef::from(method.into(), NodeType::Function),
+        ));
+    }
+
+    // Store is live through save_user, which does not keep drop_all alive
+    assert_eq!(
+        dead_of(&find_dead_code(&graph)),
+        [
+            ("Cache", DeadKind::Unused),
+            ("get", DeadKind::Unused),
+            ("drop_all", DeadKind::Unused),
+            ("unused", DeadKind::Unused),
+        ]
+    );
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_dead_code_methods() {
+    check_dead_code_methods::<ArrayGraph>();
+    check_dead_code_methods::<BTreeMapGraph>();
+    check_dead_code_methods::<IndexedGraph>();
+
+    #[cfg(feature = "sqlite")]
+    {
+        use crate::lang::graphs::SqliteGraph;
+        check_dead_code_methods::<SqliteGraph>();
+    }
+
+    #[cfg(feature = "neo4j")]
+    {
+        use crate::lang::graphs::Neo4jGraph;
+        let graph = Neo4jGraph::default();
+        graph.clear().await.unwrap();
+        check_dead_code_methods::<Neo4jGraph>();
+    }
+}
+
+fn check_dead_code_entrypoints<G: Graph>() {
+    let mut graph: G = service_graph();
+    let f = NodeType::Function;
+    let main = function(&mut graph, "main", "src/main.rs", 0, "fn main() {}");
+    let run = function(&mut graph, "run", "src/main.rs", 2, "fn run() {}");
+    graph.add_edge(Edge::calls(f.clone(), &main, f.clone(), &run));
+    // public api and trait hooks are called from outside the repo
+    function(&mut graph, "api", "src/lib.rs", 0, "pub fn api() {}");
+    function(&mut graph, "fmt", "src/lib.rs", 2, "fn fmt(&self) {}");
+    function(
+        &mut graph,
+        "shown",
+        "web/api.ts",
+        0,
+        "export function shown() {}",
+    );
+    function(
+        &mut graph,
+        "hidden",
+        "web/api.ts",
+        1,
+        "function hidden() {}",
+    );
+    let mut file = NodeData::name_file("api.ts", "web/api.ts");
+    file.body = "export function shown() {}\nfunction hidden() {}".to_string();
+    graph.add_node(NodeType::File, file);
+
+    assert_eq!(
+        dead_of(&find_dead_code(&graph)),
+        [("unused", DeadKind::Unused), ("hidden", DeadKind::Unused)]
+    );
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_dead_code_entrypoints() {
+    check_dead_code_entrypoints::<ArrayGraph>();
+    check_dead_code_entrypoints::<BTreeMapGraph>();
+    check_dead_code_entrypoints::<IndexedGraph>();
+
+    #[cfg(feature = "sqlite")]
+    {
+        use crate::lang::graphs::SqliteGraph;
+        check_dead_code_entrypoints::<SqliteGraph>();
+    }
+
+    #[cfg(feature = "neo4j")]
+    {
+        use crate::lang::graphs::Neo4jGraph;
+        let graph = Neo4jGraph::default();
+        graph.clear().await.unwrap();
+        check_dead_code_entrypoints::<Neo4jGraph>();
+    }
+}
+
+fn check_dead_code_python_exports<G: Graph>() {
+    let mut graph: G = service_graph();
+    let models = "\"\"\"see \"dump\" for writes\"\"\"\n__all__ = [\n    \"load\",  # the public api\n]\n\ndef load():\n    pass\n\ndef dump():\n    return {\"dump\": 1}\n";
+    let util = "def helper():\n    def inner():\n        pass\ndef _private():\n    pass\n";
+    for (file, name, body) in [
+        ("app/models.py", "models.py", models),
+        ("app/util.py", "util.py", util),
+    ] {
+        let mut nd = NodeData::name_file(name, file);
+        nd.body = body.to_string();
+        graph.add_node(NodeType::File, nd);
+    }
+    for (name, file, start) in [
+        ("load", "app/models.py", 5),
+        ("dump", "app/models.py", 8),
+        ("helper", "app/util.py", 0),
+        ("inner", "app/util.py", 1),
+        ("_private", "app/util.py", 3),
+    ] {
+        let mut nd = NodeData::name_file(name, file);
+        nd.start = start;
+        graph.add_node(NodeType::Function, nd);
+    }
+
+    assert_eq!(
+        dead_of(&find_dead_code(&graph)),
+        [
+            // only __all__ exports, a docstring or dict key naming it does not
+            ("dump", DeadKind::Unused),
+            // without __all__, public module level names are exported
+            ("inner", DeadKind::Unused),
+            ("_private", DeadKind::Unused),
+            ("unused", DeadKind::Unused),
+        ]
+    );
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_dead_code_python_exports() {
+    check_dead_code_python_exports::<ArrayGraph>();
+    check_dead_code_python_exports::<BTreeMapGraph>();
+    check_dead_code_python_exports::<IndexedGraph>();
+
+    #[cfg(feature = "sqlite")]
+    {
+        use crate::lang::graphs::SqliteGraph;
+        check_dead_code_python_exports::<SqliteGraph>();
+    }
+
+    #[cfg(feature = "neo4j")]
+    {
+        use crate::lang::graphs::Neo4jGraph;
+        let graph = Neo4jGraph::default();
+        graph.clear().await.unwrap();
+        check_dead_code_python_exports::<Neo4jGraph>();
+    }
+}
+
+fn packed(pack: &ContextPack) -> Vec<(&str, usize)> {
+    pack.items
+        .iter()
+        .map(|i| (i.name.as_str(), i.distance))
+        .collect()
+}
+
+fn check_context_pack<G: Graph>() {
+    let graph: G = service_graph();
+    // line 1 of service.rs is inside save_user
+    let seeds = [Seed::At {
+        file: "service.rs".to_string(),
+        line: 1,
+    }];
+    let pack = build_context_pack(&graph, &seeds, 8000, 1);
+    // the seed first, then its callees and its caller. data models have no body to pack
+    assert_eq!(
+        packed(&pack),
+        [
+            ("save_user", 0),
+            ("audit", 1),
+            ("log", 1),
+            ("create_user", 1)
+        ]
+    );
+    assert!(pack
+        .render()
+        .starts_with("// src/service.rs:1 Function save_user\nfn save_user() {"));
+
+    // two hops reach the endpoint, which has no body either
+    let pack = build_context_pack(&graph, &[Seed::Name("create_user".into())], 8000, 2);
+    assert_eq!(
+        packed(&pack),
+        [
+            ("create_user", 0),
+            ("log", 1),
+            ("save_user", 1),
+            ("audit", 2)
+        ]
+    );
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_context_pack() {
+    check_context_pack::<ArrayGraph>();
+    check_context_pack::<BTreeMapGraph>();
+    check_context_pack::<IndexedGraph>();
+
+    #[cfg(feature = "sqlite")]
+    {
+        use crate::lang::graphs::SqliteGraph;
+        check_context_pack::<SqliteGraph>();
+    }
+
+    #[cfg(feature = "neo4j")]
+    {
+        use crate::lang::graphs::Neo4jGraph;
+        let graph = Neo4jGraph::default();
+        graph.clear().await.unwrap();
+        check_context_pack::<Neo4jGraph>();
+    }
+}
+
+fn check_context_pack_budget<G: Graph>() {
+    let mut graph: G = service_graph();
+    let f = NodeType::Function;
+    let save_user = node(&graph, f.clone(), "save_user").node_data;
+    let body = format!(
+        "fn validate() {{\n{}}}",
+        "    check_field(\"name\", 1, 2, 3);\n".repeat(200)
+    );
+    let validate = function(&mut graph, "validate", "src/service.rs", 10, &body);
+    graph.add_edge(Edge::calls(f.clone(), &save_user, f, &validate));
+
+    let seeds = [Seed::Name("save_user".into())];
+    let pack = build_context_pack(&graph, &seeds, 200, 1);
+    assert!(pack.tokens <= 200);
+    // validate is too long to fit whole, so only its signature goes in
+    let validate = pack.items.iter().find(|i| i.name == "validate").unwrap();
+    assert!(validate.signature_only);
+    assert!(validate.text.ends_with("fn validate() { ..."));
+    assert!(pack
+        .items
+        .iter()
+        .all(|i| i.signature_only == (i.name == "validate")));
+    let pack = build_context_pack(&graph, &seeds, 8000, 1);
+    assert!(!pack.items.iter().any(|i| i.signature_only));
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_context_pack_budget() {
+    check_context_pack_budget::<ArrayGraph>();
+    check_context_pack_budget::<BTreeMapGraph>();
+    check_context_pack_budget::<IndexedGraph>();
+
+    #[cfg(feature = "sqlite")]
+    {
+        use crate::lang::graphs::SqliteGraph;
+        check_context_pack_budget::<SqliteGraph>();
+    }
+
+    #[cfg(feature = "neo4j")]
+    {
+        use crate::lang::graphs::Neo4jGraph;
+        let graph = Neo4jGraph::default();
+        graph.clear().await.unwrap();
+        check_context_pack_budget::<Neo4jGraph>();
+    }
+}
+
+// three totals, two of them the same loop renamed and reformatted, next to the
+// fixture's functions, which are all too short to compare
+fn clones_graph<G: Graph>() -> G {
+    let mut graph: G = service_graph();
+    function(
+        &mut graph,
+        "total",
+        "src/cart.rs",
+        0,
+        "fn total(items: &[Item]) -> u32 {
+    // only active items count
+    let mut sum = 0;
+    for item in items {
+        if item.active {
+            sum += item.price * item.count;
+        }
+    }
+    sum
+}",
+    );
+    function(
+        &mut graph,
+        "order_total",
+        "src/orders.rs",
+        0,
+        "fn order_total(lines: &[Line]) -> u32 {
+    let mut acc = 0;
+    for line in lines { if line.active { acc += line.price * line.count; } }
+    acc
+}",
+    );
+    function(
+        &mut graph,
+        "invoice_total",
+        "src/invoice.rs",
+        0,
+        "fn invoice_total(items: &[Item]) -> u32 {
+    let mut sum = 0;
+    for item in items {
+        if item.active {
+            sum += item.price * item.count;
+        }
+    }
+    log_total(sum);
+    sum
+}",
+    );
+    function(
+        &mut graph,
+        "render",
+        "src/view.rs",
+        0,
+        "fn render(view: &View) -> String {
+    let title = view.title.to_uppercase();
+    let body = view.sections.iter().map(|s| s.render()).collect::<Vec<_>>();
+    format!(\"{}\\n{}\", title, body.join(\"\\n\"))
+}",
+    );
+    graph
+}
+
+fn pairs_of(report: &CloneReport) -> Vec<(&str, &str, bool)> {
+    report
+        .pairs
+        .iter()
+        .map(|p| {
+            (
+                p.source.node_data.name.as_str(),
+                p.target.node_data.name.as_str(),
+                p.exact,
+            )
+        })
+        .collect()
+}
+
+fn check_clones<G: Graph>() {
+    let graph: G = clones_graph();
+    let report = find_clones(&graph, 0.5);
+    // renamed and reformatted is exact, an extra call is near
+    assert_eq!(
+        pairs_of(&report),
+        [
+            ("order_total", "total", true),
+            ("invoice_total", "order_total", false),
+            ("invoice_total", "total", false),
+        ]
+    );
+    assert!(report.pairs[1].similarity < 1.0 && report.pairs[1].similarity >= 0.5);
+    assert_eq!(
+        pairs_of(&find_clones(&graph, 0.99)),
+        [("order_total", "total", true)]
+    );
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_clones() {
+    check_clones::<ArrayGraph>();
+    check_clones::<BTreeMapGraph>();
+    check_clones::<IndexedGraph>();
+
+    #[cfg(feature = "sqlite")]
+    {
+        use crate::lang::graphs::SqliteGraph;
+        check_clones::<SqliteGraph>();
+    }
+
+    #[cfg(feature = "neo4j")]
+    {
+        use crate::lang::graphs::Neo4jGraph;
+        let graph = Neo4jGraph::default();
+        graph.clear().await.unwrap();
+        check_clones::<Neo4jGraph>();
+    }
+}
+
+fn check_store_clones<G: Graph>() {
+    let mut graph: G = clones_graph();
+    store_clones(&mut graph, 0.5);
+    assert_eq!(graph.count_edges_of_type(EdgeType::SimilarTo), 3);
+    let total = node(&graph, NodeType::Function, "total");
+    let order_total = node(&graph, NodeType::Function, "order_total");
+    assert!(graph.has_edge(&order_total, &total, EdgeType::SimilarTo));
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_store_clones() {
+    check_store_clones::<ArrayGraph>();
+    check_store_clones::<BTreeMapGraph>();
+    check_store_clones::<IndexedGraph>();
+
+    #[cfg(feature = "sqlite")]
+    {
+        use crate::lang::graphs::SqliteGraph;
+        check_store_clones::<SqliteGraph>();
+    }
+
+    #[cfg(feature = "neo4j")]
+    {
+        use crate::lang::graphs::Neo4jGraph;
+        let graph = Neo4jGraph::default();
+        graph.clear().await.unwrap();
+        check_store_clones::<Neo4jGraph>();
+    }
+}
+
+#[test]
+fn test_parse_unified_diff() {
+    let diff = "diff --git a/src/service.rs b/src/service.rs
+--- a/src/service.rs
++++ b/src/service.rs
+@@ -2,1 +2,2 @@ fn save_user() {
+ context
++added
+@@ -40,2 +41,0 @@
+-removed
+diff --git a/src/gone.rs b/src/gone.rs
+--- a/src/gone.rs
++++ /dev/null
+@@ -1,3 +0,0 @@
+";
+    let hunks = parse_unified_diff(diff);
+    // the deleted file has no new side to land in
+    assert_eq!(
+        hunks,
+        vec![
+            DiffHunk::from_new_side("src/service.rs", 2, 2),
+            DiffHunk::from_new_side("src/service.rs", 41, 0),
+        ]
+    );
+    // 0-based and inclusive, a pure deletion is pinned to the line before it
+    assert_eq!((hunks[0].start, hunks[0].end), (1, 2));
+    assert_eq!((hunks[1].start, hunks[1].end), (40, 40));
+}
+
+// tests for save_user and log, and a page rendering create_user
+fn impact_graph<G: Graph>() -> G {
+    let mut graph: G = service_graph();
+    let f = NodeType::Function;
+    let save_user = node(&graph, f.clone(), "save_user").node_data;
+    let log = node(&graph, f.clone(), "log").node_data;
+    let create_user = node(&graph, f.clone(), "create_user").node_data;
+    let test_save = NodeData::name_file("test_save_user", "tests/service.rs");
+    let test_log = NodeData::name_file("test_log", "tests/util.rs");
+    let signup = NodeData::name_file("Signup", "web/signup.tsx");
+    graph.add_node(NodeType::UnitTest, test_save.clone());
+    graph.add_node(NodeType::UnitTest, test_log.clone());
+    graph.add_node(NodeType::Page, signup.clone());
+    graph.add_edge(Edge::test_calls(
+        NodeType::UnitTest,
+        &test_save,
+        f.clone(),
+        &save_user,
+    ));
+    graph.add_edge(Edge::test_calls(NodeType::UnitTest, &test_log, f, &log));
+    graph.add_edge(Edge::renders(&signup, &create_user));
+    graph
+}
+
+fn affected_of(report: &ImpactReport) -> Vec<(&str, usize)> {
+    report
+        .affected
+        .iter()
+        .map(|a| (a.node.node_data.name.as_str(), a.distance))
+        .collect()
+}
+
+fn check_impact<G: Graph>() {
+    let graph: G = impact_graph();
+    // a change inside save_user
+    let hunks = [DiffHunk::from_new_side("src/service.rs", 2, 2)];
+    let report = analyze(&graph, &hunks, 5);
+    let changed: Vec<_> = report
+        .changed
+        .iter()
+        .map(|n| n.node_data.name.as_str())
+        .collect();
+    assert_eq!(changed, ["save_user"]);
+    // the endpoint and page come in through create_user, log's test is not affected
+    assert_eq!(
+        affected_of(&report),
+        [("test_save_user", 1), ("/users", 2), ("Signup", 2)]
+    );
+    let tests: Vec<_> = report
+        .tests()
+        .map(|a| a.node.node_data.name.as_str())
+        .collect();
+    assert_eq!(tests, ["test_save_user"]);
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_impact() {
+    check_impact::<ArrayGraph>();
+    check_impact::<BTreeMapGraph>();
+    check_impact::<IndexedGraph>();
+
+    #[cfg(feature = "sqlite")]
+    {
+        use crate::lang::graphs::SqliteGraph;
+        check_impact::<SqliteGraph>();
+    }
+
+    #[cfg(feature = "neo4j")]
+    {
+        use crate::lang::graphs::Neo4jGraph;
+        let graph = Neo4jGraph::default();
+        graph.clear().await.unwrap();
+        check_impact::<Neo4jGraph>();
+    }
+}
+
+fn check_impact_depth<G: Graph>() {
+    let graph: G = impact_graph();
+    let hunks = [DiffHunk::from_new_side("src/service.rs", 2, 2)];
+    // one hop only reaches the direct test
+    assert_eq!(
+        affected_of(&analyze(&graph, &hunks, 1)),
+        [("test_save_user", 1)]
+    );
+    // log is called from save_user too, so save_user's test sees its change
+    let hunks = [DiffHunk::from_new_side("src/util.rs", 2, 1)];
+    let report = analyze(&graph, &hunks, 5);
+    assert_eq!(
+        affected_of(&report),
+        [
+            ("test_log", 1),
+            ("/users", 2),
+            ("Signup", 2),
+            ("test_save_user", 2),
+        ]
+    );
+    // a hunk past every function changes nothing
+    let hunks = [DiffHunk::from_new_side("src/util.rs", 40, 0)];
+    assert!(analyze(&graph, &hunks, 5).changed.is_empty());
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_impact_depth() {
+    check_impact_depth::<ArrayGraph>();
+    check_impact_depth::<BTreeMapGraph>();
+    check_impact_depth::<IndexedGraph>();
+
+    #[cfg(feature = "sqlite")]
+    {
+        use crate::lang::graphs::SqliteGraph;
+        check_impact_depth::<SqliteGraph>();
+    }
+
+    #[cfg(feature = "neo4j")]
+    {
+        use crate::lang::graphs::Neo4jGraph;
+        let graph = Neo4jGraph::default();
+        graph.clear().await.unwrap();
+        check_impact_depth::<Neo4jGraph>();
+    }
+}
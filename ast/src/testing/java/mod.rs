@@ -121,7 +121,7 @@ import java.util.Optional;"#
 
     let import_edges_count = graph.count_edges_of_type(EdgeType::Imports);
     edges_count += import_edges_count;
-    assert_eq!(import_edges_count, 3, "Expected at 3 import edges");
+    assert_eq!(import_edges_count, 4, "Expected at 4 import edges");
 
     let instances = graph.find_nodes_by_type(NodeType::Instance);
     nodes_count += instances.len();
@@ -133,7 +133,17 @@ import java.util.Optional;"#
 
     let contains_edges_count = graph.count_edges_of_type(EdgeType::Contains);
     edges_count += contains_edges_count;
-    assert_eq!(contains_edges_count, 50, "Expected 50 contains edges");
+    assert_eq!(contains_edges_count, 57, "Expected 57 contains edges");
+
+    let packages = graph.find_nodes_by_type(NodeType::Module);
+    nodes_count += packages.len();
+    assert_eq!(packages.len(), 4, "Expected 4 packages");
+    assert!(
+        packages
+            .iter()
+            .any(|p| p.name == "graph.stakgraph.java.model"),
+        "Expected graph.stakgraph.java.model package"
+    );
 
     let handler_edges_count = graph.count_edges_of_type(EdgeType::Handler);
     edges_count += handler_edges_count;
@@ -225,6 +235,12 @@ async fn test_java() {
     test_java_generic::<ArrayGraph>().await.unwrap();
     test_java_generic::<BTreeMapGraph>().await.unwrap();
 
+    #[cfg(feature = "sqlite")]
+    {
+        use crate::lang::graphs::SqliteGraph;
+        test_java_generic::<SqliteGraph>().await.unwrap();
+    }
+
     #[cfg(feature = "neo4j")]
     {
         use crate::lang::graphs::Neo4jGraph;
@@ -633,6 +633,12 @@ async fn test_kotlin() {
     test_kotlin_generic::<ArrayGraph>().await.unwrap();
     test_kotlin_generic::<BTreeMapGraph>().await.unwrap();
 
+    #[cfg(feature = "sqlite")]
+    {
+        use crate::lang::graphs::SqliteGraph;
+        test_kotlin_generic::<SqliteGraph>().await.unwrap();
+    }
+
     #[cfg(feature = "neo4j")]
     {
         use crate::lang::graphs::Neo4jGraph;
@@ -219,6 +219,14 @@ pub async fn test_nextjs_generic<G: Graph>() -> Result<()> {
     nodes += variables.len();
     assert_eq!(variables.len(), 8, "Expected 8 Variable nodes");
 
+    let interfaces = graph.find_nodes_by_type(NodeType::Interface);
+    nodes += interfaces.len();
+    assert_eq!(interfaces.len(), 3, "Expected 3 Interface nodes");
+
+    let constants = graph.find_nodes_by_type(NodeType::Const);
+    nodes += constants.len();
+    assert_eq!(constants.len(), 5, "Expected 5 Const nodes");
+
     let libraries = graph.find_nodes_by_type(NodeType::Library);
     nodes += libraries.len();
     assert_eq!(libraries.len(), 18, "Expected 18 Library nodes");
@@ -229,7 +237,7 @@ pub async fn test_nextjs_generic<G: Graph>() -> Result<()> {
 
     let contains = graph.count_edges_of_type(EdgeType::Contains);
     edges += contains;
-    assert_eq!(contains, 146, "Expected 146 Contains edges");
+    assert_eq!(contains, 154, "Expected 154 Contains edges");
 
     let handlers = graph.count_edges_of_type(EdgeType::Handler);
     edges += handlers;
@@ -267,9 +275,9 @@ pub async fn test_nextjs_generic<G: Graph>() -> Result<()> {
     let uses = graph.count_edges_of_type(EdgeType::Uses);
     edges += uses;
     if use_lsp {
-        assert_eq!(uses, 44, "Expected 44 Uses edges with LSP");
+        assert_eq!(uses, 75, "Expected 75 Uses edges with LSP");
     } else {
-        assert_eq!(uses, 0, "Expected 0 Uses edge without LSP");
+        assert_eq!(uses, 31, "Expected 31 Uses edges without LSP");
     }
 
     if use_lsp {
@@ -843,6 +851,12 @@ async fn test_nextjs() {
     test_nextjs_generic::<ArrayGraph>().await.unwrap();
     test_nextjs_generic::<BTreeMapGraph>().await.unwrap();
 
+    #[cfg(feature = "sqlite")]
+    {
+        use crate::lang::graphs::SqliteGraph;
+        test_nextjs_generic::<SqliteGraph>().await.unwrap();
+    }
+
     #[cfg(feature = "neo4j")]
     {
         #[cfg(feature = "fulltest")]
@@ -30,7 +30,11 @@ def db_session():
 
 
 def get_person_by_id(db, person_id: int):
-    """Get a person by their ID"""
+    """Get a person by their ID
+
+    :param person_id: id of the person to look up
+    :returns: the person, or None
+    """
     return db.query(Person).filter(Person.id == person_id).first()
 
 
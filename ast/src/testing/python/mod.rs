@@ -58,7 +58,7 @@ pub async fn test_python_generic<G: Graph>() -> Result<()> {
     assert_eq!(implements, 1, "Expected 1 implements edges");
 
     let contains = graph.count_edges_of_type(EdgeType::Contains);
-    assert_eq!(contains, 102, "Expected 102 contains edges");
+    assert_eq!(contains, 111, "Expected 111 contains edges");
     edges_count += contains;
 
     let handlers = graph.count_edges_of_type(EdgeType::Handler);
@@ -159,7 +159,26 @@ from flask_app.routes import flask_bp"#
 
     let imported_edges = graph.count_edges_of_type(EdgeType::Imports);
     edges_count += imported_edges;
-    assert_eq!(imported_edges, 7, "Expected 7 import edges");
+    assert_eq!(imported_edges, 8, "Expected 8 import edges");
+
+    let modules = graph.find_nodes_by_type(NodeType::Module);
+    nodes_count += modules.len();
+    assert_eq!(modules.len(), 3, "Expected 3 packages");
+    let flask_app = modules
+        .iter()
+        .find(|m| m.name == "flask_app" && m.file == "src/testing/python/flask_app/__init__.py")
+        .map(|n| Node::new(NodeType::Module, n.clone()))
+        .expect("Package 'flask_app' not found");
+    let flask_routes = graph
+        .find_nodes_by_file_ends_with(NodeType::File, "src/testing/python/flask_app/routes.py")
+        .into_iter()
+        .next()
+        .map(|n| Node::new(NodeType::File, n))
+        .expect("flask_app/routes.py not found");
+    assert!(
+        graph.has_edge(&flask_app, &flask_routes, EdgeType::Contains),
+        "Expected flask_app to contain routes.py"
+    );
 
     let person_class = graph
         .find_nodes_by_name(NodeType::Class, "Person")
@@ -654,6 +673,39 @@ from flask_app.routes import flask_bp"#
         "Expected Django settings.py to contain DEBUG variable"
     );
 
+    let model_file = graph
+        .find_nodes_by_type(NodeType::File)
+        .into_iter()
+        .find(|n| n.file.ends_with("python/model.py"))
+        .expect("model.py file not found");
+    assert_eq!(
+        model_file.docs.as_deref(),
+        Some("SQLAlchemy and pydantic models for people."),
+        "module docstring should document the file"
+    );
+    let person_class = graph
+        .find_nodes_by_type(NodeType::Class)
+        .into_iter()
+        .find(|n| n.name == "Person")
+        .expect("Person class not found");
+    assert_eq!(
+        person_class.docs.as_deref(),
+        Some("Person model for storing user details")
+    );
+    let get_person = graph
+        .find_nodes_by_name(NodeType::Function, "get_person_by_id")
+        .into_iter()
+        .next()
+        .expect("get_person_by_id function not found");
+    assert_eq!(
+        get_person.meta.get("doc_params").map(String::as_str),
+        Some("person_id: id of the person to look up")
+    );
+    assert_eq!(
+        get_person.meta.get("doc_returns").map(String::as_str),
+        Some("the person, or None")
+    );
+
     let (nodes, edges) = graph.get_graph_size();
 
     assert_eq!(
@@ -676,6 +728,12 @@ async fn test_python() {
     test_python_generic::<ArrayGraph>().await.unwrap();
     test_python_generic::<BTreeMapGraph>().await.unwrap();
 
+    #[cfg(feature = "sqlite")]
+    {
+        use crate::lang::graphs::SqliteGraph;
+        test_python_generic::<SqliteGraph>().await.unwrap();
+    }
+
     #[cfg(feature = "neo4j")]
     {
         use crate::lang::graphs::Neo4jGraph;
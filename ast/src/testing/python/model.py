@@ -1,3 +1,5 @@
+"""SQLAlchemy and pydantic models for people."""
+
 from sqlalchemy import Column, Integer, String
 from database import Base
 from pydantic import BaseModel
@@ -351,6 +351,22 @@ import NewPerson from "./components/NewPerson";"#
     nodes_count += variables.len();
     assert_eq!(variables.len(), 5, "Expected 5 variables");
 
+    let enums = graph.find_nodes_by_type(NodeType::Enum);
+    nodes_count += enums.len();
+    assert_eq!(enums.len(), 1, "Expected 1 enum");
+
+    let interfaces = graph.find_nodes_by_type(NodeType::Interface);
+    nodes_count += interfaces.len();
+    assert_eq!(interfaces.len(), 1, "Expected 1 interface");
+
+    let type_aliases = graph.find_nodes_by_type(NodeType::TypeAlias);
+    nodes_count += type_aliases.len();
+    assert_eq!(type_aliases.len(), 1, "Expected 1 type alias");
+
+    let constants = graph.find_nodes_by_type(NodeType::Const);
+    nodes_count += constants.len();
+    assert_eq!(constants.len(), 5, "Expected 5 constants");
+
     let initial_state_var = variables
         .iter()
         .find(|v| v.name == "initialState")
@@ -490,8 +506,8 @@ import NewPerson from "./components/NewPerson";"#
     let contains_edges_count = graph.count_edges_of_type(EdgeType::Contains);
     edges_count += contains_edges_count;
     assert_eq!(
-        contains_edges_count, 68,
-        "Expected 68 contains edges, got {}",
+        contains_edges_count, 76,
+        "Expected 76 contains edges, got {}",
         contains_edges_count
     );
 
@@ -529,7 +545,9 @@ import NewPerson from "./components/NewPerson";"#
     edges_count += uses;
 
     if use_lsp {
-        assert_eq!(uses, 13, "Expected 13 uses edges");
+        assert_eq!(uses, 33, "Expected 33 uses edges");
+    } else {
+        assert_eq!(uses, 20, "Expected 20 uses edges");
     }
 
     let (nodes, edges) = graph.get_graph_size();
@@ -554,6 +572,12 @@ async fn test_react_typescript() {
         .await
         .unwrap();
 
+    #[cfg(feature = "sqlite")]
+    {
+        use crate::lang::graphs::SqliteGraph;
+        test_react_typescript_generic::<SqliteGraph>().await.unwrap();
+    }
+
     #[cfg(feature = "neo4j")]
     {
         use crate::lang::graphs::Neo4jGraph;
@@ -58,7 +58,13 @@ pub async fn test_ruby_generic<G: Graph>() -> Result<()> {
 
     let libraries = graph.find_nodes_by_type(NodeType::Library);
     nodes_count += libraries.len();
-    assert_eq!(libraries.len(), 5, "Expected 5 library nodes, got {}", libraries.len());
+    assert_eq!(libraries.len(), 66, "Expected 66 library nodes, got {}", libraries.len());
+
+    let direct_libraries = libraries
+        .iter()
+        .filter(|l| l.meta.get("direct").map(String::as_str) == Some("true"))
+        .count();
+    assert_eq!(direct_libraries, 5, "Expected 5 direct libraries");
 
     let pkg_files = graph.find_nodes_by_name(NodeType::File, "Gemfile");
     assert_eq!(pkg_files.len(), 1, "Expected 1 Gemfile");
@@ -258,14 +264,14 @@ pub async fn test_ruby_generic<G: Graph>() -> Result<()> {
     let uses = graph.count_edges_of_type(EdgeType::Uses);
     edges_count += uses;
     if use_lsp {
-    assert_eq!(uses, 14, "Expected 14 Uses edges, got {}", uses);
+    assert_eq!(uses, 130, "Expected 130 Uses edges, got {}", uses);
     } else {
-        assert_eq!(uses, 0, "Expected 0 Uses edges, got {}", uses);
+        assert_eq!(uses, 116, "Expected 116 Uses edges, got {}", uses);
     }
 
     let contains = graph.count_edges_of_type(EdgeType::Contains);
     edges_count += contains;
-    assert_eq!(contains, 139, "Expected 139 Contains edges, got {}", contains);
+    assert_eq!(contains, 200, "Expected 200 Contains edges, got {}", contains);
 
     let renders = graph.count_edges_of_type(EdgeType::Renders);
     edges_count += renders;
@@ -610,6 +616,12 @@ async fn test_ruby() {
     test_ruby_generic::<ArrayGraph>().await.unwrap();
     test_ruby_generic::<BTreeMapGraph>().await.unwrap();
 
+    #[cfg(feature = "sqlite")]
+    {
+        use crate::lang::graphs::SqliteGraph;
+        test_ruby_generic::<SqliteGraph>().await.unwrap();
+    }
+
     #[cfg(feature = "neo4j")]
     {
         use crate::lang::graphs::Neo4jGraph;
@@ -1,9 +1,12 @@
+//! Database access for the people table.
+
 use serde::{Deserialize, Serialize};
 use shared::{Context, Result};
 use sqlx::FromRow;
 use sqlx::{sqlite::SqlitePoolOptions, Pool, Sqlite};
 use std::sync::OnceLock;
 
+/// A person stored in the people table.
 #[derive(Debug, Serialize, Deserialize, FromRow)]
 pub struct Person {
     #[serde(skip_deserializing)]
@@ -22,6 +25,10 @@ async fn get_db() -> &'static Database {
     DB_INSTANCE.get().expect("Database not initialized")
 }
 
+/// Creates the in-memory pool and the people table.
+///
+/// # Errors
+/// Fails when the pool can't connect or the table can't be created.
 pub async fn init_db() -> Result<()> {
     let database_url = "sqlite::memory:";
     let pool = SqlitePoolOptions::new()
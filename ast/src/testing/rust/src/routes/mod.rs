@@ -1,3 +1,5 @@
 pub mod actix_routes;
 pub mod axum_routes;
 pub mod rocket_routes;
+
+pub use axum_routes::create_router;
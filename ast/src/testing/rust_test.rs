@@ -78,7 +78,7 @@ pub async fn test_rust_generic<G: Graph>() -> Result<()> {
 
     let imports = graph.find_nodes_by_type(NodeType::Import);
     nodes_count += imports.len();
-    assert_eq!(imports.len(), 5, "Expected 5 imports");
+    assert_eq!(imports.len(), 6, "Expected 6 imports");
 
     let traits = graph.find_nodes_by_type(NodeType::Trait);
     nodes_count += traits.len();
@@ -93,7 +93,13 @@ pub async fn test_rust_generic<G: Graph>() -> Result<()> {
     let libraries = graph.find_nodes_by_type(NodeType::Library);
     nodes_count += libraries.len();
 
-    assert_eq!(libraries.len(), 9, "Expected 9 library nodes");
+    assert_eq!(libraries.len(), 306, "Expected 306 library nodes");
+
+    let direct_libraries = libraries
+        .iter()
+        .filter(|l| l.meta.get("direct").map(String::as_str) == Some("true"))
+        .count();
+    assert_eq!(direct_libraries, 9, "Expected 9 direct libraries");
 
     let main_import_body = format!(
         r#"use crate::db::init_db;
@@ -118,6 +124,27 @@ use std::net::SocketAddr;"#
     nodes_count += vars.len();
     assert_eq!(vars.len(), 2, "Expected 2 variables");
 
+    let enums = graph.find_nodes_by_type(NodeType::Enum);
+    nodes_count += enums.len();
+    assert_eq!(enums.len(), 3, "Expected 3 enums");
+    let port_enum = enums
+        .iter()
+        .find(|e| e.name == "PORT")
+        .expect("PORT enum not found");
+    assert_eq!(
+        port_enum.meta.get("variants").map(String::as_str),
+        Some("Axum,Actix,Rocket"),
+        "Expected PORT variants"
+    );
+
+    let constants = graph.find_nodes_by_type(NodeType::Const);
+    nodes_count += constants.len();
+    assert_eq!(constants.len(), 2, "Expected 2 constants");
+
+    let uses = graph.count_edges_of_type(EdgeType::Uses);
+    edges_count += uses;
+    assert_eq!(uses, 910, "Expected 910 uses edges");
+
     let data_models = graph.find_nodes_by_type(NodeType::DataModel);
     nodes_count += data_models.len();
     assert_eq!(data_models.len(), 6, "Expected 6 data models");
@@ -192,7 +219,35 @@ use std::net::SocketAddr;"#
 
     let contains_edges = graph.count_edges_of_type(EdgeType::Contains);
     edges_count += contains_edges;
-    assert_eq!(contains_edges, 76, "Expected 76 contains edges");
+    assert_eq!(contains_edges, 392, "Expected 392 contains edges");
+
+    let modules = graph.find_nodes_by_type(NodeType::Module);
+    nodes_count += modules.len();
+    assert_eq!(modules.len(), 7, "Expected 7 modules");
+    assert!(
+        modules.iter().any(|m| m.name == "crate::routes::axum_routes"
+            && m.file.ends_with("src/testing/rust/src/routes/axum_routes.rs")),
+        "Expected crate::routes::axum_routes module"
+    );
+
+    let routes_module = modules
+        .iter()
+        .find(|m| m.name == "crate::routes")
+        .map(|n| Node::new(NodeType::Module, n.clone()))
+        .expect("Module 'crate::routes' not found");
+    let create_router = graph
+        .find_nodes_by_name(NodeType::Function, "create_router")
+        .into_iter()
+        .next()
+        .map(|n| Node::new(NodeType::Function, n))
+        .expect("Function 'create_router' not found");
+    let exports = graph.count_edges_of_type(EdgeType::Exports);
+    edges_count += exports;
+    assert_eq!(exports, 1, "Expected 1 re-export edge");
+    assert!(
+        graph.has_edge(&routes_module, &create_router, EdgeType::Exports),
+        "Expected crate::routes to re-export create_router"
+    );
 
     let functions = graph.find_nodes_by_type(NodeType::Function);
     nodes_count += functions.len();
@@ -337,6 +392,44 @@ use std::net::SocketAddr;"#
         "Expected 'init_db' function to use 'DB_INSTANCE' variable"
     );
 
+    let db_file = graph
+        .find_nodes_by_type(NodeType::File)
+        .into_iter()
+        .find(|n| n.file.ends_with("rust/src/db.rs"))
+        .expect("db.rs file not found");
+    assert_eq!(
+        db_file.docs.as_deref(),
+        Some("Database access for the people table."),
+        "crate docs should come from the //! comment"
+    );
+    let person = graph
+        .find_nodes_by_type(NodeType::DataModel)
+        .into_iter()
+        .find(|n| n.name == "Person" && n.file.ends_with("src/db.rs"))
+        .expect("Person data model not found");
+    assert_eq!(
+        person.docs.as_deref(),
+        Some("A person stored in the people table."),
+        "doc comment above #[derive] should attach to Person"
+    );
+    let init_db = graph
+        .find_nodes_by_name(NodeType::Function, "init_db")
+        .into_iter()
+        .next()
+        .expect("init_db function not found");
+    assert_eq!(
+        init_db.meta.get("doc_errors").map(String::as_str),
+        Some("Fails when the pool can't connect or the table can't be created."),
+        "# Errors section should be in meta"
+    );
+    // two nested ifs, no parameters
+    assert_eq!(
+        init_db.meta.get("cyclomatic_complexity").map(String::as_str),
+        Some("3")
+    );
+    assert_eq!(init_db.meta.get("nesting_depth").map(String::as_str), Some("2"));
+    assert_eq!(init_db.meta.get("parameter_count").map(String::as_str), Some("0"));
+
     let (nodes, edges) = graph.get_graph_size();
     assert_eq!(
         nodes as usize, nodes_count,
@@ -357,6 +450,18 @@ async fn test_rust() {
     test_rust_generic::<ArrayGraph>().await.unwrap();
     test_rust_generic::<BTreeMapGraph>().await.unwrap();
 
+    #[cfg(feature = "sqlite")]
+    {
+        use crate::lang::graphs::SqliteGraph;
+        test_rust_generic::<SqliteGraph>().await.unwrap();
+    }
+
+    #[cfg(feature = "helix")]
+    {
+        use crate::lang::graphs::HelixGraph;
+        test_rust_generic::<HelixGraph>().await.unwrap();
+    }
+
     #[cfg(feature = "neo4j")]
     {
         use crate::lang::graphs::Neo4jGraph;
@@ -365,3 +470,23 @@ async fn test_rust() {
         test_rust_generic::<Neo4jGraph>().await.unwrap();
     }
 }
+
+// needs a local helix instance with helixdb-cfg deployed
+#[cfg(feature = "helix")]
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+#[ignore]
+async fn test_rust_helix_publish() {
+    use crate::lang::graphs::HelixGraph;
+    let repo = Repo::new(
+        "src/testing/rust",
+        Lang::from_str("rust").unwrap(),
+        false,
+        Vec::new(),
+        Vec::new(),
+    )
+    .unwrap();
+    let graph = repo.build_graph_inner::<HelixGraph>().await.unwrap();
+    let (nodes, edges) = graph.publish().await.unwrap();
+    assert!(nodes > 0, "Expected nodes in the helix snapshot");
+    assert!(edges >= nodes, "Expected a containment edge per helix node");
+}
@@ -246,6 +246,18 @@ end
     nodes_count += variables.len();
     assert_eq!(variables.len(), 2, "Expected 2 variables");
 
+    let type_aliases = graph.find_nodes_by_type(NodeType::TypeAlias);
+    nodes_count += type_aliases.len();
+    assert_eq!(type_aliases.len(), 2, "Expected 2 type aliases");
+
+    let constants = graph.find_nodes_by_type(NodeType::Const);
+    nodes_count += constants.len();
+    assert_eq!(constants.len(), 1, "Expected 1 constant");
+
+    let uses = graph.count_edges_of_type(EdgeType::Uses);
+    edges_count += uses;
+    assert_eq!(uses, 2, "Expected 2 uses edges");
+
     let directories = graph.find_nodes_by_type(NodeType::Directory);
     nodes_count += directories.len();
     assert_eq!(directories.len(), 19, "Expected 19 directories");
@@ -276,7 +288,7 @@ end
 
     let contains = graph.count_edges_of_type(EdgeType::Contains);
     edges_count += contains;
-    assert_eq!(contains, 89, "Expected 89 contains edges");
+    assert_eq!(contains, 92, "Expected 92 contains edges");
 
     let operands = graph.count_edges_of_type(EdgeType::Operand);
     edges_count += operands;
@@ -346,6 +358,12 @@ async fn test_swift() {
     use crate::lang::graphs::{ArrayGraph, BTreeMapGraph};
     test_swift_generic::<ArrayGraph>().await.unwrap();
     test_swift_generic::<BTreeMapGraph>().await.unwrap();
+    #[cfg(feature = "sqlite")]
+    {
+        use crate::lang::graphs::SqliteGraph;
+        test_swift_generic::<SqliteGraph>().await.unwrap();
+    }
+
     #[cfg(feature = "neo4j")]
     {
         use crate::lang::graphs::Neo4jGraph;
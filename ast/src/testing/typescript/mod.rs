@@ -126,9 +126,31 @@ import {{ sequelize }} from "./config.js";"#
     nodes_count += variables.len();
     assert_eq!(variables.len(), 4, "Expected 4 variables");
 
+    let enums = graph.find_nodes_by_type(NodeType::Enum);
+    nodes_count += enums.len();
+    assert_eq!(enums.len(), 1, "Expected 1 enum");
+    assert_eq!(enums[0].name, "ResponseStatus");
+    assert_eq!(
+        enums[0].meta.get("variants").map(String::as_str),
+        Some("SUCCESS,CREATED,NOT_FOUND,INTERNAL_ERROR"),
+        "Expected ResponseStatus variants"
+    );
+
+    let interfaces = graph.find_nodes_by_type(NodeType::Interface);
+    nodes_count += interfaces.len();
+    assert_eq!(interfaces.len(), 3, "Expected 3 interfaces");
+
+    let type_aliases = graph.find_nodes_by_type(NodeType::TypeAlias);
+    nodes_count += type_aliases.len();
+    assert_eq!(type_aliases.len(), 4, "Expected 4 type aliases");
+
+    let constants = graph.find_nodes_by_type(NodeType::Const);
+    nodes_count += constants.len();
+    assert_eq!(constants.len(), 1, "Expected 1 constant");
+
     let contains = graph.count_edges_of_type(EdgeType::Contains);
     edges_count += contains;
-    assert_eq!(contains, 64, "Expected 64 contains edges");
+    assert_eq!(contains, 73, "Expected 73 contains edges");
 
     let import_edges_count = graph.count_edges_of_type(EdgeType::Imports);
     edges_count += import_edges_count;
@@ -171,9 +193,9 @@ import {{ sequelize }} from "./config.js";"#
     let uses = graph.count_edges_of_type(EdgeType::Uses);
     edges_count += uses;
     if use_lsp {
-        assert_eq!(uses, 5, "Expected 5 uses edges");
+        assert_eq!(uses, 17, "Expected 17 uses edges");
     } else {
-        assert_eq!(uses, 0, "Expected 0 uses edges");
+        assert_eq!(uses, 12, "Expected 12 uses edges");
     }
 
     let post_person_endpoint = endpoints
@@ -206,6 +228,26 @@ import {{ sequelize }} from "./config.js";"#
         "Expected '/person/:id' GET endpoint to be handled by getPerson"
     );
 
+    let person_data = graph
+        .find_nodes_by_type(NodeType::Interface)
+        .into_iter()
+        .find(|n| n.name == "PersonData")
+        .expect("PersonData interface not found");
+    assert_eq!(
+        person_data.docs.as_deref(),
+        Some("Person fields shared by the ORMs.\n@see PersonService"),
+        "JSDoc should attach to the interface"
+    );
+    let get_person = graph
+        .find_nodes_by_name(NodeType::Function, "getPersonById")
+        .into_iter()
+        .next()
+        .expect("getPersonById function not found");
+    assert_eq!(
+        get_person.meta.get("doc_params").map(String::as_str),
+        Some("id: the person id")
+    );
+
     let (nodes, edges) = graph.get_graph_size();
 
     assert_eq!(
@@ -227,6 +269,12 @@ async fn test_typescript() {
     test_typescript_generic::<BTreeMapGraph>().await.unwrap();
     test_typescript_generic::<ArrayGraph>().await.unwrap();
 
+    #[cfg(feature = "sqlite")]
+    {
+        use crate::lang::graphs::SqliteGraph;
+        test_typescript_generic::<SqliteGraph>().await.unwrap();
+    }
+
     #[cfg(feature = "neo4j")]
     {
         use crate::lang::graphs::Neo4jGraph;
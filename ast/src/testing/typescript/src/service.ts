@@ -1,5 +1,13 @@
+/**
+ * Person lookups over the configured ORMs.
+ * @module service
+ */
 import { SequelizePerson, TypeORMPerson } from "./model.js";
 import { AppDataSource, prisma } from "./config.js";
+/**
+ * Person fields shared by the ORMs.
+ * @see PersonService
+ */
 export interface PersonData {
   id?: number;
   name: string;
@@ -13,6 +21,11 @@ export interface PersonService {
   create(personData: PersonData): Promise<PersonData>;
 }
 
+/**
+ * Find a person by id.
+ * @param id - the person id
+ * @returns the person, or null
+ */
 export async function getPersonById(id: IdType): Promise<PersonData | null> {
   const person = await SequelizePerson.findByPk(id);
   if (!person) {
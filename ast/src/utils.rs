@@ -1,12 +1,18 @@
 use std::any::Any;
 use std::env;
 
-use crate::lang::graphs::{ArrayGraph, Node};
-use crate::lang::{BTreeMapGraph, Graph, NodeRef};
+use crate::lang::graphs::export::{self, ExportFilter};
+use crate::lang::graphs::scip_export;
+use crate::lang::graphs::{ArrayGraph, GraphSnapshot, IndexedGraph, Node};
+use crate::lang::{BTreeMapGraph, Edge, Graph, NodeRef};
+use lazy_static::lazy_static;
 use serde::Serialize;
-use shared::Result;
+use shared::{Error, Result};
+use std::collections::HashSet;
 use std::fs::File;
 use std::io::{BufWriter, Write};
+use tiktoken_rs::{get_bpe_from_model, CoreBPE};
+use tracing::warn;
 use tracing_subscriber::filter::LevelFilter;
 use tracing_subscriber::EnvFilter;
 
@@ -37,6 +43,46 @@ pub fn print_json<G: Graph + Serialize + 'static>(graph: &G, name: &str) -> Resu
                 std::fs::write(path, pretty)?;
             }
         }
+        "snapshot" => {
+            // SNAPSHOT_ZSTD=true compresses the payload (needs the zstd feature)
+            let compress = std::env::var("SNAPSHOT_ZSTD").is_ok_and(|v| v == "true" || v == "1");
+            let path = format!("{print_root}/{name}.snap");
+            if let Some(array_graph) = as_array_graph(graph) {
+                array_graph.save_snapshot(&path, compress)?;
+            } else if let Some(btreemap_graph) = as_btreemap_graph(graph) {
+                btreemap_graph.save_snapshot(&path, compress)?;
+            } else {
+                return Err(Error::Custom(
+                    "snapshots are only supported for ArrayGraph and BTreeMapGraph".into(),
+                ));
+            }
+        }
+        format @ ("graphml" | "gexf" | "dot") => {
+            // EXPORT_NODE_TYPES / EXPORT_EDGE_TYPES narrow the export
+            let filter = ExportFilter::from_env()?;
+            let out = match format {
+                "graphml" => export::to_graphml(graph, &filter),
+                "gexf" => export::to_gexf(graph, &filter),
+                _ => export::to_dot(graph, &filter),
+            };
+            let path = format!("{print_root}/{name}.{format}");
+            std::fs::write(path, out)?;
+        }
+        "scip" => {
+            // SCIP_PROJECT_ROOT is stripped from node files to get document paths
+            let project_root = std::env::var("SCIP_PROJECT_ROOT").unwrap_or_default();
+            let path = format!("{print_root}/{name}.scip");
+            scip_export::write_scip(graph, &project_root, std::path::Path::new(&path))?;
+        }
+        #[cfg(feature = "neo4j")]
+        "neo4j-csv" => {
+            let dir = std::path::Path::new(&print_root).join(format!("{name}-neo4j-csv"));
+            let export = crate::lang::graphs::neo4j_csv::write_neo4j_csv(graph, &dir)?;
+            tracing::info!(
+                "neo4j-admin database import full {}",
+                export.import_args().join(" ")
+            );
+        }
         _ => {
             let pretty = serde_json::to_string_pretty(&graph)?;
             let path = format!("{print_root}/{name}.json");
@@ -46,12 +92,57 @@ pub fn print_json<G: Graph + Serialize + 'static>(graph: &G, name: &str) -> Resu
     Ok(())
 }
 
+// the inverse of the jsonl `print_json` output: {root}/{name}-nodes.jsonl and -edges.jsonl.
+// edges pointing at nodes missing from the dump are an error unless `allow_dangling`
+pub fn read_jsonl<G: Graph>(root: &str, name: &str, allow_dangling: bool) -> Result<G> {
+    use serde_jsonlines::json_lines;
+    let nodepath = format!("{root}/{name}-nodes.jsonl");
+    let edgepath = format!("{root}/{name}-edges.jsonl");
+
+    let mut graph = G::default();
+    let mut node_keys = HashSet::new();
+    for (i, node) in json_lines::<Node, _>(&nodepath)?.enumerate() {
+        let node = node.map_err(|e| Error::Custom(format!("{nodepath}:{}: {e}", i + 1)))?;
+        node_keys.insert(create_node_key(&node));
+        graph.add_node(node.node_type, node.node_data);
+    }
+
+    let mut dangling = Vec::new();
+    for (i, edge) in json_lines::<Edge, _>(&edgepath)?.enumerate() {
+        let edge = edge.map_err(|e| Error::Custom(format!("{edgepath}:{}: {e}", i + 1)))?;
+        let missing: Vec<String> = [&edge.source, &edge.target]
+            .into_iter()
+            .map(create_node_key_from_ref)
+            .filter(|key| !node_keys.contains(key))
+            .collect();
+        if missing.is_empty() {
+            graph.add_edge(edge);
+        } else {
+            dangling.push(format!("line {}: {}", i + 1, missing.join(", ")));
+        }
+    }
+    if !dangling.is_empty() {
+        if !allow_dangling {
+            return Err(Error::Custom(format!(
+                "{} edges in {edgepath} reference missing nodes, e.g. {}",
+                dangling.len(),
+                dangling.iter().take(5).cloned().collect::<Vec<_>>().join("; ")
+            )));
+        }
+        warn!("skipped {} dangling edges from {}", dangling.len(), edgepath);
+    }
+    Ok(graph)
+}
+
 fn as_array_graph<G: Graph + Serialize + 'static>(graph: &G) -> Option<&ArrayGraph> {
     (graph as &dyn Any).downcast_ref::<ArrayGraph>()
 }
 
 fn as_btreemap_graph<G: Graph + Serialize + 'static>(graph: &G) -> Option<&BTreeMapGraph> {
-    (graph as &dyn Any).downcast_ref::<BTreeMapGraph>()
+    let graph = graph as &dyn Any;
+    graph
+        .downcast_ref::<BTreeMapGraph>()
+        .or_else(|| graph.downcast_ref::<IndexedGraph>().map(|g| g.inner()))
 }
 
 pub fn logger() {
@@ -158,6 +249,16 @@ pub fn print_json_vec<T: Serialize>(data: &Vec<T>, name: &str) -> Result<()> {
     }
     Ok(())
 }
+lazy_static! {
+    static ref TOKENIZER: CoreBPE = get_bpe_from_model("gpt-4").unwrap();
+}
+
+pub fn calculate_token_count(body: &str) -> Result<i64> {
+    let bpe = &TOKENIZER;
+    let token_count = bpe.encode_with_special_tokens(body).len() as i64;
+    Ok(token_count)
+}
+
 pub fn sync_fn<T, F, Fut>(async_fn: F) -> T
 where
     F: FnOnce() -> Fut,
@@ -152,6 +152,126 @@ QUERY create_handled_by_edges(handlers: [{endpoint_id: ID, function_id: ID}]) =>
     RETURN "success"
 
 
+// --- Snapshot Upload (used by the Rust HelixGraph uploader) ---
+// Single-node variants of the batch queries above, returning the created node so
+// that its ID can be used when the relationship edges are created afterwards.
+
+QUERY find_commit(sha: String) =>
+    commit <- N<COMMIT>({sha: sha})
+    RETURN commit::{ id }
+
+QUERY create_commit(sha: String, message: String, committed_at: Date) =>
+    commit <- AddN<COMMIT>({sha: sha, message: message, committed_at: committed_at})
+    RETURN commit
+
+QUERY find_version(sha: String) =>
+    version <- N<VERSION>({sha: sha})
+    RETURN version::{ id }
+
+// Marks every existing version of a project as no longer being the HEAD
+QUERY clear_head(project_id: ID) =>
+    versions <- N<PROJECT>(project_id)::Out<HAS_VERSION>::UPDATE({is_head: false})
+    RETURN "success"
+
+QUERY add_file(version_id: ID, path: String, language: String) =>
+    version_node <- N<VERSION>(version_id)
+    file_node <- AddN<FILE>({path: path, language: language})
+    AddE<CONTAINS_CODE>()::From(version_node)::To(file_node)
+    RETURN file_node
+
+QUERY add_library(file_id: ID, name: String, version: String) =>
+    file_node <- N<FILE>(file_id)
+    lib_node <- AddN<LIBRARY>({name: name, version: version})
+    AddE<DEPENDS_ON>()::From(file_node)::To(lib_node)
+    RETURN lib_node
+
+QUERY add_class(file_id: ID, name: String, start_line: I32, end_line: I32) =>
+    file_node <- N<FILE>(file_id)
+    class_node <- AddN<CLASS>({name: name, start_line: start_line, end_line: end_line})
+    AddE<DEFINES>()::From(file_node)::To(class_node)
+    RETURN class_node
+
+QUERY add_function(file_id: ID, name: String, signature: String, start_line: I32, end_line: I32, is_component: Boolean) =>
+    file_node <- N<FILE>(file_id)
+    func_node <- AddN<FUNCTION>({
+        name: name,
+        signature: signature,
+        start_line: start_line,
+        end_line: end_line,
+        is_component: is_component
+    })
+    AddE<DEFINES>()::From(file_node)::To(func_node)
+    RETURN func_node
+
+QUERY add_data_model(file_id: ID, name: String, construct: String, start_line: I32, end_line: I32) =>
+    file_node <- N<FILE>(file_id)
+    dm_node <- AddN<DATA_MODEL>({name: name, construct: construct, start_line: start_line, end_line: end_line})
+    AddE<DEFINES>()::From(file_node)::To(dm_node)
+    RETURN dm_node
+
+QUERY add_variable(file_id: ID, name: String, data_type: String) =>
+    file_node <- N<FILE>(file_id)
+    var_node <- AddN<VARIABLE>({name: name, data_type: data_type})
+    AddE<DEFINES>()::From(file_node)::To(var_node)
+    RETURN var_node
+
+QUERY add_test(file_id: ID, name: String, test_kind: String, start_line: I32, end_line: I32) =>
+    file_node <- N<FILE>(file_id)
+    test_node <- AddN<TEST>({name: name, test_kind: test_kind, start_line: start_line, end_line: end_line})
+    AddE<DEFINES>()::From(file_node)::To(test_node)
+    RETURN test_node
+
+QUERY add_endpoint(file_id: ID, path: String, http_method: String) =>
+    file_node <- N<FILE>(file_id)
+    ep_node <- AddN<ENDPOINT>({path: path, http_method: http_method})
+    AddE<DEFINES>()::From(file_node)::To(ep_node)
+    RETURN ep_node
+
+// Batch creates OPERAND_OF edges (a function is a method of a class)
+QUERY create_operand_edges(operands: [{class_id: ID, function_id: ID}]) =>
+    FOR { class_id, function_id } IN operands {
+        from_node <- N<CLASS>(class_id)
+        to_node <- N<FUNCTION>(function_id)
+        AddE<OPERAND_OF>()::From(from_node)::To(to_node)
+    }
+    RETURN "success"
+
+// Batch creates PARENT_OF edges between classes
+QUERY create_parent_of_edges(parents: [{parent_id: ID, child_id: ID}]) =>
+    FOR { parent_id, child_id } IN parents {
+        from_node <- N<CLASS>(parent_id)
+        to_node <- N<CLASS>(child_id)
+        AddE<PARENT_OF>()::From(from_node)::To(to_node)
+    }
+    RETURN "success"
+
+// Batch creates IMPLEMENTS edges from classes to interfaces/traits
+QUERY create_implements_edges(impls: [{class_id: ID, model_id: ID}]) =>
+    FOR { class_id, model_id } IN impls {
+        from_node <- N<CLASS>(class_id)
+        to_node <- N<DATA_MODEL>(model_id)
+        AddE<IMPLEMENTS>()::From(from_node)::To(to_node)
+    }
+    RETURN "success"
+
+// Batch creates IMPORTS edges between files
+QUERY create_imports_edges(imports: [{from_file_id: ID, to_file_id: ID}]) =>
+    FOR { from_file_id, to_file_id } IN imports {
+        from_node <- N<FILE>(from_file_id)
+        to_node <- N<FILE>(to_file_id)
+        AddE<IMPORTS>()::From(from_node)::To(to_node)
+    }
+    RETURN "success"
+
+// Batch creates USES edges from functions to libraries
+QUERY create_uses_edges(uses: [{function_id: ID, library_id: ID}]) =>
+    FOR { function_id, library_id } IN uses {
+        from_node <- N<FUNCTION>(function_id)
+        to_node <- N<LIBRARY>(library_id)
+        AddE<USES>()::From(from_node)::To(to_node)
+    }
+    RETURN "success"
+
 // =====================================================================
 // Section B: Basic Retrieval & Analysis Queries
 // =====================================================================
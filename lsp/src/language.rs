@@ -60,6 +60,19 @@ impl Language {
         }
     }
 
+    pub fn lock_files(&self) -> Vec<&'static str> {
+        match self {
+            Self::Rust => vec!["Cargo.lock"],
+            Self::Go => vec!["go.sum"],
+            Self::Typescript | Self::React | Self::Svelte | Self::Angular => {
+                vec!["package-lock.json", "yarn.lock", "pnpm-lock.yaml"]
+            }
+            Self::Python => vec!["poetry.lock"],
+            Self::Ruby => vec!["Gemfile.lock"],
+            _ => Vec::new(),
+        }
+    }
+
     pub fn exts(&self) -> Vec<&'static str> {
         match self {
             Self::Rust => vec!["rs"],
@@ -286,6 +299,10 @@ impl Language {
             .iter()
             .any(|pkg_file| file_name.ends_with(pkg_file))
     }
+    pub fn is_lock_file(&self, file_name: &str) -> bool {
+        let name = file_name.rsplit('/').next().unwrap_or(file_name);
+        self.lock_files().contains(&name)
+    }
     pub fn is_from_language(&self, file: &str) -> bool {
         if !file.contains('.') {
             return true;
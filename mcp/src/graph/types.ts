@@ -50,6 +50,11 @@ export type NodeType =
   | "Datamodel"
   | "Page"
   | "Var"
+  | "Enum"
+  | "Interface"
+  | "TypeAlias"
+  | "Const"
+  | "Module"
   | "Message"
   | "Person"
   | "Video"
@@ -64,7 +69,9 @@ export type EdgeType =
   | "IMPORTS"
   | "OF"
   | "HANDLER"
-  | "RENDERS";
+  | "RENDERS"
+  | "EXPORTS"
+  | "SIMILAR_TO";
 
 export interface EdgeTypeInterface {
   edge_type: EdgeType;
@@ -114,6 +121,10 @@ export function relevant_node_types(): NodeType[] {
     "IntegrationTest",
     "E2etest",
     "Var",
+    "Enum",
+    "Interface",
+    "TypeAlias",
+    "Const",
     "Message",
     "Person",
     "Video",
@@ -141,6 +152,11 @@ export function all_node_types(): NodeType[] {
     "Datamodel",
     "Page",
     "Var",
+    "Enum",
+    "Interface",
+    "TypeAlias",
+    "Const",
+    "Module",
     "Message",
     "Person",
     "Video",
@@ -159,6 +175,8 @@ export function all_edge_types(): EdgeType[] {
     "OF",
     "HANDLER",
     "RENDERS",
+    "EXPORTS",
+    "SIMILAR_TO",
   ];
 }
 
@@ -193,6 +211,14 @@ export function node_type_descriptions(): { [k in NodeType]: string } {
       "A structured representation of data within a system, typically defining entities, relationships, attribute types, and corresponding SQL table definitions.",
     Page: "A webpage or route within an application, representing a specific view or section of the system. It can serve as the starting point for a codemap.",
     Var: "A variable in source code, representing a value that can be used in the code.",
+    Enum: "An enum definition in source code, with its variants.",
+    Interface:
+      "An interface definition in source code, describing the shape of an object.",
+    TypeAlias: "A named type alias in source code.",
+    Const:
+      "A top-level constant in source code, representing a fixed value shared across the code.",
+    Module:
+      "A module or package (Rust mod, Go/Java/Kotlin/Python package, TS barrel) grouping files and submodules.",
     Message:
       "A message in a conversation between developers, projects managers, or other stakeholders.",
     Person: "A person working on the project.",
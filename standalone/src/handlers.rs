@@ -1,80 +1,36 @@
 use crate::types::{
-    AsyncRequestStatus, AsyncStatus, CoverageParams, CoverageStat, Coverage, EmbedCodeParams,
-    FetchRepoBody, FetchRepoResponse, HasParams, HasResponse, ProcessBody, ProcessResponse, Result,
-    UncoveredParams, UncoveredResponse, VectorSearchParams, VectorSearchResult, WebError,
+    AsyncRequestStatus, AsyncStatus, ClonesParams, ContextParams, DataModelTraceParams, CoverageParams, Coverage, DeadCodeParams,
+    EmbedCodeParams,
+    FetchRepoBody, FetchRepoResponse, HasParams, HasResponse, MermaidParams, ProcessBody, ProcessResponse, Result,
+    SubgraphParams, UncoveredParams, VectorSearchParams, VectorSearchResult, WebError,
     WebhookPayload, CodecovBody, CodecovRequestStatus,
 };
 use crate::utils::{
-    create_uncovered_response_items, format_uncovered_response_as_snippet, parse_node_type,
+    clone_report, context_pack, data_model_traces, dead_code_report, mermaid_diagram, parse_node_type, resolve_repo,
+    subgraph, uncovered_response,
 };
 use crate::webhook::{send_with_retries, validate_callback_url_async};
 use crate::AppState;
+use ast::lang::graphs::clones::CloneReport;
+use ast::lang::graphs::context_pack::{ContextPack, Seed};
+use ast::lang::graphs::data_model_trace::EndpointTrace;
+use ast::lang::graphs::dead_code::DeadCodeReport;
 use ast::lang::graphs::graph_ops::GraphOps;
+use ast::lang::graphs::ArrayGraph;
 use ast::lang::{Graph, NodeType};
 use ast::repo::{clone_repo, Repo};
 use axum::extract::{Path, Query};
 use axum::http::StatusCode;
-use axum::response::sse::{Event, KeepAlive, Sse};
 use axum::response::IntoResponse;
 use axum::{extract::State, Json};
-use broadcast::error::RecvError;
 use chrono::Utc;
-use futures::stream;
 use lsp::{git::get_commit_hash, git::validate_git_credentials, strip_tmp};
 use reqwest::Client;
 use shared::Error;
-use std::convert::Infallible;
 use std::sync::Arc;
-use std::time::Duration;
 use std::time::Instant;
-use tokio::sync::broadcast;
 use tracing::info;
 
-pub async fn sse_handler(State(app_state): State<Arc<AppState>>) -> impl IntoResponse {
-    let rx = app_state.tx.subscribe();
-
-    let stream = stream::unfold(rx, move |mut rx| async move {
-        loop {
-            match rx.recv().await {
-                Ok(msg) => {
-                    let data = msg.as_json_str();
-                    let millis = std::time::SystemTime::now()
-                        .duration_since(std::time::UNIX_EPOCH)
-                        .unwrap()
-                        .as_millis();
-                    let event = Event::default().data(data).id(format!("{}", millis));
-                    return Some((Ok::<Event, Infallible>(event), rx));
-                }
-                Err(RecvError::Lagged(skipped)) => {
-                    println!("SSE receiver lagged, skipped {} messages", skipped);
-                    continue;
-                }
-                Err(RecvError::Closed) => {
-                    return None;
-                }
-            }
-        }
-    });
-
-    let headers = [
-        ("Cache-Control", "no-cache, no-store, must-revalidate"),
-        ("Connection", "keep-alive"),
-        ("Content-Type", "text/event-stream"),
-        ("X-Accel-Buffering", "no"), // nginx
-        ("X-Proxy-Buffering", "no"), // other proxies
-        ("Access-Control-Allow-Origin", "*"),
-        ("Access-Control-Allow-Headers", "Cache-Control"),
-    ];
-    (
-        headers,
-        Sse::new(stream).keep_alive(
-            KeepAlive::new()
-                .interval(Duration::from_millis(500))
-                .text("ping"),
-        ),
-    )
-}
-
 #[axum::debug_handler]
 pub async fn process(body: Json<ProcessBody>) -> Result<Json<ProcessResponse>> {
     if body.repo_url.clone().unwrap_or_default().contains(",") {
@@ -275,8 +231,8 @@ pub async fn ingest(
     let _ = state.tx.send(ast::repo::StatusUpdate {
         status: "Complete".to_string(),
         message: "Graph building completed successfully".to_string(),
-        step: 16,
-        total_steps: 16,
+        step: 18,
+        total_steps: 18,
         progress: 100,
         stats: Some(std::collections::HashMap::from([
             ("total_nodes".to_string(), nodes as usize),
@@ -629,6 +585,7 @@ pub async fn vector_search_handler(
             node_types,
             params.similarity_threshold.unwrap_or(0.7),
             params.language.as_deref(),
+            params.importance_weight.unwrap_or(0.0),
         )
         .await?;
 
@@ -646,43 +603,6 @@ pub async fn vector_search_handler(
     Ok(Json(response))
 }
 
-fn env_not_empty(key: &str) -> Option<String> {
-    std::env::var(key).ok().filter(|v| !v.is_empty())
-}
-
-fn resolve_repo(
-    body: &ProcessBody,
-) -> Result<(
-    String,
-    String,
-    Option<String>,
-    Option<String>,
-    Option<String>,
-)> {
-    let repo_path = body
-        .repo_path
-        .clone()
-        .or_else(|| env_not_empty("REPO_PATH"));
-    let repo_url = body.repo_url.clone().or_else(|| env_not_empty("REPO_URL"));
-    let username = body.username.clone().or_else(|| env_not_empty("USERNAME"));
-    let pat = body.pat.clone().or_else(|| env_not_empty("PAT"));
-    let commit = body.commit.clone();
-
-    if repo_path.is_none() && repo_url.is_none() {
-        return Err(WebError(shared::Error::Custom(
-            "Neither REPO_PATH nor REPO_URL is set in the body or environment".into(),
-        )));
-    }
-
-    if let Some(path) = repo_path {
-        Ok((path, repo_url.unwrap_or_default(), username, pat, commit))
-    } else {
-        let url = repo_url.unwrap();
-        let tmp_path = Repo::get_path_from_url(&url)?;
-        Ok((tmp_path, url, username, pat, commit))
-    }
-}
-
 #[axum::debug_handler]
 pub async fn coverage_handler(
     Query(params): Query<CoverageParams>,
@@ -696,11 +616,7 @@ pub async fn coverage_handler(
         )
         .await?;
 
-    Ok(Json(Coverage {
-    unit_tests: totals.unit_tests.map(|s| CoverageStat { total: s.total, total_tests: s.total_tests, covered: s.covered, percent: s.percent }),
-    integration_tests: totals.integration_tests.map(|s| CoverageStat { total: s.total, total_tests: s.total_tests, covered: s.covered, percent: s.percent }),
-    e2e_tests: totals.e2e_tests.map(|s| CoverageStat { total: s.total, total_tests: s.total_tests, covered: s.covered, percent: s.percent }),
-    }))
+    Ok(Json(totals.into()))
 }
 
 #[axum::debug_handler]
@@ -717,15 +633,12 @@ pub async fn uncovered_handler(Query(params): Query<UncoveredParams>) -> Result<
 
     let node_type = parse_node_type(&params.node_type).map_err(|e| WebError(e))?;
 
-    let is_function = matches!(node_type, NodeType::Function);
-    let is_endpoint = matches!(node_type, NodeType::Endpoint);
-
     let mut graph_ops = GraphOps::new();
     graph_ops.connect().await?;
 
     let (funcs, endpoints) = graph_ops
         .list_uncovered(
-            node_type,
+            node_type.clone(),
             with_usage,
             offset,
             limit,
@@ -734,36 +647,7 @@ pub async fn uncovered_handler(Query(params): Query<UncoveredParams>) -> Result<
         )
         .await?;
 
-    let functions = if is_function {
-        Some(create_uncovered_response_items(
-            funcs,
-            &NodeType::Function,
-            concise,
-        ))
-    } else {
-        None
-    };
-    let endpoints = if is_endpoint {
-        Some(create_uncovered_response_items(
-            endpoints,
-            &NodeType::Endpoint,
-            concise,
-        ))
-    } else {
-        None
-    };
-
-    let response = UncoveredResponse {
-        functions,
-        endpoints,
-    };
-    match output {
-        "snippet" => {
-            let text = format_uncovered_response_as_snippet(&response);
-            Ok(text.into_response())
-        }
-        _ => Ok(Json(response).into_response()),
-    }
+    Ok(uncovered_response(&node_type, concise, output, funcs, endpoints))
 }
 
 #[axum::debug_handler]
@@ -788,6 +672,59 @@ pub async fn has_handler(Query(params): Query<HasParams>) -> Result<Json<HasResp
     Ok(Json(HasResponse { covered }))
 }
 
+#[axum::debug_handler]
+pub async fn mermaid_handler(Query(params): Query<MermaidParams>) -> Result<String> {
+    let mut graph_ops = GraphOps::new();
+    graph_ops.connect().await?;
+    Ok(mermaid_diagram(&graph_ops.graph, &params)?)
+}
+
+#[axum::debug_handler]
+pub async fn context_handler(Query(params): Query<ContextParams>) -> Result<Json<ContextPack>> {
+    let mut graph_ops = GraphOps::new();
+    graph_ops.connect().await?;
+    let mut seeds = Vec::new();
+    if let Some(query) = &params.query {
+        let hits = graph_ops
+            .vector_search(query, 5, Vec::new(), 0.7, None, 0.0)
+            .await?;
+        seeds.extend(hits.into_iter().map(|(node, score)| Seed::Hit(node, score)));
+    }
+    Ok(Json(context_pack(&graph_ops.graph, &params, seeds)?))
+}
+
+#[axum::debug_handler]
+pub async fn subgraph_handler(Query(params): Query<SubgraphParams>) -> Result<Json<ArrayGraph>> {
+    let mut graph_ops = GraphOps::new();
+    graph_ops.connect().await?;
+    Ok(Json(subgraph(&graph_ops.graph, &params)?))
+}
+
+#[axum::debug_handler]
+pub async fn clones_handler(Query(params): Query<ClonesParams>) -> Result<Json<CloneReport>> {
+    let mut graph_ops = GraphOps::new();
+    graph_ops.connect().await?;
+    Ok(Json(clone_report(&graph_ops.graph, &params)?))
+}
+
+#[axum::debug_handler]
+pub async fn data_model_trace_handler(
+    Query(params): Query<DataModelTraceParams>,
+) -> Result<Json<Vec<EndpointTrace>>> {
+    let mut graph_ops = GraphOps::new();
+    graph_ops.connect().await?;
+    Ok(Json(data_model_traces(&graph_ops.graph, &params)))
+}
+
+#[axum::debug_handler]
+pub async fn dead_code_handler(
+    Query(params): Query<DeadCodeParams>,
+) -> Result<Json<DeadCodeReport>> {
+    let mut graph_ops = GraphOps::new();
+    graph_ops.connect().await?;
+    Ok(Json(dead_code_report(&graph_ops.graph, &params)?))
+}
+
 #[axum::debug_handler]
 pub async fn codecov_handler(
     State(state): State<Arc<AppState>>,
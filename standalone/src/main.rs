@@ -1,9 +1,17 @@
 mod auth;
 #[cfg(feature = "neo4j")]
 mod handlers;
+#[cfg(all(feature = "sqlite", not(feature = "neo4j")))]
+mod sqlite_handlers;
+#[cfg(any(feature = "neo4j", feature = "sqlite"))]
+mod sse;
+// request types, webhooks and codecov are only wired up by the neo4j handlers
+#[cfg_attr(not(feature = "neo4j"), allow(dead_code))]
 mod types;
 mod utils;
+#[cfg_attr(not(feature = "neo4j"), allow(dead_code))]
 mod webhook;
+#[cfg_attr(not(feature = "neo4j"), allow(dead_code))]
 mod codecov;
 
 use ast::repo::StatusUpdate;
@@ -20,6 +28,7 @@ use tracing_subscriber::{filter::LevelFilter, EnvFilter};
 use types::{AsyncStatusMap, CodecovStatusMap, Result};
 
 #[derive(Clone)]
+#[cfg_attr(not(feature = "neo4j"), allow(dead_code))]
 struct AppState {
     tx: broadcast::Sender<StatusUpdate>,
     api_token: Option<String>,
@@ -27,7 +36,7 @@ struct AppState {
     codecov_status: CodecovStatusMap,
 }
 
-#[cfg(feature = "neo4j")]
+#[cfg(any(feature = "neo4j", feature = "sqlite"))]
 #[tokio::main(flavor = "multi_thread")]
 async fn main() -> Result<()> {
     let filter = EnvFilter::builder()
@@ -38,11 +47,25 @@ async fn main() -> Result<()> {
         .with_env_filter(filter)
         .init();
 
-    let mut graph_ops = ast::lang::graphs::graph_ops::GraphOps::new();
-    if let Err(e) = graph_ops.check_connection().await {
-        panic!("Failed to connect to graph db: {:?}", e);
+    #[cfg(feature = "neo4j")]
+    {
+        let mut graph_ops = ast::lang::graphs::graph_ops::GraphOps::new();
+        if let Err(e) = graph_ops.check_connection().await {
+            panic!("Failed to connect to graph db: {:?}", e);
+        }
+        graph_ops.graph.create_indexes().await?;
+    }
+    #[cfg(all(feature = "sqlite", not(feature = "neo4j")))]
+    {
+        let mut graph_ops = ast::lang::graphs::sqlite_ops::SqliteGraphOps::new();
+        if let Err(e) = graph_ops.connect().await {
+            panic!("Failed to open sqlite graph: {:?}", e);
+        }
+        tracing::info!(
+            "using sqlite graph at {}",
+            ast::lang::graphs::sqlite_ops::sqlite_path()
+        );
     }
-    graph_ops.graph.create_indexes().await?;
 
     let (tx, _rx) = broadcast::channel(10000);
 
@@ -73,25 +96,9 @@ async fn main() -> Result<()> {
     tracing::debug!("starting server");
     let cors_layer = CorsLayer::permissive();
 
-    let mut app = Router::new().route("/events", get(handlers::sse_handler));
+    let mut app = Router::new().route("/events", get(sse::sse_handler));
 
-    let mut protected_routes = Router::new()
-        .route("/process", post(handlers::process))
-        .route("/sync", post(handlers::process))
-        .route("/clear", post(handlers::clear_graph))
-        .route("/ingest", post(handlers::ingest))
-        .route("/ingest_async", post(handlers::ingest_async))
-        .route("/sync_async", post(handlers::sync_async))
-        .route("/status/:request_id", get(handlers::get_status))
-        .route("/fetch-repo", post(handlers::fetch_repo))
-        .route("/fetch-repos", get(handlers::fetch_repos))
-        .route("/embed_code", post(handlers::embed_code_handler))
-        .route("/search", post(handlers::vector_search_handler))
-        .route("/tests/coverage", get(handlers::coverage_handler))
-        .route("/tests/uncovered", get(handlers::uncovered_handler))
-        .route("/tests/has", get(handlers::has_handler))
-        .route("/codecov", post(handlers::codecov_handler))
-        .route("/codecov/:request_id", get(handlers::codecov_status_handler));
+    let mut protected_routes = graph_routes();
 
     // Add bearer auth middleware only if API token is provided
     if app_state.api_token.is_some() {
@@ -163,14 +170,62 @@ async fn main() -> Result<()> {
     Ok(())
 }
 
+#[cfg(feature = "neo4j")]
+fn graph_routes() -> Router<Arc<AppState>> {
+    Router::new()
+        .route("/process", post(handlers::process))
+        .route("/sync", post(handlers::process))
+        .route("/clear", post(handlers::clear_graph))
+        .route("/ingest", post(handlers::ingest))
+        .route("/ingest_async", post(handlers::ingest_async))
+        .route("/sync_async", post(handlers::sync_async))
+        .route("/status/:request_id", get(handlers::get_status))
+        .route("/fetch-repo", post(handlers::fetch_repo))
+        .route("/fetch-repos", get(handlers::fetch_repos))
+        .route("/embed_code", post(handlers::embed_code_handler))
+        .route("/search", post(handlers::vector_search_handler))
+        .route("/tests/coverage", get(handlers::coverage_handler))
+        .route("/tests/uncovered", get(handlers::uncovered_handler))
+        .route("/tests/has", get(handlers::has_handler))
+        .route("/mermaid", get(handlers::mermaid_handler))
+        .route("/dead_code", get(handlers::dead_code_handler))
+        .route("/endpoints/data_models", get(handlers::data_model_trace_handler))
+        .route("/clones", get(handlers::clones_handler))
+        .route("/subgraph", get(handlers::subgraph_handler))
+        .route("/context", get(handlers::context_handler))
+        .route("/codecov", post(handlers::codecov_handler))
+        .route("/codecov/:request_id", get(handlers::codecov_status_handler))
+}
+
+// the sqlite backend serves the synchronous graph routes from a single file
+#[cfg(all(feature = "sqlite", not(feature = "neo4j")))]
+fn graph_routes() -> Router<Arc<AppState>> {
+    Router::new()
+        .route("/process", post(sqlite_handlers::process))
+        .route("/sync", post(sqlite_handlers::process))
+        .route("/clear", post(sqlite_handlers::clear_graph))
+        .route("/ingest", post(sqlite_handlers::ingest))
+        .route("/fetch-repo", post(sqlite_handlers::fetch_repo))
+        .route("/fetch-repos", get(sqlite_handlers::fetch_repos))
+        .route("/tests/coverage", get(sqlite_handlers::coverage_handler))
+        .route("/tests/uncovered", get(sqlite_handlers::uncovered_handler))
+        .route("/tests/has", get(sqlite_handlers::has_handler))
+        .route("/mermaid", get(sqlite_handlers::mermaid_handler))
+        .route("/dead_code", get(sqlite_handlers::dead_code_handler))
+        .route("/endpoints/data_models", get(sqlite_handlers::data_model_trace_handler))
+        .route("/clones", get(sqlite_handlers::clones_handler))
+        .route("/subgraph", get(sqlite_handlers::subgraph_handler))
+        .route("/context", get(sqlite_handlers::context_handler))
+}
+
 fn static_file(path: &str) -> ServeFile {
     ServeFile::new(format!("standalone/static/{}", path))
 }
 
-#[cfg(not(feature = "neo4j"))]
+#[cfg(not(any(feature = "neo4j", feature = "sqlite")))]
 fn main() -> Result<()> {
     println!(
-        "The 'neo4j' feature must be enabled to build this binary. Use: cargo run --features neo4j"
+        "The 'neo4j' or 'sqlite' feature must be enabled to build this binary. Use: cargo run --features neo4j"
     );
     Ok(())
 }
@@ -0,0 +1,268 @@
+use crate::types::{
+    ClonesParams, ContextParams, DataModelTraceParams, Coverage, CoverageParams, DeadCodeParams, FetchRepoBody, FetchRepoResponse, HasParams, HasResponse,
+    MermaidParams, ProcessBody, ProcessResponse, Result, SubgraphParams, UncoveredParams, WebError,
+};
+use crate::utils::{
+    clone_report, context_pack, data_model_traces, dead_code_report, mermaid_diagram, parse_node_type, resolve_repo,
+    subgraph, uncovered_response,
+};
+use crate::AppState;
+use ast::lang::graphs::clones::CloneReport;
+use ast::lang::graphs::context_pack::ContextPack;
+use ast::lang::graphs::data_model_trace::EndpointTrace;
+use ast::lang::graphs::dead_code::DeadCodeReport;
+use ast::lang::graphs::sqlite_ops::SqliteGraphOps;
+use ast::lang::graphs::{ArrayGraph, BTreeMapGraph};
+use ast::lang::{Graph, NodeType};
+use ast::repo::{clone_repo, Repo};
+use axum::extract::{Query, State};
+use axum::response::IntoResponse;
+use axum::Json;
+use lsp::{git::get_commit_hash, git::validate_git_credentials, strip_tmp};
+use shared::Error;
+use std::sync::Arc;
+use std::time::Instant;
+use tracing::info;
+
+async fn connect() -> Result<SqliteGraphOps> {
+    let mut graph_ops = SqliteGraphOps::new();
+    graph_ops.connect().await?;
+    Ok(graph_ops)
+}
+
+#[axum::debug_handler]
+pub async fn process(body: Json<ProcessBody>) -> Result<Json<ProcessResponse>> {
+    if body.repo_url.clone().unwrap_or_default().contains(",") {
+        return Err(WebError(Error::Custom(
+            "Multiple repositories are not supported in a single request".into(),
+        )));
+    }
+    let (repo_path, repo_url, username, pat, _) = resolve_repo(&body)?;
+    validate_git_credentials(&repo_url, username.clone(), pat.clone())
+        .await
+        .map_err(WebError)?;
+
+    let total_start = Instant::now();
+    clone_repo(&repo_url, &repo_path, username.clone(), pat.clone(), None).await?;
+    let current_hash = get_commit_hash(&repo_path).await.map_err(|e| {
+        WebError(Error::Custom(format!("Could not get current hash: {e}")))
+    })?;
+
+    let mut graph_ops = connect().await?;
+    let stored_hash = graph_ops.graph.get_repository_hash(&repo_url).ok();
+    info!(
+        "Current hash: {} | Stored hash: {:?}",
+        current_hash, stored_hash
+    );
+    if stored_hash.as_deref() == Some(current_hash.as_str()) {
+        info!("Repository already processed with hash: {}", current_hash);
+        let (nodes, edges) = graph_ops.graph.get_graph_size();
+        return Ok(Json(ProcessResponse { nodes, edges }));
+    }
+
+    let (prev_nodes, prev_edges) = graph_ops.graph.get_graph_size();
+    let (nodes, edges) = graph_ops
+        .update_incremental(
+            &repo_url,
+            username,
+            pat,
+            &current_hash,
+            stored_hash.as_deref().unwrap_or_default(),
+            None,
+            body.use_lsp,
+        )
+        .await?;
+    info!("Total processing time: {:.2?}", total_start.elapsed());
+
+    Ok(Json(ProcessResponse {
+        nodes: nodes.saturating_sub(prev_nodes),
+        edges: edges.saturating_sub(prev_edges),
+    }))
+}
+
+pub async fn clear_graph() -> Result<Json<ProcessResponse>> {
+    let (nodes, edges) = connect().await?.clear().await?;
+    Ok(Json(ProcessResponse { nodes, edges }))
+}
+
+pub async fn fetch_repo(body: Json<FetchRepoBody>) -> Result<Json<FetchRepoResponse>> {
+    let repo_node = connect().await?.fetch_repo(&body.repo_name).await?;
+    Ok(Json(FetchRepoResponse {
+        status: "success".to_string(),
+        repo_name: repo_node.name,
+        hash: repo_node.hash.unwrap_or_default(),
+    }))
+}
+
+pub async fn fetch_repos() -> Result<Json<Vec<FetchRepoResponse>>> {
+    let repos = connect()
+        .await?
+        .fetch_repos()
+        .await
+        .into_iter()
+        .map(|node| FetchRepoResponse {
+            status: "success".to_string(),
+            repo_name: node.name,
+            hash: node.hash.unwrap_or_default(),
+        })
+        .collect();
+    Ok(Json(repos))
+}
+
+#[axum::debug_handler]
+pub async fn ingest(
+    State(state): State<Arc<AppState>>,
+    body: Json<ProcessBody>,
+) -> Result<Json<ProcessResponse>> {
+    let start = Instant::now();
+    let (_, repo_url, username, pat, commit) = resolve_repo(&body)?;
+    let mut repos = Repo::new_clone_multi_detect(
+        &repo_url,
+        username,
+        pat,
+        Vec::new(),
+        Vec::new(),
+        commit.as_deref(),
+        body.use_lsp,
+    )
+    .await
+    .map_err(|e| WebError(Error::Custom(format!("Repo detection Failed: {}", e))))?;
+    repos.set_status_tx(state.tx.clone()).await;
+
+    let btree_graph = repos
+        .build_graphs_inner::<BTreeMapGraph>()
+        .await
+        .map_err(|e| WebError(Error::Custom(format!("Failed to build graphs: {}", e))))?;
+
+    let mut graph_ops = connect().await?;
+    for repo in &repos.0 {
+        let stripped_root = strip_tmp(&repo.root).display().to_string();
+        info!("Clearing old data for {}...", stripped_root);
+        graph_ops.clear_existing_graph(&stripped_root).await?;
+    }
+    let (nodes, edges) = graph_ops.upload_btreemap(&btree_graph).await?;
+
+    let _ = state.tx.send(ast::repo::StatusUpdate {
+        status: "Complete".to_string(),
+        message: "Graph building completed successfully".to_string(),
+        step: 18,
+        total_steps: 18,
+        progress: 100,
+        stats: Some(std::collections::HashMap::from([
+            ("total_nodes".to_string(), nodes as usize),
+            ("total_edges".to_string(), edges as usize),
+        ])),
+        step_description: Some("Graph building completed".to_string()),
+    });
+    info!(
+        "[perf][ingest][results] repo={} total_s={:.2} nodes={} edges={}",
+        repo_url,
+        start.elapsed().as_secs_f64(),
+        nodes,
+        edges
+    );
+    Ok(Json(ProcessResponse { nodes, edges }))
+}
+
+#[axum::debug_handler]
+pub async fn coverage_handler(Query(params): Query<CoverageParams>) -> Result<Json<Coverage>> {
+    let totals = connect()
+        .await?
+        .get_coverage(params.repo.as_deref())
+        .await?;
+    Ok(Json(totals.into()))
+}
+
+#[axum::debug_handler]
+pub async fn uncovered_handler(
+    Query(params): Query<UncoveredParams>,
+) -> Result<impl IntoResponse> {
+    let with_usage = params
+        .sort
+        .as_deref()
+        .unwrap_or("usage")
+        .eq_ignore_ascii_case("usage");
+    let output = params.output.as_deref().unwrap_or("json");
+    let concise = params.concise.unwrap_or(false);
+    let node_type = parse_node_type(&params.node_type).map_err(WebError)?;
+
+    let (funcs, endpoints) = connect()
+        .await?
+        .list_uncovered(
+            node_type.clone(),
+            with_usage,
+            params.offset.unwrap_or(0),
+            params.limit.unwrap_or(50),
+            params.root.as_deref(),
+            params.tests.as_deref(),
+        )
+        .await?;
+    Ok(uncovered_response(&node_type, concise, output, funcs, endpoints))
+}
+
+#[axum::debug_handler]
+pub async fn has_handler(Query(params): Query<HasParams>) -> Result<Json<HasResponse>> {
+    let node_type = match params.node_type.to_lowercase().as_str() {
+        "function" => NodeType::Function,
+        "endpoint" => NodeType::Endpoint,
+        _ => return Err(WebError(Error::Custom("invalid node_type".into()))),
+    };
+    let covered = connect()
+        .await?
+        .has_coverage(
+            node_type,
+            &params.name,
+            &params.file,
+            params.start,
+            params.root.as_deref(),
+            params.tests.as_deref(),
+        )
+        .await?;
+    Ok(Json(HasResponse { covered }))
+}
+
+#[axum::debug_handler]
+pub async fn mermaid_handler(Query(params): Query<MermaidParams>) -> Result<String> {
+    let graph_ops = connect().await?;
+    Ok(mermaid_diagram(&graph_ops.graph, &params)?)
+}
+
+#[axum::debug_handler]
+pub async fn subgraph_handler(Query(params): Query<SubgraphParams>) -> Result<Json<ArrayGraph>> {
+    let graph_ops = connect().await?;
+    Ok(Json(subgraph(&graph_ops.graph, &params)?))
+}
+
+#[axum::debug_handler]
+pub async fn clones_handler(Query(params): Query<ClonesParams>) -> Result<Json<CloneReport>> {
+    let graph_ops = connect().await?;
+    Ok(Json(clone_report(&graph_ops.graph, &params)?))
+}
+
+#[axum::debug_handler]
+pub async fn data_model_trace_handler(
+    Query(params): Query<DataModelTraceParams>,
+) -> Result<Json<Vec<EndpointTrace>>> {
+    let graph_ops = connect().await?;
+    Ok(Json(data_model_traces(&graph_ops.graph, &params)))
+}
+
+#[axum::debug_handler]
+pub async fn dead_code_handler(
+    Query(params): Query<DeadCodeParams>,
+) -> Result<Json<DeadCodeReport>> {
+    let graph_ops = connect().await?;
+    Ok(Json(dead_code_report(&graph_ops.graph, &params)?))
+}
+
+// no embeddings in sqlite, so a query seeds nothing
+#[axum::debug_handler]
+pub async fn context_handler(Query(params): Query<ContextParams>) -> Result<Json<ContextPack>> {
+    if params.query.is_some() {
+        return Err(WebError(Error::Custom(
+            "vector search needs the neo4j backend".into(),
+        )));
+    }
+    let graph_ops = connect().await?;
+    Ok(Json(context_pack(&graph_ops.graph, &params, Vec::new())?))
+}
@@ -0,0 +1,54 @@
+use crate::AppState;
+use axum::extract::State;
+use axum::response::sse::{Event, KeepAlive, Sse};
+use axum::response::IntoResponse;
+use futures::stream;
+use std::convert::Infallible;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::broadcast::error::RecvError;
+
+pub async fn sse_handler(State(app_state): State<Arc<AppState>>) -> impl IntoResponse {
+    let rx = app_state.tx.subscribe();
+
+    let stream = stream::unfold(rx, move |mut rx| async move {
+        loop {
+            match rx.recv().await {
+                Ok(msg) => {
+                    let data = msg.as_json_str();
+                    let millis = std::time::SystemTime::now()
+                        .duration_since(std::time::UNIX_EPOCH)
+                        .unwrap()
+                        .as_millis();
+                    let event = Event::default().data(data).id(format!("{}", millis));
+                    return Some((Ok::<Event, Infallible>(event), rx));
+                }
+                Err(RecvError::Lagged(skipped)) => {
+                    println!("SSE receiver lagged, skipped {} messages", skipped);
+                    continue;
+                }
+                Err(RecvError::Closed) => {
+                    return None;
+                }
+            }
+        }
+    });
+
+    let headers = [
+        ("Cache-Control", "no-cache, no-store, must-revalidate"),
+        ("Connection", "keep-alive"),
+        ("Content-Type", "text/event-stream"),
+        ("X-Accel-Buffering", "no"), // nginx
+        ("X-Proxy-Buffering", "no"), // other proxies
+        ("Access-Control-Allow-Origin", "*"),
+        ("Access-Control-Allow-Headers", "Cache-Control"),
+    ];
+    (
+        headers,
+        Sse::new(stream).keep_alive(
+            KeepAlive::new()
+                .interval(Duration::from_millis(500))
+                .text("ping"),
+        ),
+    )
+}
@@ -1,4 +1,5 @@
 use ast::lang::asg::NodeData;
+use ast::lang::graphs::coverage;
 use axum::{
     http::StatusCode,
     response::{IntoResponse, Response},
@@ -91,6 +92,8 @@ pub struct VectorSearchParams {
     pub node_types: Option<String>,
     pub similarity_threshold: Option<f32>,
     pub language: Option<String>,
+    // how much pagerank counts against similarity, 0 to 1
+    pub importance_weight: Option<f32>,
 }
 
 #[derive(Deserialize)]
@@ -117,6 +120,27 @@ pub struct Coverage {
     pub e2e_tests: Option<CoverageStat>,
 }
 
+impl From<coverage::CoverageStat> for CoverageStat {
+    fn from(s: coverage::CoverageStat) -> Self {
+        Self {
+            total: s.total,
+            total_tests: s.total_tests,
+            covered: s.covered,
+            percent: s.percent,
+        }
+    }
+}
+
+impl From<coverage::GraphCoverage> for Coverage {
+    fn from(c: coverage::GraphCoverage) -> Self {
+        Self {
+            unit_tests: c.unit_tests.map(Into::into),
+            integration_tests: c.integration_tests.map(Into::into),
+            e2e_tests: c.e2e_tests.map(Into::into),
+        }
+    }
+}
+
 #[derive(Deserialize)]
 pub struct UncoveredParams {
     pub node_type: String,
@@ -172,6 +196,64 @@ pub struct HasResponse {
     pub covered: bool,
 }
 
+#[derive(Deserialize)]
+pub struct MermaidParams {
+    pub node_type: String,
+    pub name: String,
+    pub file: Option<String>,
+    pub kind: Option<String>,
+    pub depth: Option<usize>,
+}
+
+#[derive(Deserialize)]
+pub struct SubgraphParams {
+    pub node_type: String,
+    pub name: String,
+    pub file: Option<String>,
+    pub depth: Option<usize>,
+    // comma separated, e.g. CALLS,HANDLER and Function,Endpoint. empty allows every type
+    pub edge_types: Option<String>,
+    pub node_types: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct ContextParams {
+    // seeds: a node name, a 1-based file:line position and/or a vector search query
+    pub name: Option<String>,
+    pub file: Option<String>,
+    pub line: Option<usize>,
+    pub query: Option<String>,
+    // tokens, 8000 by default
+    pub budget: Option<usize>,
+    pub depth: Option<usize>,
+}
+
+#[derive(Deserialize)]
+pub struct DataModelTraceParams {
+    // only the endpoint with this path, and verb
+    pub endpoint: Option<String>,
+    pub verb: Option<String>,
+    // only paths into this data model, and only endpoints reaching it
+    pub data_model: Option<String>,
+    pub depth: Option<usize>,
+}
+
+#[derive(Deserialize)]
+pub struct DeadCodeParams {
+    // only report functions and classes under this path
+    pub path: Option<String>,
+    // "unused" or "unreachable", both if unset
+    pub kind: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct ClonesParams {
+    // only report pairs with a function under this path
+    pub path: Option<String>,
+    // minimum similarity, 0 to 1
+    pub threshold: Option<f64>,
+}
+
 #[derive(Deserialize)]
 pub struct CodecovBody {
     pub repo_url: String,
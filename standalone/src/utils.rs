@@ -1,9 +1,22 @@
 use ast::lang::asg::NodeData;
-use ast::lang::NodeType;
+use ast::lang::graphs::call_graph::MAX_CALL_DEPTH;
+use ast::lang::graphs::clones::{self, CloneReport};
+use ast::lang::graphs::context_pack::{build_context_pack, ContextPack, Seed};
+use ast::lang::graphs::data_model_trace::EndpointTrace;
+use ast::lang::graphs::dead_code::{self, DeadCodeReport, DeadKind};
+use ast::lang::graphs::{ArrayGraph, MermaidKind};
+use ast::lang::{EdgeType, Graph, Node, NodeType};
 use shared::Result;
 use std::str::FromStr;
 
-use crate::types::{UncoveredNode, UncoveredNodeConcise, UncoveredResponse, UncoveredResponseItem};
+use crate::types::{
+    ClonesParams, ContextParams, DataModelTraceParams, DeadCodeParams, MermaidParams, ProcessBody,
+    Result as WebResult, SubgraphParams, UncoveredNode, UncoveredNodeConcise, UncoveredResponse,
+    UncoveredResponseItem, WebError,
+};
+use ast::repo::Repo;
+use axum::response::{IntoResponse, Response};
+use axum::Json;
 
 pub fn parse_node_type(node_type: &str) -> Result<NodeType> {
     let mut chars: Vec<char> = node_type.chars().collect();
@@ -14,6 +27,156 @@ pub fn parse_node_type(node_type: &str) -> Result<NodeType> {
     NodeType::from_str(&titled_case)
 }
 
+// finds the root named in the query and renders its mermaid diagram.
+// the kind defaults to what suits the root: sequence for endpoints, class for classes
+pub fn mermaid_diagram<G: Graph>(graph: &G, params: &MermaidParams) -> Result<String> {
+    let node_type = parse_node_type(&params.node_type)?;
+    let root = match &params.file {
+        Some(file) => graph.find_node_by_name_in_file(node_type.clone(), &params.name, file),
+        None => graph
+            .find_nodes_by_name(node_type.clone(), &params.name)
+            .into_iter()
+            .next(),
+    }
+    .ok_or_else(|| {
+        shared::Error::Custom(format!(
+            "{} {} not found",
+            node_type.to_string(),
+            params.name
+        ))
+    })?;
+    let kind = match &params.kind {
+        Some(kind) => MermaidKind::from_str(kind)?,
+        None => MermaidKind::for_root(&node_type),
+    };
+    let root = Node::new(node_type, root);
+    Ok(graph.to_mermaid(&root, kind, params.depth.unwrap_or(3)))
+}
+
+// the neighborhood of the node named in the query, as an array graph
+pub fn subgraph<G: Graph>(graph: &G, params: &SubgraphParams) -> Result<ArrayGraph> {
+    let node_type = parse_node_type(&params.node_type)?;
+    let seed = match &params.file {
+        Some(file) => graph.find_node_by_name_in_file(node_type.clone(), &params.name, file),
+        None => graph
+            .find_nodes_by_name(node_type.clone(), &params.name)
+            .into_iter()
+            .next(),
+    }
+    .ok_or_else(|| {
+        shared::Error::Custom(format!(
+            "{} {} not found",
+            node_type.to_string(),
+            params.name
+        ))
+    })?;
+    let split = |list: &Option<String>| {
+        list.iter()
+            .flat_map(|l| l.split(','))
+            .map(str::trim)
+            .filter(|t| !t.is_empty())
+            .map(str::to_string)
+            .collect::<Vec<_>>()
+    };
+    let edge_types = split(&params.edge_types)
+        .iter()
+        .map(|t| EdgeType::from_str(t))
+        .collect::<Result<Vec<_>>>()?;
+    let node_types = split(&params.node_types)
+        .iter()
+        .map(|t| parse_node_type(t))
+        .collect::<Result<Vec<_>>>()?;
+    let seed = Node::new(node_type, seed);
+    Ok(graph.subgraph(&seed, params.depth.unwrap_or(2), &edge_types, &node_types))
+}
+
+// packs code around the seeds in the query plus any extra ones (vector search hits)
+pub fn context_pack<G: Graph>(
+    graph: &G,
+    params: &ContextParams,
+    mut seeds: Vec<Seed>,
+) -> Result<ContextPack> {
+    if let Some(name) = &params.name {
+        seeds.push(Seed::Name(name.clone()));
+    }
+    if let (Some(file), Some(line)) = (&params.file, params.line) {
+        seeds.push(Seed::At {
+            file: file.clone(),
+            line: line.saturating_sub(1),
+        });
+    }
+    if seeds.is_empty() {
+        return Err(shared::Error::Custom(
+            "a name, file and line, or query is required".into(),
+        ));
+    }
+    Ok(build_context_pack(
+        graph,
+        &seeds,
+        params.budget.unwrap_or(8000),
+        params.depth.unwrap_or(3),
+    ))
+}
+
+pub fn dead_code_report<G: Graph>(graph: &G, params: &DeadCodeParams) -> Result<DeadCodeReport> {
+    let kind = match params.kind.as_deref() {
+        None => None,
+        Some("unused") => Some(DeadKind::Unused),
+        Some("unreachable") => Some(DeadKind::Unreachable),
+        Some(other) => {
+            return Err(shared::Error::Custom(format!(
+                "unknown dead code kind {}, expected unused or unreachable",
+                other
+            )))
+        }
+    };
+    let mut report = dead_code::find_dead_code(graph);
+    if let Some(path) = &params.path {
+        report = report.in_path(path);
+    }
+    if let Some(kind) = kind {
+        report.dead.retain(|d| d.kind == kind);
+    }
+    Ok(report)
+}
+
+pub fn clone_report<G: Graph>(graph: &G, params: &ClonesParams) -> Result<CloneReport> {
+    let threshold = params.threshold.unwrap_or(clones::DEFAULT_THRESHOLD);
+    if !(0.0..=1.0).contains(&threshold) {
+        return Err(shared::Error::Custom(format!(
+            "threshold {} is not between 0 and 1",
+            threshold
+        )));
+    }
+    let mut report = clones::find_clones(graph, threshold);
+    if let Some(path) = &params.path {
+        report = report.in_path(path);
+    }
+    Ok(report)
+}
+
+// endpoints with the data models they reach and the call path to each
+pub fn data_model_traces<G: Graph>(graph: &G, params: &DataModelTraceParams) -> Vec<EndpointTrace> {
+    let mut traces = graph.endpoint_data_model_report(params.depth.unwrap_or(MAX_CALL_DEPTH));
+    traces.retain(|t| {
+        let endpoint = &t.endpoint.node_data;
+        let verb = endpoint.meta.get("verb").map(|v| v.as_str()).unwrap_or("");
+        params.endpoint.as_ref().is_none_or(|e| endpoint.name == *e)
+            && params
+                .verb
+                .as_ref()
+                .is_none_or(|v| verb.eq_ignore_ascii_case(v))
+    });
+    if let Some(data_model) = &params.data_model {
+        for t in &mut traces {
+            t.data_models
+                .retain(|d| d.data_model.node_data.name == *data_model);
+        }
+        traces.retain(|t| !t.data_models.is_empty());
+    }
+    traces
+}
+
 pub fn extract_ref_id(node_data: &NodeData) -> String {
     node_data
         .meta
@@ -131,3 +294,62 @@ pub fn format_uncovered_response_as_snippet(response: &UncoveredResponse) -> Str
 
     text
 }
+
+pub fn env_not_empty(key: &str) -> Option<String> {
+    std::env::var(key).ok().filter(|v| !v.is_empty())
+}
+
+pub fn resolve_repo(
+    body: &ProcessBody,
+) -> WebResult<(
+    String,
+    String,
+    Option<String>,
+    Option<String>,
+    Option<String>,
+)> {
+    let repo_path = body
+        .repo_path
+        .clone()
+        .or_else(|| env_not_empty("REPO_PATH"));
+    let repo_url = body.repo_url.clone().or_else(|| env_not_empty("REPO_URL"));
+    let username = body.username.clone().or_else(|| env_not_empty("USERNAME"));
+    let pat = body.pat.clone().or_else(|| env_not_empty("PAT"));
+    let commit = body.commit.clone();
+
+    if repo_path.is_none() && repo_url.is_none() {
+        return Err(WebError(shared::Error::Custom(
+            "Neither REPO_PATH nor REPO_URL is set in the body or environment".into(),
+        )));
+    }
+
+    if let Some(path) = repo_path {
+        Ok((path, repo_url.unwrap_or_default(), username, pat, commit))
+    } else {
+        let url = repo_url.unwrap();
+        let tmp_path = Repo::get_path_from_url(&url)?;
+        Ok((tmp_path, url, username, pat, commit))
+    }
+}
+
+// uncovered functions/endpoints as json, or as text snippets for `output=snippet`
+pub fn uncovered_response(
+    node_type: &NodeType,
+    concise: bool,
+    output: &str,
+    funcs: Vec<(NodeData, usize)>,
+    endpoints: Vec<(NodeData, usize)>,
+) -> Response {
+    let functions = matches!(node_type, NodeType::Function)
+        .then(|| create_uncovered_response_items(funcs, &NodeType::Function, concise));
+    let endpoints = matches!(node_type, NodeType::Endpoint)
+        .then(|| create_uncovered_response_items(endpoints, &NodeType::Endpoint, concise));
+    let response = UncoveredResponse {
+        functions,
+        endpoints,
+    };
+    match output {
+        "snippet" => format_uncovered_response_as_snippet(&response).into_response(),
+        _ => Json(response).into_response(),
+    }
+}